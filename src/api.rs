@@ -0,0 +1,135 @@
+//! The per-file functions of the library.
+//!
+//! Each runs the same code as the matching subcommand and reports a
+//! failure as an [`Error`], the way [`Operation::run`](crate::Operation::run)
+//! does, so callers handle one error type whichever way they go.
+
+use crate::commands::search::Match;
+use crate::commands::{
+    anonymize, backup, delete, export, list, merge, rename, restore, search, split,
+};
+use crate::encoding::Encoding;
+use crate::error::Error;
+use crate::scan::{self, LogFile};
+use crate::trash::Trash;
+use std::path::{Path, PathBuf};
+
+/// Moves `path` into `backup_dir`, which is taken from the file's own
+/// directory when relative. With `verify` the file is copied and checked
+/// by SHA-256 before the original is removed.
+pub fn backup_log_file(
+    path: &Path,
+    backup_dir: &Path,
+    dry_run: bool,
+    verify: bool,
+) -> Result<(), Error> {
+    Ok(backup::backup_log_file(path, backup_dir, dry_run, verify)?)
+}
+
+/// Bundles `files` into the zip archive `out`, which must not exist yet.
+pub fn archive_logs(files: &[PathBuf], out: &Path) -> Result<(), Error> {
+    Ok(backup::archive_logs(files, out)?)
+}
+
+/// Removes `path`, or moves it to `trash` when one is given.
+pub fn delete_log_file(path: &Path, trash: Option<&dyn Trash>, dry_run: bool) -> Result<(), Error> {
+    Ok(delete::delete_log_file(path, trash, dry_run)?)
+}
+
+/// Copies `path` into `backup_dir`, keeping its folders below `root`, and
+/// removes it once the copy is checked by SHA-256.
+pub fn safe_delete_log_file(
+    path: &Path,
+    root: &Path,
+    backup_dir: &Path,
+    dry_run: bool,
+) -> Result<(), Error> {
+    Ok(delete::safe_delete_log_file(
+        path, root, backup_dir, dry_run,
+    )?)
+}
+
+/// Writes the parsed events of `file` to the CSV file `out`.
+pub fn export_to_csv(file: &Path, out: &Path, encoding: Encoding) -> Result<(), Error> {
+    Ok(export::export_to_csv(file, out, encoding)?)
+}
+
+/// Prints the name of `path` as `list` does.
+pub fn list_log_file(path: &Path) -> Result<(), Error> {
+    Ok(list::list_log_file(path)?)
+}
+
+/// Writes the lines of `files` to `out`, ordered by their timestamps.
+pub fn merge_logs(files: &[PathBuf], out: &Path) -> Result<(), Error> {
+    Ok(merge::merge_logs(files, out)?)
+}
+
+/// Renames a log to `YYYY-MM-DD_HH-MM.log` and returns its new path.
+pub fn normalize_name(path: &Path, dry_run: bool) -> Result<PathBuf, Error> {
+    Ok(rename::normalize_name(path, dry_run)?)
+}
+
+/// Moves `bak_file` back into `log_dir`, the directory it was backed up
+/// from.
+pub fn restore_log_file(bak_file: &Path, log_dir: &Path) -> Result<(), Error> {
+    Ok(restore::restore_log_file(bak_file, log_dir)?)
+}
+
+/// The lines of every log in `dir` that contain `needle`, optionally only
+/// among the events with one of `opcodes`.
+pub fn search_logs(
+    dir: &Path,
+    needle: &str,
+    ignore_case: bool,
+    opcodes: &[u8],
+    encoding: Encoding,
+) -> Result<Vec<Match>, Error> {
+    Ok(search::search_logs(
+        dir,
+        needle,
+        ignore_case,
+        opcodes,
+        encoding,
+    )?)
+}
+
+/// Writes each encounter of `file` to its own part in `out_dir` and
+/// returns the parts written.
+pub fn split_log(file: &Path, out_dir: &Path) -> Result<Vec<PathBuf>, Error> {
+    Ok(split::split_log(file, out_dir)?)
+}
+
+/// Copies `file` to `out` with every player name replaced by a pseudonym.
+pub fn scrub(file: &Path, out: &Path) -> Result<(), Error> {
+    Ok(anonymize::scrub(file, out)?)
+}
+
+/// Returns the regular files directly inside `dir`.
+pub fn collect_log_files(dir: &Path) -> Result<Vec<LogFile>, Error> {
+    Ok(scan::collect_log_files(dir)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_failures_come_back_as_error() {
+        let temp_dir = TempDir::new().unwrap();
+        let missing = temp_dir.path().join("missing.log");
+
+        let result = restore_log_file(&missing, temp_dir.path());
+        assert!(matches!(result, Err(Error::NotFound(_))));
+        let result = collect_log_files(&missing);
+        assert!(matches!(result, Err(Error::NotFound(_))));
+
+        let file = temp_dir.path().join("a.log");
+        fs::write(&file, "a").unwrap();
+        backup_log_file(&file, Path::new("bak"), false, false).unwrap();
+        fs::write(&file, "again").unwrap();
+        let result = backup_log_file(&file, Path::new("bak"), false, false);
+        assert!(matches!(result, Err(Error::InvalidInput(_))));
+    }
+}
@@ -0,0 +1,4883 @@
+//! The command-line interface: argument parsing and the pipeline that runs
+//! each command over the selected log files.
+
+use crate::commands::list::{OutputFormat, SortKey};
+use crate::commands::rename::{NameCleanup, NameTemplate};
+use crate::commands::stats::StatsSort;
+use crate::commands::undo::UndoLog;
+use crate::commands::view::{Paging, Selection};
+use crate::commands::watch::WatchAction;
+use crate::encoding::Encoding;
+use crate::file_config::FileConfig;
+use crate::filters::{FileFilter, FileLimit, SkipReason};
+use crate::output::debug;
+use crate::progress::{Progress, ProgressFormat};
+use crate::regex::Regex;
+use crate::scan::{DirSummary, EntryStream, LogFile, ScanEntry, dir_header, holding_dir};
+use crate::style::ColorChoice;
+use crate::summary::Summary;
+use crate::trash::{SystemTrash, Trash};
+use crate::util::Date;
+use crate::{
+    commands, filters, input, interrupt, jobs, output, paths, prompt, retry, scan, style, util,
+};
+use clap::{Parser, Subcommand};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::env;
+use std::fs::{self, metadata};
+use std::io;
+use std::iter;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::{Duration, Instant};
+
+/// Runs the command `config` was parsed into, printing its progress and
+/// summary, and returns the exit status described in the `--help` text.
+pub fn run(config: Config) -> Result<ExitCode, io::Error> {
+    let start = Instant::now();
+
+    output::set_verbosity(config.verbose);
+    output::set_timestamps(config.timestamps);
+    style::set_color(config.color);
+    retry::set_retries(config.retries);
+
+    // Each directory can be given positionally or with --fflogs-dir.
+    let targets = match &config.command {
+        Command::List {
+            fflogs_dir, paths, ..
+        }
+        | Command::Delete {
+            fflogs_dir, paths, ..
+        }
+        | Command::Backup {
+            fflogs_dir, paths, ..
+        }
+        | Command::Restore {
+            fflogs_dir,
+            dirs: paths,
+            ..
+        }
+        | Command::Rename {
+            fflogs_dir,
+            dirs: paths,
+            ..
+        } => {
+            if paths.is_empty() {
+                fflogs_dir.clone()
+            } else {
+                paths.clone()
+            }
+        }
+        Command::View {
+            fflogs_dir, dir, ..
+        }
+        | Command::Stats {
+            fflogs_dir, dir, ..
+        }
+        | Command::Index {
+            fflogs_dir, dir, ..
+        }
+        | Command::Undo {
+            fflogs_dir, dir, ..
+        }
+        | Command::Watch {
+            fflogs_dir, dir, ..
+        }
+        | Command::Doctor {
+            fflogs_dir, dir, ..
+        } => vec![dir.clone().unwrap_or_else(|| fflogs_dir.clone())],
+        Command::Search { fflogs_dir, .. } => vec![fflogs_dir.clone()],
+        Command::Export { fflogs_dir, .. } => vec![fflogs_dir.clone()],
+        Command::Anonymize { fflogs_dir, .. } => vec![fflogs_dir.clone()],
+        Command::Split { fflogs_dir, .. } => vec![fflogs_dir.clone()],
+        Command::Merge { fflogs_dir, .. } => vec![fflogs_dir.clone()],
+    };
+    let allow_default =
+        !config.no_default && !paths::no_default_from_env(env::var_os(paths::NO_DEFAULT_VAR));
+
+    // The checks must run even when the directory is missing, so doctor
+    // resolves its target itself.
+    if let Command::Doctor { backup_dir, .. } = &config.command {
+        return doctor(&targets[0], backup_dir, allow_default);
+    }
+
+    // Only list, delete and backup accept individual files as targets.
+    let accepts_files = matches!(
+        config.command,
+        Command::List { .. } | Command::Delete { .. } | Command::Backup { .. }
+    );
+
+    let include_dirs = match &config.command {
+        Command::List { include_dirs, .. } | Command::Stats { include_dirs, .. } => *include_dirs,
+        _ => false,
+    };
+
+    let paths_from_json = match &config.command {
+        Command::List {
+            paths_from_json, ..
+        }
+        | Command::Delete {
+            paths_from_json, ..
+        }
+        | Command::Backup {
+            paths_from_json, ..
+        } => paths_from_json.clone(),
+        Command::View { .. }
+        | Command::Stats { .. }
+        | Command::Index { .. }
+        | Command::Restore { .. }
+        | Command::Search { .. }
+        | Command::Export { .. }
+        | Command::Anonymize { .. }
+        | Command::Split { .. }
+        | Command::Merge { .. }
+        | Command::Undo { .. }
+        | Command::Rename { .. }
+        | Command::Doctor { .. }
+        | Command::Watch { .. } => None,
+    };
+    if paths_from_json.as_deref() == Some("-") && asks_before_deleting(&config.command) {
+        return Ok(usage_error(
+            "--paths-from-json - reads the files from stdin, which leaves no way to answer the delete prompt; add --yes or --dry-run.",
+        ));
+    }
+
+    let recursive = match &config.command {
+        Command::List { recursive, .. }
+        | Command::Delete { recursive, .. }
+        | Command::Backup { recursive, .. } => *recursive,
+        Command::View { .. }
+        | Command::Stats { .. }
+        | Command::Index { .. }
+        | Command::Restore { .. }
+        | Command::Search { .. }
+        | Command::Export { .. }
+        | Command::Anonymize { .. }
+        | Command::Split { .. }
+        | Command::Merge { .. }
+        | Command::Undo { .. }
+        | Command::Rename { .. }
+        | Command::Doctor { .. }
+        | Command::Watch { .. } => false,
+    };
+
+    let max_depth = match &config.command {
+        Command::List { max_depth, .. }
+        | Command::Delete { max_depth, .. }
+        | Command::Backup { max_depth, .. } => *max_depth,
+        Command::View { .. }
+        | Command::Stats { .. }
+        | Command::Index { .. }
+        | Command::Restore { .. }
+        | Command::Search { .. }
+        | Command::Export { .. }
+        | Command::Anonymize { .. }
+        | Command::Split { .. }
+        | Command::Merge { .. }
+        | Command::Undo { .. }
+        | Command::Rename { .. }
+        | Command::Doctor { .. }
+        | Command::Watch { .. } => None,
+    };
+
+    let limit = match &config.command {
+        Command::List { limit, .. }
+        | Command::Delete { limit, .. }
+        | Command::Backup { limit, .. } => *limit,
+        _ => None,
+    };
+    // A sorted listing is cut after sorting, not while scanning.
+    let limit_after_sort = matches!(config.command, Command::List { sort: Some(_), .. });
+
+    let (summary_json, summary_file) = match &config.command {
+        Command::List {
+            summary_json,
+            summary_file,
+            ..
+        }
+        | Command::Delete {
+            summary_json,
+            summary_file,
+            ..
+        }
+        | Command::Backup {
+            summary_json,
+            summary_file,
+            ..
+        } => (*summary_json, summary_file.clone()),
+        Command::View { .. }
+        | Command::Stats { .. }
+        | Command::Index { .. }
+        | Command::Restore { .. }
+        | Command::Search { .. }
+        | Command::Export { .. }
+        | Command::Anonymize { .. }
+        | Command::Split { .. }
+        | Command::Merge { .. }
+        | Command::Undo { .. }
+        | Command::Rename { .. }
+        | Command::Doctor { .. }
+        | Command::Watch { .. } => (false, None),
+    };
+
+    let selection = match &config.command {
+        Command::View {
+            select: Some(number),
+            ..
+        } => Some(Selection::Number(*number)),
+        Command::View {
+            file: Some(name), ..
+        } => Some(Selection::Name(name.clone())),
+        Command::View { newest: true, .. } => Some(Selection::Newest),
+        Command::View { oldest: true, .. } => Some(Selection::Oldest),
+        _ => None,
+    };
+
+    let (head, tail, follow) = match &config.command {
+        Command::View {
+            head, tail, follow, ..
+        } => (*head, *tail, *follow),
+        _ => (None, None, false),
+    };
+    let encoding = match &config.command {
+        Command::View { encoding, .. }
+        | Command::Search { encoding, .. }
+        | Command::Export { encoding, .. } => *encoding,
+        _ => Encoding::Utf8,
+    };
+    let paging = match &config.command {
+        Command::View { page: true, .. } => Paging::Prompt,
+        Command::View { pager: true, .. } => Paging::Pager,
+        _ => Paging::Off,
+    };
+
+    let output = match &config.command {
+        Command::List { output, .. } | Command::Stats { output, .. } => *output,
+        _ => OutputFormat::Text,
+    };
+    // JSON on stdout must not be mixed with status lines.
+    let json_output = output == OutputFormat::Json
+        || matches!(
+            &config.command,
+            Command::Export {
+                json_lines: true,
+                out: None,
+                ..
+            }
+        );
+    let no_header = match &config.command {
+        Command::List { no_header, .. } | Command::Stats { no_header, .. } => *no_header,
+        _ => false,
+    };
+    let group_by_ext = matches!(
+        &config.command,
+        Command::List {
+            group_by_ext: true,
+            ..
+        }
+    );
+    let (sort, reverse, names_only) = match &config.command {
+        Command::List {
+            sort,
+            reverse,
+            names_only,
+            ..
+        } => (*sort, *reverse, *names_only),
+        _ => (None, false, false),
+    };
+    let status = move |message: String| {
+        if json_output {
+            output::emit_err(message);
+        } else {
+            output::emit(message);
+        }
+    };
+    let detail = move |message: String| {
+        if !output::is_quiet() {
+            status(message);
+        }
+    };
+
+    let dry_run = match &config.command {
+        Command::Delete { dry_run, .. }
+        | Command::Backup { dry_run, .. }
+        | Command::Rename { dry_run, .. } => *dry_run,
+        _ => false,
+    };
+
+    let use_trash = matches!(&config.command, Command::Delete { trash: true, .. });
+    let filter = file_filter(&config.command);
+
+    let interactive = matches!(
+        &config.command,
+        Command::Delete {
+            interactive: true,
+            ..
+        }
+    );
+    let confirm_delete = confirm_delete(&config.command);
+    let progress_format = progress_format(&config.command);
+
+    let mut resolved = Vec::new();
+    let mut invalid = Vec::new();
+    for target in targets {
+        let target = if target == "default" {
+            match default_fflogs_dir(allow_default) {
+                Ok(dir) => dir,
+                Err(code) => return Ok(code),
+            }
+        } else {
+            expand_dir(Path::new(&target))
+        };
+        debug!("Target resolved to {}", target.display());
+        if target.is_dir() || (accepts_files && target.is_file()) {
+            resolved.push(target);
+        } else {
+            invalid.push(target);
+        }
+    }
+    // Relative settings such as --safe-delete and --paths-from-json resolve
+    // against the first directory, or the folder of the first file.
+    let base_dir = match resolved.iter().find(|target| target.is_dir()) {
+        Some(dir) => dir.clone(),
+        None => match resolved.first() {
+            Some(file) => file
+                .parent()
+                .filter(|parent| !parent.as_os_str().is_empty())
+                .unwrap_or(Path::new("."))
+                .to_path_buf(),
+            None => {
+                let message = match invalid.as_slice() {
+                    [target] => format!("Not a valid directory: {}", target.display()),
+                    _ => "None of the given paths is a valid file or directory".to_string(),
+                };
+                return Ok(usage_error(&message));
+            }
+        },
+    };
+    let path = base_dir.as_path();
+    for target in &invalid {
+        eprintln!("Not a valid file or directory: {}", target.display());
+    }
+
+    let quiet = match &config.command {
+        Command::Delete { quiet, .. } | Command::Backup { quiet, .. } => *quiet,
+        _ => false,
+    };
+    output::set_quiet(quiet);
+
+    let jobs = match &config.command {
+        Command::Delete { jobs, .. } | Command::Backup { jobs, .. } => usize::from(*jobs),
+        _ => 1,
+    };
+
+    let keep_last = match &config.command {
+        Command::Backup { keep_last, .. } => *keep_last,
+        _ => None,
+    };
+
+    let backup_dir = match &config.command {
+        Command::Backup { backup_dir, .. }
+        | Command::Restore { backup_dir, .. }
+        | Command::Watch { backup_dir, .. } => backup_dir.clone(),
+        _ => PathBuf::from(commands::backup::DEFAULT_BACKUP_DIR),
+    };
+
+    let max_bak_size = match &config.command {
+        Command::Backup { max_bak_size, .. } => *max_bak_size,
+        _ => None,
+    };
+
+    let dedupe = matches!(&config.command, Command::Backup { dedupe: true, .. });
+    let name_template = match &config.command {
+        Command::Rename {
+            format_name,
+            lowercase,
+            replace_spaces,
+            ascii_only,
+            ..
+        } => {
+            let cleanup = NameCleanup {
+                ascii_only: *ascii_only,
+                replace_spaces: *replace_spaces,
+                lowercase: *lowercase,
+            };
+            // Clean-ups alone tidy the current names instead of dating them.
+            let template = match format_name {
+                Some(template) => template.clone(),
+                None if !cleanup.is_empty() => NameTemplate::current_name(),
+                None => NameTemplate::default(),
+            };
+            template.with_cleanup(cleanup)
+        }
+        _ => NameTemplate::default(),
+    };
+    let verify = matches!(&config.command, Command::Backup { verify: true, .. });
+    let archive = match &config.command {
+        Command::Backup { archive, .. } => archive.as_ref().map(|archive| path.join(archive)),
+        _ => None,
+    };
+    let delete_after = matches!(
+        &config.command,
+        Command::Backup {
+            delete_after: true,
+            ..
+        }
+    );
+
+    let operation = match config.command {
+        Command::List { .. } => "list",
+        Command::Delete { .. } => "delete",
+        Command::Backup { .. } => "backup",
+        Command::View { .. } => "view",
+        Command::Stats { .. } => "stats",
+        Command::Index { .. } => "index",
+        Command::Restore { .. } => "restore",
+        Command::Undo { .. } => "undo",
+        Command::Rename { .. } => "rename",
+        Command::Search { .. } => "search",
+        Command::Export { .. } => "export",
+        Command::Anonymize { .. } => "anonymize",
+        Command::Split { .. } => "split",
+        Command::Merge { .. } => "merge",
+        Command::Watch { .. } => "watch",
+        Command::Doctor { .. } => "doctor",
+    };
+    let mut summary = Summary::new(operation, path.to_path_buf());
+    for _ in &invalid {
+        summary.record(false);
+    }
+
+    // Every way the run ends comes back here, so the summary is written
+    // whether it finished, stopped early or failed.
+    let outcome = (|| -> io::Result<Option<u8>> {
+        let require = match &config.command {
+            Command::Delete { require, .. } | Command::Backup { require, .. } => require.clone(),
+            _ => None,
+        };
+        if let Some(pattern) = &require {
+            // Every directory of the run has to hold the file, not just the first.
+            let mut found = 0;
+            for dir in resolved.iter().filter(|target| target.is_dir()) {
+                let matching = filters::count_matching_files(dir, pattern)?;
+                if matching == 0 {
+                    return Ok(Some(usage_error_code(&format!(
+                        "Required file matching '{pattern}' not found in {}; nothing was touched.",
+                        dir.display()
+                    ))));
+                }
+                found += matching;
+            }
+            output::emit(format!(
+                "Required file matching '{pattern}' found ({found} matching)."
+            ));
+        }
+
+        let safe_delete_dir = match &config.command {
+            Command::Delete {
+                safe_delete: Some(backup_dir),
+                ..
+            } => Some(path.join(backup_dir)),
+            _ => None,
+        };
+
+        match config.command {
+            Command::View { tui: true, .. } => {
+                #[cfg(all(feature = "tui", unix))]
+                crate::tui::browse(path)?;
+                #[cfg(not(all(feature = "tui", unix)))]
+                return Ok(Some(usage_error_code(
+                    "This build has no --tui browser; rebuild with `cargo build --features tui` (Unix only).",
+                )));
+            }
+            Command::View {
+                save: Some(dest),
+                yes,
+                ..
+            } => {
+                commands::view::save_log_files(path, selection.as_ref(), &dest, yes)?;
+            }
+            Command::View { .. } => {
+                commands::view::view_log_files(
+                    path,
+                    selection.as_ref(),
+                    head,
+                    tail,
+                    paging,
+                    follow,
+                    encoding,
+                )?;
+            }
+            Command::Stats {
+                sort, output, deep, ..
+            } => {
+                let mut stats = commands::stats::summarize(path, include_dirs, deep)?;
+                commands::stats::sort_files(&mut stats.files, sort);
+                match output {
+                    OutputFormat::Json => println!("{}", stats.to_json()),
+                    OutputFormat::Text | OutputFormat::Table => commands::stats::print_summary(
+                        &stats,
+                        output == OutputFormat::Table && !no_header,
+                    ),
+                }
+            }
+            Command::Index { html, .. } => commands::index::index_dir(path, html)?,
+            Command::Search {
+                needle,
+                query,
+                ignore_case,
+                opcode,
+                ..
+            } => {
+                let needle = needle.or(query).unwrap_or_default();
+                let matches =
+                    commands::search::search_logs(path, &needle, ignore_case, &opcode, encoding)?;
+                commands::search::print_matches(&matches);
+            }
+            Command::Export {
+                file,
+                out,
+                json_lines: true,
+                ..
+            } => {
+                let file = path.join(file);
+                commands::export::export_json_lines(&file, out.as_deref(), encoding)?;
+            }
+            Command::Export { file, out, .. } => {
+                let file = path.join(file);
+                let out = out.unwrap_or_else(|| commands::export::default_csv_path(&file));
+                commands::export::export_to_csv(&file, &out, encoding)?;
+            }
+            Command::Anonymize { file, out, .. } => {
+                let file = path.join(file);
+                let out = out.unwrap_or_else(|| commands::anonymize::default_anon_path(&file));
+                commands::anonymize::scrub(&file, &out)?;
+            }
+            Command::Split { file, out_dir, .. } => {
+                let file = path.join(file);
+                let out_dir = out_dir.map_or_else(|| path.to_path_buf(), |dir| path.join(dir));
+                commands::split::split_log(&file, &out_dir)?;
+            }
+            Command::Merge { files, out, .. } => {
+                let files: Vec<PathBuf> = files.iter().map(|file| path.join(file)).collect();
+                commands::merge::merge_logs(&files, &path.join(out))?;
+            }
+            Command::Watch { action, .. } => {
+                commands::watch::watch_dir(path, action, &backup_dir, &mut summary)?;
+            }
+            Command::Undo { .. } => {
+                commands::undo::undo_last_backup(path, &mut summary)?;
+                output::emit(summary.report_line());
+            }
+            _ => {
+                let scan_targets: Vec<PathBuf> = match config.command {
+                    Command::Restore { .. } => resolved
+                        .iter()
+                        .map(|dir| dir.join(&backup_dir))
+                        .filter(|bak| {
+                            let found = bak.is_dir();
+                            if !found {
+                                output::emit(format!(
+                                    "No backup directory found: {}",
+                                    bak.display()
+                                ));
+                            }
+                            found
+                        })
+                        .collect(),
+                    _ => resolved.clone(),
+                };
+                if scan_targets.is_empty() {
+                    return Ok(Some(0));
+                }
+                // With several directories, each one's files are introduced by
+                // a header; the summary still covers them all.
+                let dirs: Vec<PathBuf> = resolved
+                    .iter()
+                    .filter(|target| target.is_dir())
+                    .cloned()
+                    .collect();
+                let headed_dirs = if dirs.len() > 1 {
+                    dirs.clone()
+                } else {
+                    Vec::new()
+                };
+                let mut current_dir = None;
+                // `--interactive` asks about each file until answered with 'a'.
+                let mut ask_each = interactive;
+
+                let mut entries: EntryStream = match &paths_from_json {
+                    Some(source) => match input::read_json_paths(source, path) {
+                        Ok(paths) => {
+                            Box::new(paths.into_iter().map(|path| Ok(ScanEntry::from_path(path))))
+                        }
+                        Err(err) => {
+                            return Ok(Some(usage_error_code(&format!(
+                                "Could not read the files to process from {err}"
+                            ))));
+                        }
+                    },
+                    None => {
+                        let mut chained: EntryStream = Box::new(iter::empty());
+                        for target in scan_targets.iter().cloned() {
+                            if !target.is_dir() {
+                                let file = iter::once(Ok(ScanEntry::File(target)));
+                                chained = Box::new(chained.chain(file));
+                                continue;
+                            }
+                            debug!("Scanning {}", target.display());
+                            let mut scan = match scan::entries(&target) {
+                                Ok(scan) => scan,
+                                Err(err) => {
+                                    let err = scan::with_path(&target, err);
+                                    chained = Box::new(chained.chain(iter::once(Err(err))));
+                                    continue;
+                                }
+                            };
+                            if recursive {
+                                scan = scan.recursive(&target);
+                                if let Some(depth) = max_depth {
+                                    scan = scan.max_depth(depth);
+                                }
+                                let backup_dir = match &config.command {
+                                    Command::Backup { .. } => Some(backup_dir.as_path()),
+                                    _ => safe_delete_dir.as_deref(),
+                                };
+                                if let Some(name) = backup_dir.and_then(Path::file_name) {
+                                    scan = scan.excluding(&name.to_string_lossy());
+                                }
+                            }
+                            chained = Box::new(chained.chain(scan));
+                        }
+                        chained
+                    }
+                };
+
+                // Filtered files stay in the stream as `Skipped` so the summary
+                // can say why they were left out.
+                let entry_filter = filter.clone();
+                entries = Box::new(entries.map(move |entry| match entry {
+                    Ok(ScanEntry::File(path)) => match LogFile::from_path(path.clone()) {
+                        Ok(file) => match entry_filter.check(&file) {
+                            Ok(()) => Ok(ScanEntry::File(path)),
+                            Err(reason) => {
+                                if let Some(message) = entry_filter.skip_message(&file, reason) {
+                                    detail(message);
+                                }
+                                Ok(ScanEntry::Skipped(path, reason))
+                            }
+                        },
+                        // Left for the operation to report.
+                        Err(_) => Ok(ScanEntry::File(path)),
+                    },
+                    other => other,
+                }));
+
+                let mut listed = Vec::new();
+                let mut listed_dirs = Vec::new();
+                if let Some(keep) = keep_last {
+                    entries = commands::backup::keep_most_recent(entries, keep, &mut summary);
+                }
+
+                // Duplicates stay in the run, in name order, and are removed
+                // instead of backed up when their turn comes, after any plan.
+                let mut duplicates = HashSet::new();
+                if dedupe {
+                    (entries, duplicates) = commands::backup::find_duplicates(entries);
+                }
+
+                // Renames number the logs in name order for `{index}`. A preview
+                // also needs every file at once to spot names that would
+                // collide, so it is planned here instead of per file.
+                let mut rename_indices = HashMap::new();
+                if let Command::Rename { dry_run, .. } = config.command {
+                    if dry_run {
+                        entries = commands::rename::preview_renames(
+                            entries,
+                            &name_template,
+                            &headed_dirs,
+                            &mut summary,
+                        );
+                    } else {
+                        (entries, rename_indices) = commands::rename::number_files(entries);
+                    }
+                }
+
+                // Only a run that gathers its entries anyway shows the plan;
+                // the rest hand each file on as soon as it is scanned.
+                let mut progress = None;
+                let show_plan = matches!(
+                    config.command,
+                    Command::Backup { .. } | Command::Delete { .. }
+                );
+                if gathers_entries(&config.command) {
+                    let buffered: Vec<_> = entries.collect();
+                    let files: Vec<&Path> = buffered
+                        .iter()
+                        .filter_map(|entry| match entry {
+                            Ok(ScanEntry::File(path)) => Some(path.as_path()),
+                            _ => None,
+                        })
+                        .take(limit.unwrap_or(usize::MAX))
+                        .collect();
+                    let total = files.len();
+                    let bytes = files.iter().filter_map(|file| file_size(file)).sum();
+
+                    if show_plan && total > 0 {
+                        let source = match headed_dirs.len() {
+                            0 => path.display().to_string(),
+                            n => format!("{n} directories"),
+                        };
+                        let scope = plan_scope(total, bytes, &source);
+                        let action = match &config.command {
+                            Command::Backup { .. } => {
+                                let action = match &archive {
+                                    Some(archive) => {
+                                        format!("archive {scope} to {}", archive.display())
+                                    }
+                                    None if !headed_dirs.is_empty() && backup_dir.is_relative() => {
+                                        format!(
+                                            "back up {scope} to {} in each",
+                                            backup_dir.display()
+                                        )
+                                    }
+                                    None => format!(
+                                        "back up {scope} to {}",
+                                        path.join(&backup_dir).display()
+                                    ),
+                                };
+                                match duplicates.len() {
+                                    0 => action,
+                                    1 => format!("{action}, removing 1 duplicate instead"),
+                                    n => format!("{action}, removing {n} duplicates instead"),
+                                }
+                            }
+                            _ if use_trash => format!("move {scope} to the trash"),
+                            _ => format!("delete {scope}"),
+                        };
+                        let lead = if dry_run { "Would" } else { "About to" };
+                        detail(format!("{lead} {action}"));
+                    }
+                    if confirm_delete
+                        && total > 0
+                        && !prompt::confirm(&format!("Delete {total} files?"))?
+                    {
+                        output::emit("Aborted, no files were deleted.");
+                        return Ok(Some(0));
+                    }
+                    progress = progress_format.map(|format| Progress::new(format, total, bytes));
+                    entries = Box::new(buffered.into_iter());
+                }
+
+                // Everything but listing only depends on the path, so with
+                // `--jobs` the files are collected and handed to worker threads.
+                let file_op = |path: &Path| -> io::Result<()> {
+                    match &config.command {
+                        Command::Backup { .. } if duplicates.contains(path) => {
+                            commands::delete::delete_log_file(path, None, dry_run)
+                        }
+                        Command::Backup { .. } => {
+                            commands::backup::backup_log_file(path, &backup_dir, dry_run, verify)
+                        }
+                        Command::Delete { .. } => match &safe_delete_dir {
+                            Some(backup_dir) => {
+                                // Subfolders of a recursive run are kept below
+                                // the backup directory.
+                                let root = holding_dir(&dirs, path).unwrap_or(path);
+                                commands::delete::safe_delete_log_file(
+                                    path, root, backup_dir, dry_run,
+                                )
+                            }
+                            None => {
+                                let trash = use_trash.then_some(&SystemTrash as &dyn Trash);
+                                commands::delete::delete_log_file(path, trash, dry_run)
+                            }
+                        },
+                        Command::Restore { .. } => {
+                            // Files go back to the directory whose backups they are.
+                            let log_dir = resolved
+                                .iter()
+                                .find(|dir| path.starts_with(dir.join(&backup_dir)))
+                                .map(PathBuf::as_path)
+                                .or_else(|| path.parent())
+                                .unwrap_or(path);
+                            commands::restore::restore_log_file(path, log_dir)
+                        }
+                        Command::Rename { .. } => {
+                            let index = rename_indices.get(path).copied().unwrap_or(1);
+                            commands::rename::rename_to_template(
+                                path,
+                                &name_template,
+                                index,
+                                dry_run,
+                            )
+                            .map(|_| ())
+                        }
+                        Command::List { .. }
+                        | Command::View { .. }
+                        | Command::Stats { .. }
+                        | Command::Index { .. }
+                        | Command::Search { .. }
+                        | Command::Export { .. }
+                        | Command::Anonymize { .. }
+                        | Command::Split { .. }
+                        | Command::Merge { .. }
+                        | Command::Undo { .. }
+                        | Command::Doctor { .. }
+                        | Command::Watch { .. } => unreachable!(),
+                    }
+                };
+                let frees_space = matches!(config.command, Command::Delete { .. }) && !dry_run;
+                let freed_by = |path: &Path| frees_space || (duplicates.contains(path) && !dry_run);
+                let mut undo_log = match config.command {
+                    Command::Backup { .. } if !dry_run => Some(UndoLog::new(path)),
+                    _ => None,
+                };
+                // The backup directories that received a file, pruned to
+                // `--max-bak-size` once the run is done.
+                let mut budget_dirs = BTreeSet::new();
+                let mut record_move = |moved: &Path, result: &io::Result<()>| {
+                    if duplicates.contains(moved) {
+                        return;
+                    }
+                    if max_bak_size.is_some() && !dry_run && result.is_ok() {
+                        budget_dirs.insert(commands::backup::backup_dir_for(moved, &backup_dir));
+                    }
+                    if let (Some(undo_log), Ok(())) = (undo_log.as_mut(), result)
+                        && let Err(err) = undo_log.record_backup(moved, &backup_dir)
+                    {
+                        eprintln!("Could not record {} for undo: {err}", moved.display());
+                    }
+                };
+                let mut parallel = Vec::new();
+                let mut to_archive = Vec::new();
+                let mut file_limit = FileLimit::new(limit.filter(|_| !limit_after_sort));
+                // Ctrl-C stops the run between files, never in the middle of a
+                // move, and the partial summary is still printed.
+                interrupt::install();
+                for entry in entries {
+                    if interrupt::is_interrupted() {
+                        summary.interrupted = true;
+                        break;
+                    }
+                    if let Some(progress) = progress.as_mut() {
+                        progress.clear();
+                    }
+                    let path = match entry {
+                        Ok(ScanEntry::File(path)) => path,
+                        Ok(ScanEntry::Directory(path)) if include_dirs => {
+                            let result = DirSummary::from_path(path.clone())
+                                .map(|dir| listed_dirs.push(dir))
+                                .inspect_err(|err| {
+                                    status(format!(
+                                        "{} {}: {err}",
+                                        style::failure("Failed to read"),
+                                        path.display()
+                                    ))
+                                });
+                            summary.record(result.is_ok());
+                            continue;
+                        }
+                        Ok(ScanEntry::Directory(path)) => {
+                            if output::is_verbose() {
+                                detail(format!(
+                                    "Ignoring path as it is a directory: {}",
+                                    path.display()
+                                ));
+                            }
+                            summary.record_skip(SkipReason::Directory);
+                            continue;
+                        }
+                        Ok(ScanEntry::Missing(path)) => {
+                            detail(format!(
+                                "{} {}: no longer exists",
+                                style::skipped("Skipping"),
+                                path.display()
+                            ));
+                            summary.record_skip(SkipReason::Missing);
+                            continue;
+                        }
+                        Ok(ScanEntry::Skipped(_, reason)) => {
+                            summary.record_skip(reason);
+                            continue;
+                        }
+                        // An unreadable directory or entry, e.g. one locked by
+                        // ACT, fails on its own instead of ending the run.
+                        Err(err) => {
+                            status(format!("{} {err}", style::skipped("Skipping")));
+                            summary.record(false);
+                            continue;
+                        }
+                    };
+
+                    if !matches!(config.command, Command::List { .. })
+                        && let Some(dir) = holding_dir(&headed_dirs, &path)
+                        && current_dir != Some(dir)
+                    {
+                        detail(dir_header(dir, current_dir.is_none()));
+                        current_dir = Some(dir);
+                    }
+                    if !file_limit.allow() {
+                        debug!("Stopping at {}: limit reached", path.display());
+                        summary.limited = limit;
+                        break;
+                    }
+                    if ask_each {
+                        let size =
+                            file_size(&path).map_or("size unknown".to_string(), util::format_size);
+                        let name = path.file_name().unwrap_or_default().to_string_lossy();
+                        match prompt::ask(&format!("Delete {name} ({size})?"))? {
+                            prompt::Answer::Yes => {}
+                            prompt::Answer::All => ask_each = false,
+                            prompt::Answer::No => {
+                                summary.record_skip(SkipReason::Declined);
+                                continue;
+                            }
+                            prompt::Answer::Quit => {
+                                summary.record_skip(SkipReason::Declined);
+                                status("Stopped; the remaining files were left alone.".to_string());
+                                break;
+                            }
+                        }
+                    }
+                    debug!("Processing {}", path.display());
+                    if archive.is_some() && !duplicates.contains(&path) {
+                        to_archive.push(path);
+                        continue;
+                    }
+                    if jobs > 1 {
+                        parallel.push(path);
+                        continue;
+                    }
+                    let bytes = file_size(&path);
+                    let result = match config.command {
+                        Command::List { .. } => LogFile::from_path(path.clone())
+                            .map(|log_file| listed.push(log_file))
+                            .inspect_err(|err| {
+                                status(format!(
+                                    "{} {}: {err}",
+                                    style::failure("Failed to read"),
+                                    path.display()
+                                ))
+                            }),
+                        _ => file_op(&path),
+                    };
+                    record_move(&path, &result);
+                    record_file(
+                        &mut summary,
+                        progress.as_mut(),
+                        &path,
+                        bytes,
+                        &result,
+                        freed_by(&path),
+                    );
+                }
+
+                // An archive needs every file at once, so it is written after
+                // the loop has collected them.
+                if let Some(archive) = archive.as_ref().filter(|_| !to_archive.is_empty()) {
+                    let frees_space = delete_after && !dry_run;
+                    let sizes: Vec<_> = to_archive.iter().map(|file| file_size(file)).collect();
+                    match commands::backup::archive_files(
+                        &to_archive,
+                        archive,
+                        delete_after,
+                        dry_run,
+                    ) {
+                        Ok(results) => {
+                            for ((file, bytes), result) in to_archive.iter().zip(sizes).zip(results)
+                            {
+                                record_file(
+                                    &mut summary,
+                                    progress.as_mut(),
+                                    file,
+                                    bytes,
+                                    &result,
+                                    frees_space,
+                                );
+                            }
+                        }
+                        Err(err) => {
+                            status(format!(
+                                "{} could not write {}: {err}",
+                                style::failure("Failed:"),
+                                archive.display()
+                            ));
+                            for _ in &to_archive {
+                                summary.record(false);
+                            }
+                        }
+                    }
+                }
+
+                if let Command::Backup { .. } = config.command
+                    && !dry_run
+                {
+                    // Create each backup directory before the workers start so
+                    // they never race to create the same one.
+                    for path in parallel.iter().filter(|path| !duplicates.contains(*path)) {
+                        fs::create_dir_all(commands::backup::backup_dir_for(path, &backup_dir))?;
+                    }
+                }
+                if !parallel.is_empty() {
+                    debug!("Handing {} files to {jobs} workers", parallel.len());
+                }
+                jobs::for_each_file(
+                    parallel,
+                    jobs,
+                    |path| (!interrupt::is_interrupted()).then(|| (file_size(path), file_op(path))),
+                    |path, outcome| match outcome {
+                        Some((bytes, result)) => {
+                            record_move(path, &result);
+                            record_file(
+                                &mut summary,
+                                progress.as_mut(),
+                                path,
+                                bytes,
+                                &result,
+                                freed_by(path),
+                            )
+                        }
+                        None => summary.interrupted = true,
+                    },
+                );
+                if let Some(progress) = progress.as_mut() {
+                    progress.finish();
+                }
+
+                if let Some(budget) = max_bak_size {
+                    // The manifest is rewritten below, so the run's batch
+                    // has to be complete first.
+                    drop(undo_log);
+                    let pruned =
+                        commands::backup::enforce_budgets(&budget_dirs, budget, &mut summary);
+                    if let Err(err) = commands::undo::forget_backups(path, &pruned) {
+                        eprintln!("Could not drop the pruned backups from undo: {err}");
+                    }
+                }
+
+                if let Command::List { .. } = config.command {
+                    if let Some(key) = sort {
+                        commands::list::sort_log_files(&mut listed, key);
+                    }
+                    if reverse {
+                        listed.reverse();
+                    }
+                    if let Some(limit) =
+                        limit.filter(|&limit| limit_after_sort && listed.len() > limit)
+                    {
+                        listed.truncate(limit);
+                        summary.limited = Some(limit);
+                    }
+                    listed_dirs.sort_by_key(DirSummary::name);
+                    commands::list::list_by_dir(
+                        &listed,
+                        &listed_dirs,
+                        &headed_dirs,
+                        output,
+                        names_only,
+                        group_by_ext,
+                        no_header,
+                    )?;
+                }
+                if let Some(pattern) = &filter.pattern
+                    && summary.processed == 0
+                {
+                    status(format!("No files matched {pattern}"));
+                }
+                status(summary.report_line());
+                if let Some(line) = summary.skip_line() {
+                    status(line);
+                }
+                if filter.empty_only {
+                    let noun = if summary.succeeded == 1 {
+                        "file"
+                    } else {
+                        "files"
+                    };
+                    let verb = if dry_run { "Would remove" } else { "Removed" };
+                    status(format!("{verb} {} empty {noun}.", summary.succeeded));
+                }
+                if frees_space || summary.freed_files > 0 {
+                    status(summary.freed_line());
+                }
+            }
+        }
+        Ok(None)
+    })();
+    let exit_code = match &outcome {
+        Ok(Some(code)) => *code,
+        Ok(None) => summary.exit_code(),
+        Err(_) => 1,
+    };
+    let duration = start.elapsed();
+    if let Ok(None) = outcome {
+        status(format!("Completed in: {duration:?}"));
+    }
+
+    if let Some(summary_file) = &summary_file {
+        fs::write(summary_file, summary.to_json(duration, exit_code) + "\n")?;
+    } else if summary_json {
+        eprintln!("{}", summary.to_json(duration, exit_code));
+    }
+    outcome.map(|_| ExitCode::from(exit_code))
+}
+
+/// The log directory used for `default`: the `FFLOGS_DIR` environment
+/// variable, then `fflogs_dir` from the config file, or else the platform's
+/// usual ACT location. A usage error unless `allow_default`.
+fn default_fflogs_dir(allow_default: bool) -> Result<PathBuf, ExitCode> {
+    if !allow_default {
+        return Err(usage_error(&format!(
+            "No log directory given and --no-default (or {}) forbids using the default one; pass the directory explicitly.",
+            paths::NO_DEFAULT_VAR
+        )));
+    }
+    if let Some(dir) = paths::fflogs_dir_from_env(env::var_os(paths::FFLOGS_DIR_VAR)) {
+        debug!("Using {} from the environment", paths::FFLOGS_DIR_VAR);
+        return Ok(expand_dir(&dir));
+    }
+    match FileConfig::load() {
+        Ok(FileConfig {
+            fflogs_dir: Some(dir),
+        }) => {
+            debug!("Using fflogs_dir from the config file");
+            return Ok(expand_dir(Path::new(&dir)));
+        }
+        Ok(_) => {}
+        Err(err) => {
+            eprintln!("{err}");
+            return Err(ExitCode::from(2));
+        }
+    }
+    paths::default_fflogs_dir().ok_or_else(|| {
+        usage_error("Could not detect the default ACT log directory; pass --fflogs-dir explicitly.")
+    })
+}
+
+/// The file selection flags of `list`, `delete` and `backup` as one
+/// filter; the other commands take every file.
+fn file_filter(command: &Command) -> FileFilter {
+    match command {
+        Command::List {
+            pattern,
+            regex,
+            exclude,
+            older_than,
+            newer_than,
+            modified_within,
+            since,
+            until,
+            min_size,
+            max_size,
+            min_lines,
+            max_lines,
+            strict,
+            ..
+        }
+        | Command::Delete {
+            pattern,
+            regex,
+            exclude,
+            older_than,
+            newer_than,
+            modified_within,
+            since,
+            until,
+            min_size,
+            max_size,
+            min_lines,
+            max_lines,
+            strict,
+            ..
+        }
+        | Command::Backup {
+            pattern,
+            regex,
+            exclude,
+            older_than,
+            newer_than,
+            modified_within,
+            since,
+            until,
+            min_size,
+            max_size,
+            min_lines,
+            max_lines,
+            strict,
+            ..
+        } => FileFilter {
+            pattern: pattern.clone(),
+            regex: regex.clone(),
+            exclude: exclude.clone(),
+            older_than: *older_than,
+            newer_than: *newer_than,
+            modified_within: *modified_within,
+            since: *since,
+            until: *until,
+            min_size: *min_size,
+            max_size: *max_size,
+            min_lines: *min_lines,
+            max_lines: *max_lines,
+            empty_only: matches!(
+                command,
+                Command::Delete {
+                    empty_only: true,
+                    ..
+                }
+            ),
+            strict: *strict,
+        },
+        _ => FileFilter::default(),
+    }
+}
+
+/// Whether `delete` asks before deleting anything.
+fn confirm_delete(command: &Command) -> bool {
+    match command {
+        Command::Delete {
+            yes,
+            dry_run,
+            interactive,
+            ..
+        } => !yes && !dry_run && !interactive,
+        _ => false,
+    }
+}
+
+/// Whether `delete` reads an answer from stdin at some point: the
+/// confirmation, or the per-file questions of `--interactive`, which are
+/// asked even in a dry run.
+fn asks_before_deleting(command: &Command) -> bool {
+    confirm_delete(command)
+        || matches!(
+            command,
+            Command::Delete {
+                interactive: true,
+                ..
+            }
+        )
+}
+
+/// How progress is reported, if at all.
+fn progress_format(command: &Command) -> Option<ProgressFormat> {
+    match command {
+        Command::Delete {
+            progress_format,
+            progress,
+            ..
+        }
+        | Command::Backup {
+            progress_format,
+            progress,
+            ..
+        } => progress_format.or(progress.then_some(ProgressFormat::Human)),
+        _ => None,
+    }
+}
+
+/// Whether a run has to gather every entry before handling the first: to
+/// count the files for the confirmation prompt and the plan shown with
+/// it, or for the totals of the progress display.
+fn gathers_entries(command: &Command) -> bool {
+    confirm_delete(command) || progress_format(command).is_some()
+}
+
+/// `dir` as an absolute path, with a leading `~` standing for the home
+/// directory. If the working directory is unknown, relative paths are kept.
+fn expand_dir(dir: &Path) -> PathBuf {
+    let cwd = env::current_dir().unwrap_or_default();
+    paths::expand_path(dir, paths::home_dir().as_deref(), &cwd)
+}
+
+/// Prints the `doctor` checklist for `target` and fails if any check did.
+fn doctor(target: &str, backup_dir: &Path, allow_default: bool) -> Result<ExitCode, io::Error> {
+    let dir = if target == "default" {
+        match default_fflogs_dir(allow_default) {
+            Ok(dir) => dir,
+            Err(code) => return Ok(code),
+        }
+    } else {
+        expand_dir(Path::new(target))
+    };
+    output::emit(format!("Checking {}\n", dir.display()));
+    let candidates = paths::default_dir_candidates();
+    let checks = commands::doctor::diagnose(&dir, backup_dir, &candidates);
+    if commands::doctor::print_checklist(&checks) {
+        Ok(ExitCode::SUCCESS)
+    } else {
+        Ok(ExitCode::FAILURE)
+    }
+}
+
+/// The "37 files (2.1 GB) from <dir>" part of the plan line printed before
+/// a backup or delete.
+fn plan_scope(total: usize, bytes: u64, source: &str) -> String {
+    let files = if total == 1 { "file" } else { "files" };
+    format!(
+        "{total} {files} ({}) from {source}",
+        util::format_size(bytes)
+    )
+}
+
+fn file_size(path: &Path) -> Option<u64> {
+    metadata(path).map(|meta| meta.len()).ok()
+}
+
+/// Counts one processed file in the summary and advances the progress output.
+fn record_file(
+    summary: &mut Summary,
+    progress: Option<&mut Progress>,
+    path: &Path,
+    bytes: Option<u64>,
+    result: &io::Result<()>,
+    frees_space: bool,
+) {
+    if let Err(err) = result
+        && output::is_quiet()
+    {
+        eprintln!("Failed: {}: {err}", path.display());
+    }
+    if result.is_ok() && frees_space {
+        summary.record_freed(bytes);
+    }
+    summary.record(result.is_ok());
+    if let Some(progress) = progress {
+        progress.advance(path, bytes.unwrap_or(0));
+    }
+}
+
+const USAGE: &str = "Usage: ff-log-cli <list|delete|backup|restore|undo|rename|view|stats|index|search|export|anonymize|split|merge|watch|doctor> [--fflogs-dir <DIR>]";
+
+fn usage_error(message: &str) -> ExitCode {
+    ExitCode::from(usage_error_code(message))
+}
+
+/// Prints a usage error and returns its exit code, 2.
+fn usage_error_code(message: &str) -> u8 {
+    eprintln!("{message}");
+    eprintln!("{USAGE}");
+    eprintln!("Run 'ff-log-cli --help' for more information.");
+    2
+}
+
+#[derive(Parser)]
+#[command(name = "ff-log-cli")]
+#[command(
+    about = "A CLI tool for managing Final Fantasy XIV log files generated by Advanced Combat Tracker (ACT)"
+)]
+#[command(
+    long_about = "ff-log-cli helps you manage Final Fantasy XIV combat log files from ACT.\n\nSupported operations:\n  • List log files in a directory\n  • Backup log files to a 'bak/' subdirectory\n  • Delete log files permanently\n  • View log file contents interactively\n  • Summarize log files with counts, sizes and dates\n  • Restore backed up log files from 'bak/'\n  • Undo the most recent backup run\n  • Rename log files to a sortable YYYY-MM-DD_HH-MM.log scheme\n  • Search all log files for a line containing some text\n  • Export a log file to CSV for spreadsheet analysis\n  • Anonymize a log by replacing player names with Player1, Player2...\n  • Split a log into one file per encounter\n  • Merge logs split across several files into one, in time order\n  • Watch for new log files and back them up as ACT finishes them\n  • Check that the log directory is set up correctly\n\nUse 'default' as the directory to auto-detect the ACT log folder location, or set the FFLOGS_DIR environment variable or fflogs_dir in ~/.config/ff-log-cli/config.toml (%APPDATA%\\ff-log-cli\\config.toml on Windows)."
+)]
+#[command(
+    after_long_help = "Exit status:\n    0  every file was processed successfully\n    1  at least one file failed, or an error stopped the run\n    2  usage error, e.g. an invalid directory or config file\n  130  interrupted with Ctrl-C; the file in flight was finished first"
+)]
+#[command(version)]
+pub struct Config {
+    #[command(subcommand)]
+    pub command: Command,
+
+    /// Explain what the tool decides
+    ///
+    /// `-v` also reports paths that are passed over, such as
+    /// subdirectories. `-vv` additionally traces every decision on stderr:
+    /// which directory was resolved, each file scanned, why a file was
+    /// filtered out and where it is moved.
+    #[arg(
+        short,
+        long,
+        global = true,
+        action = clap::ArgAction::Count,
+        help = "Print more detail (-v skipped paths, -vv debug trace)"
+    )]
+    pub verbose: u8,
+
+    /// When to color the per-file status words
+    ///
+    /// `auto` colors only when stdout is a terminal and the `NO_COLOR`
+    /// environment variable is not set.
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        default_value = "auto",
+        help = "Color status output: auto, always or never"
+    )]
+    pub color: ColorChoice,
+
+    /// Prefix every status line with the time it was printed
+    ///
+    /// The time is an ISO-8601 UTC timestamp such as
+    /// `[2024-01-01T20:15:42Z]`. JSON output is never prefixed.
+    #[arg(long, global = true, help = "Prefix status lines with the time")]
+    pub timestamps: bool,
+
+    /// Refuse to run on the `default` log directory
+    ///
+    /// For scripts: when the directory argument is left out or is
+    /// `default`, the run stops with a usage error instead of falling back
+    /// to FFLOGS_DIR, the config file or the detected ACT folder, so a
+    /// mis-set variable cannot touch the real logs. Setting
+    /// FF_LOG_CLI_NO_DEFAULT to any non-empty value does the same.
+    #[arg(
+        long,
+        global = true,
+        help = "Fail instead of using the default log directory"
+    )]
+    pub no_default: bool,
+
+    /// How often to retry a move or delete that fails only for a moment
+    ///
+    /// While ACT has a log open, Windows refuses to move or delete it with a
+    /// sharing violation. Such failures are retried after 100 ms, then
+    /// 200 ms, 400 ms... before the file is reported as failed. `0` gives
+    /// up at the first failure.
+    #[arg(
+        long,
+        global = true,
+        value_name = "N",
+        default_value_t = retry::DEFAULT_RETRIES,
+        help = "Retry transient move/delete failures N times"
+    )]
+    pub retries: u32,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// List all log files in the specified directory
+    ///
+    /// This command displays all log files found in the target directory.
+    /// Use this to see what log files are available before performing
+    /// backup or delete operations.
+    ///
+    /// Examples:
+    ///   ff-log-cli list
+    ///   ff-log-cli list --fflogs-dir "C:\ACT\Logs"
+    ///   ff-log-cli list -f "/home/user/ACT/Logs"
+    List {
+        /// Directory containing FFXIV log files (use "default" for auto-detection)
+        ///
+        /// Specify the path to your ACT log directory, or use "default" to
+        /// automatically detect the standard ACT installation location:
+        /// - Windows: %APPDATA%\Advanced Combat Tracker\FFXIVLogs
+        /// - macOS: ~/Library/Application Support/Advanced Combat Tracker/FFXIVLogs
+        ///   (Wine/CrossOver), falling back to $HOME/Advanced Combat Tracker/FFXIVLogs
+        /// - Other Unix: $HOME/Advanced Combat Tracker/FFXIVLogs
+        ///
+        /// Individual log files may be given instead of (or next to) a
+        /// directory, e.g. `-f Network_1.log Network_2.log`; they are
+        /// processed directly without scanning. A path that does not exist
+        /// is reported and counted as failed while the others still run.
+        #[arg(
+            short,
+            long,
+            value_name = "PATH",
+            num_args = 1..,
+            default_value = "default",
+            help = "Log directory, 'default' for auto-detection, or log files"
+        )]
+        fflogs_dir: Vec<String>,
+
+        /// Log directories or files, the same as --fflogs-dir without the flag
+        #[arg(
+            value_name = "PATH",
+            conflicts_with = "fflogs_dir",
+            help = "Same as --fflogs-dir"
+        )]
+        paths: Vec<String>,
+
+        /// Only process files whose name matches GLOB
+        ///
+        /// Supports '*' and '?' wildcards and matches the file name only,
+        /// case-insensitively, e.g. `--pattern "Network_202401*.log"`.
+        #[arg(
+            long,
+            value_name = "GLOB",
+            help = "Only process files whose name matches GLOB"
+        )]
+        pattern: Option<String>,
+
+        /// Only process files whose name matches the regular expression REGEX
+        ///
+        /// Matches anywhere in the file name unless anchored with `^` or `$`,
+        /// e.g. `--regex '^Network_\d{8}\.log$'`. Prefix `(?i)` to ignore
+        /// case. Cannot be combined with `--pattern`.
+        #[arg(
+            long,
+            value_name = "REGEX",
+            value_parser = Regex::parse,
+            conflicts_with = "pattern",
+            help = "Only process files whose name matches REGEX"
+        )]
+        regex: Option<Regex>,
+
+        /// Skip files whose name matches GLOB
+        ///
+        /// Uses the same wildcards as `--pattern` and is applied after it, so
+        /// a file matching both is skipped. Repeat the flag to exclude
+        /// several globs, e.g. `--exclude "*_current.log"`.
+        #[arg(
+            long,
+            value_name = "GLOB",
+            help = "Skip files whose name matches GLOB (repeatable)"
+        )]
+        exclude: Vec<String>,
+
+        /// Descend into subdirectories and process every file found
+        ///
+        /// Subdirectories are walked depth-first instead of being ignored,
+        /// and each directory is visited only once so symlink loops are safe.
+        #[arg(short, long, help = "Process files in subdirectories too")]
+        recursive: bool,
+
+        /// Descend at most N directory levels below the log directory
+        ///
+        /// 0 keeps to the log directory itself, 1 also covers its immediate
+        /// subdirectories, and so on. Deeper directories are passed over
+        /// like any other directory (reported with `-v`).
+        #[arg(
+            long,
+            value_name = "N",
+            requires = "recursive",
+            help = "Limit how many directory levels --recursive descends"
+        )]
+        max_depth: Option<usize>,
+
+        /// Only process files last modified at least DAYS days ago
+        #[arg(
+            long,
+            value_name = "DAYS",
+            help = "Only process files older than DAYS days"
+        )]
+        older_than: Option<u64>,
+
+        /// Only process files last modified at most DAYS days ago
+        ///
+        /// Combine with --older-than to select a window, e.g.
+        /// `--older-than 7 --newer-than 30`.
+        #[arg(
+            long,
+            value_name = "DAYS",
+            help = "Only process files newer than DAYS days"
+        )]
+        newer_than: Option<u64>,
+
+        /// Only process files last modified within DURATION of now
+        ///
+        /// DURATION is a number with a unit: `s`, `m`, `h`, `d` or `w`,
+        /// combined as in `1h30m`. Handy for picking just the current
+        /// session, e.g. `--modified-within 2h`.
+        #[arg(
+            long,
+            value_name = "DURATION",
+            value_parser = util::parse_duration,
+            help = "Only process files modified within DURATION (e.g. 2h, 1h30m)"
+        )]
+        modified_within: Option<Duration>,
+
+        /// Only process files of at least SIZE bytes
+        ///
+        /// Accepts K, M and G suffixes (binary units), e.g. `10M`.
+        #[arg(
+            long,
+            value_name = "SIZE",
+            value_parser = util::parse_size,
+            help = "Only process files of at least SIZE (e.g. 10M)"
+        )]
+        min_size: Option<u64>,
+
+        /// Only process files of at most SIZE bytes
+        #[arg(
+            long,
+            value_name = "SIZE",
+            value_parser = util::parse_size,
+            help = "Only process files of at most SIZE (e.g. 1G)"
+        )]
+        max_size: Option<u64>,
+
+        /// Only process files with at least N lines
+        ///
+        /// Skips the stubs of aborted pulls. Every file is read in full to
+        /// count its lines, so this is slower than the other filters and
+        /// only done when --min-lines or --max-lines is given.
+        #[arg(
+            long,
+            value_name = "N",
+            help = "Only process files with at least N lines"
+        )]
+        min_lines: Option<usize>,
+
+        /// Only process files with at most N lines
+        #[arg(
+            long,
+            value_name = "N",
+            help = "Only process files with at most N lines"
+        )]
+        max_lines: Option<usize>,
+
+        /// Stop after N files, once every filter has been applied
+        ///
+        /// Handy for trying a rule on a few files first. For `list` with
+        /// `--sort`, the first N files in the sorted order are shown.
+        #[arg(long, value_name = "N", help = "Process at most N files")]
+        limit: Option<usize>,
+
+        /// Only process files dated on or after DATE
+        ///
+        /// The date in an ACT file name (`Network_26802_20240101.log`) is
+        /// used when present, otherwise the modification date in UTC.
+        #[arg(
+            long,
+            value_name = "YYYY-MM-DD",
+            value_parser = Date::parse,
+            help = "Only process files dated on or after this day"
+        )]
+        since: Option<Date>,
+
+        /// Only process files dated on or before DATE
+        #[arg(
+            long,
+            value_name = "YYYY-MM-DD",
+            value_parser = Date::parse,
+            help = "Only process files dated on or before this day"
+        )]
+        until: Option<Date>,
+
+        /// Skip files that do not look like ACT network logs
+        ///
+        /// A file qualifies when it has a `.log` extension and its first
+        /// line parses as an ACT event. Other files are reported as
+        /// `Skipping non-log file: <name>` and left untouched.
+        #[arg(long, help = "Only process files that look like ACT logs")]
+        strict: bool,
+
+        /// List subdirectories with their total size and file count
+        ///
+        /// Without this flag subdirectories are passed over (mentioned only
+        /// with --verbose). Delete and backup always leave them alone.
+        #[arg(long, help = "Include subdirectories in the listing")]
+        include_dirs: bool,
+
+        /// Operate on the files listed in a JSON array instead of scanning the directory
+        ///
+        /// Accepts an array of objects with a "name" or "path" key, or of
+        /// plain path strings; use '-' to read stdin.
+        /// Relative entries are resolved against the log directory, and
+        /// entries that no longer exist are reported as skipped.
+        #[arg(
+            long,
+            value_name = "FILE",
+            help = "Read the files to process from a JSON array ('-' for stdin)"
+        )]
+        paths_from_json: Option<String>,
+
+        /// Print a JSON summary of the run to stderr when it finishes
+        ///
+        /// The object holds the operation, resolved directory, processed,
+        /// succeeded, failed and skipped counts, the --limit that stopped
+        /// the run, freed bytes, elapsed milliseconds and the exit code,
+        /// and is written however the run ends, even when it stops early or
+        /// some files failed.
+        #[arg(long, help = "Print a JSON run summary to stderr")]
+        summary_json: bool,
+
+        /// Write the JSON run summary to a file instead of stderr
+        #[arg(long, value_name = "FILE", help = "Write the JSON run summary to FILE")]
+        summary_file: Option<PathBuf>,
+
+        /// Output format for the file listing
+        ///
+        /// 'text' prints the name, size and date of each file in aligned
+        /// columns; 'table' adds a header row over them. 'json' prints a
+        /// single array of {"name","size","modified"} objects on stdout,
+        /// with `modified` as an RFC 3339 UTC timestamp; status messages
+        /// move to stderr so the output can be piped straight into other
+        /// tools.
+        #[arg(
+            long,
+            value_enum,
+            default_value = "text",
+            help = "Output format (text, table or json)"
+        )]
+        output: OutputFormat,
+
+        /// Leave out the header row of `--output table`
+        #[arg(long, help = "Omit the table header row")]
+        no_header: bool,
+
+        /// Sort the listed files by name, size or modification date
+        ///
+        /// Names sort A to Z; sizes and dates sort largest and newest first,
+        /// which makes the biggest space hogs easy to spot. Without this
+        /// option files are listed in the order the directory returns them.
+        #[arg(long, value_enum, help = "Sort by name, size or date")]
+        sort: Option<SortKey>,
+
+        /// Reverse the listing order
+        #[arg(long, help = "Reverse the listing order")]
+        reverse: bool,
+
+        /// Print only `- name` lines instead of the name, size and date columns
+        ///
+        /// Keeps the original terse text output for scripts that parse it.
+        #[arg(long, help = "Print file names only")]
+        names_only: bool,
+
+        /// Group the listing under one header per file extension
+        ///
+        /// Each header shows the extension with its file count and total
+        /// size, e.g. `.log (12 files, 1.4 GB)`; files without an extension
+        /// are grouped under `(none)`. Useful for seeing what a directory
+        /// holds before deleting anything.
+        #[arg(
+            long,
+            conflicts_with = "output",
+            help = "Group files by extension with counts and sizes"
+        )]
+        group_by_ext: bool,
+    },
+    /// Permanently delete all log files in the specified directory
+    ///
+    /// WARNING: This operation cannot be undone! All log files in the
+    /// target directory will be permanently removed from your system.
+    /// Consider using the 'backup' command first to create copies.
+    ///
+    /// Examples:
+    ///   ff-log-cli delete
+    ///   ff-log-cli delete --fflogs-dir "C:\ACT\Logs"
+    Delete {
+        /// Directory containing FFXIV log files (use "default" for auto-detection)
+        ///
+        /// Specify the path to your ACT log directory, or use "default" to
+        /// automatically detect the standard ACT installation location.
+        ///
+        /// Individual log files may be given instead of (or next to) a
+        /// directory, e.g. `-f Network_1.log Network_2.log`; they are
+        /// processed directly without scanning. A path that does not exist
+        /// is reported and counted as failed while the others still run.
+        #[arg(
+            short,
+            long,
+            value_name = "PATH",
+            num_args = 1..,
+            default_value = "default",
+            help = "Log directory, 'default' for auto-detection, or log files"
+        )]
+        fflogs_dir: Vec<String>,
+
+        /// Log directories or files, the same as --fflogs-dir without the flag
+        #[arg(
+            value_name = "PATH",
+            conflicts_with = "fflogs_dir",
+            help = "Same as --fflogs-dir"
+        )]
+        paths: Vec<String>,
+
+        /// Only process files whose name matches GLOB
+        ///
+        /// Supports '*' and '?' wildcards and matches the file name only,
+        /// case-insensitively, e.g. `--pattern "Network_202401*.log"`.
+        #[arg(
+            long,
+            value_name = "GLOB",
+            help = "Only process files whose name matches GLOB"
+        )]
+        pattern: Option<String>,
+
+        /// Only process files whose name matches the regular expression REGEX
+        ///
+        /// Matches anywhere in the file name unless anchored with `^` or `$`,
+        /// e.g. `--regex '^Network_\d{8}\.log$'`. Prefix `(?i)` to ignore
+        /// case. Cannot be combined with `--pattern`.
+        #[arg(
+            long,
+            value_name = "REGEX",
+            value_parser = Regex::parse,
+            conflicts_with = "pattern",
+            help = "Only process files whose name matches REGEX"
+        )]
+        regex: Option<Regex>,
+
+        /// Skip files whose name matches GLOB
+        ///
+        /// Uses the same wildcards as `--pattern` and is applied after it, so
+        /// a file matching both is skipped. Repeat the flag to exclude
+        /// several globs, e.g. `--exclude "*_current.log"`.
+        #[arg(
+            long,
+            value_name = "GLOB",
+            help = "Skip files whose name matches GLOB (repeatable)"
+        )]
+        exclude: Vec<String>,
+
+        /// Descend into subdirectories and process every file found
+        ///
+        /// Subdirectories are walked depth-first instead of being ignored,
+        /// and each directory is visited only once so symlink loops are safe.
+        #[arg(short, long, help = "Process files in subdirectories too")]
+        recursive: bool,
+
+        /// Descend at most N directory levels below the log directory
+        ///
+        /// 0 keeps to the log directory itself, 1 also covers its immediate
+        /// subdirectories, and so on. Deeper directories are passed over
+        /// like any other directory (reported with `-v`).
+        #[arg(
+            long,
+            value_name = "N",
+            requires = "recursive",
+            help = "Limit how many directory levels --recursive descends"
+        )]
+        max_depth: Option<usize>,
+
+        /// Only process files last modified at least DAYS days ago
+        #[arg(
+            long,
+            value_name = "DAYS",
+            help = "Only process files older than DAYS days"
+        )]
+        older_than: Option<u64>,
+
+        /// Only process files last modified at most DAYS days ago
+        ///
+        /// Combine with --older-than to select a window, e.g.
+        /// `--older-than 7 --newer-than 30`.
+        #[arg(
+            long,
+            value_name = "DAYS",
+            help = "Only process files newer than DAYS days"
+        )]
+        newer_than: Option<u64>,
+
+        /// Only process files last modified within DURATION of now
+        ///
+        /// DURATION is a number with a unit: `s`, `m`, `h`, `d` or `w`,
+        /// combined as in `1h30m`. Handy for picking just the current
+        /// session, e.g. `--modified-within 2h`.
+        #[arg(
+            long,
+            value_name = "DURATION",
+            value_parser = util::parse_duration,
+            help = "Only process files modified within DURATION (e.g. 2h, 1h30m)"
+        )]
+        modified_within: Option<Duration>,
+
+        /// Only process files of at least SIZE bytes
+        ///
+        /// Accepts K, M and G suffixes (binary units), e.g. `10M`.
+        #[arg(
+            long,
+            value_name = "SIZE",
+            value_parser = util::parse_size,
+            help = "Only process files of at least SIZE (e.g. 10M)"
+        )]
+        min_size: Option<u64>,
+
+        /// Only process files of at most SIZE bytes
+        #[arg(
+            long,
+            value_name = "SIZE",
+            value_parser = util::parse_size,
+            help = "Only process files of at most SIZE (e.g. 1G)"
+        )]
+        max_size: Option<u64>,
+
+        /// Only process files with at least N lines
+        ///
+        /// Skips the stubs of aborted pulls. Every file is read in full to
+        /// count its lines, so this is slower than the other filters and
+        /// only done when --min-lines or --max-lines is given.
+        #[arg(
+            long,
+            value_name = "N",
+            help = "Only process files with at least N lines"
+        )]
+        min_lines: Option<usize>,
+
+        /// Only process files with at most N lines
+        #[arg(
+            long,
+            value_name = "N",
+            help = "Only process files with at most N lines"
+        )]
+        max_lines: Option<usize>,
+
+        /// Stop after N files, once every filter has been applied
+        ///
+        /// Handy for trying a rule on a few files first. For `list` with
+        /// `--sort`, the first N files in the sorted order are shown.
+        #[arg(long, value_name = "N", help = "Process at most N files")]
+        limit: Option<usize>,
+
+        /// Only process files dated on or after DATE
+        ///
+        /// The date in an ACT file name (`Network_26802_20240101.log`) is
+        /// used when present, otherwise the modification date in UTC.
+        #[arg(
+            long,
+            value_name = "YYYY-MM-DD",
+            value_parser = Date::parse,
+            help = "Only process files dated on or after this day"
+        )]
+        since: Option<Date>,
+
+        /// Only process files dated on or before DATE
+        #[arg(
+            long,
+            value_name = "YYYY-MM-DD",
+            value_parser = Date::parse,
+            help = "Only process files dated on or before this day"
+        )]
+        until: Option<Date>,
+
+        /// Skip files that do not look like ACT network logs
+        ///
+        /// A file qualifies when it has a `.log` extension and its first
+        /// line parses as an ACT event. Other files are reported as
+        /// `Skipping non-log file: <name>` and left untouched.
+        #[arg(long, help = "Only process files that look like ACT logs")]
+        strict: bool,
+
+        /// Operate on the files listed in a JSON array instead of scanning the directory
+        ///
+        /// Accepts an array of objects with a "name" or "path" key, or of
+        /// plain path strings; use '-' to read stdin.
+        /// Relative entries are resolved against the log directory, and
+        /// entries that no longer exist are reported as skipped. Reading
+        /// stdin needs --yes or --dry-run, as no prompt could be answered.
+        #[arg(
+            long,
+            value_name = "FILE",
+            help = "Read the files to process from a JSON array ('-' for stdin)"
+        )]
+        paths_from_json: Option<String>,
+
+        /// Print a JSON summary of the run to stderr when it finishes
+        ///
+        /// The object holds the operation, resolved directory, processed,
+        /// succeeded, failed and skipped counts, the --limit that stopped
+        /// the run, freed bytes, elapsed milliseconds and the exit code,
+        /// and is written however the run ends, even when it stops early or
+        /// some files failed.
+        #[arg(long, help = "Print a JSON run summary to stderr")]
+        summary_json: bool,
+
+        /// Write the JSON run summary to a file instead of stderr
+        #[arg(long, value_name = "FILE", help = "Write the JSON run summary to FILE")]
+        summary_file: Option<PathBuf>,
+
+        /// Refuse to run unless each directory contains a file matching GLOB
+        ///
+        /// Guards against pointing a destructive operation at the wrong
+        /// folder, e.g. `--require "Network_*.log"`. Every directory given
+        /// is checked before any file is touched; names match
+        /// case-insensitively.
+        #[arg(
+            long,
+            value_name = "GLOB",
+            help = "Require a file matching GLOB before running"
+        )]
+        require: Option<String>,
+
+        /// Show what would happen without touching any file
+        #[arg(long, help = "Preview the operation without changing any file")]
+        dry_run: bool,
+
+        /// Report progress on stderr while files are processed
+        ///
+        /// 'human' shows a `[done/total] file (~2m remaining)` counter
+        /// (redrawn in place on a terminal, one line per file otherwise),
+        /// 'json' prints throttled objects such as
+        /// {"done":12,"total":340,"bytes_done":1024,"bytes_total":8192,"eta_secs":95,"current":"file.log"}
+        /// for wrappers that drive their own progress UI. The estimate goes
+        /// by the bytes of the last few files. Results stay on stdout.
+        #[arg(long, value_enum, help = "Report progress on stderr (human or json)")]
+        progress_format: Option<ProgressFormat>,
+
+        /// Show a `[done/total] file` counter on stderr
+        ///
+        /// Shorthand for `--progress-format human`. On a terminal the counter
+        /// is redrawn in place on a single line.
+        #[arg(long, help = "Show a progress counter on stderr")]
+        progress: bool,
+
+        /// Process this many files at once
+        ///
+        /// Speeds up large batches on slow disks. With more than one job the
+        /// per-file messages may appear in any order; the final summary is
+        /// still exact.
+        #[arg(
+            long,
+            value_name = "N",
+            default_value_t = 1,
+            value_parser = clap::value_parser!(u16).range(1..),
+            help = "Number of files to process in parallel"
+        )]
+        jobs: u16,
+
+        /// Back up each file before deleting it, keeping the source on any failure
+        ///
+        /// Every file is copied into BACKUP_DIR, the copy is verified against
+        /// the source by SHA-256, and only then is the source removed. If any
+        /// step fails the source is left untouched and the step is reported.
+        /// A relative BACKUP_DIR is resolved against the log directory, so
+        /// `--safe-delete bak` uses the same folder as the 'backup' command.
+        /// With --recursive each file keeps its subfolder inside BACKUP_DIR,
+        /// and a file whose backup already exists is kept, never replaced.
+        #[arg(
+            long,
+            value_name = "BACKUP_DIR",
+            help = "Copy, verify and only then delete each file"
+        )]
+        safe_delete: Option<PathBuf>,
+
+        /// Skip the confirmation prompt
+        ///
+        /// By default the number of files is shown and the deletion only
+        /// proceeds after answering 'y'. Use this flag in scripts.
+        #[arg(short, long, help = "Delete without asking for confirmation")]
+        yes: bool,
+
+        /// Ask about each file in turn instead of once for all of them
+        ///
+        /// Every file is shown as `Delete <name> (<size>)? [y/n/a/q]`:
+        /// 'y' deletes it, 'n' keeps it, 'a' deletes it and all the
+        /// remaining files without asking again, 'q' keeps it and stops.
+        #[arg(
+            short,
+            long,
+            conflicts_with = "yes",
+            help = "Ask before deleting each file (y/n/a/q)"
+        )]
+        interactive: bool,
+
+        /// Move files to the system trash instead of deleting them permanently
+        ///
+        /// Uses the freedesktop.org trash on Linux, ~/.Trash on macOS and the
+        /// Recycle Bin on Windows, so files can be restored from the file
+        /// manager. If the trash is not available the file is left in place
+        /// and the error is reported. A trash on another filesystem cannot
+        /// take the file; use --safe-delete there.
+        #[arg(
+            long,
+            conflicts_with = "safe_delete",
+            help = "Move files to the trash instead of deleting them"
+        )]
+        trash: bool,
+
+        /// Only delete zero-byte files
+        ///
+        /// ACT sometimes leaves empty log stubs behind. With this flag every
+        /// file that has any content is skipped, and the number of empty files
+        /// removed is reported at the end.
+        #[arg(long, help = "Only delete empty (zero-byte) files")]
+        empty_only: bool,
+
+        /// Suppress the per-file status lines
+        ///
+        /// Failures are still reported on stderr, and the closing
+        /// `Processed N files: X ok, Y failed` summary is always printed.
+        #[arg(short, long, help = "Only print failures and the final summary")]
+        quiet: bool,
+    },
+    /// Move log files to a backup directory (creates 'bak/' subdirectory)
+    ///
+    /// This command safely moves all log files to a 'bak/' subdirectory
+    /// within the source directory. The backup directory is created
+    /// automatically if it doesn't exist. This is the recommended way
+    /// to archive old logs while keeping them accessible.
+    ///
+    /// Examples:
+    ///   ff-log-cli backup
+    ///   ff-log-cli backup --fflogs-dir "C:\ACT\Logs"
+    Backup {
+        /// Directory containing FFXIV log files (use "default" for auto-detection)
+        ///
+        /// Specify the path to your ACT log directory, or use "default" to
+        /// automatically detect the standard ACT installation location.
+        ///
+        /// Individual log files may be given instead of (or next to) a
+        /// directory, e.g. `-f Network_1.log Network_2.log`; they are
+        /// processed directly without scanning. A path that does not exist
+        /// is reported and counted as failed while the others still run.
+        #[arg(
+            short,
+            long,
+            value_name = "PATH",
+            num_args = 1..,
+            default_value = "default",
+            help = "Log directory, 'default' for auto-detection, or log files"
+        )]
+        fflogs_dir: Vec<String>,
+
+        /// Log directories or files, the same as --fflogs-dir without the flag
+        #[arg(
+            value_name = "PATH",
+            conflicts_with = "fflogs_dir",
+            help = "Same as --fflogs-dir"
+        )]
+        paths: Vec<String>,
+
+        /// Only process files whose name matches GLOB
+        ///
+        /// Supports '*' and '?' wildcards and matches the file name only,
+        /// case-insensitively, e.g. `--pattern "Network_202401*.log"`.
+        #[arg(
+            long,
+            value_name = "GLOB",
+            help = "Only process files whose name matches GLOB"
+        )]
+        pattern: Option<String>,
+
+        /// Only process files whose name matches the regular expression REGEX
+        ///
+        /// Matches anywhere in the file name unless anchored with `^` or `$`,
+        /// e.g. `--regex '^Network_\d{8}\.log$'`. Prefix `(?i)` to ignore
+        /// case. Cannot be combined with `--pattern`.
+        #[arg(
+            long,
+            value_name = "REGEX",
+            value_parser = Regex::parse,
+            conflicts_with = "pattern",
+            help = "Only process files whose name matches REGEX"
+        )]
+        regex: Option<Regex>,
+
+        /// Skip files whose name matches GLOB
+        ///
+        /// Uses the same wildcards as `--pattern` and is applied after it, so
+        /// a file matching both is skipped. Repeat the flag to exclude
+        /// several globs, e.g. `--exclude "*_current.log"`.
+        #[arg(
+            long,
+            value_name = "GLOB",
+            help = "Skip files whose name matches GLOB (repeatable)"
+        )]
+        exclude: Vec<String>,
+
+        /// Descend into subdirectories and process every file found
+        ///
+        /// Subdirectories are walked depth-first instead of being ignored,
+        /// and each directory is visited only once so symlink loops are safe.
+        #[arg(short, long, help = "Process files in subdirectories too")]
+        recursive: bool,
+
+        /// Descend at most N directory levels below the log directory
+        ///
+        /// 0 keeps to the log directory itself, 1 also covers its immediate
+        /// subdirectories, and so on. Deeper directories are passed over
+        /// like any other directory (reported with `-v`).
+        #[arg(
+            long,
+            value_name = "N",
+            requires = "recursive",
+            help = "Limit how many directory levels --recursive descends"
+        )]
+        max_depth: Option<usize>,
+
+        /// Only process files last modified at least DAYS days ago
+        #[arg(
+            long,
+            value_name = "DAYS",
+            help = "Only process files older than DAYS days"
+        )]
+        older_than: Option<u64>,
+
+        /// Only process files last modified at most DAYS days ago
+        ///
+        /// Combine with --older-than to select a window, e.g.
+        /// `--older-than 7 --newer-than 30`.
+        #[arg(
+            long,
+            value_name = "DAYS",
+            help = "Only process files newer than DAYS days"
+        )]
+        newer_than: Option<u64>,
+
+        /// Only process files last modified within DURATION of now
+        ///
+        /// DURATION is a number with a unit: `s`, `m`, `h`, `d` or `w`,
+        /// combined as in `1h30m`. Handy for picking just the current
+        /// session, e.g. `--modified-within 2h`.
+        #[arg(
+            long,
+            value_name = "DURATION",
+            value_parser = util::parse_duration,
+            help = "Only process files modified within DURATION (e.g. 2h, 1h30m)"
+        )]
+        modified_within: Option<Duration>,
+
+        /// Only process files of at least SIZE bytes
+        ///
+        /// Accepts K, M and G suffixes (binary units), e.g. `10M`.
+        #[arg(
+            long,
+            value_name = "SIZE",
+            value_parser = util::parse_size,
+            help = "Only process files of at least SIZE (e.g. 10M)"
+        )]
+        min_size: Option<u64>,
+
+        /// Only process files of at most SIZE bytes
+        #[arg(
+            long,
+            value_name = "SIZE",
+            value_parser = util::parse_size,
+            help = "Only process files of at most SIZE (e.g. 1G)"
+        )]
+        max_size: Option<u64>,
+
+        /// Only process files with at least N lines
+        ///
+        /// Skips the stubs of aborted pulls. Every file is read in full to
+        /// count its lines, so this is slower than the other filters and
+        /// only done when --min-lines or --max-lines is given.
+        #[arg(
+            long,
+            value_name = "N",
+            help = "Only process files with at least N lines"
+        )]
+        min_lines: Option<usize>,
+
+        /// Only process files with at most N lines
+        #[arg(
+            long,
+            value_name = "N",
+            help = "Only process files with at most N lines"
+        )]
+        max_lines: Option<usize>,
+
+        /// Stop after N files, once every filter has been applied
+        ///
+        /// Handy for trying a rule on a few files first. For `list` with
+        /// `--sort`, the first N files in the sorted order are shown.
+        #[arg(long, value_name = "N", help = "Process at most N files")]
+        limit: Option<usize>,
+
+        /// Only process files dated on or after DATE
+        ///
+        /// The date in an ACT file name (`Network_26802_20240101.log`) is
+        /// used when present, otherwise the modification date in UTC.
+        #[arg(
+            long,
+            value_name = "YYYY-MM-DD",
+            value_parser = Date::parse,
+            help = "Only process files dated on or after this day"
+        )]
+        since: Option<Date>,
+
+        /// Only process files dated on or before DATE
+        #[arg(
+            long,
+            value_name = "YYYY-MM-DD",
+            value_parser = Date::parse,
+            help = "Only process files dated on or before this day"
+        )]
+        until: Option<Date>,
+
+        /// Skip files that do not look like ACT network logs
+        ///
+        /// A file qualifies when it has a `.log` extension and its first
+        /// line parses as an ACT event. Other files are reported as
+        /// `Skipping non-log file: <name>` and left untouched.
+        #[arg(long, help = "Only process files that look like ACT logs")]
+        strict: bool,
+
+        /// Operate on the files listed in a JSON array instead of scanning the directory
+        ///
+        /// Accepts an array of objects with a "name" or "path" key, or of
+        /// plain path strings; use '-' to read stdin.
+        /// Relative entries are resolved against the log directory, and
+        /// entries that no longer exist are reported as skipped.
+        #[arg(
+            long,
+            value_name = "FILE",
+            help = "Read the files to process from a JSON array ('-' for stdin)"
+        )]
+        paths_from_json: Option<String>,
+
+        /// Print a JSON summary of the run to stderr when it finishes
+        ///
+        /// The object holds the operation, resolved directory, processed,
+        /// succeeded, failed and skipped counts, the --limit that stopped
+        /// the run, freed bytes, elapsed milliseconds and the exit code,
+        /// and is written however the run ends, even when it stops early or
+        /// some files failed.
+        #[arg(long, help = "Print a JSON run summary to stderr")]
+        summary_json: bool,
+
+        /// Write the JSON run summary to a file instead of stderr
+        #[arg(long, value_name = "FILE", help = "Write the JSON run summary to FILE")]
+        summary_file: Option<PathBuf>,
+
+        /// Refuse to run unless each directory contains a file matching GLOB
+        ///
+        /// Guards against pointing a destructive operation at the wrong
+        /// folder, e.g. `--require "Network_*.log"`. Every directory given
+        /// is checked before any file is touched; names match
+        /// case-insensitively.
+        #[arg(
+            long,
+            value_name = "GLOB",
+            help = "Require a file matching GLOB before running"
+        )]
+        require: Option<String>,
+
+        /// Show what would happen without touching any file
+        #[arg(long, help = "Preview the operation without changing any file")]
+        dry_run: bool,
+
+        /// Report progress on stderr while files are processed
+        ///
+        /// 'human' shows a `[done/total] file (~2m remaining)` counter
+        /// (redrawn in place on a terminal, one line per file otherwise),
+        /// 'json' prints throttled objects such as
+        /// {"done":12,"total":340,"bytes_done":1024,"bytes_total":8192,"eta_secs":95,"current":"file.log"}
+        /// for wrappers that drive their own progress UI. The estimate goes
+        /// by the bytes of the last few files. Results stay on stdout.
+        #[arg(long, value_enum, help = "Report progress on stderr (human or json)")]
+        progress_format: Option<ProgressFormat>,
+
+        /// Show a `[done/total] file` counter on stderr
+        ///
+        /// Shorthand for `--progress-format human`. On a terminal the counter
+        /// is redrawn in place on a single line.
+        #[arg(long, help = "Show a progress counter on stderr")]
+        progress: bool,
+
+        /// Process this many files at once
+        ///
+        /// Speeds up large batches on slow disks. With more than one job the
+        /// per-file messages may appear in any order; the final summary is
+        /// still exact.
+        #[arg(
+            long,
+            value_name = "N",
+            default_value_t = 1,
+            value_parser = clap::value_parser!(u16).range(1..),
+            help = "Number of files to process in parallel"
+        )]
+        jobs: u16,
+
+        /// Leave the N most recently modified files in place and back up the rest
+        ///
+        /// Files are ordered by modification time, newest first, and the
+        /// first N are skipped so logs you may still analyze stay put.
+        /// `--keep-last 0` backs up every file.
+        #[arg(
+            long,
+            value_name = "N",
+            help = "Keep the N most recent files out of the backup"
+        )]
+        keep_last: Option<usize>,
+
+        /// Delete files whose content duplicates another file instead of backing them up
+        ///
+        /// Each file is hashed with SHA-256; when several share a hash the
+        /// first by name is backed up and the others are removed in their
+        /// turn, after any plan is shown. Empty files all count as
+        /// duplicates of each other.
+        #[arg(long, help = "Remove duplicate files and back up only one copy")]
+        dedupe: bool,
+
+        /// Where backed up files go
+        ///
+        /// A name such as `archive` is created next to each file; an
+        /// absolute path (for example on another drive) collects every
+        /// backup there, creating the whole tree if needed.
+        #[arg(
+            long,
+            value_name = "DIR",
+            default_value = "bak",
+            help = "Backup subdirectory name or absolute path"
+        )]
+        backup_dir: PathBuf,
+
+        /// Check every backup by SHA-256 before removing the original
+        ///
+        /// Backups on another drive are always copied and verified. With this
+        /// flag same-drive backups are copied and verified too instead of
+        /// being renamed; on a mismatch the original is kept and the file is
+        /// reported as failed.
+        #[arg(long, help = "Copy and verify each backup by SHA-256")]
+        verify: bool,
+
+        /// Keep the backup directory under SIZE bytes
+        ///
+        /// After the backup, the oldest files in the backup directory are
+        /// deleted until the rest fit in SIZE, and each removal is reported.
+        /// A file larger than SIZE on its own is kept with a warning. Pruned
+        /// files are dropped from what `undo` restores.
+        /// Accepts K, M and G suffixes (binary units), e.g. `20G`.
+        #[arg(
+            long,
+            value_name = "SIZE",
+            value_parser = util::parse_size,
+            conflicts_with = "archive",
+            help = "Prune the oldest backups to stay under SIZE (e.g. 20G)"
+        )]
+        max_bak_size: Option<u64>,
+
+        /// Bundle the selected logs into one zip archive instead of `bak/`
+        ///
+        /// Each log is stored under its file name with its modification
+        /// time. A relative name is created in the log directory, and an
+        /// existing archive is never overwritten. The logs are left in
+        /// place unless `--delete-after` is given.
+        #[arg(
+            long,
+            value_name = "NAME.zip",
+            help = "Bundle the logs into a zip archive"
+        )]
+        archive: Option<PathBuf>,
+
+        /// Delete the logs once the archive has been written
+        ///
+        /// Nothing is deleted if writing the archive fails.
+        #[arg(
+            long,
+            requires = "archive",
+            help = "Delete the logs after archiving them"
+        )]
+        delete_after: bool,
+
+        /// Suppress the per-file status lines
+        ///
+        /// Failures are still reported on stderr, and the closing
+        /// `Processed N files: X ok, Y failed` summary is always printed.
+        #[arg(short, long, help = "Only print failures and the final summary")]
+        quiet: bool,
+    },
+    /// Interactively view log file contents
+    ///
+    /// This command presents a numbered list of all log files in the
+    /// directory and allows you to select one to view its contents.
+    /// Perfect for quickly inspecting log files before deciding what
+    /// to do with them.
+    ///
+    /// Examples:
+    ///   ff-log-cli view
+    ///   ff-log-cli view --fflogs-dir "C:\ACT\Logs"
+    ///   ff-log-cli view --file Network_26802_20240101.log --head
+    View {
+        /// Directory containing FFXIV log files (use "default" for auto-detection)
+        ///
+        /// Specify the path to your ACT log directory, or use "default" to
+        /// automatically detect the standard ACT installation location.
+        #[arg(
+            short,
+            long,
+            default_value = "default",
+            help = "Path to log directory or 'default' for auto-detection"
+        )]
+        fflogs_dir: String,
+
+        /// The log directory, the same as --fflogs-dir without the flag
+        #[arg(
+            value_name = "DIR",
+            conflicts_with = "fflogs_dir",
+            help = "Same as --fflogs-dir"
+        )]
+        dir: Option<String>,
+
+        /// View the file with this number in the listing instead of prompting
+        ///
+        /// Files are listed in name order, so numbers are stable between
+        /// runs. Without --select or --file and with stdin not a terminal,
+        /// the listing is printed and the command exits.
+        #[arg(
+            long,
+            value_name = "N",
+            conflicts_with = "file",
+            help = "View file number N without prompting"
+        )]
+        select: Option<usize>,
+
+        /// View the file with this name instead of prompting
+        #[arg(
+            long,
+            value_name = "NAME",
+            help = "View the named file without prompting"
+        )]
+        file: Option<String>,
+
+        /// View the most recently modified file instead of prompting
+        ///
+        /// Handy for reading the log of the last pull right after it. An
+        /// empty directory prints the usual message and exits cleanly.
+        #[arg(
+            long,
+            conflicts_with_all = ["select", "file", "oldest"],
+            help = "View the most recently modified file"
+        )]
+        newest: bool,
+
+        /// View the least recently modified file instead of prompting
+        #[arg(
+            long,
+            conflicts_with_all = ["select", "file"],
+            help = "View the least recently modified file"
+        )]
+        oldest: bool,
+
+        /// Show only the first N lines of the selected file
+        ///
+        /// Reading stops after N lines, so the start of a huge log shows up
+        /// instantly. Passing `--head` without a value shows the first 50
+        /// lines.
+        #[arg(
+            long,
+            value_name = "N",
+            num_args = 0..=1,
+            default_missing_value = "50",
+            conflicts_with = "tail",
+            help = "Show only the first N lines (default 50)"
+        )]
+        head: Option<usize>,
+
+        /// Show only the last N lines of the selected file
+        ///
+        /// The file is read backwards from the end, so even logs that are
+        /// hundreds of MB open instantly. Passing `--tail` without a value
+        /// shows the last 50 lines; without the flag the whole file is shown.
+        #[arg(
+            long,
+            value_name = "N",
+            num_args = 0..=1,
+            default_missing_value = "50",
+            help = "Show only the last N lines (default 50)"
+        )]
+        tail: Option<usize>,
+
+        /// Pause after every screenful of output
+        ///
+        /// Press Enter to show the next page or 'q' to stop. The page height
+        /// follows the LINES environment variable and defaults to 24 lines.
+        #[arg(long, help = "Page the output one screenful at a time")]
+        page: bool,
+
+        /// Keep printing lines as they are appended, like `tail -f`
+        ///
+        /// After the selected file is shown (combine with `--tail` to start
+        /// from its last lines), it is checked twice a second for new lines
+        /// until Ctrl-C. If ACT truncates or replaces the file, or starts a
+        /// new log in the same directory, that is followed from its start
+        /// with a "Rotation detected" note. With several files picked at
+        /// the prompt, the last one is followed.
+        #[arg(
+            long,
+            conflicts_with_all = ["head", "page"],
+            help = "Keep printing new lines until Ctrl-C"
+        )]
+        follow: bool,
+
+        /// Browse the logs full screen instead of the numbered prompt
+        ///
+        /// The logs are listed with their size and date, and the start of
+        /// the highlighted one is previewed beside the list. Move with the
+        /// arrow keys, PgUp/PgDn, Home/End (or j/k), press `b` to back up
+        /// the highlighted log to `bak/`, `d` then `y` to delete it, `r` to
+        /// reload the listing and `q` to quit. Only available in builds
+        /// with the `tui` feature, on Unix.
+        #[arg(
+            long,
+            conflicts_with_all = ["select", "file", "newest", "oldest", "head", "tail", "page", "follow"],
+            help = "Browse the logs in a full-screen list with a preview"
+        )]
+        tui: bool,
+
+        /// Show the selected files in the system pager for scrollback
+        ///
+        /// The output is piped to $PAGER, or `less`, or `more`. When stdout
+        /// is not a terminal or no pager can be started, the files are
+        /// printed directly as usual.
+        #[arg(
+            long,
+            conflicts_with_all = ["page", "follow", "tui"],
+            help = "Show the output in $PAGER (or less/more)"
+        )]
+        pager: bool,
+
+        /// Copy the selected file to PATH instead of printing it
+        ///
+        /// PATH is either a directory, which receives the file under its
+        /// own name, or the name of the copy. Combined with --select,
+        /// --file or --newest this extracts a log without any prompt. Several
+        /// files picked at the prompt need an existing directory. An
+        /// existing file is only replaced after confirming, or with --yes.
+        #[arg(
+            long,
+            value_name = "PATH",
+            conflicts_with_all = ["head", "tail", "page", "pager", "follow", "tui"],
+            help = "Copy the selected file to PATH instead of printing it"
+        )]
+        save: Option<PathBuf>,
+
+        /// Overwrite an existing file at the --save destination without asking
+        #[arg(
+            short,
+            long,
+            requires = "save",
+            help = "Overwrite without asking when saving"
+        )]
+        yes: bool,
+
+        /// How the bytes of the log are decoded
+        ///
+        /// Logs are read as UTF-8 by default, with any invalid byte shown
+        /// as U+FFFD instead of stopping the read. Pick `latin1` for older
+        /// logs from Western locales or `shift_jis` for logs written by the
+        /// Japanese client.
+        #[arg(
+            long,
+            value_enum,
+            default_value = "utf8",
+            help = "Text encoding of the logs (utf8, latin1 or shift_jis)"
+        )]
+        encoding: Encoding,
+    },
+    /// Summarize the log files in the specified directory
+    ///
+    /// This command prints an overview of the directory without opening
+    /// any file: how many log files there are, their combined size, the
+    /// largest and smallest files, and the oldest and newest modification
+    /// times. Useful for deciding when it's time to back up or prune.
+    ///
+    /// Examples:
+    ///   ff-log-cli stats
+    ///   ff-log-cli stats --fflogs-dir "C:\ACT\Logs"
+    Stats {
+        /// Directory containing FFXIV log files (use "default" for auto-detection)
+        ///
+        /// Specify the path to your ACT log directory, or use "default" to
+        /// automatically detect the standard ACT installation location.
+        #[arg(
+            short,
+            long,
+            default_value = "default",
+            help = "Path to log directory or 'default' for auto-detection"
+        )]
+        fflogs_dir: String,
+
+        /// The log directory, the same as --fflogs-dir without the flag
+        #[arg(
+            value_name = "DIR",
+            conflicts_with = "fflogs_dir",
+            help = "Same as --fflogs-dir"
+        )]
+        dir: Option<String>,
+
+        /// Count subdirectories too, with the size of everything inside them
+        #[arg(long, help = "Include subdirectories and their contents")]
+        include_dirs: bool,
+
+        /// Also count the distinct players and NPCs in each log
+        ///
+        /// Every file is read in full and its add-combatant (`03`) lines are
+        /// parsed, so this is much slower than the default, which only
+        /// looks at file metadata and the first and last line. The table
+        /// shows the count per file (`?` for files that could not be
+        /// parsed) and the summary the number of distinct names across all
+        /// files.
+        #[arg(long, help = "Count distinct combatants per log (reads every file)")]
+        deep: bool,
+
+        /// Order of the per-file table, which lists each log's size and the
+        /// time between its first and last line
+        #[arg(
+            long,
+            value_enum,
+            default_value = "name",
+            help = "Sort the per-file table by name, size or span"
+        )]
+        sort: StatsSort,
+
+        /// Output format for the statistics
+        ///
+        /// 'text' prints the summary and per-file table; 'table' adds a
+        /// header row over the per-file table. 'json' prints one object
+        /// with file_count, total_bytes, largest, smallest, oldest and
+        /// newest (plus combatants with --deep) on stdout, with status
+        /// messages moved to stderr.
+        #[arg(
+            long,
+            value_enum,
+            default_value = "text",
+            help = "Output format (text, table or json)"
+        )]
+        output: OutputFormat,
+
+        /// Leave out the header row of `--output table`
+        #[arg(long, help = "Omit the table header row")]
+        no_header: bool,
+    },
+    /// Write an index of the logs in a directory for browsing without the tool
+    ///
+    /// Every log is read in full, as with 'stats --deep', and its name,
+    /// size, modification time, span and number of distinct combatants go
+    /// into 'index.json' in the same directory. With --html an
+    /// 'index.html' page with the same table is written too, ready to hand
+    /// to someone along with the folder. Running it again replaces both
+    /// files. Only '.log' files, gzipped or not, are listed.
+    ///
+    /// Examples:
+    ///   ff-log-cli index "C:\ACT\Logs\archive"
+    ///   ff-log-cli index "C:\ACT\Logs\archive" --html
+    Index {
+        /// Directory containing FFXIV log files (use "default" for auto-detection)
+        ///
+        /// Specify the path to your ACT log directory, or use "default" to
+        /// automatically detect the standard ACT installation location.
+        #[arg(
+            short,
+            long,
+            default_value = "default",
+            help = "Path to log directory or 'default' for auto-detection"
+        )]
+        fflogs_dir: String,
+
+        /// The log directory, the same as --fflogs-dir without the flag
+        #[arg(
+            value_name = "DIR",
+            conflicts_with = "fflogs_dir",
+            help = "Same as --fflogs-dir"
+        )]
+        dir: Option<String>,
+
+        /// Also write 'index.html', a page with the same table
+        #[arg(long, help = "Write index.html as well as index.json")]
+        html: bool,
+    },
+    /// Move backed up log files out of 'bak/' into the log directory
+    ///
+    /// This command reverses 'backup': every file in the 'bak/'
+    /// subdirectory is moved back next to it. If a file with the same
+    /// name already exists in the log directory, the restored copy gets
+    /// a '.restored' suffix instead of overwriting it.
+    ///
+    /// Examples:
+    ///   ff-log-cli restore
+    ///   ff-log-cli restore --fflogs-dir "C:\ACT\Logs"
+    Restore {
+        /// Directory containing FFXIV log files (use "default" for auto-detection)
+        ///
+        /// Specify the path to your ACT log directory, or use "default" to
+        /// automatically detect the standard ACT installation location.
+        ///
+        /// Several directories may be given; each is handled in turn under
+        /// its own header and the summary covers them all.
+        #[arg(
+            short,
+            long,
+            value_name = "DIR",
+            num_args = 1..,
+            default_value = "default",
+            help = "Path to log directories or 'default' for auto-detection"
+        )]
+        fflogs_dir: Vec<String>,
+
+        /// The log directories, the same as --fflogs-dir without the flag
+        #[arg(
+            value_name = "DIR",
+            conflicts_with = "fflogs_dir",
+            help = "Same as --fflogs-dir"
+        )]
+        dirs: Vec<String>,
+
+        /// Where the backed up files are, as given to `backup --backup-dir`
+        ///
+        /// A name such as `archive` is looked for inside each log directory;
+        /// with an absolute path every directory restores from there.
+        #[arg(
+            long,
+            value_name = "DIR",
+            default_value = "bak",
+            help = "Backup subdirectory name or absolute path"
+        )]
+        backup_dir: PathBuf,
+    },
+    /// Move the files of the most recent backup run back
+    ///
+    /// Each backup records its moves in a '.ff-log-cli-undo' file in the
+    /// log directory. Undo reverses the last run and forgets it, so running
+    /// it again reverts the run before. If any original location is taken
+    /// again or any backup is missing, nothing is moved.
+    ///
+    /// Examples:
+    ///   ff-log-cli undo
+    ///   ff-log-cli undo --fflogs-dir "C:\ACT\Logs"
+    Undo {
+        /// Directory containing FFXIV log files (use "default" for auto-detection)
+        ///
+        /// Specify the path to your ACT log directory, or use "default" to
+        /// automatically detect the standard ACT installation location.
+        #[arg(
+            short,
+            long,
+            default_value = "default",
+            help = "Path to log directory or 'default' for auto-detection"
+        )]
+        fflogs_dir: String,
+
+        /// The log directory, the same as --fflogs-dir without the flag
+        #[arg(
+            value_name = "DIR",
+            conflicts_with = "fflogs_dir",
+            help = "Same as --fflogs-dir"
+        )]
+        dir: Option<String>,
+    },
+    /// Rename log files to YYYY-MM-DD_HH-MM.log
+    ///
+    /// The time comes from the first line of each log, or from its
+    /// modification time (UTC) when that line has no timestamp. Names that
+    /// are already taken get a '_2', '_3'... suffix. Files without a '.log'
+    /// extension are left alone.
+    ///
+    /// Examples:
+    ///   ff-log-cli rename --dry-run
+    ///   ff-log-cli rename --fflogs-dir "C:\ACT\Logs"
+    ///   ff-log-cli rename --format-name "raid_{date}_{index}.{ext}"
+    Rename {
+        /// Directory containing FFXIV log files (use "default" for auto-detection)
+        ///
+        /// Specify the path to your ACT log directory, or use "default" to
+        /// automatically detect the standard ACT installation location.
+        ///
+        /// Several directories may be given; each is handled in turn under
+        /// its own header and the summary covers them all.
+        #[arg(
+            short,
+            long,
+            value_name = "DIR",
+            num_args = 1..,
+            default_value = "default",
+            help = "Path to log directories or 'default' for auto-detection"
+        )]
+        fflogs_dir: Vec<String>,
+
+        /// The log directories, the same as --fflogs-dir without the flag
+        #[arg(
+            value_name = "DIR",
+            conflicts_with = "fflogs_dir",
+            help = "Same as --fflogs-dir"
+        )]
+        dirs: Vec<String>,
+
+        /// Show the new names without renaming anything
+        #[arg(long, help = "Preview the new names without renaming")]
+        dry_run: bool,
+
+        /// Name files after a template instead of YYYY-MM-DD_HH-MM.log
+        ///
+        /// {date} (YYYY-MM-DD) and {time} (HH-MM) come from the first line
+        /// or the modification time, {index} numbers the files from 1 in
+        /// name order and {ext} is the file's extension. Any other
+        /// placeholder is rejected before anything is renamed.
+        #[arg(
+            long,
+            value_name = "TEMPLATE",
+            value_parser = NameTemplate::parse,
+            help = "File name template with {date}, {time}, {index} and {ext}"
+        )]
+        format_name: Option<NameTemplate>,
+
+        /// Lowercase each name
+        ///
+        /// The clean-up flags tidy the current names; with --format-name they
+        /// tidy the names it gives instead. They apply in a fixed order:
+        /// --ascii-only, then --replace-spaces, then --lowercase. A name
+        /// already taken gets a _2, _3... suffix as usual.
+        #[arg(long, help = "Lowercase file names")]
+        lowercase: bool,
+
+        /// Replace each space in a name with an underscore
+        #[arg(long, help = "Replace spaces in file names with underscores")]
+        replace_spaces: bool,
+
+        /// Replace each non-ASCII character in a name with an underscore
+        #[arg(long, help = "Replace non-ASCII characters with underscores")]
+        ascii_only: bool,
+    },
+    /// Search every log file for lines containing some text
+    ///
+    /// Each file is read line by line, so even very large logs are searched
+    /// without loading them into memory. Matches are printed as
+    /// `filename:line_number: line`, followed by the total match count.
+    ///
+    /// Examples:
+    ///   ff-log-cli search "Tank Name"
+    ///   ff-log-cli search --query rampart --ignore-case
+    Search {
+        /// Directory containing FFXIV log files (use "default" for auto-detection)
+        ///
+        /// Specify the path to your ACT log directory, or use "default" to
+        /// automatically detect the standard ACT installation location.
+        #[arg(
+            short,
+            long,
+            default_value = "default",
+            help = "Path to log directory or 'default' for auto-detection"
+        )]
+        fflogs_dir: String,
+
+        /// Text to search for
+        #[arg(
+            value_name = "TEXT",
+            required_unless_present_any = ["query", "opcode"],
+            help = "Text to search for"
+        )]
+        needle: Option<String>,
+
+        /// Text to search for, as an alternative to the positional argument
+        #[arg(
+            long,
+            value_name = "TEXT",
+            conflicts_with = "needle",
+            help = "Text to search for"
+        )]
+        query: Option<String>,
+
+        /// Match letters regardless of case
+        #[arg(short, long, help = "Ignore case when matching")]
+        ignore_case: bool,
+
+        /// Only search lines with this opcode, e.g. 21 for abilities
+        ///
+        /// Can be repeated. Each line is parsed as an ACT network log event
+        /// and only searched if its opcode is one of those given; lines that
+        /// do not parse are ignored. Without any text, every line with one
+        /// of the opcodes matches.
+        #[arg(
+            long,
+            value_name = "N",
+            help = "Only search lines with opcode N (repeatable)"
+        )]
+        opcode: Vec<u8>,
+
+        /// How the bytes of the log are decoded
+        ///
+        /// Logs are read as UTF-8 by default, with any invalid byte shown
+        /// as U+FFFD instead of stopping the read. Pick `latin1` for older
+        /// logs from Western locales or `shift_jis` for logs written by the
+        /// Japanese client.
+        #[arg(
+            long,
+            value_enum,
+            default_value = "utf8",
+            help = "Text encoding of the logs (utf8, latin1 or shift_jis)"
+        )]
+        encoding: Encoding,
+    },
+    /// Convert a log file to CSV
+    ///
+    /// Each line is parsed as an ACT network log event and written as a row
+    /// with the columns timestamp, opcode, source, target and ability. Lines
+    /// that cannot be parsed are copied to a '.skipped' file next to the CSV.
+    ///
+    /// Examples:
+    ///   ff-log-cli export Network_26802_20240101.log
+    ///   ff-log-cli export Network_26802_20240101.log --out fight.csv
+    Export {
+        /// Directory containing FFXIV log files (use "default" for auto-detection)
+        ///
+        /// Specify the path to your ACT log directory, or use "default" to
+        /// automatically detect the standard ACT installation location.
+        #[arg(
+            short,
+            long,
+            default_value = "default",
+            help = "Path to log directory or 'default' for auto-detection"
+        )]
+        fflogs_dir: String,
+
+        /// Log file to export, relative to the log directory
+        #[arg(value_name = "FILE", help = "Log file to export")]
+        file: PathBuf,
+
+        /// Where to write the CSV; defaults to `<name>.csv` next to the log
+        ///
+        /// With `--json-lines` the events go to stdout unless this is given.
+        #[arg(
+            long,
+            value_name = "PATH",
+            help = "File to write (default: <name>.csv next to the log, or stdout for --json-lines)"
+        )]
+        out: Option<PathBuf>,
+
+        /// Write newline-delimited JSON instead of CSV, one object per event
+        ///
+        /// Every line the parser understands becomes an object with its
+        /// `timestamp`, `opcode` and `type` (`chat`, `add_combatant`,
+        /// `ability` or `other`) plus the fields of that event. Unparsed
+        /// lines are skipped and counted at the end. The log is streamed, so
+        /// even huge logs export in constant memory.
+        #[arg(long, help = "Stream events as JSON lines instead of CSV")]
+        json_lines: bool,
+
+        /// How the bytes of the log are decoded
+        ///
+        /// Logs are read as UTF-8 by default, with any invalid byte shown
+        /// as U+FFFD instead of stopping the read. Pick `latin1` for older
+        /// logs from Western locales or `shift_jis` for logs written by the
+        /// Japanese client.
+        #[arg(
+            long,
+            value_enum,
+            default_value = "utf8",
+            help = "Text encoding of the logs (utf8, latin1 or shift_jis)"
+        )]
+        encoding: Encoding,
+    },
+    /// Replace player names in a log with stable pseudonyms
+    ///
+    /// Every player name becomes `Player1`, `Player2`... in order of
+    /// appearance, the same name always getting the same pseudonym. NPC
+    /// names and all other fields are copied unchanged, so the log can be
+    /// shared publicly. The original file is left untouched.
+    ///
+    /// Examples:
+    ///   ff-log-cli anonymize Network_26802_20240101.log
+    ///   ff-log-cli anonymize Network_26802_20240101.log --out shared.log
+    Anonymize {
+        /// Directory containing FFXIV log files (use "default" for auto-detection)
+        ///
+        /// Specify the path to your ACT log directory, or use "default" to
+        /// automatically detect the standard ACT installation location.
+        #[arg(
+            short,
+            long,
+            default_value = "default",
+            help = "Path to log directory or 'default' for auto-detection"
+        )]
+        fflogs_dir: String,
+
+        /// Log file to anonymize, relative to the log directory
+        #[arg(value_name = "FILE", help = "Log file to anonymize")]
+        file: PathBuf,
+
+        /// Where to write the result; defaults to `<name>.anon.log` next to the log
+        #[arg(
+            long,
+            value_name = "PATH",
+            help = "File to write (default: <name>.anon.log next to the log)"
+        )]
+        out: Option<PathBuf>,
+    },
+    /// Split a log into one file per encounter
+    ///
+    /// ACT keeps writing one file for a whole session. Each wipe or clear,
+    /// and each "... has ended." system message, closes the current part,
+    /// and the parts are written as `<name>_part01.log`, `_part02.log`...
+    /// Lines before the first boundary form part 1. The original file is
+    /// left untouched.
+    ///
+    /// Examples:
+    ///   ff-log-cli split Network_26802_20240101.log
+    ///   ff-log-cli split Network_26802_20240101.log --out-dir pulls
+    Split {
+        /// Directory containing FFXIV log files (use "default" for auto-detection)
+        ///
+        /// Specify the path to your ACT log directory, or use "default" to
+        /// automatically detect the standard ACT installation location.
+        #[arg(
+            short,
+            long,
+            default_value = "default",
+            help = "Path to log directory or 'default' for auto-detection"
+        )]
+        fflogs_dir: String,
+
+        /// Log file to split, relative to the log directory
+        #[arg(value_name = "FILE", help = "Log file to split")]
+        file: PathBuf,
+
+        /// Where to write the parts; defaults to the log directory
+        #[arg(
+            long,
+            value_name = "DIR",
+            help = "Directory for the parts (default: the log directory)"
+        )]
+        out_dir: Option<PathBuf>,
+    },
+    /// Combine log files into one, ordered by timestamp
+    ///
+    /// ACT sometimes splits a single fight across several files. Every line
+    /// is sorted by its leading timestamp; lines without one stay with the
+    /// line before them.
+    ///
+    /// Examples:
+    ///   ff-log-cli merge Network_1.log Network_2.log --out pull.log
+    Merge {
+        /// Directory containing FFXIV log files (use "default" for auto-detection)
+        ///
+        /// Specify the path to your ACT log directory, or use "default" to
+        /// automatically detect the standard ACT installation location.
+        #[arg(
+            short,
+            long,
+            default_value = "default",
+            help = "Path to log directory or 'default' for auto-detection"
+        )]
+        fflogs_dir: String,
+
+        /// Log files to merge, relative to the log directory
+        #[arg(
+            value_name = "FILE",
+            num_args = 2..,
+            required = true,
+            help = "Log files to merge"
+        )]
+        files: Vec<PathBuf>,
+
+        /// Where to write the merged log, relative to the log directory
+        #[arg(long, value_name = "PATH", help = "Merged log file to write")]
+        out: PathBuf,
+    },
+    /// Back up (or delete) new log files as ACT finishes writing them
+    ///
+    /// The directory is polled for files that appear or change after the
+    /// watch starts. Once a file has stayed the same for 5 seconds it is
+    /// handed to the chosen action. Files already there are left alone.
+    /// When ACT rotates to a new log, the previous one is handed over at
+    /// once. Press Ctrl-C to stop; a summary of the handled files is printed.
+    ///
+    /// Examples:
+    ///   ff-log-cli watch
+    ///   ff-log-cli watch --action delete --fflogs-dir "C:\ACT\Logs"
+    ///   ff-log-cli watch --backup-dir "D:\FFXIV Backups"
+    Watch {
+        /// Directory containing FFXIV log files (use "default" for auto-detection)
+        ///
+        /// Specify the path to your ACT log directory, or use "default" to
+        /// automatically detect the standard ACT installation location.
+        #[arg(
+            short,
+            long,
+            default_value = "default",
+            help = "Path to log directory or 'default' for auto-detection"
+        )]
+        fflogs_dir: String,
+
+        /// The log directory, the same as --fflogs-dir without the flag
+        #[arg(
+            value_name = "DIR",
+            conflicts_with = "fflogs_dir",
+            help = "Same as --fflogs-dir"
+        )]
+        dir: Option<String>,
+
+        /// What to do with each finished file
+        #[arg(
+            long,
+            value_enum,
+            default_value = "backup",
+            help = "Action to run on each finished file"
+        )]
+        action: WatchAction,
+
+        /// Where backed up files go, as for `backup --backup-dir`
+        ///
+        /// A name such as `archive` is created inside the log directory; an
+        /// absolute path (for example on another drive) is used as is.
+        #[arg(
+            long,
+            value_name = "DIR",
+            default_value = "bak",
+            help = "Backup subdirectory name or absolute path"
+        )]
+        backup_dir: PathBuf,
+    },
+    /// Check that the log directory is set up correctly
+    ///
+    /// Prints a checklist: whether ACT's default log folder was found on
+    /// this system, whether the directory exists and is readable, how many
+    /// log files it holds, and whether the backup directory exists and is
+    /// writable. Every failed check comes with a hint on how to fix it.
+    /// Exits with status 1 if any check fails.
+    ///
+    /// Examples:
+    ///   ff-log-cli doctor
+    ///   ff-log-cli doctor "C:\ACT\Logs"
+    Doctor {
+        /// Directory containing FFXIV log files (use "default" for auto-detection)
+        ///
+        /// Specify the path to your ACT log directory, or use "default" to
+        /// automatically detect the standard ACT installation location.
+        #[arg(
+            short,
+            long,
+            default_value = "default",
+            help = "Path to log directory or 'default' for auto-detection"
+        )]
+        fflogs_dir: String,
+
+        /// The log directory, the same as --fflogs-dir without the flag
+        #[arg(
+            value_name = "DIR",
+            conflicts_with = "fflogs_dir",
+            help = "Same as --fflogs-dir"
+        )]
+        dir: Option<String>,
+
+        /// The backup directory to check, as given to `backup --backup-dir`
+        #[arg(
+            long,
+            value_name = "DIR",
+            default_value = "bak",
+            help = "Backup subdirectory name or absolute path"
+        )]
+        backup_dir: PathBuf,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json;
+    use clap::Parser;
+    use std::fs::File;
+    use std::io::Write;
+    use std::time::SystemTime;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_config_list_command() {
+        let args = ["program", "list"];
+        let config = Config::try_parse_from(args).unwrap();
+
+        assert!(matches!(config.command, Command::List { .. }));
+        if let Command::List { fflogs_dir, .. } = config.command {
+            assert_eq!(fflogs_dir, ["default"]);
+        }
+    }
+
+    #[test]
+    fn test_config_backup_command() {
+        let args = ["program", "backup"];
+        let config = Config::try_parse_from(args).unwrap();
+
+        assert!(matches!(config.command, Command::Backup { .. }));
+        if let Command::Backup { fflogs_dir, .. } = config.command {
+            assert_eq!(fflogs_dir, ["default"]);
+        }
+    }
+
+    #[test]
+    fn test_config_delete_command() {
+        let args = ["program", "delete"];
+        let config = Config::try_parse_from(args).unwrap();
+
+        assert!(matches!(config.command, Command::Delete { .. }));
+        if let Command::Delete { fflogs_dir, .. } = config.command {
+            assert_eq!(fflogs_dir, ["default"]);
+        }
+    }
+
+    #[test]
+    fn test_config_view_command() {
+        let args = ["program", "view"];
+        let config = Config::try_parse_from(args).unwrap();
+
+        assert!(matches!(config.command, Command::View { .. }));
+        if let Command::View { fflogs_dir, .. } = config.command {
+            assert_eq!(fflogs_dir, "default");
+        }
+    }
+
+    #[test]
+    fn test_config_stats_command() {
+        let args = ["program", "stats"];
+        let config = Config::try_parse_from(args).unwrap();
+
+        assert!(matches!(config.command, Command::Stats { .. }));
+        if let Command::Stats { fflogs_dir, .. } = config.command {
+            assert_eq!(fflogs_dir, "default");
+        }
+    }
+
+    #[test]
+    fn test_config_restore_command() {
+        let args = ["program", "restore"];
+        let config = Config::try_parse_from(args).unwrap();
+
+        assert!(matches!(config.command, Command::Restore { .. }));
+        if let Command::Restore { fflogs_dir, .. } = config.command {
+            assert_eq!(fflogs_dir, ["default"]);
+        }
+    }
+
+    #[test]
+    fn test_config_restore_backup_dir() {
+        let config = Config::try_parse_from(["program", "restore"]).unwrap();
+        if let Command::Restore { backup_dir, .. } = config.command {
+            assert_eq!(backup_dir, PathBuf::from("bak"));
+        }
+
+        let args = ["program", "restore", "--backup-dir", "/mnt/archive"];
+        let config = Config::try_parse_from(args).unwrap();
+        if let Command::Restore { backup_dir, .. } = config.command {
+            assert_eq!(backup_dir, PathBuf::from("/mnt/archive"));
+        }
+    }
+
+    #[test]
+    fn test_config_with_custom_directory() {
+        let args = ["program", "list", "--fflogs-dir", "/path/to/logs"];
+        let config = Config::try_parse_from(args).unwrap();
+
+        assert!(matches!(config.command, Command::List { .. }));
+        if let Command::List { fflogs_dir, .. } = config.command {
+            assert_eq!(fflogs_dir, ["/path/to/logs"]);
+        }
+    }
+
+    #[test]
+    fn test_config_with_short_flag() {
+        let args = ["program", "list", "-f", "/path/to/logs"];
+        let config = Config::try_parse_from(args).unwrap();
+
+        assert!(matches!(config.command, Command::List { .. }));
+        if let Command::List { fflogs_dir, .. } = config.command {
+            assert_eq!(fflogs_dir, ["/path/to/logs"]);
+        }
+    }
+
+    #[test]
+    fn test_config_with_spaces_in_path() {
+        let args = ["program", "list", "--fflogs-dir", "/path/with spaces/logs"];
+        let config = Config::try_parse_from(args).unwrap();
+
+        if let Command::List { fflogs_dir, .. } = config.command {
+            assert_eq!(fflogs_dir, ["/path/with spaces/logs"]);
+        }
+    }
+
+    #[test]
+    fn test_config_default_directory() {
+        let args = ["program", "list"];
+        let config = Config::try_parse_from(args).unwrap();
+
+        if let Command::List { fflogs_dir, .. } = config.command {
+            assert_eq!(fflogs_dir, ["default"]);
+        }
+    }
+
+    #[test]
+    fn test_command_enum_variants() {
+        let list_cmd = Command::List {
+            fflogs_dir: vec!["default".to_string()],
+            paths: Vec::new(),
+            pattern: None,
+            regex: None,
+            exclude: Vec::new(),
+            recursive: false,
+            max_depth: None,
+            older_than: None,
+            newer_than: None,
+            modified_within: None,
+            min_size: None,
+            max_size: None,
+            min_lines: None,
+            max_lines: None,
+            limit: None,
+            since: None,
+            until: None,
+            strict: false,
+            include_dirs: false,
+            paths_from_json: None,
+            summary_json: false,
+            summary_file: None,
+            output: OutputFormat::Text,
+            no_header: false,
+            sort: None,
+            reverse: false,
+            names_only: false,
+            group_by_ext: false,
+        };
+        let backup_cmd = Command::Backup {
+            fflogs_dir: vec!["default".to_string()],
+            progress_format: None,
+            progress: false,
+            jobs: 1,
+            paths: Vec::new(),
+            pattern: None,
+            regex: None,
+            exclude: Vec::new(),
+            recursive: false,
+            max_depth: None,
+            older_than: None,
+            newer_than: None,
+            modified_within: None,
+            min_size: None,
+            max_size: None,
+            min_lines: None,
+            max_lines: None,
+            limit: None,
+            since: None,
+            until: None,
+            strict: false,
+            paths_from_json: None,
+            summary_json: false,
+            summary_file: None,
+            require: None,
+            dry_run: false,
+            keep_last: None,
+            dedupe: false,
+            backup_dir: PathBuf::from("bak"),
+            verify: false,
+            max_bak_size: None,
+            archive: None,
+            delete_after: false,
+            quiet: false,
+        };
+        let delete_cmd = Command::Delete {
+            fflogs_dir: vec!["default".to_string()],
+            progress_format: None,
+            progress: false,
+            jobs: 1,
+            safe_delete: None,
+            paths: Vec::new(),
+            pattern: None,
+            regex: None,
+            exclude: Vec::new(),
+            recursive: false,
+            max_depth: None,
+            older_than: None,
+            newer_than: None,
+            modified_within: None,
+            min_size: None,
+            max_size: None,
+            min_lines: None,
+            max_lines: None,
+            limit: None,
+            since: None,
+            until: None,
+            strict: false,
+            paths_from_json: None,
+            summary_json: false,
+            summary_file: None,
+            require: None,
+            dry_run: false,
+            yes: false,
+            interactive: false,
+            trash: false,
+            empty_only: false,
+            quiet: false,
+        };
+        let view_cmd = Command::View {
+            fflogs_dir: "default".to_string(),
+            dir: None,
+            select: None,
+            file: None,
+            head: None,
+            tail: None,
+            page: false,
+            follow: false,
+            newest: false,
+            oldest: false,
+            tui: false,
+            pager: false,
+            save: None,
+            yes: false,
+            encoding: Encoding::Utf8,
+        };
+        let stats_cmd = Command::Stats {
+            fflogs_dir: "default".to_string(),
+            dir: None,
+            include_dirs: false,
+            deep: false,
+            sort: StatsSort::Name,
+            output: OutputFormat::Text,
+            no_header: false,
+        };
+        let restore_cmd = Command::Restore {
+            fflogs_dir: vec!["default".to_string()],
+            dirs: Vec::new(),
+            backup_dir: PathBuf::from("bak"),
+        };
+
+        assert!(matches!(list_cmd, Command::List { .. }));
+        assert!(matches!(backup_cmd, Command::Backup { .. }));
+        assert!(matches!(delete_cmd, Command::Delete { .. }));
+        assert!(matches!(view_cmd, Command::View { .. }));
+        assert!(matches!(stats_cmd, Command::Stats { .. }));
+        assert!(matches!(restore_cmd, Command::Restore { .. }));
+        let undo_cmd = Command::Undo {
+            fflogs_dir: "default".to_string(),
+            dir: None,
+        };
+        assert!(matches!(undo_cmd, Command::Undo { .. }));
+        let rename_cmd = Command::Rename {
+            fflogs_dir: vec!["default".to_string()],
+            dirs: Vec::new(),
+            dry_run: true,
+            format_name: None,
+            lowercase: false,
+            replace_spaces: false,
+            ascii_only: false,
+        };
+        assert!(matches!(rename_cmd, Command::Rename { .. }));
+        let search_cmd = Command::Search {
+            fflogs_dir: "default".to_string(),
+            needle: Some("Rampart".to_string()),
+            query: None,
+            ignore_case: false,
+            opcode: Vec::new(),
+            encoding: Encoding::Utf8,
+        };
+        assert!(matches!(search_cmd, Command::Search { .. }));
+        let export_cmd = Command::Export {
+            fflogs_dir: "default".to_string(),
+            file: PathBuf::from("Network_1.log"),
+            out: None,
+            json_lines: false,
+            encoding: Encoding::Utf8,
+        };
+        assert!(matches!(export_cmd, Command::Export { .. }));
+        let anonymize_cmd = Command::Anonymize {
+            fflogs_dir: "default".to_string(),
+            file: PathBuf::from("Network_1.log"),
+            out: None,
+        };
+        assert!(matches!(anonymize_cmd, Command::Anonymize { .. }));
+        let split_cmd = Command::Split {
+            fflogs_dir: "default".to_string(),
+            file: PathBuf::from("Network_1.log"),
+            out_dir: None,
+        };
+        assert!(matches!(split_cmd, Command::Split { .. }));
+        let merge_cmd = Command::Merge {
+            fflogs_dir: "default".to_string(),
+            files: vec![
+                PathBuf::from("Network_1.log"),
+                PathBuf::from("Network_2.log"),
+            ],
+            out: PathBuf::from("pull.log"),
+        };
+        assert!(matches!(merge_cmd, Command::Merge { .. }));
+        let watch_cmd = Command::Watch {
+            fflogs_dir: "default".to_string(),
+            dir: None,
+            action: WatchAction::Backup,
+            backup_dir: PathBuf::from("bak"),
+        };
+        assert!(matches!(watch_cmd, Command::Watch { .. }));
+        let doctor_cmd = Command::Doctor {
+            fflogs_dir: "default".to_string(),
+            dir: None,
+            backup_dir: PathBuf::from("bak"),
+        };
+        assert!(matches!(doctor_cmd, Command::Doctor { .. }));
+        let index_cmd = Command::Index {
+            fflogs_dir: "default".to_string(),
+            dir: None,
+            html: false,
+        };
+        assert!(matches!(index_cmd, Command::Index { .. }));
+    }
+
+    #[test]
+    fn test_config_positional_directory() {
+        let config = Config::try_parse_from(["program", "backup", "default"]).unwrap();
+        match config.command {
+            Command::Backup {
+                paths, fflogs_dir, ..
+            } => {
+                assert_eq!(paths, ["default"]);
+                assert_eq!(fflogs_dir, ["default"]);
+            }
+            _ => panic!("Expected Backup command"),
+        }
+
+        let config = Config::try_parse_from(["program", "stats", "/logs"]).unwrap();
+        assert!(matches!(
+            config.command,
+            Command::Stats { dir: Some(dir), .. } if dir == "/logs"
+        ));
+
+        let both = ["program", "list", "/logs", "--fflogs-dir", "/other"];
+        assert!(Config::try_parse_from(both).is_err());
+    }
+
+    #[test]
+    fn test_config_view_follow() {
+        let config =
+            Config::try_parse_from(["program", "view", "--follow", "--tail", "20"]).unwrap();
+        assert!(matches!(
+            config.command,
+            Command::View {
+                follow: true,
+                tail: Some(20),
+                ..
+            }
+        ));
+
+        let with_head = ["program", "view", "--follow", "--head"];
+        assert!(Config::try_parse_from(with_head).is_err());
+    }
+
+    #[test]
+    fn test_config_invalid_command_fails() {
+        let args = ["program", "invalid"];
+        let result = Config::try_parse_from(args);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_config_no_command_fails() {
+        let args = ["program"];
+        let result = Config::try_parse_from(args);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_config_progress_format() {
+        let args = ["program", "backup", "--progress-format", "json"];
+        let config = Config::try_parse_from(args).unwrap();
+
+        if let Command::Backup {
+            progress_format, ..
+        } = config.command
+        {
+            assert_eq!(progress_format, Some(ProgressFormat::Json));
+        }
+    }
+
+    #[test]
+    fn test_config_progress_format_defaults_to_none() {
+        let args = ["program", "delete"];
+        let config = Config::try_parse_from(args).unwrap();
+
+        if let Command::Delete {
+            progress_format, ..
+        } = config.command
+        {
+            assert_eq!(progress_format, None);
+        }
+    }
+
+    #[test]
+    fn test_config_progress_flag() {
+        let args = ["program", "delete", "--progress"];
+        let config = Config::try_parse_from(args).unwrap();
+
+        if let Command::Delete {
+            progress_format,
+            progress,
+            ..
+        } = config.command
+        {
+            assert!(progress);
+            assert_eq!(progress_format, None);
+        }
+    }
+
+    #[test]
+    fn test_config_jobs() {
+        let config = Config::try_parse_from(["program", "backup", "--jobs", "4"]).unwrap();
+        if let Command::Backup { jobs, .. } = config.command {
+            assert_eq!(jobs, 4);
+        }
+
+        let config = Config::try_parse_from(["program", "delete"]).unwrap();
+        if let Command::Delete { jobs, .. } = config.command {
+            assert_eq!(jobs, 1);
+        }
+
+        assert!(Config::try_parse_from(["program", "delete", "--jobs", "0"]).is_err());
+    }
+
+    #[test]
+    fn test_config_invalid_progress_format_fails() {
+        let args = ["program", "delete", "--progress-format", "xml"];
+        let result = Config::try_parse_from(args);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_config_safe_delete() {
+        let args = ["program", "delete", "--safe-delete", "bak"];
+        let config = Config::try_parse_from(args).unwrap();
+
+        if let Command::Delete { safe_delete, .. } = config.command {
+            assert_eq!(safe_delete, Some(PathBuf::from("bak")));
+        }
+    }
+
+    #[test]
+    fn test_config_safe_delete_requires_destination() {
+        let args = ["program", "delete", "--safe-delete"];
+        let result = Config::try_parse_from(args);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_config_paths_from_json() {
+        let args = ["program", "delete", "--paths-from-json", "-"];
+        let config = Config::try_parse_from(args).unwrap();
+
+        if let Command::Delete {
+            paths_from_json, ..
+        } = config.command
+        {
+            assert_eq!(paths_from_json.as_deref(), Some("-"));
+        }
+    }
+
+    #[test]
+    fn test_paths_from_stdin_leave_no_prompt_to_answer() {
+        let asks = |args: &[&str]| {
+            let args = ["program", "delete", "--paths-from-json", "-"]
+                .iter()
+                .chain(args);
+            asks_before_deleting(&Config::try_parse_from(args).unwrap().command)
+        };
+
+        assert!(asks(&[]));
+        assert!(asks(&["--interactive"]));
+        assert!(asks(&["--interactive", "--dry-run"]));
+        assert!(!asks(&["--yes"]));
+        assert!(!asks(&["--dry-run"]));
+    }
+
+    #[test]
+    fn test_config_summary_options() {
+        let args = [
+            "program",
+            "backup",
+            "--summary-json",
+            "--summary-file",
+            "summary.json",
+        ];
+        let config = Config::try_parse_from(args).unwrap();
+
+        if let Command::Backup {
+            summary_json,
+            summary_file,
+            ..
+        } = config.command
+        {
+            assert!(summary_json);
+            assert_eq!(summary_file, Some(PathBuf::from("summary.json")));
+        }
+    }
+
+    #[test]
+    fn test_config_require() {
+        let args = ["program", "delete", "--require", "Network_*.log"];
+        let config = Config::try_parse_from(args).unwrap();
+
+        if let Command::Delete { require, .. } = config.command {
+            assert_eq!(require.as_deref(), Some("Network_*.log"));
+        }
+    }
+
+    #[test]
+    fn test_config_require_not_available_for_list() {
+        let args = ["program", "list", "--require", "Network_*.log"];
+        let result = Config::try_parse_from(args);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_config_dry_run() {
+        let args = ["program", "delete", "--dry-run"];
+        let config = Config::try_parse_from(args).unwrap();
+
+        if let Command::Delete { dry_run, .. } = config.command {
+            assert!(dry_run);
+        }
+    }
+
+    #[test]
+    fn test_config_dry_run_defaults_to_false() {
+        let args = ["program", "backup"];
+        let config = Config::try_parse_from(args).unwrap();
+
+        if let Command::Backup { dry_run, .. } = config.command {
+            assert!(!dry_run);
+        }
+    }
+
+    #[test]
+    fn test_usage_error_exit_code() {
+        assert_eq!(
+            usage_error("Not a valid directory: /nope"),
+            ExitCode::from(2)
+        );
+    }
+
+    #[test]
+    fn test_plan_scope() {
+        assert_eq!(
+            plan_scope(37, 2_254_857_830, "logs"),
+            "37 files (2.1 GB) from logs"
+        );
+        assert_eq!(plan_scope(1, 512, "logs"), "1 file (512 B) from logs");
+    }
+
+    #[test]
+    fn test_backup_and_delete_yes_stream_their_entries() {
+        let gathers =
+            |args: &[&str]| gathers_entries(&Config::try_parse_from(args).unwrap().command);
+
+        assert!(!gathers(&["program", "backup"]));
+        assert!(!gathers(&["program", "delete", "--yes"]));
+        assert!(!gathers(&["program", "delete", "--dry-run"]));
+        assert!(!gathers(&["program", "list"]));
+        assert!(gathers(&["program", "delete"]));
+        assert!(gathers(&["program", "backup", "--progress"]));
+    }
+
+    #[test]
+    fn test_config_restore_and_rename_take_several_directories() {
+        let config = Config::try_parse_from(["program", "restore", "live", "archive"]).unwrap();
+        if let Command::Restore { dirs, .. } = config.command {
+            assert_eq!(dirs, ["live", "archive"]);
+        }
+
+        let args = ["program", "rename", "-f", "live", "archive", "--dry-run"];
+        let config = Config::try_parse_from(args).unwrap();
+        if let Command::Rename { fflogs_dir, .. } = config.command {
+            assert_eq!(fflogs_dir, ["live", "archive"]);
+        }
+    }
+
+    #[test]
+    fn test_config_timestamps_is_global() {
+        let config = Config::try_parse_from(["program", "backup", "--timestamps"]).unwrap();
+        assert!(config.timestamps);
+
+        let config = Config::try_parse_from(["program", "list"]).unwrap();
+        assert!(!config.timestamps);
+    }
+
+    #[test]
+    fn test_config_color_choice() {
+        let config = Config::try_parse_from(["program", "list"]).unwrap();
+        assert_eq!(config.color, ColorChoice::Auto);
+
+        let config = Config::try_parse_from(["program", "backup", "--color", "never"]).unwrap();
+        assert_eq!(config.color, ColorChoice::Never);
+        assert!(Config::try_parse_from(["program", "list", "--color", "rainbow"]).is_err());
+    }
+
+    #[test]
+    fn test_help_documents_exit_codes() {
+        let help = <Config as clap::CommandFactory>::command()
+            .render_long_help()
+            .to_string();
+
+        assert!(help.contains("Exit status:"));
+        assert!(help.contains("1  at least one file failed"));
+    }
+
+    #[test]
+    fn test_config_delete_yes() {
+        let config = Config::try_parse_from(["program", "delete", "-y"]).unwrap();
+        if let Command::Delete { yes, .. } = config.command {
+            assert!(yes);
+        }
+
+        let config = Config::try_parse_from(["program", "delete", "--yes"]).unwrap();
+        if let Command::Delete { yes, .. } = config.command {
+            assert!(yes);
+        }
+    }
+
+    #[test]
+    fn test_config_delete_interactive() {
+        let config = Config::try_parse_from(["program", "delete", "-i"]).unwrap();
+        assert!(matches!(
+            config.command,
+            Command::Delete {
+                interactive: true,
+                ..
+            }
+        ));
+
+        let args = ["program", "delete", "--interactive", "--yes"];
+        assert!(Config::try_parse_from(args).is_err());
+    }
+
+    #[test]
+    fn test_config_delete_trash() {
+        let args = ["program", "delete", "--trash"];
+        let config = Config::try_parse_from(args).unwrap();
+
+        assert!(matches!(
+            config.command,
+            Command::Delete { trash: true, .. }
+        ));
+    }
+
+    #[test]
+    fn test_config_delete_empty_only() {
+        let args = ["program", "delete", "--empty-only"];
+        let config = Config::try_parse_from(args).unwrap();
+
+        assert!(matches!(
+            config.command,
+            Command::Delete {
+                empty_only: true,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_config_delete_trash_conflicts_with_safe_delete() {
+        let args = ["program", "delete", "--trash", "--safe-delete", "bak"];
+
+        assert!(Config::try_parse_from(args).is_err());
+    }
+
+    #[test]
+    fn test_config_backup_verify() {
+        let config = Config::try_parse_from(["program", "backup", "--verify"]).unwrap();
+
+        assert!(matches!(
+            config.command,
+            Command::Backup { verify: true, .. }
+        ));
+    }
+
+    #[test]
+    fn test_config_backup_archive() {
+        let config = Config::try_parse_from([
+            "program",
+            "backup",
+            "--archive",
+            "logs.zip",
+            "--delete-after",
+        ])
+        .unwrap();
+
+        match config.command {
+            Command::Backup {
+                archive,
+                delete_after,
+                ..
+            } => {
+                assert_eq!(archive, Some(PathBuf::from("logs.zip")));
+                assert!(delete_after);
+            }
+            _ => panic!("Expected Backup command"),
+        }
+        assert!(Config::try_parse_from(["program", "backup", "--delete-after"]).is_err());
+    }
+
+    #[test]
+    fn test_config_backup_max_bak_size() {
+        let args = ["program", "backup", "--max-bak-size", "20G"];
+        let config = Config::try_parse_from(args).unwrap();
+
+        if let Command::Backup { max_bak_size, .. } = config.command {
+            assert_eq!(max_bak_size, Some(20 * 1024 * 1024 * 1024));
+        }
+        let args = ["program", "backup", "--max-bak-size=1M", "--archive=a.zip"];
+        assert!(Config::try_parse_from(args).is_err());
+    }
+
+    #[test]
+    fn test_backup_limit_moves_only_the_first_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let logs = temp_dir.path().join("logs");
+        fs::create_dir(&logs).unwrap();
+        for i in 0..10 {
+            File::create(logs.join(format!("test_{i}.log"))).unwrap();
+        }
+        let summary_file = temp_dir.path().join("summary.json");
+
+        let args = [
+            "program",
+            "backup",
+            logs.to_str().unwrap(),
+            "--limit",
+            "3",
+            "--summary-file",
+            summary_file.to_str().unwrap(),
+        ];
+        assert_eq!(
+            run(Config::try_parse_from(args).unwrap()).unwrap(),
+            ExitCode::SUCCESS
+        );
+
+        assert_eq!(fs::read_dir(logs.join("bak")).unwrap().count(), 3);
+        assert_eq!(crate::scan::collect_log_files(&logs).unwrap().len(), 7);
+        let summary = json::parse(&fs::read_to_string(&summary_file).unwrap()).unwrap();
+        assert_eq!(summary.get("processed"), Some(&json::Value::Number(3.0)));
+        assert_eq!(summary.get("limited"), Some(&json::Value::Number(3.0)));
+    }
+
+    #[test]
+    fn test_undo_after_pruning_restores_the_backups_kept() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path().to_str().unwrap();
+        let old = temp_dir.path().join("a.log");
+        let new = temp_dir.path().join("b.log");
+        for (path, age) in [(&old, 60), (&new, 0)] {
+            let file = File::create(path).unwrap();
+            file.set_len(60).unwrap();
+            file.set_modified(SystemTime::now() - Duration::from_secs(age))
+                .unwrap();
+        }
+
+        let args = ["program", "backup", dir, "--max-bak-size", "100"];
+        assert_eq!(
+            run(Config::try_parse_from(args).unwrap()).unwrap(),
+            ExitCode::SUCCESS
+        );
+        assert!(!temp_dir.path().join("bak").join("a.log").exists());
+
+        let args = ["program", "undo", dir];
+        assert_eq!(
+            run(Config::try_parse_from(args).unwrap()).unwrap(),
+            ExitCode::SUCCESS
+        );
+        assert!(new.exists());
+        assert!(!old.exists());
+        assert!(!commands::undo::manifest_path(temp_dir.path()).exists());
+    }
+
+    #[test]
+    fn test_config_backup_keep_last() {
+        let args = ["program", "backup", "--keep-last", "2"];
+        let config = Config::try_parse_from(args).unwrap();
+
+        if let Command::Backup { keep_last, .. } = config.command {
+            assert_eq!(keep_last, Some(2));
+        }
+    }
+
+    #[test]
+    fn test_config_backup_dedupe() {
+        let config = Config::try_parse_from(["program", "backup", "--dedupe"]).unwrap();
+
+        if let Command::Backup { dedupe, .. } = config.command {
+            assert!(dedupe);
+        }
+        assert!(Config::try_parse_from(["program", "delete", "--dedupe"]).is_err());
+    }
+
+    #[test]
+    fn test_config_backup_dir() {
+        let config = Config::try_parse_from(["program", "backup"]).unwrap();
+        if let Command::Backup { backup_dir, .. } = config.command {
+            assert_eq!(backup_dir, PathBuf::from("bak"));
+        }
+
+        let args = ["program", "backup", "--backup-dir", "/mnt/archive"];
+        let config = Config::try_parse_from(args).unwrap();
+        if let Command::Backup { backup_dir, .. } = config.command {
+            assert_eq!(backup_dir, PathBuf::from("/mnt/archive"));
+        }
+    }
+
+    #[test]
+    fn test_config_keep_last_not_available_for_delete() {
+        let args = ["program", "delete", "--keep-last", "2"];
+
+        assert!(Config::try_parse_from(args).is_err());
+    }
+
+    #[test]
+    fn test_config_quiet() {
+        let args = ["program", "backup", "-q"];
+        let config = Config::try_parse_from(args).unwrap();
+
+        assert!(matches!(
+            config.command,
+            Command::Backup { quiet: true, .. }
+        ));
+    }
+
+    #[test]
+    fn test_config_delete_prompts_by_default() {
+        let config = Config::try_parse_from(["program", "delete"]).unwrap();
+
+        if let Command::Delete { yes, .. } = config.command {
+            assert!(!yes);
+        }
+    }
+
+    #[test]
+    fn test_config_pattern() {
+        let args = ["program", "backup", "--pattern", "Network_*.log"];
+        let config = Config::try_parse_from(args).unwrap();
+
+        if let Command::Backup { pattern, .. } = config.command {
+            assert_eq!(pattern.as_deref(), Some("Network_*.log"));
+        }
+    }
+
+    #[test]
+    fn test_config_regex() {
+        let args = ["program", "list", "--regex", r"^Network_\d+\.log$"];
+        let config = Config::try_parse_from(args).unwrap();
+
+        if let Command::List { regex, .. } = config.command {
+            assert!(regex.unwrap().is_match("Network_1.log"));
+        }
+    }
+
+    #[test]
+    fn test_config_regex_fails_fast_and_conflicts_with_pattern() {
+        let err = Config::try_parse_from(["program", "list", "--regex", "(Network"])
+            .err()
+            .unwrap();
+        assert!(
+            err.to_string()
+                .contains("invalid regex '(Network': unclosed group")
+        );
+
+        let args = ["program", "backup", "--regex", "a", "--pattern", "*.log"];
+        let err = Config::try_parse_from(args).err().unwrap();
+        assert_eq!(err.kind(), clap::error::ErrorKind::ArgumentConflict);
+    }
+
+    #[test]
+    fn test_config_exclude_accumulates() {
+        let args = [
+            "program",
+            "backup",
+            "--exclude",
+            "*_current.log",
+            "--exclude",
+            "*.tmp",
+        ];
+        let config = Config::try_parse_from(args).unwrap();
+
+        if let Command::Backup { exclude, .. } = config.command {
+            assert_eq!(exclude, ["*_current.log", "*.tmp"]);
+        }
+    }
+
+    #[test]
+    fn test_config_max_depth_requires_recursive() {
+        let args = ["program", "backup", "-r", "--max-depth", "1"];
+        let config = Config::try_parse_from(args).unwrap();
+        if let Command::Backup { max_depth, .. } = config.command {
+            assert_eq!(max_depth, Some(1));
+        }
+
+        assert!(Config::try_parse_from(["program", "backup", "--max-depth", "1"]).is_err());
+    }
+
+    #[test]
+    fn test_config_recursive() {
+        let config = Config::try_parse_from(["program", "list", "-r"]).unwrap();
+        if let Command::List { recursive, .. } = config.command {
+            assert!(recursive);
+        }
+
+        let config = Config::try_parse_from(["program", "backup", "--recursive"]).unwrap();
+        if let Command::Backup { recursive, .. } = config.command {
+            assert!(recursive);
+        }
+    }
+
+    #[test]
+    fn test_config_age_window() {
+        let args = [
+            "program",
+            "delete",
+            "--older-than",
+            "30",
+            "--newer-than",
+            "90",
+        ];
+        let config = Config::try_parse_from(args).unwrap();
+
+        if let Command::Delete {
+            older_than,
+            newer_than,
+            ..
+        } = config.command
+        {
+            assert_eq!(older_than, Some(30));
+            assert_eq!(newer_than, Some(90));
+        }
+    }
+
+    #[test]
+    fn test_config_modified_within() {
+        let args = ["program", "list", "--modified-within", "1h30m"];
+        let config = Config::try_parse_from(args).unwrap();
+
+        if let Command::List {
+            modified_within, ..
+        } = config.command
+        {
+            assert_eq!(modified_within, Some(Duration::from_secs(5400)));
+        }
+        let error = Config::try_parse_from(["program", "backup", "--modified-within", "2"])
+            .err()
+            .unwrap();
+        assert!(error.to_string().contains("invalid duration '2'"));
+    }
+
+    #[test]
+    fn test_config_line_count_filters() {
+        let args = [
+            "program",
+            "backup",
+            "--min-lines",
+            "10",
+            "--max-lines",
+            "100",
+        ];
+        let config = Config::try_parse_from(args).unwrap();
+        if let Command::Backup {
+            min_lines,
+            max_lines,
+            ..
+        } = config.command
+        {
+            assert_eq!(min_lines, Some(10));
+            assert_eq!(max_lines, Some(100));
+        }
+        assert!(Config::try_parse_from(["program", "list", "--min-lines", "many"]).is_err());
+    }
+
+    #[test]
+    fn test_config_limit() {
+        let config = Config::try_parse_from(["program", "delete", "--limit", "3"]).unwrap();
+        if let Command::Delete { limit, .. } = config.command {
+            assert_eq!(limit, Some(3));
+        }
+        assert!(Config::try_parse_from(["program", "list", "--limit", "-1"]).is_err());
+    }
+
+    #[test]
+    fn test_config_size_range() {
+        let args = ["program", "backup", "--min-size", "10M", "--max-size", "1G"];
+        let config = Config::try_parse_from(args).unwrap();
+
+        if let Command::Backup {
+            min_size, max_size, ..
+        } = config.command
+        {
+            assert_eq!(min_size, Some(10 * 1024 * 1024));
+            assert_eq!(max_size, Some(1024 * 1024 * 1024));
+        }
+        assert!(Config::try_parse_from(["program", "list", "--min-size", "big"]).is_err());
+    }
+
+    #[test]
+    fn test_config_date_range() {
+        let args = [
+            "program",
+            "list",
+            "--since",
+            "2024-01-08",
+            "--until",
+            "2024-01-14",
+        ];
+        let config = Config::try_parse_from(args).unwrap();
+
+        if let Command::List { since, until, .. } = config.command {
+            assert_eq!(since, Date::new(2024, 1, 8));
+            assert_eq!(until, Date::new(2024, 1, 14));
+        }
+        let error = Config::try_parse_from(["program", "list", "--since", "2024-02-30"])
+            .err()
+            .unwrap();
+        assert!(error.to_string().contains("expected YYYY-MM-DD"));
+    }
+
+    #[test]
+    fn test_config_age_rejects_negative_days() {
+        let args = ["program", "list", "--older-than", "-3"];
+        let result = Config::try_parse_from(args);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_config_list_output() {
+        let args = ["program", "list", "--output", "json"];
+        let config = Config::try_parse_from(args).unwrap();
+
+        if let Command::List { output, .. } = config.command {
+            assert_eq!(output, OutputFormat::Json);
+        }
+    }
+
+    #[test]
+    fn test_config_stats_output() {
+        let args = ["program", "stats", "--output", "json"];
+        let config = Config::try_parse_from(args).unwrap();
+
+        assert!(matches!(
+            config.command,
+            Command::Stats {
+                output: OutputFormat::Json,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_config_list_output_defaults_to_text() {
+        let args = ["program", "list"];
+        let config = Config::try_parse_from(args).unwrap();
+
+        if let Command::List { output, .. } = config.command {
+            assert_eq!(output, OutputFormat::Text);
+        }
+    }
+
+    #[test]
+    fn test_config_view_tail() {
+        let args = ["program", "view", "--tail", "20"];
+        let config = Config::try_parse_from(args).unwrap();
+
+        if let Command::View { tail, .. } = config.command {
+            assert_eq!(tail, Some(20));
+        }
+    }
+
+    #[test]
+    fn test_config_view_tail_without_value_defaults_to_50() {
+        let args = ["program", "view", "--tail"];
+        let config = Config::try_parse_from(args).unwrap();
+
+        if let Command::View { tail, .. } = config.command {
+            assert_eq!(tail, Some(50));
+        }
+    }
+
+    #[test]
+    fn test_config_view_without_tail_shows_everything() {
+        let args = ["program", "view"];
+        let config = Config::try_parse_from(args).unwrap();
+
+        if let Command::View { tail, .. } = config.command {
+            assert_eq!(tail, None);
+        }
+    }
+
+    #[test]
+    fn test_config_view_page() {
+        let args = ["program", "view", "--page", "--tail"];
+        let config = Config::try_parse_from(args).unwrap();
+
+        if let Command::View { tail, page, .. } = config.command {
+            assert!(page);
+            assert_eq!(tail, Some(50));
+        }
+    }
+
+    #[test]
+    fn test_config_view_head() {
+        let config = Config::try_parse_from(["program", "view", "--head"]).unwrap();
+        if let Command::View { head, tail, .. } = config.command {
+            assert_eq!(head, Some(50));
+            assert_eq!(tail, None);
+        }
+
+        let config = Config::try_parse_from(["program", "view", "--head", "5"]).unwrap();
+        if let Command::View { head, .. } = config.command {
+            assert_eq!(head, Some(5));
+        }
+    }
+
+    #[test]
+    fn test_config_view_select() {
+        let config = Config::try_parse_from(["program", "view", "--select", "3"]).unwrap();
+        if let Command::View { select, file, .. } = config.command {
+            assert_eq!(select, Some(3));
+            assert_eq!(file, None);
+        }
+
+        let config = Config::try_parse_from(["program", "view", "--file", "a.log"]).unwrap();
+        if let Command::View { file, .. } = config.command {
+            assert_eq!(file.as_deref(), Some("a.log"));
+        }
+
+        let both = ["program", "view", "--select", "3", "--file", "a.log"];
+        assert!(Config::try_parse_from(both).is_err());
+    }
+
+    #[test]
+    fn test_config_view_newest_and_oldest() {
+        let args = ["program", "view", "default", "--newest"];
+        let config = Config::try_parse_from(args).unwrap();
+        if let Command::View { newest, oldest, .. } = config.command {
+            assert!(newest);
+            assert!(!oldest);
+        }
+
+        let config = Config::try_parse_from(["program", "view", "--oldest"]).unwrap();
+        assert!(matches!(config.command, Command::View { oldest: true, .. }));
+
+        for args in [
+            ["program", "view", "--newest", "--oldest"],
+            ["program", "view", "--newest", "--select=1"],
+            ["program", "view", "--oldest", "--file=a.log"],
+        ] {
+            assert!(Config::try_parse_from(args).is_err());
+        }
+    }
+
+    #[test]
+    fn test_config_view_tui() {
+        let config = Config::try_parse_from(["program", "view", "default", "--tui"]).unwrap();
+        assert!(matches!(config.command, Command::View { tui: true, .. }));
+
+        let args = ["program", "view", "--tui", "--newest"];
+        assert!(Config::try_parse_from(args).is_err());
+    }
+
+    #[test]
+    fn test_config_view_pager() {
+        let args = ["program", "view", "--newest", "--tail", "--pager"];
+        let config = Config::try_parse_from(args).unwrap();
+        assert!(matches!(config.command, Command::View { pager: true, .. }));
+
+        for args in [
+            ["program", "view", "--pager", "--page"],
+            ["program", "view", "--pager", "--follow"],
+        ] {
+            assert!(Config::try_parse_from(args).is_err());
+        }
+    }
+
+    #[test]
+    fn test_config_view_save() {
+        let args = ["program", "view", "--newest", "--save", "out", "--yes"];
+        let config = Config::try_parse_from(args).unwrap();
+        if let Command::View { save, yes, .. } = config.command {
+            assert_eq!(save, Some(PathBuf::from("out")));
+            assert!(yes);
+        }
+
+        for args in [
+            ["program", "view", "--save=out", "--tail"],
+            ["program", "view", "--save=out", "--follow"],
+            ["program", "view", "--newest", "--yes"],
+        ] {
+            assert!(Config::try_parse_from(args).is_err());
+        }
+    }
+
+    #[test]
+    fn test_config_view_head_conflicts_with_tail() {
+        let args = ["program", "view", "--head", "5", "--tail", "5"];
+
+        assert!(Config::try_parse_from(args).is_err());
+    }
+
+    #[test]
+    fn test_config_search_positional() {
+        let args = ["program", "search", "Rampart", "-i"];
+        let config = Config::try_parse_from(args).unwrap();
+
+        if let Command::Search {
+            needle,
+            query,
+            ignore_case,
+            ..
+        } = config.command
+        {
+            assert_eq!(needle.as_deref(), Some("Rampart"));
+            assert_eq!(query, None);
+            assert!(ignore_case);
+        }
+    }
+
+    #[test]
+    fn test_config_search_query_flag() {
+        let args = ["program", "search", "--query", "Rampart"];
+        let config = Config::try_parse_from(args).unwrap();
+
+        if let Command::Search { needle, query, .. } = config.command {
+            assert_eq!(needle, None);
+            assert_eq!(query.as_deref(), Some("Rampart"));
+        }
+    }
+
+    #[test]
+    fn test_config_search_opcode() {
+        let args = [
+            "program", "search", "Flare", "--opcode", "21", "--opcode", "22",
+        ];
+        let config = Config::try_parse_from(args).unwrap();
+        if let Command::Search { opcode, .. } = config.command {
+            assert_eq!(opcode, [21, 22]);
+        }
+
+        assert!(Config::try_parse_from(["program", "search", "--opcode", "0"]).is_ok());
+        assert!(Config::try_parse_from(["program", "search", "--opcode", "999"]).is_err());
+    }
+
+    #[test]
+    fn test_config_rename_clean_ups() {
+        let args = ["program", "rename", "--lowercase", "--replace-spaces"];
+        let config = Config::try_parse_from(args).unwrap();
+        if let Command::Rename {
+            lowercase,
+            replace_spaces,
+            ascii_only,
+            ..
+        } = config.command
+        {
+            assert!(lowercase && replace_spaces && !ascii_only);
+        }
+    }
+
+    #[test]
+    fn test_config_rename_format_name() {
+        let args = ["program", "rename", "--format-name", "{date}_{index}.{ext}"];
+        let config = Config::try_parse_from(args).unwrap();
+        if let Command::Rename { format_name, .. } = config.command {
+            assert_eq!(
+                format_name,
+                Some(NameTemplate::parse("{date}_{index}.{ext}").unwrap())
+            );
+        }
+
+        let args = ["program", "rename", "--format-name", "{date}_{zone}.log"];
+        assert!(Config::try_parse_from(args).is_err());
+    }
+
+    #[test]
+    fn test_config_search_requires_text() {
+        assert!(Config::try_parse_from(["program", "search"]).is_err());
+        assert!(Config::try_parse_from(["program", "search", "a", "--query", "b"]).is_err());
+    }
+
+    #[test]
+    fn test_config_export_out() {
+        let args = ["program", "export", "Network_1.log", "--out", "fight.csv"];
+        let config = Config::try_parse_from(args).unwrap();
+
+        if let Command::Export { file, out, .. } = config.command {
+            assert_eq!(file, PathBuf::from("Network_1.log"));
+            assert_eq!(out, Some(PathBuf::from("fight.csv")));
+        }
+        assert!(Config::try_parse_from(["program", "export"]).is_err());
+    }
+
+    #[test]
+    fn test_config_export_json_lines() {
+        let args = ["program", "export", "Network_1.log", "--json-lines"];
+        let config = Config::try_parse_from(args).unwrap();
+
+        if let Command::Export {
+            json_lines, out, ..
+        } = config.command
+        {
+            assert!(json_lines);
+            assert_eq!(out, None);
+        }
+    }
+
+    #[test]
+    fn test_config_encoding() {
+        let args = ["program", "search", "攻撃", "--encoding", "shift_jis"];
+        let config = Config::try_parse_from(args).unwrap();
+
+        if let Command::Search { encoding, .. } = config.command {
+            assert_eq!(encoding, Encoding::ShiftJis);
+        }
+        let config = Config::try_parse_from(["program", "view", "--newest"]).unwrap();
+        if let Command::View { encoding, .. } = config.command {
+            assert_eq!(encoding, Encoding::Utf8);
+        }
+        let args = ["program", "export", "a.log", "--encoding", "utf16"];
+        assert!(Config::try_parse_from(args).is_err());
+    }
+
+    #[test]
+    fn test_config_anonymize_out() {
+        let args = [
+            "program",
+            "anonymize",
+            "Network_1.log",
+            "--out",
+            "shared.log",
+        ];
+        let config = Config::try_parse_from(args).unwrap();
+
+        if let Command::Anonymize { file, out, .. } = config.command {
+            assert_eq!(file, PathBuf::from("Network_1.log"));
+            assert_eq!(out, Some(PathBuf::from("shared.log")));
+        }
+        assert!(Config::try_parse_from(["program", "anonymize"]).is_err());
+    }
+
+    #[test]
+    fn test_config_split_out_dir() {
+        let args = ["program", "split", "Network_1.log", "--out-dir", "pulls"];
+        let config = Config::try_parse_from(args).unwrap();
+
+        if let Command::Split { file, out_dir, .. } = config.command {
+            assert_eq!(file, PathBuf::from("Network_1.log"));
+            assert_eq!(out_dir, Some(PathBuf::from("pulls")));
+        }
+        assert!(Config::try_parse_from(["program", "split"]).is_err());
+    }
+
+    #[test]
+    fn test_config_merge_files_and_out() {
+        let args = ["program", "merge", "a.log", "b.log", "--out", "pull.log"];
+        let config = Config::try_parse_from(args).unwrap();
+
+        if let Command::Merge { files, out, .. } = config.command {
+            assert_eq!(files, [PathBuf::from("a.log"), PathBuf::from("b.log")]);
+            assert_eq!(out, PathBuf::from("pull.log"));
+        }
+        assert!(Config::try_parse_from(["program", "merge", "a.log", "--out", "x"]).is_err());
+        assert!(Config::try_parse_from(["program", "merge", "a.log", "b.log"]).is_err());
+    }
+
+    #[test]
+    fn test_config_watch_action() {
+        let config = Config::try_parse_from(["program", "watch"]).unwrap();
+        if let Command::Watch { action, .. } = config.command {
+            assert_eq!(action, WatchAction::Backup);
+        }
+
+        let config = Config::try_parse_from(["program", "watch", "--action", "delete"]).unwrap();
+        if let Command::Watch { action, .. } = config.command {
+            assert_eq!(action, WatchAction::Delete);
+        }
+    }
+
+    #[test]
+    fn test_config_watch_backup_dir() {
+        let config = Config::try_parse_from(["program", "watch"]).unwrap();
+        if let Command::Watch { backup_dir, .. } = config.command {
+            assert_eq!(backup_dir, PathBuf::from("bak"));
+        }
+
+        let args = ["program", "watch", "--backup-dir", "/mnt/archive"];
+        let config = Config::try_parse_from(args).unwrap();
+        if let Command::Watch { backup_dir, .. } = config.command {
+            assert_eq!(backup_dir, PathBuf::from("/mnt/archive"));
+        }
+    }
+
+    #[test]
+    fn test_config_doctor() {
+        let config = Config::try_parse_from(["program", "doctor", "/logs"]).unwrap();
+        match config.command {
+            Command::Doctor {
+                fflogs_dir,
+                dir,
+                backup_dir,
+            } => {
+                assert_eq!(fflogs_dir, "default");
+                assert_eq!(dir.as_deref(), Some("/logs"));
+                assert_eq!(backup_dir, PathBuf::from("bak"));
+            }
+            _ => panic!("Expected Doctor command"),
+        }
+    }
+
+    #[test]
+    fn test_config_no_default() {
+        let config = Config::try_parse_from(["program", "backup"]).unwrap();
+        assert!(!config.no_default);
+
+        let config = Config::try_parse_from(["program", "delete", "logs", "--no-default"]).unwrap();
+        assert!(config.no_default);
+    }
+
+    #[test]
+    fn test_config_retries() {
+        let config = Config::try_parse_from(["program", "backup"]).unwrap();
+        assert_eq!(config.retries, retry::DEFAULT_RETRIES);
+
+        let config = Config::try_parse_from(["program", "delete", "--retries", "5"]).unwrap();
+        assert_eq!(config.retries, 5);
+    }
+
+    #[test]
+    fn test_config_strict() {
+        for command in ["list", "delete", "backup"] {
+            let config = Config::try_parse_from(["program", command, "--strict"]).unwrap();
+            match config.command {
+                Command::List { strict, .. }
+                | Command::Delete { strict, .. }
+                | Command::Backup { strict, .. } => assert!(strict),
+                _ => unreachable!(),
+            }
+        }
+        assert!(Config::try_parse_from(["program", "view", "--strict"]).is_err());
+    }
+
+    #[test]
+    fn test_config_multiple_targets() {
+        let args = ["program", "backup", "-f", "a.log", "logs", "--dry-run"];
+        let config = Config::try_parse_from(args).unwrap();
+
+        if let Command::Backup {
+            fflogs_dir,
+            dry_run,
+            ..
+        } = config.command
+        {
+            assert_eq!(fflogs_dir, ["a.log", "logs"]);
+            assert!(dry_run);
+        }
+    }
+
+    #[test]
+    fn test_config_output_table_no_header() {
+        let args = ["program", "list", "--output", "table", "--no-header"];
+        let config = Config::try_parse_from(args).unwrap();
+        if let Command::List {
+            output, no_header, ..
+        } = config.command
+        {
+            assert_eq!(output, OutputFormat::Table);
+            assert!(no_header);
+        }
+
+        let config = Config::try_parse_from(["program", "stats", "--output", "table"]).unwrap();
+        if let Command::Stats {
+            output, no_header, ..
+        } = config.command
+        {
+            assert_eq!(output, OutputFormat::Table);
+            assert!(!no_header);
+        }
+    }
+
+    #[test]
+    fn test_config_stats_sort() {
+        let config = Config::try_parse_from(["program", "stats"]).unwrap();
+        if let Command::Stats { sort, .. } = config.command {
+            assert_eq!(sort, StatsSort::Name);
+        }
+
+        let config = Config::try_parse_from(["program", "stats", "--sort", "span"]).unwrap();
+        if let Command::Stats { sort, .. } = config.command {
+            assert_eq!(sort, StatsSort::Span);
+        }
+    }
+
+    #[test]
+    fn test_config_stats_deep() {
+        let config = Config::try_parse_from(["program", "stats"]).unwrap();
+        assert!(matches!(config.command, Command::Stats { deep: false, .. }));
+
+        let config = Config::try_parse_from(["program", "stats", "--deep"]).unwrap();
+        assert!(matches!(config.command, Command::Stats { deep: true, .. }));
+    }
+
+    #[test]
+    fn test_config_index() {
+        let config =
+            Config::try_parse_from(["program", "index", "/logs/archive", "--html"]).unwrap();
+
+        assert!(matches!(
+            config.command,
+            Command::Index { dir: Some(ref dir), html: true, .. } if dir == "/logs/archive"
+        ));
+        let config = Config::try_parse_from(["program", "index"]).unwrap();
+        assert!(matches!(config.command, Command::Index { html: false, .. }));
+    }
+
+    #[test]
+    fn test_config_include_dirs() {
+        let config = Config::try_parse_from(["program", "stats", "--include-dirs"]).unwrap();
+        if let Command::Stats { include_dirs, .. } = config.command {
+            assert!(include_dirs);
+        }
+
+        let config = Config::try_parse_from(["program", "list", "--include-dirs"]).unwrap();
+        if let Command::List { include_dirs, .. } = config.command {
+            assert!(include_dirs);
+        }
+
+        assert!(Config::try_parse_from(["program", "delete", "--include-dirs"]).is_err());
+    }
+
+    #[test]
+    fn test_config_verbose_is_global() {
+        let config = Config::try_parse_from(["program", "backup", "-v"]).unwrap();
+
+        assert_eq!(config.verbose, 1);
+        assert_eq!(
+            Config::try_parse_from(["program", "list"]).unwrap().verbose,
+            0
+        );
+        let config = Config::try_parse_from(["program", "-vv", "list"]).unwrap();
+        assert_eq!(config.verbose, 2);
+    }
+
+    #[test]
+    fn test_config_list_sort() {
+        let args = ["program", "list", "--sort", "size", "--reverse"];
+        let config = Config::try_parse_from(args).unwrap();
+
+        if let Command::List { sort, reverse, .. } = config.command {
+            assert_eq!(sort, Some(SortKey::Size));
+            assert!(reverse);
+        }
+    }
+
+    #[test]
+    fn test_config_list_names_only() {
+        let args = ["program", "list", "--names-only"];
+        let config = Config::try_parse_from(args).unwrap();
+
+        if let Command::List { names_only, .. } = config.command {
+            assert!(names_only);
+        }
+    }
+
+    #[test]
+    fn test_config_list_group_by_ext() {
+        let config = Config::try_parse_from(["program", "list", "--group-by-ext"]).unwrap();
+        assert!(matches!(
+            config.command,
+            Command::List {
+                group_by_ext: true,
+                ..
+            }
+        ));
+
+        let with_json = ["program", "list", "--group-by-ext", "--output", "json"];
+        assert!(Config::try_parse_from(with_json).is_err());
+    }
+
+    #[test]
+    fn test_config_list_invalid_sort_fails() {
+        let args = ["program", "list", "--sort", "color"];
+
+        assert!(Config::try_parse_from(args).is_err());
+    }
+
+    #[test]
+    fn test_main_function_with_valid_directory() {
+        let temp_dir = TempDir::new().unwrap();
+
+        let test_file = temp_dir.path().join("test.log");
+        let mut file = File::create(&test_file).unwrap();
+        writeln!(file, "test log content").unwrap();
+
+        let args = [
+            "program",
+            "list",
+            "--fflogs-dir",
+            &temp_dir.path().to_string_lossy(),
+        ];
+        let config = Config::try_parse_from(args).unwrap();
+
+        assert!(matches!(config.command, Command::List { .. }));
+        if let Command::List { fflogs_dir, .. } = config.command {
+            assert_eq!(fflogs_dir, [temp_dir.path().to_string_lossy()]);
+        }
+    }
+}
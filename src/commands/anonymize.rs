@@ -0,0 +1,181 @@
+use crate::parser::{Event, Payload, parse_line};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// The default target: `<name>.anon.log` next to the source file.
+pub fn default_anon_path(file: &Path) -> PathBuf {
+    file.with_extension("anon.log")
+}
+
+/// Stable pseudonyms for player names, numbered in order of appearance.
+#[derive(Debug, Default)]
+struct Pseudonyms {
+    names: HashMap<String, String>,
+}
+
+impl Pseudonyms {
+    fn add(&mut self, name: &str) {
+        if name.is_empty() || self.names.contains_key(name) {
+            return;
+        }
+        let pseudonym = format!("Player{}", self.names.len() + 1);
+        self.names.insert(name.to_string(), pseudonym);
+    }
+
+    fn get(&self, name: &str) -> Option<&str> {
+        self.names.get(name).map(String::as_str)
+    }
+}
+
+/// Copies `file` to `out` with every player name replaced by `Player1`,
+/// `Player2`... The same name always gets the same pseudonym within a file.
+///
+/// Players are recognized by their combatant id in the lines that pair an
+/// id with a name (combatants joining, abilities). A first pass collects
+/// the names; the second replaces every field that is exactly one of them,
+/// such as a chat sender. NPC names and all other fields are copied as is,
+/// though ACT's per-line checksums no longer match the edited lines.
+pub fn scrub(file: &Path, out: &Path) -> io::Result<()> {
+    println!("Anonymizing {} -> {}...", file.display(), out.display());
+    let mut pseudonyms = Pseudonyms::default();
+    for_each_line(file, |line| {
+        if let Some(event) = parse_line(line) {
+            player_names(&event)
+                .into_iter()
+                .for_each(|name| pseudonyms.add(name));
+        }
+        Ok(())
+    })?;
+
+    let mut writer = BufWriter::new(File::create(out)?);
+    let mut replaced = 0;
+    for_each_line(file, |line| {
+        let (scrubbed, count) = scrub_line(line, &pseudonyms);
+        replaced += count;
+        writeln!(writer, "{scrubbed}")
+    })?;
+    writer.flush()?;
+
+    println!(
+        "Replaced {replaced} names of {} players.",
+        pseudonyms.names.len()
+    );
+    Ok(())
+}
+
+fn for_each_line(file: &Path, mut f: impl FnMut(&str) -> io::Result<()>) -> io::Result<()> {
+    let mut reader = BufReader::new(File::open(file)?);
+    let mut buffer = Vec::new();
+    while reader.read_until(b'\n', &mut buffer)? > 0 {
+        let line = String::from_utf8_lossy(&buffer);
+        f(line.trim_end_matches(['\r', '\n']))?;
+        buffer.clear();
+    }
+    Ok(())
+}
+
+/// The names `event` ties to a player id.
+fn player_names(event: &Event) -> Vec<&str> {
+    match &event.payload {
+        Payload::AddCombatant { id, name, .. } if Event::is_player(id) => vec![name],
+        Payload::Ability {
+            source_id,
+            source_name,
+            target_id,
+            target_name,
+            ..
+        } => [(source_id, source_name), (target_id, target_name)]
+            .into_iter()
+            .filter(|(id, _)| Event::is_player(id))
+            .map(|(_, name)| name.as_str())
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Replaces the fields of a log line that are player names, leaving the
+/// opcode, the timestamp and lines that are not log events untouched.
+/// Returns the new line and how many fields were replaced.
+fn scrub_line(line: &str, pseudonyms: &Pseudonyms) -> (String, usize) {
+    if parse_line(line).is_none() {
+        return (line.to_string(), 0);
+    }
+    let mut replaced = 0;
+    let fields: Vec<&str> = line
+        .split('|')
+        .enumerate()
+        .map(|(index, field)| match pseudonyms.get(field) {
+            Some(pseudonym) if index >= 2 => {
+                replaced += 1;
+                pseudonym
+            }
+            _ => field,
+        })
+        .collect();
+    (fields.join("|"), replaced)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    const LOG: &str = "\
+03|2024-01-01T20:15:40.0000000+01:00|10001234|Tank Name|13|5A|0000|49|Gilgamesh|hash
+03|2024-01-01T20:15:40.0000000+01:00|40001234|Boss|00|5A|0000|00||hash
+00|2024-01-01T20:15:41.0000000+01:00|000e|Tank Name|Ready?|hash
+21|2024-01-01T20:15:42.0000000+01:00|10001234|Tank Name|1F|Attack|40001234|Boss|hash
+21|2024-01-01T20:15:43.0000000+01:00|10005678|Healer Name|78|Cure|10001234|Tank Name|hash
+not a log line mentioning Tank Name
+";
+
+    #[test]
+    fn test_scrub_replaces_player_names_consistently() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("Network_1.log");
+        let out = default_anon_path(&file);
+        fs::write(&file, LOG).unwrap();
+
+        scrub(&file, &out).unwrap();
+
+        assert_eq!(out, temp_dir.path().join("Network_1.anon.log"));
+        let scrubbed = fs::read_to_string(&out).unwrap();
+        let expected = "\
+03|2024-01-01T20:15:40.0000000+01:00|10001234|Player1|13|5A|0000|49|Gilgamesh|hash
+03|2024-01-01T20:15:40.0000000+01:00|40001234|Boss|00|5A|0000|00||hash
+00|2024-01-01T20:15:41.0000000+01:00|000e|Player1|Ready?|hash
+21|2024-01-01T20:15:42.0000000+01:00|10001234|Player1|1F|Attack|40001234|Boss|hash
+21|2024-01-01T20:15:43.0000000+01:00|10005678|Player2|78|Cure|10001234|Player1|hash
+not a log line mentioning Tank Name
+";
+        assert_eq!(scrubbed, expected);
+        assert_eq!(fs::read_to_string(&file).unwrap(), LOG);
+    }
+
+    #[test]
+    fn test_pseudonyms_are_stable() {
+        let mut pseudonyms = Pseudonyms::default();
+        pseudonyms.add("A");
+        pseudonyms.add("B");
+        pseudonyms.add("A");
+        pseudonyms.add("");
+
+        assert_eq!(pseudonyms.get("A"), Some("Player1"));
+        assert_eq!(pseudonyms.get("B"), Some("Player2"));
+        assert_eq!(pseudonyms.get(""), None);
+    }
+
+    #[test]
+    fn test_scrub_missing_input_writes_nothing() {
+        let temp_dir = TempDir::new().unwrap();
+        let out = temp_dir.path().join("out.log");
+
+        let result = scrub(&temp_dir.path().join("gone.log"), &out);
+
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::NotFound);
+        assert!(!out.exists());
+    }
+}
@@ -1,33 +1,406 @@
-use std::fs::{create_dir, rename};
-use std::path::Path;
+use crate::commands::delete::{delete_log_file, verify_copy};
+use crate::filters::SkipReason;
+use crate::hash::hash_file;
+use crate::output::{self, debug, sayln};
+use crate::paths::{self, explain_long_path, to_extended_path};
+use crate::retry::with_retries;
+use crate::scan::{self, EntryStream, LogFile, collect_log_files};
+use crate::style;
+use crate::summary::Summary;
+use crate::table::{self, Column};
+use crate::util::format_size;
+use crate::zip::ZipWriter;
+use std::cmp::Reverse;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::ffi::OsString;
+use std::fs::{File, create_dir_all, metadata, remove_file, rename};
+use std::io::{self, BufWriter, Read};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
-pub fn backup_log_file(path: &Path) {
-    let file_name = path.file_name().expect("not a file");
-    println!("Moving {}...", file_name.display());
+pub const DEFAULT_BACKUP_DIR: &str = "bak";
 
-    let mut new_path = path.to_owned().clone();
-    new_path.pop();
-    new_path = new_path.join(Path::new("bak"));
+/// Splits `files` into the `keep` most recently modified files and the rest,
+/// both newest first. With `keep` set to 0 every file ends up in the rest.
+pub fn split_most_recent(mut files: Vec<LogFile>, keep: usize) -> (Vec<LogFile>, Vec<LogFile>) {
+    files.sort_by_key(|file| Reverse(file.modified));
+    let rest = files.split_off(keep.min(files.len()));
+    (files, rest)
+}
+
+/// The `--keep-last` stage of a run: leaves the `keep` most recently
+/// modified files of `entries` out, reporting each as kept, and hands the
+/// rest on. Files that cannot be read are handed on for the backup to report.
+pub fn keep_most_recent(entries: EntryStream, keep: usize, summary: &mut Summary) -> EntryStream {
+    let (paths, mut others) = scan::split_files(entries, |_| true);
+    let mut files = Vec::new();
+    for path in paths {
+        match LogFile::from_path(path.clone()) {
+            Ok(file) => files.push(file),
+            Err(err) => others.push(Err(scan::with_path(&path, err))),
+        }
+    }
+    let (kept, rest) = split_most_recent(files, keep);
+    for file in &kept {
+        sayln!("Keeping {} (one of the {keep} most recent)", file.name());
+        summary.record_skip(SkipReason::Kept);
+    }
+    scan::rejoin(others, rest.into_iter().map(|file| file.path).collect())
+}
+
+/// Splits `files` into the first file of each group with identical content
+/// and the later copies, each paired with the file it duplicates. Empty
+/// files all count as copies of each other; files that cannot be read are
+/// treated as unique so the backup reports the error.
+pub fn split_duplicates(files: Vec<PathBuf>) -> (Vec<PathBuf>, Vec<(PathBuf, PathBuf)>) {
+    let mut originals: HashMap<String, PathBuf> = HashMap::new();
+    let mut unique = Vec::new();
+    let mut duplicates = Vec::new();
+    for path in files {
+        let key = match metadata(&path) {
+            Ok(meta) if meta.len() == 0 => Ok(String::new()),
+            _ => hash_file(&path),
+        };
+        match key {
+            Ok(key) => match originals.get(&key) {
+                Some(original) => duplicates.push((path, original.clone())),
+                None => {
+                    originals.insert(key, path.clone());
+                    unique.push(path);
+                }
+            },
+            Err(err) => {
+                debug!("Could not hash {}: {err}", path.display());
+                unique.push(path);
+            }
+        }
+    }
+    (unique, duplicates)
+}
+
+/// Lines of the table of `duplicates` shown before they are removed: each
+/// copy next to the file it duplicates.
+pub fn render_duplicates(duplicates: &[(PathBuf, PathBuf)]) -> Vec<String> {
+    const COLUMNS: [Column; 2] = [Column::left("Duplicate"), Column::left("Same as")];
+    let rows: Vec<Vec<String>> = duplicates
+        .iter()
+        .map(|(duplicate, original)| {
+            vec![
+                duplicate.display().to_string(),
+                original.display().to_string(),
+            ]
+        })
+        .collect();
+    table::render(&COLUMNS, &rows, true)
+}
+
+/// The `--dedupe` stage of a run: shows the duplicates among the files of
+/// `entries` and returns them next to the stream. Duplicates stay in the
+/// stream, in name order, to be removed instead of backed up in their turn.
+pub fn find_duplicates(entries: EntryStream) -> (EntryStream, HashSet<PathBuf>) {
+    let (mut files, others) = scan::split_files(entries, |_| true);
+    files.sort();
+    let (_, copies) = split_duplicates(files.clone());
+    for line in render_duplicates(&copies) {
+        sayln!("{line}");
+    }
+    let duplicates = copies.into_iter().map(|(duplicate, _)| duplicate).collect();
+    (scan::rejoin(others, files), duplicates)
+}
+
+/// Where backups of `file` go: a relative `backup_dir` sits next to the
+/// file, an absolute one is used as is.
+pub fn backup_dir_for(file: &Path, backup_dir: &Path) -> PathBuf {
+    file.parent().unwrap_or(Path::new("")).join(backup_dir)
+}
+
+/// Where `file` itself ends up once backed up, or an error for a path such
+/// as `..` that does not name a file.
+pub fn backup_path(file: &Path, backup_dir: &Path) -> io::Result<PathBuf> {
+    Ok(backup_dir_for(file, backup_dir).join(paths::file_name(file)?))
+}
+
+/// Moves `path` into its backup directory. With `verify` the file is
+/// always copied and checked by SHA-256 before the source is removed, as
+/// happens anyway when the backup directory is on another drive.
+pub fn backup_log_file(
+    path: &Path,
+    backup_dir: &Path,
+    dry_run: bool,
+    verify: bool,
+) -> io::Result<()> {
+    let file_name = paths::file_name(path)?;
+
+    let mut new_path = backup_dir_for(path, backup_dir);
+    if dry_run {
+        sayln!(
+            "Would move {} -> {}",
+            path.display(),
+            backup_path(path, backup_dir)?.display()
+        );
+        return Ok(());
+    }
+
+    sayln!("Moving {}...", file_name.display());
     if !new_path.is_dir() {
-        create_dir(&new_path).expect("could not create backup dir");
+        debug!("Creating backup directory {}", new_path.display());
+        if let Err(err) = create_dir_all(to_extended_path(&new_path))
+            .map_err(|err| explain_long_path(err, &new_path))
+        {
+            sayln!(
+                "{} could not create {}: {err}",
+                style::failure("Failed:"),
+                new_path.display()
+            );
+            return Err(err);
+        }
     }
     new_path = new_path.join(file_name);
+    // A rename would silently replace an earlier backup of the same name,
+    // say from another subfolder, and leave undo pointing at the wrong file.
+    if to_extended_path(&new_path).exists() {
+        let err = io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} already exists, source kept", new_path.display()),
+        );
+        sayln!("{} {err}", style::failure("Failed:"));
+        return Err(err);
+    }
 
-    match rename(path, &new_path) {
+    let moved = with_retries(|| {
+        if verify {
+            let (from, to) = (to_extended_path(path), to_extended_path(&new_path));
+            copy_then_remove(&from, &to).map_err(|err| explain_long_path(err, &new_path))
+        } else {
+            move_file(path, &new_path)
+        }
+    });
+    match moved {
         Ok(_result) => {
-            println!("Moved.");
+            sayln!("{}", style::success("Moved."));
+            Ok(())
         }
         Err(err) => {
-            println!("Failed: {err}");
+            sayln!("{} {err}", style::failure("Failed:"));
+            Err(err)
+        }
+    }
+}
+
+/// Renames `from` to `to`, falling back to copy-and-remove when the backup
+/// directory is on another drive.
+pub fn move_file(from: &Path, to: &Path) -> io::Result<()> {
+    let (from_path, to_path) = (to_extended_path(from), to_extended_path(to));
+    match rename(&from_path, &to_path) {
+        Err(err) if err.kind() == io::ErrorKind::CrossesDevices => {
+            debug!("{} is on another device, copying", to.display());
+            copy_then_remove(&from_path, &to_path)
         }
+        result => result,
     }
+    .map_err(|err| explain_long_path(err, to))
+}
+
+/// Copies `from` to `to` with its modification time, so backups still sort
+/// by date, and removes the original only once the copy's SHA-256 matches.
+/// A copy that does not match is discarded and the original kept.
+fn copy_then_remove(from: &Path, to: &Path) -> io::Result<()> {
+    let copied = File::open(from)
+        .and_then(|source| {
+            let modified = source.metadata()?.modified()?;
+            copy_into_place(source, modified, to)
+        })
+        .and_then(|_| verify_copy(from, to));
+    if copied? {
+        remove_file(from)
+    } else {
+        let _ = remove_file(to);
+        Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "verification failed: backup checksum mismatch, source kept",
+        ))
+    }
+}
+
+/// Writes `source` to `to` so that `to` never exists half-written: the data
+/// goes to `.tmp-<name>` next to it, is synced to disk with its modification
+/// time set to `modified`, and is only then renamed into place. On failure
+/// the temporary file is removed and `to` left as it was.
+fn copy_into_place(mut source: impl Read, modified: SystemTime, to: &Path) -> io::Result<()> {
+    let mut temp_name = OsString::from(".tmp-");
+    temp_name.push(paths::file_name(to)?);
+    let temp_path = to.with_file_name(temp_name);
+
+    let written = File::create(&temp_path).and_then(|mut temp| {
+        io::copy(&mut source, &mut temp)?;
+        temp.set_modified(modified)?;
+        temp.sync_all()
+    });
+    match written.and_then(|_| rename(&temp_path, to)) {
+        Ok(()) => Ok(()),
+        Err(err) => {
+            debug!("Removing unfinished copy {}", temp_path.display());
+            let _ = remove_file(&temp_path);
+            Err(err)
+        }
+    }
+}
+
+/// Bundles `files` into the zip archive `out`, each under its file name
+/// and with its modification time. Files are streamed into the archive one
+/// by one, and an existing `out` is never overwritten. On failure the
+/// partial archive is removed; the files themselves are left untouched.
+pub fn archive_logs(files: &[PathBuf], out: &Path) -> io::Result<()> {
+    let mut names = HashSet::new();
+    for file in files {
+        let name = paths::file_name(file)?;
+        if !names.insert(name) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "more than one file is named {}; archive them separately",
+                    name.display()
+                ),
+            ));
+        }
+    }
+
+    let archive = File::create_new(out).map_err(|err| explain_long_path(err, out))?;
+    let result = write_archive(files, ZipWriter::new(BufWriter::new(archive)));
+    if result.is_err() {
+        let _ = remove_file(out);
+    }
+    result
+}
+
+fn write_archive(files: &[PathBuf], mut zip: ZipWriter<BufWriter<File>>) -> io::Result<()> {
+    for file in files {
+        let name = paths::file_name(file)?.to_string_lossy();
+        sayln!("Adding {name}...");
+        let mut source =
+            File::open(to_extended_path(file)).map_err(|err| explain_long_path(err, file))?;
+        let modified = source.metadata()?.modified()?;
+        zip.add(&name, modified, &mut source)
+            .map_err(|err| io::Error::new(err.kind(), format!("{name}: {err}")))?;
+    }
+    zip.finish()?;
+    Ok(())
+}
+
+/// Writes the `--archive` of a run once every file is gathered, reporting
+/// it, and with `delete_after` removes the archived files. Returns the
+/// outcome for each file; a dry run only lists what would be added.
+pub fn archive_files(
+    files: &[PathBuf],
+    archive: &Path,
+    delete_after: bool,
+    dry_run: bool,
+) -> io::Result<Vec<io::Result<()>>> {
+    if dry_run {
+        for file in files {
+            sayln!("Would add {} to {}", file.display(), archive.display());
+        }
+        return Ok(files.iter().map(|_| Ok(())).collect());
+    }
+    archive_logs(files, archive)?;
+    output::emit(format!("Wrote {}", archive.display()));
+    Ok(files
+        .iter()
+        .map(|file| match delete_after {
+            true => delete_log_file(file, None, false),
+            false => Ok(()),
+        })
+        .collect())
+}
+
+/// Deletes the oldest files in `bak_dir` until the rest add up to at most
+/// `budget` bytes, and returns the files removed. A file larger than the
+/// whole budget is kept with a warning and left out of the total, so one
+/// oversized log does not get every other backup pruned. A file that cannot
+/// be removed is reported and the next oldest is tried instead.
+pub fn enforce_budget(bak_dir: &Path, budget: u64) -> io::Result<Vec<LogFile>> {
+    if !bak_dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut files = collect_log_files(bak_dir)?;
+    files.sort_by(|a, b| (a.modified, &a.path).cmp(&(b.modified, &b.path)));
+
+    let (oversized, files): (Vec<_>, Vec<_>) =
+        files.into_iter().partition(|file| file.size > budget);
+    for file in &oversized {
+        sayln!(
+            "{} {} ({}) is larger than the budget of {}; keeping it",
+            style::skipped("Warning:"),
+            file.name(),
+            format_size(file.size),
+            format_size(budget)
+        );
+    }
+
+    let mut total: u64 = files.iter().map(|file| file.size).sum();
+    let mut removed = Vec::new();
+    for file in files {
+        if total <= budget {
+            break;
+        }
+        match remove_file(to_extended_path(&file.path)) {
+            Ok(()) => {
+                debug!("Pruned {} to stay under the budget", file.path.display());
+                sayln!("Pruned {} ({})", file.name(), format_size(file.size));
+                total -= file.size;
+                removed.push(file);
+            }
+            Err(err) => sayln!(
+                "{} could not prune {}: {err}",
+                style::failure("Failed:"),
+                file.path.display()
+            ),
+        }
+    }
+    Ok(removed)
+}
+
+/// The `--max-bak-size` stage of a run: prunes each of `bak_dirs` down to
+/// `budget`, counts what was removed as freed in `summary` and returns the
+/// paths removed.
+pub fn enforce_budgets(
+    bak_dirs: &BTreeSet<PathBuf>,
+    budget: u64,
+    summary: &mut Summary,
+) -> Vec<PathBuf> {
+    let mut removed = Vec::new();
+    for bak_dir in bak_dirs {
+        match enforce_budget(bak_dir, budget) {
+            Ok(pruned) if !pruned.is_empty() => {
+                let bytes = pruned.iter().map(|file| file.size).sum();
+                summary.freed_files += pruned.len();
+                summary.freed_bytes += bytes;
+                output::emit(format!(
+                    "Pruned {} from {} to stay under {}",
+                    format_size(bytes),
+                    bak_dir.display(),
+                    format_size(budget)
+                ));
+                removed.extend(pruned.into_iter().map(|file| file.path));
+            }
+            Ok(_) => {}
+            Err(err) => output::emit(format!(
+                "{} could not prune {}: {err}",
+                style::failure("Failed:"),
+                bak_dir.display()
+            )),
+        }
+    }
+    removed
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::zip;
     use std::fs::{self, File};
     use std::io::Write;
+    use std::time::{Duration, UNIX_EPOCH};
     use tempfile::TempDir;
 
     #[test]
@@ -38,7 +411,7 @@ mod tests {
         let mut file = File::create(&file_path).unwrap();
         writeln!(file, "test content").unwrap();
 
-        backup_log_file(&file_path);
+        backup_log_file(&file_path, Path::new(DEFAULT_BACKUP_DIR), false, false).unwrap();
 
         let bak_dir = temp_dir.path().join("bak");
         assert!(bak_dir.exists());
@@ -53,7 +426,7 @@ mod tests {
         let mut file = File::create(&file_path).unwrap();
         writeln!(file, "test content").unwrap();
 
-        backup_log_file(&file_path);
+        backup_log_file(&file_path, Path::new(DEFAULT_BACKUP_DIR), false, false).unwrap();
 
         let backup_path = temp_dir.path().join("bak").join("test.log");
         assert!(!file_path.exists());
@@ -69,7 +442,7 @@ mod tests {
         let mut file = File::create(&file_path).unwrap();
         write!(file, "{test_content}").unwrap();
 
-        backup_log_file(&file_path);
+        backup_log_file(&file_path, Path::new(DEFAULT_BACKUP_DIR), false, false).unwrap();
 
         let backup_path = temp_dir.path().join("bak").join("test.log");
         let backup_content = fs::read_to_string(&backup_path).unwrap();
@@ -86,7 +459,7 @@ mod tests {
         let mut file = File::create(&file_path).unwrap();
         writeln!(file, "test content").unwrap();
 
-        backup_log_file(&file_path);
+        backup_log_file(&file_path, Path::new(DEFAULT_BACKUP_DIR), false, false).unwrap();
 
         let backup_path = bak_dir.join("test.log");
         assert!(!file_path.exists());
@@ -101,7 +474,7 @@ mod tests {
         let mut file = File::create(&file_path).unwrap();
         writeln!(file, "test content").unwrap();
 
-        backup_log_file(&file_path);
+        backup_log_file(&file_path, Path::new(DEFAULT_BACKUP_DIR), false, false).unwrap();
 
         let backup_path = temp_dir
             .path()
@@ -110,4 +483,440 @@ mod tests {
         assert!(!file_path.exists());
         assert!(backup_path.exists());
     }
+
+    #[test]
+    fn test_backup_log_file_custom_dir_name() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("test.log");
+        fs::write(&file_path, "test content").unwrap();
+
+        backup_log_file(&file_path, Path::new("archive"), false, false).unwrap();
+
+        assert!(!file_path.exists());
+        assert!(temp_dir.path().join("archive").join("test.log").exists());
+        assert!(!temp_dir.path().join("bak").exists());
+    }
+
+    #[test]
+    fn test_backup_log_file_absolute_dir_creates_tree() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("test.log");
+        fs::write(&file_path, "test content").unwrap();
+        let backup_dir = temp_dir.path().join("other").join("drive").join("logs");
+
+        backup_log_file(&file_path, &backup_dir, false, false).unwrap();
+
+        assert!(!file_path.exists());
+        assert_eq!(
+            fs::read_to_string(backup_dir.join("test.log")).unwrap(),
+            "test content"
+        );
+    }
+
+    #[test]
+    fn test_backup_dir_for() {
+        let file = Path::new("/logs/test.log");
+
+        assert_eq!(
+            backup_dir_for(file, Path::new("bak")),
+            Path::new("/logs/bak")
+        );
+        assert_eq!(
+            backup_dir_for(file, Path::new("/mnt/archive")),
+            Path::new("/mnt/archive")
+        );
+    }
+
+    #[test]
+    fn test_copy_then_remove_moves_content() {
+        let temp_dir = TempDir::new().unwrap();
+        let from = temp_dir.path().join("test.log");
+        let to = temp_dir.path().join("copy.log");
+        fs::write(&from, "test content").unwrap();
+
+        copy_then_remove(&from, &to).unwrap();
+
+        assert!(!from.exists());
+        assert_eq!(fs::read_to_string(&to).unwrap(), "test content");
+    }
+
+    #[test]
+    fn test_backup_log_file_never_replaces_same_named_backup() {
+        let temp_dir = TempDir::new().unwrap();
+        let backup_dir = temp_dir.path().join("archive");
+        for sub in ["a", "b"] {
+            fs::create_dir(temp_dir.path().join(sub)).unwrap();
+            fs::write(temp_dir.path().join(sub).join("Network_1.log"), sub).unwrap();
+        }
+        let second = temp_dir.path().join("b").join("Network_1.log");
+
+        backup_log_file(
+            &temp_dir.path().join("a").join("Network_1.log"),
+            &backup_dir,
+            false,
+            false,
+        )
+        .unwrap();
+        let err = backup_log_file(&second, &backup_dir, false, false).unwrap_err();
+
+        assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+        assert_eq!(fs::read_to_string(&second).unwrap(), "b");
+        assert_eq!(
+            fs::read_to_string(backup_dir.join("Network_1.log")).unwrap(),
+            "a"
+        );
+    }
+
+    #[test]
+    fn test_backup_log_file_verify_copies_and_checks() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("test.log");
+        fs::write(&file_path, "test content").unwrap();
+
+        backup_log_file(&file_path, Path::new(DEFAULT_BACKUP_DIR), false, true).unwrap();
+
+        assert!(!file_path.exists());
+        let backup = temp_dir.path().join(DEFAULT_BACKUP_DIR).join("test.log");
+        assert_eq!(fs::read_to_string(backup).unwrap(), "test content");
+    }
+
+    #[test]
+    fn test_copy_then_remove_keeps_source_when_copy_fails() {
+        let temp_dir = TempDir::new().unwrap();
+        let from = temp_dir.path().join("test.log");
+        let to = temp_dir.path().join("missing").join("copy.log");
+        fs::write(&from, "test content").unwrap();
+
+        assert!(copy_then_remove(&from, &to).is_err());
+
+        assert_eq!(fs::read_to_string(&from).unwrap(), "test content");
+        assert!(!to.exists());
+    }
+
+    /// Yields `data`, then fails as a disk or network error would mid-copy.
+    struct FailingReader<'a> {
+        data: &'a [u8],
+    }
+
+    impl Read for FailingReader<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.data.is_empty() {
+                return Err(io::Error::other("connection lost"));
+            }
+            let len = buf.len().min(self.data.len());
+            buf[..len].copy_from_slice(&self.data[..len]);
+            self.data = &self.data[len..];
+            Ok(len)
+        }
+    }
+
+    #[test]
+    fn test_copy_into_place_leaves_nothing_behind_on_failure() {
+        let temp_dir = TempDir::new().unwrap();
+        let bak = temp_dir.path().join("bak");
+        fs::create_dir(&bak).unwrap();
+        let to = bak.join("test.log");
+        let source = FailingReader {
+            data: b"first half of the log",
+        };
+
+        let err = copy_into_place(source, SystemTime::now(), &to).unwrap_err();
+
+        assert_eq!(err.to_string(), "connection lost");
+        assert!(!to.exists());
+        assert_eq!(fs::read_dir(&bak).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_copy_into_place_replaces_target_only_when_complete() {
+        let temp_dir = TempDir::new().unwrap();
+        let to = temp_dir.path().join("test.log");
+        fs::write(&to, "older backup").unwrap();
+
+        let failing = FailingReader { data: b"partial" };
+        assert!(copy_into_place(failing, SystemTime::now(), &to).is_err());
+        assert_eq!(fs::read_to_string(&to).unwrap(), "older backup");
+
+        copy_into_place(&b"new backup"[..], SystemTime::UNIX_EPOCH, &to).unwrap();
+        assert_eq!(fs::read_to_string(&to).unwrap(), "new backup");
+        assert_eq!(
+            fs::metadata(&to).unwrap().modified().unwrap(),
+            SystemTime::UNIX_EPOCH
+        );
+        assert_eq!(fs::read_dir(temp_dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_copy_then_remove_preserves_modified_time() {
+        let temp_dir = TempDir::new().unwrap();
+        let from = temp_dir.path().join("test.log");
+        let to = temp_dir.path().join("copy.log");
+        let old = std::time::SystemTime::now() - std::time::Duration::from_secs(30 * 86400);
+        File::create(&from).unwrap().set_modified(old).unwrap();
+
+        copy_then_remove(&from, &to).unwrap();
+
+        let copied = fs::metadata(&to).unwrap().modified().unwrap();
+        let drift = copied
+            .duration_since(old)
+            .unwrap_or_else(|err| err.duration());
+        assert!(drift < std::time::Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_backup_log_file_rejects_path_without_file_name() {
+        let err = backup_log_file(Path::new("/"), Path::new(DEFAULT_BACKUP_DIR), false, false)
+            .unwrap_err();
+
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn test_paths_without_file_name_are_errors_not_panics() {
+        let parent = Path::new("logs/..");
+
+        let err = backup_path(parent, Path::new(DEFAULT_BACKUP_DIR)).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        let err = archive_logs(&[parent.to_path_buf()], Path::new("out.zip")).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert!(!Path::new("out.zip").exists());
+    }
+
+    #[test]
+    fn test_backup_log_file_dry_run_touches_nothing() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("test.log");
+
+        let mut file = File::create(&file_path).unwrap();
+        writeln!(file, "test content").unwrap();
+
+        backup_log_file(&file_path, Path::new(DEFAULT_BACKUP_DIR), true, false).unwrap();
+
+        assert!(file_path.exists());
+        assert!(!temp_dir.path().join("bak").exists());
+    }
+
+    #[test]
+    fn test_archive_logs_bundles_names_contents_and_times() {
+        let temp_dir = TempDir::new().unwrap();
+        let first = temp_dir.path().join("Network_1.log");
+        let second = temp_dir.path().join("Network_2.log");
+        fs::write(&first, "first\n").unwrap();
+        fs::write(&second, "second\n").unwrap();
+        let modified = UNIX_EPOCH + Duration::from_secs(1_704_139_200);
+        File::options()
+            .write(true)
+            .open(&first)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+        let out = temp_dir.path().join("logs.zip");
+
+        archive_logs(&[first.clone(), second.clone()], &out).unwrap();
+
+        let entries = zip::read_entries(&fs::read(&out).unwrap());
+        let names: Vec<_> = entries.iter().map(|entry| entry.name.as_str()).collect();
+        assert_eq!(names, ["Network_1.log", "Network_2.log"]);
+        assert_eq!(entries[0].data, b"first\n");
+        assert_eq!(entries[1].data, b"second\n");
+        assert_eq!(entries[0].modified, 1_704_139_200);
+        assert!(first.exists() && second.exists());
+    }
+
+    #[test]
+    fn test_archive_logs_never_overwrites() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("Network_1.log");
+        fs::write(&file, "log\n").unwrap();
+        let out = temp_dir.path().join("logs.zip");
+        fs::write(&out, "keep me").unwrap();
+
+        let err = archive_logs(&[file], &out).unwrap_err();
+
+        assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+        assert_eq!(fs::read_to_string(&out).unwrap(), "keep me");
+    }
+
+    #[test]
+    fn test_archive_logs_failure_removes_partial_archive() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("Network_1.log");
+        fs::write(&file, "log\n").unwrap();
+        let out = temp_dir.path().join("logs.zip");
+        let gone = temp_dir.path().join("gone.log");
+
+        assert!(archive_logs(&[file.clone(), gone], &out).is_err());
+        assert!(!out.exists());
+
+        let twin = temp_dir.path().join("sub").join("Network_1.log");
+        let err = archive_logs(&[file, twin], &out).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert!(!out.exists());
+    }
+
+    #[test]
+    fn test_keep_last_backs_up_all_but_newest() {
+        let temp_dir = TempDir::new().unwrap();
+        let now = std::time::SystemTime::now();
+        let mut files = Vec::new();
+        for age in 0..5 {
+            let file_path = temp_dir.path().join(format!("test_{age}.log"));
+            let file = File::create(&file_path).unwrap();
+            file.set_modified(now - std::time::Duration::from_secs(age * 3600))
+                .unwrap();
+            files.push(LogFile::from_path(file_path).unwrap());
+        }
+
+        let (kept, rest) = split_most_recent(files, 2);
+        for file in &rest {
+            backup_log_file(&file.path, Path::new(DEFAULT_BACKUP_DIR), false, false).unwrap();
+        }
+
+        let names: Vec<String> = kept.iter().map(LogFile::name).collect();
+        assert_eq!(names, ["test_0.log", "test_1.log"]);
+        assert!(temp_dir.path().join("test_0.log").exists());
+        assert!(temp_dir.path().join("test_1.log").exists());
+        for age in 2..5 {
+            let name = format!("test_{age}.log");
+            assert!(!temp_dir.path().join(&name).exists());
+            assert!(temp_dir.path().join("bak").join(&name).exists());
+        }
+    }
+
+    #[test]
+    fn test_split_most_recent_zero_keeps_nothing() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("test.log");
+        File::create(&file_path).unwrap();
+        let files = vec![LogFile::from_path(file_path).unwrap()];
+
+        let (kept, rest) = split_most_recent(files, 0);
+
+        assert!(kept.is_empty());
+        assert_eq!(rest.len(), 1);
+    }
+
+    #[test]
+    fn test_split_most_recent_more_than_available() {
+        let (kept, rest) = split_most_recent(Vec::new(), 3);
+
+        assert!(kept.is_empty());
+        assert!(rest.is_empty());
+    }
+
+    #[test]
+    fn test_split_duplicates_keeps_first_copy() {
+        let temp_dir = TempDir::new().unwrap();
+        let first = temp_dir.path().join("a.log");
+        let copy = temp_dir.path().join("b.log");
+        let unique = temp_dir.path().join("c.log");
+        fs::write(&first, "same content").unwrap();
+        fs::write(&copy, "same content").unwrap();
+        fs::write(&unique, "other content").unwrap();
+
+        let (kept, duplicates) =
+            split_duplicates(vec![first.clone(), copy.clone(), unique.clone()]);
+
+        assert_eq!(kept, [first.clone(), unique]);
+        assert_eq!(duplicates, [(copy, first)]);
+    }
+
+    #[test]
+    fn test_render_duplicates() {
+        let duplicates = [
+            (PathBuf::from("b.log"), PathBuf::from("a.log")),
+            (PathBuf::from("copy_of_a.log"), PathBuf::from("a.log")),
+        ];
+
+        assert_eq!(
+            render_duplicates(&duplicates),
+            [
+                "Duplicate      Same as",
+                "b.log          a.log",
+                "copy_of_a.log  a.log",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_split_duplicates_empty_files_are_duplicates() {
+        let temp_dir = TempDir::new().unwrap();
+        let paths: Vec<_> = (0..3)
+            .map(|i| temp_dir.path().join(format!("empty_{i}.log")))
+            .collect();
+        for path in &paths {
+            File::create(path).unwrap();
+        }
+
+        let (kept, duplicates) = split_duplicates(paths.clone());
+
+        assert_eq!(kept, [paths[0].clone()]);
+        assert_eq!(
+            duplicates,
+            [
+                (paths[1].clone(), paths[0].clone()),
+                (paths[2].clone(), paths[0].clone()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_split_duplicates_unreadable_file_is_unique() {
+        let temp_dir = TempDir::new().unwrap();
+        let missing = temp_dir.path().join("gone.log");
+
+        let (kept, duplicates) = split_duplicates(vec![missing.clone()]);
+
+        assert_eq!(kept, [missing]);
+        assert!(duplicates.is_empty());
+    }
+
+    fn write_aged(dir: &Path, name: &str, size: usize, secs: u64) {
+        let file = File::create(dir.join(name)).unwrap();
+        file.set_len(size as u64).unwrap();
+        file.set_modified(UNIX_EPOCH + Duration::from_secs(secs))
+            .unwrap();
+    }
+
+    #[test]
+    fn test_enforce_budget_removes_oldest_first() {
+        let temp_dir = TempDir::new().unwrap();
+        let bak = temp_dir.path();
+        write_aged(bak, "newest.log", 40, 300);
+        write_aged(bak, "oldest.log", 40, 100);
+        write_aged(bak, "middle.log", 40, 200);
+
+        let removed = enforce_budget(bak, 90).unwrap();
+
+        let names: Vec<_> = removed.iter().map(LogFile::name).collect();
+        assert_eq!(names, ["oldest.log"]);
+        assert!(bak.join("middle.log").exists());
+        assert!(bak.join("newest.log").exists());
+        assert!(enforce_budget(bak, 90).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_enforce_budget_keeps_a_file_larger_than_the_budget() {
+        let temp_dir = TempDir::new().unwrap();
+        let bak = temp_dir.path();
+        write_aged(bak, "huge.log", 500, 100);
+        write_aged(bak, "a.log", 30, 200);
+        write_aged(bak, "b.log", 30, 300);
+
+        let removed = enforce_budget(bak, 50).unwrap();
+
+        let names: Vec<_> = removed.iter().map(LogFile::name).collect();
+        assert_eq!(names, ["a.log"]);
+        assert!(bak.join("huge.log").exists());
+        assert!(bak.join("b.log").exists());
+    }
+
+    #[test]
+    fn test_enforce_budget_without_backup_directory() {
+        let temp_dir = TempDir::new().unwrap();
+
+        let removed = enforce_budget(&temp_dir.path().join("bak"), 0).unwrap();
+
+        assert!(removed.is_empty());
+    }
 }
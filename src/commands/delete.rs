@@ -1,95 +1,477 @@
-use std::fs::remove_file;
-use std::path::Path;
-
-pub fn delete_log_file(path: &Path) {
-    print!("Removing {}...", path.display());
-    match remove_file(path) {
-        Ok(_result) => {
-            println!("Removed.");
-        }
-        Err(err) => {
-            println!("Failed: {err}");
-        }
-    }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::fs::File;
-    use std::io::Write;
-    use tempfile::TempDir;
-
-    #[test]
-    fn test_delete_log_file_removes_file() {
-        let temp_dir = TempDir::new().unwrap();
-        let file_path = temp_dir.path().join("test.log");
-
-        let mut file = File::create(&file_path).unwrap();
-        writeln!(file, "test content").unwrap();
-
-        assert!(file_path.exists());
-
-        delete_log_file(&file_path);
-
-        assert!(!file_path.exists());
-    }
-
-    #[test]
-    fn test_delete_log_file_with_special_characters() {
-        let temp_dir = TempDir::new().unwrap();
-        let file_path = temp_dir.path().join("test file with spaces.log");
-
-        let mut file = File::create(&file_path).unwrap();
-        writeln!(file, "test content").unwrap();
-
-        assert!(file_path.exists());
-
-        delete_log_file(&file_path);
-
-        assert!(!file_path.exists());
-    }
-
-    #[test]
-    fn test_delete_log_file_empty_file() {
-        let temp_dir = TempDir::new().unwrap();
-        let file_path = temp_dir.path().join("empty.log");
-
-        File::create(&file_path).unwrap();
-
-        assert!(file_path.exists());
-
-        delete_log_file(&file_path);
-
-        assert!(!file_path.exists());
-    }
-
-    #[test]
-    fn test_delete_log_file_large_file() {
-        let temp_dir = TempDir::new().unwrap();
-        let file_path = temp_dir.path().join("large.log");
-
-        let mut file = File::create(&file_path).unwrap();
-        for i in 0..1000 {
-            writeln!(file, "Line {i} with some content").unwrap();
-        }
-
-        assert!(file_path.exists());
-
-        delete_log_file(&file_path);
-
-        assert!(!file_path.exists());
-    }
-
-    #[test]
-    fn test_delete_log_file_nonexistent() {
-        let temp_dir = TempDir::new().unwrap();
-        let file_path = temp_dir.path().join("nonexistent.log");
-
-        assert!(!file_path.exists());
-
-        delete_log_file(&file_path);
-
-        assert!(!file_path.exists());
-    }
-}
+use crate::hash::hash_file;
+use crate::output::{debug, say, sayln};
+use crate::paths::{self, explain_long_path, to_extended_path};
+use crate::retry::with_retries;
+use crate::style;
+use crate::trash::Trash;
+use std::fs::{copy, create_dir_all, remove_file, rename};
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub fn delete_log_file(path: &Path, trash: Option<&dyn Trash>, dry_run: bool) -> io::Result<()> {
+    if dry_run {
+        match trash {
+            Some(_) => sayln!("Would move {} to the trash", path.display()),
+            None => sayln!("Would remove {}", path.display()),
+        }
+        return Ok(());
+    }
+    let (result, done) = match trash {
+        Some(trash) => {
+            say!("Moving {} to the trash...", path.display());
+            (with_retries(|| trash.trash(path)), "Trashed.")
+        }
+        None => {
+            say!("Removing {}...", path.display());
+            let removed = with_retries(|| remove_file(to_extended_path(path)));
+            (
+                removed.map_err(|err| explain_long_path(err, path)),
+                "Removed.",
+            )
+        }
+    };
+    match result {
+        Ok(()) => {
+            sayln!("{}", style::success(done));
+            Ok(())
+        }
+        Err(err) => {
+            sayln!("{} {err}", style::failure("Failed:"));
+            Err(err)
+        }
+    }
+}
+
+/// Copies `path` into `backup_dir`, checks the copy by SHA-256 and only
+/// then removes `path`. The copy keeps the file's folders below `root`, so
+/// same-named logs from different subfolders of a recursive run stay apart;
+/// a file outside `root` goes straight into `backup_dir`. An existing
+/// backup is never replaced: the file is kept and an error returned. A copy
+/// that fails the check is removed again and the file kept.
+pub fn safe_delete_log_file(
+    path: &Path,
+    root: &Path,
+    backup_dir: &Path,
+    dry_run: bool,
+) -> io::Result<()> {
+    let file_name = paths::file_name(path)?;
+    let relative = path
+        .strip_prefix(root)
+        .ok()
+        .filter(|relative| relative.parent().is_some())
+        .unwrap_or(Path::new(file_name));
+    let backup_path = backup_dir.join(relative);
+    if dry_run {
+        sayln!(
+            "Would back up {} -> {}, verify and remove",
+            path.display(),
+            backup_path.display()
+        );
+        return Ok(());
+    }
+    say!("Removing {} safely...", path.display());
+
+    if backup_path.exists() {
+        sayln!(
+            "{} {} already exists, source kept.",
+            style::failure("aborted at backup:"),
+            backup_path.display()
+        );
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} already exists, source kept", backup_path.display()),
+        ));
+    }
+    let backup_parent = backup_path.parent().unwrap_or(backup_dir);
+    let mut partial_path = backup_path.clone().into_os_string();
+    partial_path.push(".partial");
+    let partial_path = PathBuf::from(partial_path);
+
+    let backed_up = create_dir_all(to_extended_path(backup_parent))
+        .and_then(|_| copy(to_extended_path(path), to_extended_path(&partial_path)))
+        .and_then(|_| {
+            rename(
+                to_extended_path(&partial_path),
+                to_extended_path(&backup_path),
+            )
+        })
+        .map_err(|err| explain_long_path(err, &backup_path));
+    if let Err(err) = backed_up {
+        let _ = remove_file(to_extended_path(&partial_path));
+        sayln!("{} {err}", style::failure("aborted at backup:"));
+        return Err(err);
+    }
+    say!("backed up, ");
+
+    match verify_backup(path, &backup_path) {
+        Ok(()) => say!("verified, "),
+        Err(err) => {
+            sayln!("{} {err}", style::failure("aborted at verify:"));
+            return Err(err);
+        }
+    }
+
+    match remove_file(to_extended_path(path)).map_err(|err| explain_long_path(err, path)) {
+        Ok(_result) => {
+            sayln!("{}", style::success("deleted."));
+            Ok(())
+        }
+        Err(err) => {
+            sayln!("{} {err}", style::failure("aborted at delete:"));
+            Err(err)
+        }
+    }
+}
+
+/// Checks `backup_path` against `path`. A copy that does not match, or
+/// cannot be checked, is removed so a later run can back the file up again
+/// instead of stopping at the existing backup.
+fn verify_backup(path: &Path, backup_path: &Path) -> io::Result<()> {
+    let verified = verify_copy(path, backup_path).and_then(|same| match same {
+        true => Ok(()),
+        false => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "backup checksum mismatch, source kept",
+        )),
+    });
+    if verified.is_err() {
+        let _ = remove_file(to_extended_path(backup_path));
+    }
+    verified
+}
+
+/// Whether `copy` has the same SHA-256 as `source`.
+pub fn verify_copy(source: &Path, copy: &Path) -> io::Result<bool> {
+    let source_hash = hash_file(&to_extended_path(source))?;
+    let copy_hash = hash_file(&to_extended_path(copy))?;
+    debug!("SHA-256 of source {source_hash}, of copy {copy_hash}");
+    Ok(source_hash == copy_hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::filters::FileFilter;
+    use crate::scan::LogFile;
+    use std::fs::{self, File};
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_delete_log_file_removes_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("test.log");
+
+        let mut file = File::create(&file_path).unwrap();
+        writeln!(file, "test content").unwrap();
+
+        assert!(file_path.exists());
+
+        delete_log_file(&file_path, None, false).unwrap();
+
+        assert!(!file_path.exists());
+    }
+
+    #[test]
+    fn test_delete_log_file_with_special_characters() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("test file with spaces.log");
+
+        let mut file = File::create(&file_path).unwrap();
+        writeln!(file, "test content").unwrap();
+
+        assert!(file_path.exists());
+
+        delete_log_file(&file_path, None, false).unwrap();
+
+        assert!(!file_path.exists());
+    }
+
+    #[test]
+    fn test_delete_log_file_empty_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("empty.log");
+        let full_path = temp_dir.path().join("full.log");
+
+        File::create(&file_path).unwrap();
+        let mut file = File::create(&full_path).unwrap();
+        writeln!(file, "test content").unwrap();
+
+        assert!(file_path.exists());
+
+        let filter = FileFilter {
+            empty_only: true,
+            ..FileFilter::default()
+        };
+        for path in [&file_path, &full_path] {
+            if filter
+                .check(&LogFile::from_path(path.clone()).unwrap())
+                .is_ok()
+            {
+                delete_log_file(path, None, false).unwrap();
+            }
+        }
+
+        assert!(!file_path.exists());
+        assert!(full_path.exists());
+    }
+
+    #[test]
+    fn test_delete_log_file_large_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("large.log");
+
+        let mut file = File::create(&file_path).unwrap();
+        for i in 0..1000 {
+            writeln!(file, "Line {i} with some content").unwrap();
+        }
+
+        assert!(file_path.exists());
+
+        delete_log_file(&file_path, None, false).unwrap();
+
+        assert!(!file_path.exists());
+    }
+
+    #[test]
+    fn test_delete_log_file_nonexistent() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("nonexistent.log");
+
+        assert!(!file_path.exists());
+
+        assert!(delete_log_file(&file_path, None, false).is_err());
+
+        assert!(!file_path.exists());
+    }
+
+    struct StubTrash {
+        dir: std::path::PathBuf,
+    }
+
+    impl Trash for StubTrash {
+        fn trash(&self, path: &Path) -> io::Result<()> {
+            create_dir_all(&self.dir)?;
+            rename(path, self.dir.join(path.file_name().unwrap()))
+        }
+    }
+
+    struct UnavailableTrash;
+
+    impl Trash for UnavailableTrash {
+        fn trash(&self, _path: &Path) -> io::Result<()> {
+            Err(io::Error::new(io::ErrorKind::Unsupported, "no trash here"))
+        }
+    }
+
+    #[test]
+    fn test_delete_log_file_to_trash() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("test.log");
+        let trash = StubTrash {
+            dir: temp_dir.path().join("trash"),
+        };
+
+        File::create(&file_path).unwrap();
+
+        delete_log_file(&file_path, Some(&trash), false).unwrap();
+
+        assert!(!file_path.exists());
+        assert!(trash.dir.join("test.log").exists());
+    }
+
+    #[test]
+    fn test_delete_log_file_trash_unavailable_keeps_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("test.log");
+
+        File::create(&file_path).unwrap();
+
+        let result = delete_log_file(&file_path, Some(&UnavailableTrash), false);
+
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::Unsupported);
+        assert!(file_path.exists());
+    }
+
+    #[test]
+    fn test_delete_log_file_trash_dry_run_keeps_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("test.log");
+
+        File::create(&file_path).unwrap();
+
+        delete_log_file(&file_path, Some(&UnavailableTrash), true).unwrap();
+
+        assert!(file_path.exists());
+    }
+
+    #[test]
+    fn test_safe_delete_log_file_rejects_path_without_file_name() {
+        let temp_dir = TempDir::new().unwrap();
+
+        let err = safe_delete_log_file(Path::new("/"), temp_dir.path(), temp_dir.path(), false)
+            .unwrap_err();
+
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert!(temp_dir.path().read_dir().unwrap().next().is_none());
+    }
+
+    #[test]
+    fn test_safe_delete_log_file_backs_up_then_removes() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("test.log");
+        let backup_dir = temp_dir.path().join("bak");
+
+        let mut file = File::create(&file_path).unwrap();
+        writeln!(file, "test content").unwrap();
+
+        safe_delete_log_file(&file_path, temp_dir.path(), &backup_dir, false).unwrap();
+
+        assert!(!file_path.exists());
+        let backup_content = std::fs::read_to_string(backup_dir.join("test.log")).unwrap();
+        assert_eq!(backup_content, "test content\n");
+        assert!(!backup_dir.join("test.log.partial").exists());
+    }
+
+    #[test]
+    fn test_safe_delete_log_file_keeps_same_named_files_apart() {
+        let temp_dir = TempDir::new().unwrap();
+        let backup_dir = temp_dir.path().join("bak");
+        for (sub, content) in [("z1", "first\n"), ("z2", "second\n")] {
+            fs::create_dir(temp_dir.path().join(sub)).unwrap();
+            fs::write(temp_dir.path().join(sub).join("Network_1.log"), content).unwrap();
+        }
+
+        for sub in ["z1", "z2"] {
+            let file_path = temp_dir.path().join(sub).join("Network_1.log");
+            safe_delete_log_file(&file_path, temp_dir.path(), &backup_dir, false).unwrap();
+            assert!(!file_path.exists());
+        }
+
+        assert_eq!(
+            fs::read_to_string(backup_dir.join("z1").join("Network_1.log")).unwrap(),
+            "first\n"
+        );
+        assert_eq!(
+            fs::read_to_string(backup_dir.join("z2").join("Network_1.log")).unwrap(),
+            "second\n"
+        );
+    }
+
+    #[test]
+    fn test_safe_delete_log_file_never_replaces_a_backup() {
+        let temp_dir = TempDir::new().unwrap();
+        let backup_dir = temp_dir.path().join("bak");
+        let file_path = temp_dir.path().join("Network_1.log");
+        fs::create_dir(&backup_dir).unwrap();
+        fs::write(backup_dir.join("Network_1.log"), "old\n").unwrap();
+        fs::write(&file_path, "new\n").unwrap();
+
+        let err =
+            safe_delete_log_file(&file_path, temp_dir.path(), &backup_dir, false).unwrap_err();
+
+        assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+        assert_eq!(fs::read_to_string(&file_path).unwrap(), "new\n");
+        assert_eq!(
+            fs::read_to_string(backup_dir.join("Network_1.log")).unwrap(),
+            "old\n"
+        );
+    }
+
+    #[test]
+    fn test_verify_backup_removes_a_mismatched_copy() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("Network_1.log");
+        let backup_path = temp_dir.path().join("bak").join("Network_1.log");
+        fs::create_dir(temp_dir.path().join("bak")).unwrap();
+        fs::write(&file_path, "source\n").unwrap();
+        fs::write(&backup_path, "corrupt\n").unwrap();
+
+        let err = verify_backup(&file_path, &backup_path).unwrap_err();
+
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(fs::read_to_string(&file_path).unwrap(), "source\n");
+        assert!(!backup_path.exists());
+
+        // With the bad copy gone the file can be safe-deleted again.
+        safe_delete_log_file(
+            &file_path,
+            temp_dir.path(),
+            &temp_dir.path().join("bak"),
+            false,
+        )
+        .unwrap();
+        assert!(!file_path.exists());
+        assert_eq!(fs::read_to_string(&backup_path).unwrap(), "source\n");
+    }
+
+    #[test]
+    fn test_safe_delete_log_file_creates_nested_backup_dir() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("test.log");
+        let backup_dir = temp_dir.path().join("archive").join("2024");
+
+        File::create(&file_path).unwrap();
+
+        safe_delete_log_file(&file_path, temp_dir.path(), &backup_dir, false).unwrap();
+
+        assert!(!file_path.exists());
+        assert!(backup_dir.join("test.log").exists());
+    }
+
+    #[test]
+    fn test_safe_delete_log_file_keeps_source_when_backup_fails() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("test.log");
+        let blocker = temp_dir.path().join("blocker");
+
+        File::create(&file_path).unwrap();
+        File::create(&blocker).unwrap();
+
+        assert!(
+            safe_delete_log_file(&file_path, temp_dir.path(), &blocker.join("bak"), false).is_err()
+        );
+
+        assert!(file_path.exists());
+    }
+
+    #[test]
+    fn test_delete_log_file_dry_run_keeps_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("test.log");
+
+        File::create(&file_path).unwrap();
+
+        delete_log_file(&file_path, None, true).unwrap();
+
+        assert!(file_path.exists());
+    }
+
+    #[test]
+    fn test_safe_delete_log_file_dry_run_touches_nothing() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("test.log");
+        let backup_dir = temp_dir.path().join("bak");
+
+        File::create(&file_path).unwrap();
+
+        safe_delete_log_file(&file_path, temp_dir.path(), &backup_dir, true).unwrap();
+
+        assert!(file_path.exists());
+        assert!(!backup_dir.exists());
+    }
+
+    #[test]
+    fn test_verify_copy_detects_mismatch() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = temp_dir.path().join("source.log");
+        let copy = temp_dir.path().join("copy.log");
+
+        let mut file = File::create(&source).unwrap();
+        writeln!(file, "original").unwrap();
+        let mut file = File::create(&copy).unwrap();
+        writeln!(file, "corrupted").unwrap();
+
+        assert!(!verify_copy(&source, &copy).unwrap());
+    }
+}
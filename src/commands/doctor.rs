@@ -0,0 +1,247 @@
+//! Diagnosing a misconfigured log directory.
+//!
+//! `doctor` runs the checks a first run usually trips over and prints them
+//! as a checklist, each failure with a hint on what to do about it.
+
+use crate::filters::has_log_extension;
+use crate::scan::collect_log_files;
+use crate::style;
+use std::fs::{File, read_dir, remove_file};
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Created and removed again to find out whether a directory is writable.
+const PROBE_NAME: &str = ".ff-log-cli-doctor";
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Check {
+    pub passed: bool,
+    pub description: String,
+    /// What to do about a failed check.
+    pub hint: Option<String>,
+}
+
+impl Check {
+    fn pass(description: String) -> Self {
+        Check {
+            passed: true,
+            description,
+            hint: None,
+        }
+    }
+
+    fn fail(description: String, hint: &str) -> Self {
+        Check {
+            passed: false,
+            description,
+            hint: Some(hint.to_string()),
+        }
+    }
+}
+
+/// Checks whether any of `candidates`, the places ACT usually keeps its
+/// logs, exists, then `dir` and the `backup_dir` inside it. Checks that
+/// depend on `dir` being readable are left out when it is not.
+pub fn diagnose(dir: &Path, backup_dir: &Path, candidates: &[PathBuf]) -> Vec<Check> {
+    let mut checks = vec![default_dir_check(candidates)];
+
+    if !dir.exists() {
+        checks.push(Check::fail(
+            format!("Directory {} does not exist", dir.display()),
+            "Check the path for typos, or pass 'default' to use the detected ACT folder.",
+        ));
+        return checks;
+    }
+    if !dir.is_dir() {
+        checks.push(Check::fail(
+            format!("{} is not a directory", dir.display()),
+            "Pass the folder that holds the logs rather than a file in it.",
+        ));
+        return checks;
+    }
+    if let Err(err) = read_dir(dir) {
+        checks.push(Check::fail(
+            format!("Directory {} cannot be read: {err}", dir.display()),
+            "Check the folder's permissions, or close programs that lock it.",
+        ));
+        return checks;
+    }
+    checks.push(Check::pass(format!(
+        "Directory {} exists and is readable",
+        dir.display()
+    )));
+
+    let logs = collect_log_files(dir).map_or(0, |files| {
+        files
+            .iter()
+            .filter(|file| has_log_extension(&file.path))
+            .count()
+    });
+    checks.push(match logs {
+        0 => Check::fail(
+            format!("No .log files in {}", dir.display()),
+            "ACT writes its Network_*.log files to the folder set in its FFXIV plugin options; make sure this is that folder.",
+        ),
+        1 => Check::pass("1 log file found".to_string()),
+        _ => Check::pass(format!("{logs} log files found")),
+    });
+
+    checks.push(backup_dir_check(&dir.join(backup_dir), dir));
+    checks
+}
+
+fn default_dir_check(candidates: &[PathBuf]) -> Check {
+    const HINT: &str =
+        "Pass the log directory explicitly, or set FFLOGS_DIR or fflogs_dir in the config file.";
+    match candidates.iter().find(|candidate| candidate.is_dir()) {
+        Some(found) => Check::pass(format!("Default ACT log folder found: {}", found.display())),
+        None if candidates.is_empty() => Check::fail(
+            "Default ACT log folder cannot be located on this system".to_string(),
+            HINT,
+        ),
+        None => {
+            let looked_in: Vec<_> = candidates
+                .iter()
+                .map(|candidate| candidate.display().to_string())
+                .collect();
+            Check::fail(
+                format!(
+                    "Default ACT log folder not found (looked in {})",
+                    looked_in.join(", ")
+                ),
+                HINT,
+            )
+        }
+    }
+}
+
+fn backup_dir_check(bak_dir: &Path, dir: &Path) -> Check {
+    if bak_dir.is_dir() {
+        return match probe_writable(bak_dir) {
+            Ok(()) => Check::pass(format!(
+                "Backup directory {} is writable",
+                bak_dir.display()
+            )),
+            Err(err) => Check::fail(
+                format!(
+                    "Backup directory {} is not writable: {err}",
+                    bak_dir.display()
+                ),
+                "Check its permissions, or pick another one with backup --backup-dir.",
+            ),
+        };
+    }
+    if bak_dir.exists() {
+        return Check::fail(
+            format!("{} exists but is not a directory", bak_dir.display()),
+            "Rename or remove it so backups can be stored there.",
+        );
+    }
+    match probe_writable(dir) {
+        Ok(()) => Check::pass(format!(
+            "Backup directory {} will be created on the first backup",
+            bak_dir.display()
+        )),
+        Err(err) => Check::fail(
+            format!(
+                "Backup directory {} cannot be created: {err}",
+                bak_dir.display()
+            ),
+            "Check the permissions of the log directory, or pick another backup directory with backup --backup-dir.",
+        ),
+    }
+}
+
+fn probe_writable(dir: &Path) -> io::Result<()> {
+    let probe = dir.join(PROBE_NAME);
+    File::create_new(&probe)?;
+    remove_file(probe)
+}
+
+/// Prints `checks` as a checklist and returns whether they all passed.
+pub fn print_checklist(checks: &[Check]) -> bool {
+    for check in checks {
+        if check.passed {
+            println!("{} {}", style::success("[ok]"), check.description);
+        } else {
+            println!("{} {}", style::failure("[fail]"), check.description);
+        }
+        if let Some(hint) = &check.hint {
+            println!("       {hint}");
+        }
+    }
+    let failed = checks.iter().filter(|check| !check.passed).count();
+    if failed == 0 {
+        println!("\nAll checks passed.");
+    } else {
+        println!("\n{failed} of {} checks failed.", checks.len());
+    }
+    failed == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn passed(checks: &[Check]) -> Vec<bool> {
+        checks.iter().map(|check| check.passed).collect()
+    }
+
+    #[test]
+    fn test_diagnose_healthy_directory() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("Network_1.log"), "").unwrap();
+        fs::write(temp_dir.path().join("notes.txt"), "").unwrap();
+        fs::create_dir(temp_dir.path().join("bak")).unwrap();
+        let candidates = [temp_dir.path().to_path_buf()];
+
+        let checks = diagnose(temp_dir.path(), Path::new("bak"), &candidates);
+
+        assert_eq!(passed(&checks), [true, true, true, true]);
+        assert_eq!(checks[2].description, "1 log file found");
+        assert!(!temp_dir.path().join("bak").join(PROBE_NAME).exists());
+    }
+
+    #[test]
+    fn test_diagnose_missing_directory_stops_early() {
+        let temp_dir = TempDir::new().unwrap();
+        let missing = temp_dir.path().join("missing");
+
+        let checks = diagnose(&missing, Path::new("bak"), std::slice::from_ref(&missing));
+
+        assert_eq!(passed(&checks), [false, false]);
+        assert!(checks.iter().all(|check| check.hint.is_some()));
+    }
+
+    #[test]
+    fn test_diagnose_empty_directory_without_backup_dir() {
+        let temp_dir = TempDir::new().unwrap();
+
+        let checks = diagnose(temp_dir.path(), Path::new("bak"), &[]);
+
+        assert_eq!(passed(&checks), [false, true, false, true]);
+        assert!(
+            checks[3]
+                .description
+                .ends_with("will be created on the first backup")
+        );
+        assert!(!temp_dir.path().join(PROBE_NAME).exists());
+    }
+
+    #[test]
+    fn test_diagnose_backup_path_that_is_a_file() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("bak"), "").unwrap();
+
+        let checks = diagnose(temp_dir.path(), Path::new("bak"), &[]);
+
+        assert!(!checks[3].passed);
+        assert!(
+            checks[3]
+                .description
+                .ends_with("exists but is not a directory")
+        );
+    }
+}
@@ -0,0 +1,364 @@
+use crate::encoding::Encoding;
+use crate::gzip::open_maybe_gz;
+use crate::json;
+use crate::parser::{Event, Payload, parse_line};
+use std::ffi::OsString;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+const CSV_HEADER: &str = "timestamp,opcode,source,target,ability";
+
+/// The default export target: `<name>.csv` next to the source file.
+pub fn default_csv_path(file: &Path) -> PathBuf {
+    file.with_extension("csv")
+}
+
+/// Where lines that could not be parsed are written, e.g. `out.csv.skipped`.
+pub fn skipped_path(out: &Path) -> PathBuf {
+    let mut name = OsString::from(out.as_os_str());
+    name.push(".skipped");
+    PathBuf::from(name)
+}
+
+pub fn export_to_csv(file: &Path, out: &Path, encoding: Encoding) -> io::Result<()> {
+    println!("Exporting {} -> {}...", file.display(), out.display());
+    let mut reader = BufReader::new(File::open(file)?);
+    let mut writer = BufWriter::new(File::create(out)?);
+    writeln!(writer, "{CSV_HEADER}")?;
+
+    let skipped_out = skipped_path(out);
+    let mut skipped: Option<BufWriter<File>> = None;
+    let (mut exported, mut skipped_count) = (0, 0);
+    let mut buffer = Vec::new();
+    while reader.read_until(b'\n', &mut buffer)? > 0 {
+        let line = encoding.decode(&buffer);
+        let line = line.trim_end_matches(['\r', '\n']);
+        if !line.is_empty() {
+            match parse_line(line) {
+                Some(event) => {
+                    writeln!(writer, "{}", csv_row(&event))?;
+                    exported += 1;
+                }
+                None => {
+                    if skipped.is_none() {
+                        skipped = Some(BufWriter::new(File::create(&skipped_out)?));
+                    }
+                    if let Some(skipped) = skipped.as_mut() {
+                        writeln!(skipped, "{line}")?;
+                    }
+                    skipped_count += 1;
+                }
+            }
+        }
+        buffer.clear();
+    }
+    writer.flush()?;
+    if let Some(mut skipped) = skipped {
+        skipped.flush()?;
+    }
+
+    if skipped_count > 0 {
+        println!(
+            "Exported {exported} events, {skipped_count} unparsed lines written to {}.",
+            skipped_out.display()
+        );
+    } else {
+        println!("Exported {exported} events.");
+    }
+    Ok(())
+}
+
+/// Writes every parsed event of `file` as one JSON object per line to
+/// `out`, or to stdout. Unparsed lines are skipped and counted. The log is
+/// read and written line by line, so memory use does not grow with its
+/// size; with stdout as the output, progress goes to stderr.
+pub fn export_json_lines(file: &Path, out: Option<&Path>, encoding: Encoding) -> io::Result<()> {
+    let mut reader = open_maybe_gz(file)?;
+    let (exported, skipped) = match out {
+        Some(out) => {
+            println!("Exporting {} -> {}...", file.display(), out.display());
+            let mut writer = BufWriter::new(File::create(out)?);
+            let counts = write_json_lines(&mut reader, &mut writer, encoding)?;
+            writer.flush()?;
+            counts
+        }
+        None => {
+            let mut writer = BufWriter::new(io::stdout().lock());
+            let counts = write_json_lines(&mut reader, &mut writer, encoding)?;
+            writer.flush()?;
+            counts
+        }
+    };
+
+    let report = format!("Exported {exported} events, skipped {skipped} unparsed lines.");
+    if out.is_some() {
+        println!("{report}");
+    } else {
+        eprintln!("{report}");
+    }
+    Ok(())
+}
+
+/// Returns how many events were written and how many lines were skipped.
+fn write_json_lines(
+    reader: &mut impl BufRead,
+    out: &mut impl Write,
+    encoding: Encoding,
+) -> io::Result<(usize, usize)> {
+    let (mut exported, mut skipped) = (0, 0);
+    let mut buffer = Vec::new();
+    while reader.read_until(b'\n', &mut buffer)? > 0 {
+        let line = encoding.decode(&buffer);
+        let line = line.trim_end_matches(['\r', '\n']);
+        if !line.is_empty() {
+            match parse_line(line) {
+                Some(event) => {
+                    writeln!(out, "{}", event_json(&event))?;
+                    exported += 1;
+                }
+                None => skipped += 1,
+            }
+        }
+        buffer.clear();
+    }
+    Ok((exported, skipped))
+}
+
+/// One event as a JSON object: its timestamp, opcode and `type`, followed
+/// by the fields of its payload, or the raw `fields` of other opcodes.
+fn event_json(event: &Event) -> String {
+    let (kind, fields): (&str, Vec<(&str, &str)>) = match &event.payload {
+        Payload::Chat {
+            channel,
+            sender,
+            message,
+        } => (
+            "chat",
+            vec![
+                ("channel", channel.as_str()),
+                ("sender", sender.as_str()),
+                ("message", message.as_str()),
+            ],
+        ),
+        Payload::AddCombatant {
+            id,
+            name,
+            job,
+            level,
+            owner_id,
+            world,
+        } => (
+            "add_combatant",
+            vec![
+                ("id", id.as_str()),
+                ("name", name.as_str()),
+                ("job", job.as_str()),
+                ("level", level.as_str()),
+                ("owner_id", owner_id.as_str()),
+                ("world", world.as_str()),
+            ],
+        ),
+        Payload::Ability {
+            source_id,
+            source_name,
+            ability_id,
+            ability_name,
+            target_id,
+            target_name,
+        } => (
+            "ability",
+            vec![
+                ("source_id", source_id.as_str()),
+                ("source_name", source_name.as_str()),
+                ("ability_id", ability_id.as_str()),
+                ("ability_name", ability_name.as_str()),
+                ("target_id", target_id.as_str()),
+                ("target_name", target_name.as_str()),
+            ],
+        ),
+        Payload::Other(fields) => {
+            let fields: Vec<String> = fields.iter().map(|field| json::escape(field)).collect();
+            return format!(
+                "{{\"timestamp\":{},\"opcode\":{},\"type\":\"other\",\"fields\":[{}]}}",
+                json::escape(&event.timestamp.to_string()),
+                event.opcode,
+                fields.join(",")
+            );
+        }
+    };
+    let mut object = format!(
+        "{{\"timestamp\":{},\"opcode\":{},\"type\":\"{kind}\"",
+        json::escape(&event.timestamp.to_string()),
+        event.opcode
+    );
+    for (key, value) in fields {
+        object.push_str(&format!(",\"{key}\":{}", json::escape(value)));
+    }
+    object.push('}');
+    object
+}
+
+fn csv_row(event: &Event) -> String {
+    let (source, target, ability) = match &event.payload {
+        Payload::Ability {
+            source_name,
+            target_name,
+            ability_name,
+            ..
+        } => (
+            source_name.as_str(),
+            target_name.as_str(),
+            ability_name.as_str(),
+        ),
+        Payload::Chat { sender, .. } => (sender.as_str(), "", ""),
+        Payload::AddCombatant { name, .. } => (name.as_str(), "", ""),
+        Payload::Other(_) => ("", "", ""),
+    };
+    [
+        event.timestamp.to_string().as_str(),
+        &format!("{:02}", event.opcode),
+        source,
+        target,
+        ability,
+    ]
+    .map(csv_field)
+    .join(",")
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    const ABILITY: &str = "21|2024-01-01T20:15:43.5000000+01:00|10001234|Tank Name|1F|Attack|40001234|Boss, the Great|710003|0|hash";
+    const CHAT: &str = "00|2024-01-01T20:15:42.0000000+01:00|0839||Ready check initiated.|hash";
+
+    #[test]
+    fn test_export_to_csv_writes_rows() {
+        let temp_dir = TempDir::new().unwrap();
+        let log = temp_dir.path().join("Network_1.log");
+        fs::write(&log, format!("{CHAT}\n{ABILITY}\n")).unwrap();
+        let out = default_csv_path(&log);
+
+        export_to_csv(&log, &out, Encoding::Utf8).unwrap();
+
+        assert_eq!(out, temp_dir.path().join("Network_1.csv"));
+        assert_eq!(
+            fs::read_to_string(&out).unwrap(),
+            "timestamp,opcode,source,target,ability\n\
+             2024-01-01T20:15:42.0000000+01:00,00,,,\n\
+             2024-01-01T20:15:43.5000000+01:00,21,Tank Name,\"Boss, the Great\",Attack\n"
+        );
+        assert!(!skipped_path(&out).exists());
+    }
+
+    #[test]
+    fn test_export_to_csv_writes_unparsed_lines_to_sidecar() {
+        let temp_dir = TempDir::new().unwrap();
+        let log = temp_dir.path().join("Network_1.log");
+        fs::write(&log, format!("garbage\r\n\n{ABILITY}\r\nmore garbage\r\n")).unwrap();
+        let out = temp_dir.path().join("out.csv");
+
+        export_to_csv(&log, &out, Encoding::Utf8).unwrap();
+
+        assert_eq!(fs::read_to_string(&out).unwrap().lines().count(), 2);
+        assert_eq!(
+            fs::read_to_string(temp_dir.path().join("out.csv.skipped")).unwrap(),
+            "garbage\nmore garbage\n"
+        );
+    }
+
+    #[test]
+    fn test_export_to_csv_missing_source() {
+        let temp_dir = TempDir::new().unwrap();
+
+        let result = export_to_csv(
+            &temp_dir.path().join("gone.log"),
+            &temp_dir.path().join("out.csv"),
+            Encoding::Utf8,
+        );
+
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::NotFound);
+        assert!(!temp_dir.path().join("out.csv").exists());
+    }
+
+    #[test]
+    fn test_write_json_lines_streams_one_object_per_event() {
+        let other = "33|2024-01-01T20:15:44.0000000+01:00|8003757F|40000003|hash";
+        let input = format!("{CHAT}\nnot a log line\n{ABILITY}\n\n{other}\n");
+        let mut out = Vec::new();
+
+        let counts = write_json_lines(&mut input.as_bytes(), &mut out, Encoding::Utf8).unwrap();
+
+        assert_eq!(counts, (3, 1));
+        let out = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines.len(), 3);
+        let chat = json::parse(lines[0]).unwrap();
+        assert_eq!(chat.get("type").and_then(json::Value::as_str), Some("chat"));
+        assert_eq!(
+            chat.get("message").and_then(json::Value::as_str),
+            Some("Ready check initiated.")
+        );
+        assert_eq!(
+            chat.get("timestamp").and_then(json::Value::as_str),
+            Some("2024-01-01T20:15:42.0000000+01:00")
+        );
+        let ability = json::parse(lines[1]).unwrap();
+        assert_eq!(
+            ability.get("target_name").and_then(json::Value::as_str),
+            Some("Boss, the Great")
+        );
+        assert_eq!(
+            lines[2],
+            r#"{"timestamp":"2024-01-01T20:15:44.0000000+01:00","opcode":33,"type":"other","fields":["8003757F","40000003"]}"#
+        );
+    }
+
+    #[test]
+    fn test_write_json_lines_decodes_shift_jis() {
+        // "00|...|0839||こんにちは|hash" as the Japanese client writes it.
+        let input = b"00|2024-01-01T20:15:42.0000000+01:00|0839||\x82\xb1\x82\xf1\x82\xc9\x82\xbf\x82\xcd|hash\n";
+        let mut out = Vec::new();
+
+        let counts = write_json_lines(&mut &input[..], &mut out, Encoding::ShiftJis).unwrap();
+
+        assert_eq!(counts, (1, 0));
+        let chat = json::parse(String::from_utf8(out).unwrap().trim_end()).unwrap();
+        assert_eq!(
+            chat.get("message").and_then(json::Value::as_str),
+            Some("こんにちは")
+        );
+    }
+
+    #[test]
+    fn test_export_json_lines_to_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let log = temp_dir.path().join("Network_1.log");
+        fs::write(&log, format!("{ABILITY}\n")).unwrap();
+        let out = temp_dir.path().join("events.jsonl");
+
+        export_json_lines(&log, Some(&out), Encoding::Utf8).unwrap();
+
+        let written = fs::read_to_string(&out).unwrap();
+        assert_eq!(written.lines().count(), 1);
+        assert!(written.starts_with(r#"{"timestamp":"#));
+    }
+
+    #[test]
+    fn test_csv_field_quotes_when_needed() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+}
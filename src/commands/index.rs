@@ -0,0 +1,249 @@
+//! A manifest of an archive folder.
+//!
+//! `index` gathers the same per-file figures as `stats --deep` and writes
+//! them next to the logs as `index.json`, and optionally `index.html`, so
+//! the folder can be reviewed without the tool.
+
+use crate::commands::stats::{FileStats, summarize};
+use crate::filters::has_log_extension;
+use crate::gzip::is_gz;
+use crate::json;
+use crate::output::sayln;
+use crate::util::{format_rfc3339, format_size, format_span, format_time};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+pub const INDEX_JSON: &str = "index.json";
+pub const INDEX_HTML: &str = "index.html";
+
+/// The `index` command: writes the index of `dir` and reports each file
+/// written.
+pub fn index_dir(dir: &Path, html: bool) -> io::Result<()> {
+    for written in write_index(dir, html)? {
+        sayln!("Wrote {}", written.display());
+    }
+    Ok(())
+}
+
+/// Writes `index.json` into `dir`, and `index.html` too with `html`,
+/// replacing any earlier index. Only logs are listed, gzipped or not, so
+/// notes and the index files themselves stay out. Every log is read in
+/// full to count its combatants. Returns the paths written.
+pub fn write_index(dir: &Path, html: bool) -> io::Result<Vec<PathBuf>> {
+    let summary = summarize(dir, false, true)?;
+    let files: Vec<&FileStats> = summary
+        .files
+        .iter()
+        .filter(|file| is_indexed(&file.name))
+        .collect();
+    let generated = SystemTime::now();
+
+    let mut written = vec![replace_file(
+        &dir.join(INDEX_JSON),
+        &index_json(&files, generated),
+    )?];
+    if html {
+        let title = dir.file_name().unwrap_or(dir.as_os_str()).to_string_lossy();
+        written.push(replace_file(
+            &dir.join(INDEX_HTML),
+            &index_html(&title, &files, generated),
+        )?);
+    }
+    Ok(written)
+}
+
+/// Whether the file `name` is a log, taking `Network_1.log.gz` as one too.
+fn is_indexed(name: &str) -> bool {
+    let path = Path::new(name);
+    match is_gz(path) {
+        true => has_log_extension(Path::new(path.file_stem().unwrap_or_default())),
+        false => has_log_extension(path),
+    }
+}
+
+/// Writes `contents` to a temporary file beside `path` and moves it over
+/// `path`, so an interrupted run never leaves half an index behind.
+fn replace_file(path: &Path, contents: &str) -> io::Result<PathBuf> {
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(".tmp");
+    let temp = PathBuf::from(temp);
+    fs::write(&temp, contents)?;
+    fs::rename(&temp, path).inspect_err(|_| {
+        let _ = fs::remove_file(&temp);
+    })?;
+    Ok(path.to_path_buf())
+}
+
+/// The index as one JSON object: when it was generated, the totals, and a
+/// `files` array with each log's name, size, modification time, span in
+/// seconds and combatant count. Unknown values are `null`.
+fn index_json(files: &[&FileStats], generated: SystemTime) -> String {
+    let or_null = |value: Option<String>| value.unwrap_or_else(|| "null".to_string());
+    let entries: Vec<String> = files
+        .iter()
+        .map(|file| {
+            format!(
+                "{{\"name\":{},\"size\":{},\"modified\":{},\"span_secs\":{},\"combatants\":{}}}",
+                json::escape(&file.name),
+                file.size,
+                or_null(
+                    file.modified
+                        .map(|time| json::escape(&format_rfc3339(time)))
+                ),
+                or_null(file.span.map(|span| span.as_secs().to_string())),
+                or_null(file.combatants.map(|count| count.to_string())),
+            )
+        })
+        .collect();
+    format!(
+        "{{\"generated\":{},\"file_count\":{},\"total_bytes\":{},\"files\":[{}]}}\n",
+        json::escape(&format_rfc3339(generated)),
+        files.len(),
+        files.iter().map(|file| file.size).sum::<u64>(),
+        entries.join(",")
+    )
+}
+
+/// A standalone page with a table of the same figures as [`index_json`].
+fn index_html(title: &str, files: &[&FileStats], generated: SystemTime) -> String {
+    let rows: String = files
+        .iter()
+        .map(|file| {
+            format!(
+                "<tr><td>{}</td><td class=\"n\">{}</td><td>{}</td><td>{}</td><td class=\"n\">{}</td></tr>\n",
+                escape_html(&file.name),
+                format_size(file.size),
+                file.modified.map_or_else(|| "-".to_string(), format_time),
+                file.span.map_or_else(|| "-".to_string(), format_span),
+                file.combatants.map_or_else(|| "?".to_string(), |count| count.to_string()),
+            )
+        })
+        .collect();
+    let title = escape_html(title);
+    format!(
+        "<!DOCTYPE html>
+<html>
+<head>
+<meta charset=\"utf-8\">
+<title>{title}</title>
+<style>
+body {{ font-family: sans-serif; }}
+table {{ border-collapse: collapse; }}
+th, td {{ padding: 2px 12px; text-align: left; }}
+.n {{ text-align: right; }}
+</style>
+</head>
+<body>
+<h1>{title}</h1>
+<p>{} files, {}. Generated {}.</p>
+<table>
+<tr><th>Name</th><th class=\"n\">Size</th><th>Modified</th><th>Span</th><th class=\"n\">Combatants</th></tr>
+{rows}</table>
+</body>
+</html>
+",
+        files.len(),
+        format_size(files.iter().map(|file| file.size).sum()),
+        format_time(generated),
+    )
+}
+
+fn escape_html(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const ADD_TANK: &str =
+        "03|2024-01-01T20:00:00.0000000+01:00|10001234|Tank Name|13|5A|0000|49|Gilgamesh|hash";
+    const ABILITY: &str = "21|2024-01-01T20:05:00.0000000+01:00|10001234|Tank Name|1F|Attack|40001234|Boss|710003|0|hash";
+
+    #[test]
+    fn test_write_index_lists_each_log() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("Network_1.log"),
+            format!("{ADD_TANK}\n{ABILITY}\n"),
+        )
+        .unwrap();
+        fs::write(temp_dir.path().join("notes.txt"), "x").unwrap();
+
+        let written = write_index(temp_dir.path(), false).unwrap();
+
+        assert_eq!(written, [temp_dir.path().join(INDEX_JSON)]);
+        let index = json::parse(&fs::read_to_string(&written[0]).unwrap()).unwrap();
+        assert_eq!(index.get("file_count"), Some(&json::Value::Number(1.0)));
+        let Some(json::Value::Array(files)) = index.get("files") else {
+            panic!("no files array in {index:?}");
+        };
+        assert_eq!(files.len(), 1);
+        let log = &files[0];
+        assert_eq!(
+            log.get("name").and_then(json::Value::as_str),
+            Some("Network_1.log")
+        );
+        assert_eq!(log.get("span_secs"), Some(&json::Value::Number(300.0)));
+        assert_eq!(log.get("combatants"), Some(&json::Value::Number(1.0)));
+        assert!(log.get("modified").and_then(json::Value::as_str).is_some());
+        assert!(!temp_dir.path().join(INDEX_HTML).exists());
+    }
+
+    #[test]
+    fn test_write_index_overwrites_and_skips_itself() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("a.log"), "x\n").unwrap();
+
+        write_index(temp_dir.path(), true).unwrap();
+        fs::write(temp_dir.path().join("b&c.log"), "y\n").unwrap();
+        let written = write_index(temp_dir.path(), true).unwrap();
+
+        assert_eq!(written.len(), 2);
+        let index = fs::read_to_string(temp_dir.path().join(INDEX_JSON)).unwrap();
+        let index = json::parse(&index).unwrap();
+        assert_eq!(index.get("file_count"), Some(&json::Value::Number(2.0)));
+        let html = fs::read_to_string(temp_dir.path().join(INDEX_HTML)).unwrap();
+        assert!(html.contains("<td>b&amp;c.log</td>"));
+        assert!(!html.contains("index.json"));
+        let leftovers = fs::read_dir(temp_dir.path())
+            .unwrap()
+            .filter(|entry| {
+                let name = entry.as_ref().unwrap().file_name();
+                name.to_string_lossy().ends_with(".tmp")
+            })
+            .count();
+        assert_eq!(leftovers, 0);
+    }
+
+    #[test]
+    fn test_is_indexed() {
+        assert!(is_indexed("Network_1.log"));
+        assert!(is_indexed("Network_1.LOG.gz"));
+        assert!(!is_indexed("notes.txt"));
+        assert!(!is_indexed("notes.gz"));
+        assert!(!is_indexed(INDEX_JSON));
+        assert!(!is_indexed(INDEX_HTML));
+    }
+
+    #[test]
+    fn test_escape_html() {
+        assert_eq!(
+            escape_html("a & <b> \"c\""),
+            "a &amp; &lt;b&gt; &quot;c&quot;"
+        );
+    }
+}
@@ -1,8 +1,219 @@
-use std::path::Path;
+use crate::json;
+use crate::output;
+use crate::paths;
+use crate::scan::{DirSummary, LogFile, dir_header, holding_dir};
+use crate::table::{self, Column};
+use crate::util::{format_rfc3339, format_size, format_time};
+use clap::ValueEnum;
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::io;
+use std::iter;
+use std::path::{Path, PathBuf};
 
-pub fn list_log_file(path: &Path) {
-    let file_name = path.file_name().expect("not a file");
-    println!("- {}", file_name.display());
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Name, size and date in aligned columns
+    #[default]
+    Text,
+    /// Aligned columns under a header row (`--no-header` drops it)
+    Table,
+    /// A JSON array of `{"name","size","modified"}` objects
+    Json,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum SortKey {
+    /// File name, A to Z
+    Name,
+    /// File size, largest first
+    Size,
+    /// Modification time, newest first
+    Date,
+}
+
+pub fn sort_log_files(files: &mut [LogFile], key: SortKey) {
+    match key {
+        SortKey::Name => files.sort_by(|a, b| a.path.file_name().cmp(&b.path.file_name())),
+        SortKey::Size => files.sort_by_key(|file| Reverse(file.size)),
+        SortKey::Date => files.sort_by_key(|file| Reverse(file.modified)),
+    }
+}
+
+pub fn list_log_file(path: &Path) -> io::Result<()> {
+    let file_name = paths::file_name(path)?;
+    output::emit(format_args!("- {}", file_name.display()));
+    Ok(())
+}
+
+/// Prints the files, followed by the directories `--include-dirs` found.
+/// With `group_by_ext` the text output is split under one header per
+/// extension.
+pub fn list_log_files(
+    files: &[LogFile],
+    dirs: &[DirSummary],
+    format: OutputFormat,
+    names_only: bool,
+    group_by_ext: bool,
+    no_header: bool,
+) -> io::Result<()> {
+    let header = format == OutputFormat::Table && !no_header;
+    match format {
+        OutputFormat::Text | OutputFormat::Table if group_by_ext => {
+            for (extension, group) in group_by_extension(files) {
+                output::emit(group_header(&extension, &group));
+                let rows = match names_only {
+                    true => group
+                        .iter()
+                        .map(|file| format!("- {}", file.name()))
+                        .collect(),
+                    false => render_table(&group, &[], header),
+                };
+                rows.iter()
+                    .for_each(|row| output::emit(format_args!("  {row}")));
+            }
+            if !dirs.is_empty() {
+                list_log_files(&[], dirs, format, names_only, false, no_header)?;
+            }
+        }
+        OutputFormat::Text | OutputFormat::Table if names_only => {
+            files
+                .iter()
+                .try_for_each(|file| list_log_file(&file.path))?;
+            dirs.iter()
+                .for_each(|dir| output::emit(format_args!("- {}/", dir.name())));
+        }
+        OutputFormat::Text | OutputFormat::Table => render_table(files, dirs, header)
+            .iter()
+            .for_each(output::emit),
+        OutputFormat::Json => println!("{}", to_json(files, dirs)),
+    }
+    Ok(())
+}
+
+/// Lists a run over several directories, each under its own header in the
+/// order of `headed_dirs`. Files given on their own come first, without a
+/// header. Without `headed_dirs`, or as JSON, everything is listed at once.
+pub fn list_by_dir(
+    files: &[LogFile],
+    dirs: &[DirSummary],
+    headed_dirs: &[PathBuf],
+    format: OutputFormat,
+    names_only: bool,
+    group_by_ext: bool,
+    no_header: bool,
+) -> io::Result<()> {
+    if headed_dirs.is_empty() || format == OutputFormat::Json {
+        return list_log_files(files, dirs, format, names_only, group_by_ext, no_header);
+    }
+    let groups = iter::once(None).chain(headed_dirs.iter().map(|dir| Some(dir.as_path())));
+    let mut first = true;
+    for group in groups {
+        let group_files: Vec<LogFile> = files
+            .iter()
+            .filter(|file| holding_dir(headed_dirs, &file.path) == group)
+            .cloned()
+            .collect();
+        let group_dirs: Vec<DirSummary> = dirs
+            .iter()
+            .filter(|sub| holding_dir(headed_dirs, &sub.path) == group)
+            .cloned()
+            .collect();
+        match group {
+            Some(dir) => output::emit(dir_header(dir, first)),
+            None if group_files.is_empty() && group_dirs.is_empty() => continue,
+            None => {}
+        }
+        first = false;
+        list_log_files(
+            &group_files,
+            &group_dirs,
+            format,
+            names_only,
+            group_by_ext,
+            no_header,
+        )?;
+    }
+    Ok(())
+}
+
+/// Buckets `files` by lowercase extension, keeping their order within each
+/// bucket. Buckets are sorted by extension, with files that have none last
+/// under `(none)`.
+fn group_by_extension(files: &[LogFile]) -> Vec<(String, Vec<LogFile>)> {
+    let mut groups: BTreeMap<Option<String>, Vec<LogFile>> = BTreeMap::new();
+    for file in files {
+        let extension = file
+            .path
+            .extension()
+            .map(|ext| format!(".{}", ext.to_string_lossy().to_lowercase()));
+        groups.entry(extension).or_default().push(file.clone());
+    }
+    let none = groups.remove(&None);
+    groups
+        .into_iter()
+        .map(|(extension, group)| (extension.unwrap_or_default(), group))
+        .chain(none.map(|group| ("(none)".to_string(), group)))
+        .collect()
+}
+
+fn group_header(extension: &str, files: &[LogFile]) -> String {
+    let noun = if files.len() == 1 { "file" } else { "files" };
+    let size = files.iter().map(|file| file.size).sum();
+    format!(
+        "{extension} ({} {noun}, {})",
+        files.len(),
+        format_size(size)
+    )
+}
+
+fn render_table(files: &[LogFile], dirs: &[DirSummary], header: bool) -> Vec<String> {
+    const COLUMNS: [Column; 3] = [
+        Column::left("Name"),
+        Column::right("Size"),
+        Column::left("Modified"),
+    ];
+    let file_rows = files.iter().map(|file| {
+        let modified = file.modified.map_or_else(|| "-".to_string(), format_time);
+        vec![file.name(), format_size(file.size), modified]
+    });
+    let dir_rows = dirs.iter().map(|dir| {
+        let noun = if dir.files == 1 { "file" } else { "files" };
+        let name = format!("{}/", dir.name());
+        vec![name, format_size(dir.size), format!("{} {noun}", dir.files)]
+    });
+    let rows: Vec<Vec<String>> = file_rows.chain(dir_rows).collect();
+    table::render(&COLUMNS, &rows, header)
+}
+
+fn to_json(files: &[LogFile], dirs: &[DirSummary]) -> String {
+    let entries: Vec<String> = files
+        .iter()
+        .map(entry_json)
+        .chain(dirs.iter().map(dir_json))
+        .collect();
+    format!("[{}]", entries.join(","))
+}
+
+fn dir_json(dir: &DirSummary) -> String {
+    format!(
+        "{{\"name\":{},\"size\":{},\"dir\":true,\"files\":{}}}",
+        json::escape(&dir.name()),
+        dir.size,
+        dir.files
+    )
+}
+
+fn entry_json(file: &LogFile) -> String {
+    let modified = file
+        .modified
+        .map(|time| json::escape(&format_rfc3339(time)))
+        .unwrap_or_else(|| "null".to_string());
+    format!(
+        "{{\"name\":{},\"size\":{},\"modified\":{modified}}}",
+        json::escape(&file.name()),
+        file.size
+    )
 }
 
 #[cfg(test)]
@@ -10,6 +221,7 @@ mod tests {
     use super::*;
     use std::fs::File;
     use std::io::Write;
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
     use tempfile::TempDir;
 
     #[test]
@@ -20,7 +232,7 @@ mod tests {
         let mut file = File::create(&file_path).unwrap();
         writeln!(file, "test content").unwrap();
 
-        list_log_file(&file_path);
+        list_log_file(&file_path).unwrap();
     }
 
     #[test]
@@ -31,7 +243,7 @@ mod tests {
         let mut file = File::create(&file_path).unwrap();
         writeln!(file, "test content").unwrap();
 
-        list_log_file(&file_path);
+        list_log_file(&file_path).unwrap();
     }
 
     #[test]
@@ -41,7 +253,7 @@ mod tests {
 
         File::create(&file_path).unwrap();
 
-        list_log_file(&file_path);
+        list_log_file(&file_path).unwrap();
     }
 
     #[test]
@@ -53,7 +265,7 @@ mod tests {
         for file_name in test_files {
             let file_path = temp_dir.path().join(file_name);
             File::create(&file_path).unwrap();
-            list_log_file(&file_path);
+            list_log_file(&file_path).unwrap();
         }
     }
 
@@ -65,15 +277,238 @@ mod tests {
         let mut file = File::create(&file_path).unwrap();
         writeln!(file, "test content").unwrap();
 
-        list_log_file(&file_path);
+        list_log_file(&file_path).unwrap();
+    }
+
+    #[test]
+    fn test_to_json_lists_name_size_and_modified() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("test \"quoted\".log");
+        let mut file = File::create(&file_path).unwrap();
+        write!(file, "12345").unwrap();
+        file.set_modified(UNIX_EPOCH).unwrap();
+
+        let log_file = LogFile::from_path(file_path).unwrap();
+
+        let value = json::parse(&to_json(&[log_file], &[])).unwrap();
+
+        let json::Value::Array(entries) = value else {
+            panic!("expected an array");
+        };
+        assert_eq!(entries.len(), 1);
+        assert_eq!(
+            entries[0].get("name").and_then(json::Value::as_str),
+            Some("test \"quoted\".log")
+        );
+        assert_eq!(entries[0].get("size"), Some(&json::Value::Number(5.0)));
+        assert_eq!(
+            entries[0].get("modified").and_then(json::Value::as_str),
+            Some("1970-01-01T00:00:00Z")
+        );
+    }
+
+    #[test]
+    fn test_to_json_empty() {
+        assert_eq!(to_json(&[], &[]), "[]");
+    }
+
+    #[test]
+    fn test_to_json_unknown_modified_is_null() {
+        let log_file = LogFile {
+            path: "gone.log".into(),
+            size: 0,
+            modified: None,
+        };
+
+        let value = json::parse(&to_json(&[log_file], &[])).unwrap();
+
+        let json::Value::Array(entries) = value else {
+            panic!("expected an array");
+        };
+        assert_eq!(entries[0].get("modified"), Some(&json::Value::Null));
+    }
+
+    fn create_sized(dir: &Path, name: &str, size: usize, age_secs: u64) -> LogFile {
+        let path = dir.join(name);
+        let mut file = File::create(&path).unwrap();
+        file.write_all(&vec![b'x'; size]).unwrap();
+        file.set_modified(SystemTime::now() - Duration::from_secs(age_secs))
+            .unwrap();
+        LogFile::from_path(path).unwrap()
+    }
+
+    fn names(files: &[LogFile]) -> Vec<String> {
+        files.iter().map(LogFile::name).collect()
+    }
+
+    #[test]
+    fn test_render_table_aligns_columns() {
+        let files = [
+            LogFile {
+                path: "short.log".into(),
+                size: 10,
+                modified: Some(UNIX_EPOCH),
+            },
+            LogFile {
+                path: "much_longer_name.log".into(),
+                size: 2048,
+                modified: Some(UNIX_EPOCH + Duration::from_secs(86_400)),
+            },
+        ];
+
+        let rows = render_table(&files, &[], false);
+
+        assert_eq!(
+            rows,
+            [
+                "short.log               10 B  1970-01-01 00:00:00 UTC",
+                "much_longer_name.log  2.0 KB  1970-01-02 00:00:00 UTC",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render_table_with_header() {
+        let files = [LogFile {
+            path: "a.log".into(),
+            size: 10,
+            modified: None,
+        }];
+
+        assert_eq!(
+            render_table(&files, &[], true),
+            ["Name   Size  Modified", "a.log  10 B  -"]
+        );
+    }
+
+    #[test]
+    fn test_group_by_extension() {
+        let file = |path: &str, size| LogFile {
+            path: path.into(),
+            size,
+            modified: None,
+        };
+        let files = [
+            file("b.log", 10),
+            file("README", 1),
+            file("notes.dat", 2048),
+            file("a.LOG", 20),
+        ];
+
+        let groups = group_by_extension(&files);
+
+        let summary: Vec<_> = groups
+            .iter()
+            .map(|(extension, group)| {
+                let names: Vec<_> = group.iter().map(LogFile::name).collect();
+                (group_header(extension, group), names)
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (
+                    ".dat (1 file, 2.0 KB)".to_string(),
+                    vec!["notes.dat".to_string()]
+                ),
+                (
+                    ".log (2 files, 30 B)".to_string(),
+                    vec!["b.log".to_string(), "a.LOG".to_string()]
+                ),
+                (
+                    "(none) (1 file, 1 B)".to_string(),
+                    vec!["README".to_string()]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render_table_lists_dirs_after_files() {
+        let files = [LogFile {
+            path: "test.log".into(),
+            size: 10,
+            modified: None,
+        }];
+        let dirs = [DirSummary {
+            path: "bak".into(),
+            size: 4096,
+            files: 3,
+        }];
+
+        assert_eq!(
+            render_table(&files, &dirs, false),
+            ["test.log    10 B  -", "bak/      4.0 KB  3 files"]
+        );
+    }
+
+    #[test]
+    fn test_to_json_marks_dirs() {
+        let dirs = [DirSummary {
+            path: "bak".into(),
+            size: 10,
+            files: 1,
+        }];
+
+        assert_eq!(
+            to_json(&[], &dirs),
+            "[{\"name\":\"bak\",\"size\":10,\"dir\":true,\"files\":1}]"
+        );
+    }
+
+    #[test]
+    fn test_render_table_unknown_modified_shows_dash() {
+        let files = [LogFile {
+            path: "test.log".into(),
+            size: 0,
+            modified: None,
+        }];
+
+        assert_eq!(render_table(&files, &[], false), ["test.log  0 B  -"]);
+    }
+
+    #[test]
+    fn test_sort_log_files_by_each_key() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut files = vec![
+            create_sized(temp_dir.path(), "b.log", 300, 100),
+            create_sized(temp_dir.path(), "c.log", 10, 10),
+            create_sized(temp_dir.path(), "a.log", 20, 1000),
+        ];
+
+        sort_log_files(&mut files, SortKey::Name);
+        assert_eq!(names(&files), ["a.log", "b.log", "c.log"]);
+
+        sort_log_files(&mut files, SortKey::Size);
+        assert_eq!(names(&files), ["b.log", "a.log", "c.log"]);
+
+        sort_log_files(&mut files, SortKey::Date);
+        assert_eq!(names(&files), ["c.log", "b.log", "a.log"]);
+    }
+
+    #[test]
+    fn test_sort_log_files_unknown_modified_sorts_last_by_date() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut files = vec![
+            LogFile {
+                path: "unknown.log".into(),
+                size: 0,
+                modified: None,
+            },
+            create_sized(temp_dir.path(), "a.log", 5, 0),
+        ];
+
+        sort_log_files(&mut files, SortKey::Date);
+
+        assert_eq!(names(&files), ["a.log", "unknown.log"]);
     }
 
     #[test]
-    #[should_panic(expected = "not a file")]
     fn test_list_log_file_invalid_path() {
         use std::path::Path;
 
         let invalid_path = Path::new("/");
-        list_log_file(invalid_path);
+        let err = list_log_file(invalid_path).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
     }
 }
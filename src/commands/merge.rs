@@ -0,0 +1,157 @@
+use crate::parser::parse_timestamp;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// A timestamped line together with the untimestamped lines that follow it
+/// in its file. Lines before the first timestamp of a file have no key and
+/// sort ahead of everything else.
+struct Block {
+    key: Option<i64>,
+    lines: Vec<String>,
+}
+
+/// Writes the lines of every file in `files` to `out`, ordered by their
+/// leading timestamp. Lines with the same timestamp keep their input order,
+/// and lines without one stay attached to the timestamped line above them.
+pub fn merge_logs(files: &[PathBuf], out: &Path) -> io::Result<()> {
+    println!("Merging {} files -> {}...", files.len(), out.display());
+    let mut blocks: Vec<Block> = Vec::new();
+    for file in files {
+        let mut reader = BufReader::new(File::open(file)?);
+        let mut current: Option<Block> = None;
+        let mut buffer = Vec::new();
+        while reader.read_until(b'\n', &mut buffer)? > 0 {
+            let line = String::from_utf8_lossy(&buffer);
+            let line = line.trim_end_matches(['\r', '\n']).to_string();
+            buffer.clear();
+            match parse_timestamp(&line) {
+                Some(timestamp) => {
+                    blocks.extend(current.take());
+                    current = Some(Block {
+                        key: Some(timestamp.unix_millis()),
+                        lines: vec![line],
+                    });
+                }
+                None => current
+                    .get_or_insert_with(|| Block {
+                        key: None,
+                        lines: Vec::new(),
+                    })
+                    .lines
+                    .push(line),
+            }
+        }
+        blocks.extend(current);
+    }
+    blocks.sort_by_key(|block| block.key);
+
+    let mut writer = BufWriter::new(File::create(out)?);
+    let mut merged = 0;
+    for line in blocks.iter().flat_map(|block| &block.lines) {
+        writeln!(writer, "{line}")?;
+        merged += 1;
+    }
+    writer.flush()?;
+
+    println!("Merged {merged} lines.");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn line(time: &str, text: &str) -> String {
+        format!("00|2024-01-01T{time}.0000000+01:00|0839||{text}|hash")
+    }
+
+    #[test]
+    fn test_merge_logs_interleaves_by_timestamp() {
+        let temp_dir = TempDir::new().unwrap();
+        let first = temp_dir.path().join("Network_1.log");
+        let second = temp_dir.path().join("Network_2.log");
+        let out = temp_dir.path().join("merged.log");
+        fs::write(
+            &first,
+            format!("{}\n{}\n", line("20:00:00", "a"), line("20:00:02", "c")),
+        )
+        .unwrap();
+        fs::write(
+            &second,
+            format!("{}\r\n{}\r\n", line("20:00:01", "b"), line("20:00:03", "d")),
+        )
+        .unwrap();
+
+        merge_logs(&[second, first], &out).unwrap();
+
+        let merged = fs::read_to_string(&out).unwrap();
+        let texts: Vec<&str> = merged
+            .lines()
+            .map(|l| l.split('|').nth(4).unwrap())
+            .collect();
+        assert_eq!(texts, ["a", "b", "c", "d"]);
+    }
+
+    #[test]
+    fn test_merge_logs_keeps_untimestamped_lines_with_previous() {
+        let temp_dir = TempDir::new().unwrap();
+        let first = temp_dir.path().join("Network_1.log");
+        let second = temp_dir.path().join("Network_2.log");
+        let out = temp_dir.path().join("merged.log");
+        fs::write(
+            &first,
+            format!(
+                "header\n{}\ncontinued\n{}\n",
+                line("20:00:00", "a"),
+                line("20:00:05", "c")
+            ),
+        )
+        .unwrap();
+        fs::write(&second, format!("{}\n", line("20:00:01", "b"))).unwrap();
+
+        merge_logs(&[first, second], &out).unwrap();
+
+        let merged = fs::read_to_string(&out).unwrap();
+        let expected = [
+            "header".to_string(),
+            line("20:00:00", "a"),
+            "continued".to_string(),
+            line("20:00:01", "b"),
+            line("20:00:05", "c"),
+        ];
+        assert_eq!(merged.lines().collect::<Vec<_>>(), expected);
+    }
+
+    #[test]
+    fn test_merge_logs_orders_across_offsets() {
+        let temp_dir = TempDir::new().unwrap();
+        let first = temp_dir.path().join("Network_1.log");
+        let second = temp_dir.path().join("Network_2.log");
+        let out = temp_dir.path().join("merged.log");
+        let later = "00|2024-01-01T20:00:00.0000000+01:00|0839||later|hash";
+        let earlier = "00|2024-01-01T13:30:00.0000000-05:00|0839||earlier|hash";
+        fs::write(&first, format!("{later}\n")).unwrap();
+        fs::write(&second, format!("{earlier}\n")).unwrap();
+
+        merge_logs(&[first, second], &out).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&out).unwrap(),
+            format!("{earlier}\n{later}\n")
+        );
+    }
+
+    #[test]
+    fn test_merge_logs_missing_input_writes_nothing() {
+        let temp_dir = TempDir::new().unwrap();
+        let out = temp_dir.path().join("merged.log");
+
+        let result = merge_logs(&[temp_dir.path().join("gone.log")], &out);
+
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::NotFound);
+        assert!(!out.exists());
+    }
+}
@@ -1,4 +1,16 @@
+pub mod anonymize;
 pub mod backup;
 pub mod delete;
+pub mod doctor;
+pub mod export;
+pub mod index;
 pub mod list;
+pub mod merge;
+pub mod rename;
+pub mod restore;
+pub mod search;
+pub mod split;
+pub mod stats;
+pub mod undo;
 pub mod view;
+pub mod watch;
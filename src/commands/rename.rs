@@ -0,0 +1,766 @@
+use crate::filters::{SkipReason, has_log_extension, read_first_line};
+use crate::output::{self, sayln};
+use crate::parser::{DateTime, parse_timestamp};
+use crate::paths;
+use crate::scan::{self, EntryStream, dir_header, holding_dir, with_path};
+use crate::style;
+use crate::summary::Summary;
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::fmt::Write;
+use std::fs::{metadata, rename};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/// The names `rename` gives without `--format-name`.
+pub const DEFAULT_TEMPLATE: &str = "{date}_{time}.{ext}";
+
+/// A value a name template can refer to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    /// `{date}`: `YYYY-MM-DD`.
+    Date,
+    /// `{time}`: `HH-MM`.
+    Time,
+    /// `{index}`: the file's position in the run, from 1.
+    Index,
+    /// `{ext}`: the file's extension without the dot.
+    Ext,
+    /// The file's current name without its extension. Not a placeholder
+    /// users can write; it lets the clean-ups run without a template.
+    Stem,
+}
+
+impl Field {
+    fn from_name(name: &str) -> Option<Field> {
+        match name {
+            "date" => Some(Field::Date),
+            "time" => Some(Field::Time),
+            "index" => Some(Field::Index),
+            "ext" => Some(Field::Ext),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Part {
+    Text(String),
+    Field(Field),
+}
+
+/// A file name pattern such as `{date}_{time}.{ext}`, checked when it is
+/// parsed so a typo fails before any file is touched.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NameTemplate {
+    parts: Vec<Part>,
+    cleanup: NameCleanup,
+}
+
+/// Clean-ups applied to a name once its template is filled in, in this
+/// order: non-ASCII characters become `_`, then spaces become `_`, then
+/// the name is lowercased.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NameCleanup {
+    pub ascii_only: bool,
+    pub replace_spaces: bool,
+    pub lowercase: bool,
+}
+
+impl NameCleanup {
+    /// Whether no clean-up is asked for.
+    pub fn is_empty(self) -> bool {
+        self == NameCleanup::default()
+    }
+
+    /// `name` with the chosen clean-ups applied.
+    pub fn apply(self, name: &str) -> String {
+        let mut cleaned = String::with_capacity(name.len());
+        for c in name.chars() {
+            let c = if self.ascii_only && !c.is_ascii() {
+                '_'
+            } else {
+                c
+            };
+            let c = if self.replace_spaces && c == ' ' {
+                '_'
+            } else {
+                c
+            };
+            if self.lowercase {
+                cleaned.extend(c.to_lowercase());
+            } else {
+                cleaned.push(c);
+            }
+        }
+        cleaned
+    }
+}
+
+impl NameTemplate {
+    /// Parses `template`, rejecting unknown placeholders, unbalanced
+    /// braces and anything that would leave the log's directory.
+    pub fn parse(template: &str) -> Result<NameTemplate, String> {
+        if template.is_empty() {
+            return Err("the name template is empty".to_string());
+        }
+        if template.contains(['/', '\\']) {
+            return Err(format!(
+                "'{template}' contains a path separator; a template names a file, not a directory"
+            ));
+        }
+        let mut parts = Vec::new();
+        let mut rest = template;
+        while let Some(start) = rest.find(['{', '}']) {
+            if rest[start..].starts_with('}') {
+                return Err(format!("unmatched '}}' in '{template}'"));
+            }
+            if start > 0 {
+                parts.push(Part::Text(rest[..start].to_string()));
+            }
+            let Some(len) = rest[start..].find('}') else {
+                return Err(format!("unclosed '{{' in '{template}'"));
+            };
+            let name = &rest[start + 1..start + len];
+            let field = Field::from_name(name).ok_or_else(|| {
+                format!(
+                    "unknown placeholder '{{{name}}}' in '{template}' (expected {{date}}, {{time}}, {{index}} or {{ext}})"
+                )
+            })?;
+            parts.push(Part::Field(field));
+            rest = &rest[start + len + 1..];
+        }
+        if !rest.is_empty() {
+            parts.push(Part::Text(rest.to_string()));
+        }
+        Ok(NameTemplate {
+            parts,
+            cleanup: NameCleanup::default(),
+        })
+    }
+
+    /// The template that keeps each file's current name, for cleaning
+    /// names up without renaming them after their date.
+    pub fn current_name() -> NameTemplate {
+        NameTemplate {
+            parts: vec![
+                Part::Field(Field::Stem),
+                Part::Text(".".to_string()),
+                Part::Field(Field::Ext),
+            ],
+            cleanup: NameCleanup::default(),
+        }
+    }
+
+    /// This template with `cleanup` applied to every name it gives.
+    pub fn with_cleanup(self, cleanup: NameCleanup) -> NameTemplate {
+        NameTemplate { cleanup, ..self }
+    }
+}
+
+impl Default for NameTemplate {
+    fn default() -> Self {
+        NameTemplate::parse(DEFAULT_TEMPLATE).expect("a valid template")
+    }
+}
+
+/// The values a [`NameTemplate`] is filled in with for one file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NameFields {
+    /// The time of the first line, or the modification time.
+    pub time: DateTime,
+    pub index: usize,
+    pub ext: String,
+    /// The current name without its extension.
+    pub stem: String,
+}
+
+/// The file name `template` gives a file with these `fields`.
+pub fn render_template(template: &NameTemplate, fields: &NameFields) -> String {
+    let time = &fields.time;
+    let mut name = String::new();
+    for part in &template.parts {
+        let _ = match part {
+            Part::Text(text) => write!(name, "{text}"),
+            Part::Field(Field::Date) => {
+                write!(name, "{:04}-{:02}-{:02}", time.year, time.month, time.day)
+            }
+            Part::Field(Field::Time) => write!(name, "{:02}-{:02}", time.hour, time.minute),
+            Part::Field(Field::Index) => write!(name, "{}", fields.index),
+            Part::Field(Field::Ext) => write!(name, "{}", fields.ext),
+            Part::Field(Field::Stem) => write!(name, "{}", fields.stem),
+        };
+    }
+    template.cleanup.apply(&name)
+}
+
+/// Renames a log to `YYYY-MM-DD_HH-MM.log` after the time of its first
+/// line, or its modification time (UTC) when that line has no timestamp.
+/// A name already taken by another file gets a `_2`, `_3`... suffix. Files
+/// without a `.log` extension are left alone. Returns the file's new path.
+pub fn normalize_name(path: &Path, dry_run: bool) -> io::Result<PathBuf> {
+    rename_to_template(path, &NameTemplate::default(), 1, dry_run)
+}
+
+/// Like [`normalize_name`], but names the file after `template`, with
+/// `index` standing for `{index}`.
+pub fn rename_to_template(
+    path: &Path,
+    template: &NameTemplate,
+    index: usize,
+    dry_run: bool,
+) -> io::Result<PathBuf> {
+    let file_name = paths::file_name(path)?;
+    if !has_log_extension(path) {
+        sayln!(
+            "{} {}: not a .log file",
+            style::skipped("Skipping"),
+            file_name.display()
+        );
+        return Ok(path.to_path_buf());
+    }
+
+    let name = render_template(template, &name_fields(path, index)?);
+    let target = target_path(path, &name);
+    if target == path {
+        sayln!("{} is already normalized.", file_name.display());
+        return Ok(target);
+    }
+    let target_name = target.file_name().unwrap_or_default();
+    if dry_run {
+        sayln!(
+            "Would rename {} -> {}",
+            file_name.display(),
+            target_name.display()
+        );
+        return Ok(target);
+    }
+
+    sayln!(
+        "Renaming {} -> {}...",
+        file_name.display(),
+        target_name.display()
+    );
+    match rename(path, &target) {
+        Ok(()) => {
+            sayln!("{}", style::success("Renamed."));
+            Ok(target)
+        }
+        Err(err) => {
+            sayln!("{} {err}", style::failure("Failed:"));
+            Err(err)
+        }
+    }
+}
+
+/// One row of the `--dry-run` preview.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Proposal {
+    pub from: PathBuf,
+    /// The normalized name before any `_2`, `_3`... suffix is added.
+    pub to: PathBuf,
+    /// Another file already has, or would get, the same name.
+    pub collides: bool,
+}
+
+/// Works out the name `template` gives every `.log` file in `paths`
+/// without renaming anything, numbering them from 1 for `{index}`. Non-log
+/// files are left out of the plan; files whose time cannot be read come
+/// back as errors.
+pub fn plan_renames(paths: &[PathBuf], template: &NameTemplate) -> Vec<io::Result<Proposal>> {
+    let mut plan: Vec<io::Result<Proposal>> = paths
+        .iter()
+        .filter(|path| has_log_extension(path))
+        .enumerate()
+        .map(|(i, path)| {
+            let fields = name_fields(path, i + 1).map_err(|err| with_path(path, err))?;
+            let name = render_template(template, &fields);
+            let to = if has_name(path, &name) {
+                path.clone()
+            } else {
+                path.with_file_name(&name)
+            };
+            Ok(Proposal {
+                from: path.clone(),
+                to,
+                collides: false,
+            })
+        })
+        .collect();
+
+    let mut claims: HashMap<PathBuf, usize> = HashMap::new();
+    for proposal in plan.iter().flatten() {
+        *claims.entry(proposal.to.clone()).or_default() += 1;
+    }
+    for proposal in plan.iter_mut().flatten() {
+        let taken = proposal.to != proposal.from && proposal.to.exists();
+        proposal.collides = taken || claims[&proposal.to] > 1;
+    }
+    plan
+}
+
+/// Lines of the preview table: current names, then proposed names, with
+/// `!` marking a collision.
+pub fn render_plan(plan: &[Proposal]) -> Vec<String> {
+    let name = |path: &Path| {
+        path.file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned()
+    };
+    let width = plan
+        .iter()
+        .map(|proposal| name(&proposal.from).chars().count())
+        .max()
+        .unwrap_or(0);
+    plan.iter()
+        .map(|proposal| {
+            let from = name(&proposal.from);
+            if proposal.from == proposal.to {
+                return format!("  {from:<width$}  =  (already normalized)");
+            }
+            let mark = if proposal.collides {
+                style::failure("!")
+            } else {
+                " ".to_string()
+            };
+            format!("{mark} {from:<width$}  -> {}", name(&proposal.to))
+        })
+        .collect()
+}
+
+/// Numbers the `.log` files of `entries` in name order for `{index}` and
+/// hands them on in that order after every other entry.
+pub fn number_files(entries: EntryStream) -> (EntryStream, HashMap<PathBuf, usize>) {
+    let (mut files, others) = scan::split_files(entries, has_log_extension);
+    files.sort();
+    let indices = files.iter().cloned().zip(1..).collect();
+    (scan::rejoin(others, files), indices)
+}
+
+/// The `--dry-run` stage of a rename: shows the names `template` would give
+/// every `.log` file of `entries` at once, so colliding names can be
+/// flagged, grouped under `headed_dirs` when the run covers several. Only
+/// the entries that are not files are handed on.
+pub fn preview_renames(
+    entries: EntryStream,
+    template: &NameTemplate,
+    headed_dirs: &[PathBuf],
+    summary: &mut Summary,
+) -> EntryStream {
+    let (mut files, others) = scan::split_files(entries, |_| true);
+    files.retain(|path| {
+        let is_log = has_log_extension(path);
+        if !is_log {
+            sayln!(
+                "{} {}: not a .log file",
+                style::skipped("Skipping"),
+                path.display()
+            );
+            summary.record_skip(SkipReason::NotLog);
+        }
+        is_log
+    });
+    files.sort();
+    let mut plan = Vec::new();
+    for proposal in plan_renames(&files, template) {
+        summary.record(proposal.is_ok());
+        match proposal {
+            Ok(proposal) => plan.push(proposal),
+            Err(err) => output::emit(format!("{} {err}", style::failure("Failed:"))),
+        }
+    }
+    if !plan.is_empty() {
+        sayln!("Would rename:");
+        let group = |proposal: &Proposal| holding_dir(headed_dirs, &proposal.from);
+        for (i, proposals) in plan.chunk_by(|a, b| group(a) == group(b)).enumerate() {
+            if let Some(dir) = group(&proposals[0]) {
+                sayln!("{}", dir_header(dir, i == 0));
+            }
+            for line in render_plan(proposals) {
+                sayln!("{line}");
+            }
+        }
+    }
+    let collisions = plan.iter().filter(|proposal| proposal.collides).count();
+    if collisions > 0 {
+        output::emit(format!(
+            "{} {collisions} proposed names collide; renaming adds a _2, _3... suffix to keep them apart",
+            style::failure("!")
+        ));
+    }
+    Box::new(others.into_iter())
+}
+
+fn log_time(path: &Path) -> io::Result<DateTime> {
+    if let Some(timestamp) = read_first_line(path).and_then(|line| parse_timestamp(&line)) {
+        return Ok(timestamp);
+    }
+    let modified = metadata(path)?.modified()?;
+    let millis = match modified.duration_since(UNIX_EPOCH) {
+        Ok(elapsed) => elapsed.as_millis() as i64,
+        Err(err) => -(err.duration().as_millis() as i64),
+    };
+    Ok(DateTime::from_unix_millis(millis))
+}
+
+fn name_fields(path: &Path, index: usize) -> io::Result<NameFields> {
+    let text = |part: Option<&OsStr>| part.unwrap_or_default().to_string_lossy().into_owned();
+    Ok(NameFields {
+        time: log_time(path)?,
+        index,
+        ext: text(path.extension()),
+        stem: text(path.file_stem()),
+    })
+}
+
+/// `name` split before its extension, the dot staying with the extension.
+fn split_extension(name: &str) -> (&str, &str) {
+    match name.rfind('.') {
+        Some(dot) if dot > 0 => name.split_at(dot),
+        _ => (name, ""),
+    }
+}
+
+/// `name` with `_n` added before its extension: `a.log` becomes `a_2.log`.
+fn numbered(name: &str, n: u32) -> String {
+    let (stem, ext) = split_extension(name);
+    format!("{stem}_{n}{ext}")
+}
+
+/// Whether `path` is already named `name` or `name` with a `_N` suffix, as
+/// a previous rename would have left it.
+fn has_name(path: &Path, name: &str) -> bool {
+    let current = path.file_name().unwrap_or_default().to_string_lossy();
+    if current == name {
+        return true;
+    }
+    let (stem, ext) = split_extension(name);
+    current
+        .strip_prefix(stem)
+        .and_then(|rest| rest.strip_suffix(ext))
+        .and_then(|suffix| suffix.strip_prefix('_'))
+        .and_then(|n| n.parse::<u32>().ok())
+        .is_some_and(|n| n >= 2)
+}
+
+/// The first free name among `name`, then `name` with a `_2`, `_3`...
+/// suffix, next to `path`; `path` itself counts as free.
+fn target_path(path: &Path, name: &str) -> PathBuf {
+    let dir = path.parent().unwrap_or(Path::new(""));
+    (1..)
+        .map(|n| match n {
+            1 => dir.join(name),
+            n => dir.join(numbered(name, n)),
+        })
+        .find(|candidate| candidate == path || !candidate.exists())
+        .expect("an unused name")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{self, File};
+    use std::time::{Duration, SystemTime};
+    use tempfile::TempDir;
+
+    const FIRST_LINE: &str = "00|2024-01-01T20:15:42.0000000+01:00|0839||Ready check.|hash\n";
+
+    #[test]
+    fn test_normalize_name_uses_first_timestamp() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("Network_26802_20240101.log");
+        fs::write(&path, FIRST_LINE).unwrap();
+
+        let renamed = normalize_name(&path, false).unwrap();
+
+        assert_eq!(renamed, temp_dir.path().join("2024-01-01_20-15.log"));
+        assert!(renamed.exists());
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_normalize_name_falls_back_to_mtime() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("Network_empty.log");
+        let modified = SystemTime::UNIX_EPOCH + Duration::from_secs(1_704_067_200 + 3661);
+        File::create(&path).unwrap().set_modified(modified).unwrap();
+
+        let renamed = normalize_name(&path, false).unwrap();
+
+        assert_eq!(renamed, temp_dir.path().join("2024-01-01_01-01.log"));
+    }
+
+    #[test]
+    fn test_normalize_name_appends_suffix_on_collision() {
+        let temp_dir = TempDir::new().unwrap();
+        let first = temp_dir.path().join("a.log");
+        let second = temp_dir.path().join("b.log");
+        fs::write(&first, FIRST_LINE).unwrap();
+        fs::write(&second, FIRST_LINE).unwrap();
+
+        normalize_name(&first, false).unwrap();
+        let renamed = normalize_name(&second, false).unwrap();
+
+        assert_eq!(renamed, temp_dir.path().join("2024-01-01_20-15_2.log"));
+        assert!(temp_dir.path().join("2024-01-01_20-15.log").exists());
+    }
+
+    #[test]
+    fn test_plan_renames_flags_collisions() {
+        let temp_dir = TempDir::new().unwrap();
+        let normalized = temp_dir.path().join("2024-01-01_20-15.log");
+        let first = temp_dir.path().join("Network_1.log");
+        let second = temp_dir.path().join("Network_2.log");
+        let other = temp_dir.path().join("Network_3.log");
+        let notes = temp_dir.path().join("notes.txt");
+        fs::write(&normalized, FIRST_LINE).unwrap();
+        fs::write(&first, FIRST_LINE).unwrap();
+        fs::write(&second, FIRST_LINE).unwrap();
+        fs::write(
+            &other,
+            "00|2024-01-02T08:00:00.0000000+01:00|0839||Ready check.|hash\n",
+        )
+        .unwrap();
+        fs::write(&notes, FIRST_LINE).unwrap();
+
+        let plan: Vec<Proposal> = plan_renames(
+            &[first.clone(), other.clone(), notes],
+            &NameTemplate::default(),
+        )
+        .into_iter()
+        .map(Result::unwrap)
+        .collect();
+
+        assert_eq!(
+            plan,
+            vec![
+                Proposal {
+                    from: first.clone(),
+                    to: normalized.clone(),
+                    collides: true,
+                },
+                Proposal {
+                    from: other,
+                    to: temp_dir.path().join("2024-01-02_08-00.log"),
+                    collides: false,
+                },
+            ]
+        );
+        assert!(first.exists());
+        assert!(second.exists());
+
+        let suffixed = temp_dir.path().join("2024-01-01_20-15_2.log");
+        fs::write(&suffixed, FIRST_LINE).unwrap();
+        let plan = plan_renames(std::slice::from_ref(&suffixed), &NameTemplate::default());
+        assert_eq!(plan[0].as_ref().unwrap().to, suffixed);
+
+        fs::remove_file(&normalized).unwrap();
+        let plan = plan_renames(&[first, second], &NameTemplate::default());
+        assert!(
+            plan.iter()
+                .all(|proposal| proposal.as_ref().unwrap().collides)
+        );
+    }
+
+    #[test]
+    fn test_render_plan_aligns_and_marks_collisions() {
+        let dir = Path::new("logs");
+        let plan = [
+            Proposal {
+                from: dir.join("a.log"),
+                to: dir.join("2024-01-01_20-15.log"),
+                collides: true,
+            },
+            Proposal {
+                from: dir.join("Network_1.log"),
+                to: dir.join("2024-01-02_07-00.log"),
+                collides: false,
+            },
+            Proposal {
+                from: dir.join("2024-01-03_09-00.log"),
+                to: dir.join("2024-01-03_09-00.log"),
+                collides: false,
+            },
+        ];
+
+        assert_eq!(
+            render_plan(&plan),
+            [
+                "! a.log                 -> 2024-01-01_20-15.log",
+                "  Network_1.log         -> 2024-01-02_07-00.log",
+                "  2024-01-03_09-00.log  =  (already normalized)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_normalize_name_keeps_normalized_and_dry_run_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let normalized = temp_dir.path().join("2024-01-01_20-15.log");
+        let pending = temp_dir.path().join("Network_1.log");
+        let notes = temp_dir.path().join("notes.txt");
+        fs::write(&normalized, FIRST_LINE).unwrap();
+        fs::write(&pending, FIRST_LINE).unwrap();
+        fs::write(&notes, FIRST_LINE).unwrap();
+
+        assert_eq!(normalize_name(&normalized, false).unwrap(), normalized);
+        assert_eq!(
+            normalize_name(&pending, true).unwrap(),
+            temp_dir.path().join("2024-01-01_20-15_2.log")
+        );
+        assert_eq!(normalize_name(&notes, false).unwrap(), notes);
+        assert!(pending.exists());
+        assert!(notes.exists());
+    }
+
+    #[test]
+    fn test_name_template_rejects_unknown_placeholders() {
+        let err = NameTemplate::parse("{date}_{zone}.log").unwrap_err();
+        assert!(err.contains("unknown placeholder '{zone}'"));
+        assert!(
+            NameTemplate::parse("{date")
+                .unwrap_err()
+                .contains("unclosed")
+        );
+        assert!(
+            NameTemplate::parse("date}")
+                .unwrap_err()
+                .contains("unmatched")
+        );
+        assert!(NameTemplate::parse("raids/{date}").is_err());
+        assert!(NameTemplate::parse("").is_err());
+    }
+
+    #[test]
+    fn test_render_template_substitutes_fields() {
+        let fields = NameFields {
+            time: parse_timestamp(FIRST_LINE).unwrap(),
+            index: 3,
+            ext: "log".to_string(),
+            stem: "Network_1".to_string(),
+        };
+
+        let render = |template| render_template(&NameTemplate::parse(template).unwrap(), &fields);
+
+        assert_eq!(render(DEFAULT_TEMPLATE), "2024-01-01_20-15.log");
+        assert_eq!(render("raid_{index}_{date}.{ext}"), "raid_3_2024-01-01.log");
+        assert_eq!(render("{time}"), "20-15");
+    }
+
+    #[test]
+    fn test_rename_to_template_rejects_path_without_file_name() {
+        let template = NameTemplate::default();
+
+        let err = rename_to_template(Path::new("logs/.."), &template, 1, true).unwrap_err();
+
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn test_name_cleanup_applies_each_clean_up_in_order() {
+        let name = "Raid Night Café.LOG";
+        let cleanup = |ascii_only, replace_spaces, lowercase| NameCleanup {
+            ascii_only,
+            replace_spaces,
+            lowercase,
+        };
+
+        assert_eq!(cleanup(false, false, false).apply(name), name);
+        assert_eq!(
+            cleanup(false, false, true).apply(name),
+            "raid night café.log"
+        );
+        assert_eq!(
+            cleanup(false, true, false).apply(name),
+            "Raid_Night_Café.LOG"
+        );
+        assert_eq!(
+            cleanup(true, false, false).apply(name),
+            "Raid Night Caf_.LOG"
+        );
+        assert_eq!(cleanup(true, true, true).apply(name), "raid_night_caf_.log");
+        assert!(cleanup(false, false, false).is_empty());
+    }
+
+    #[test]
+    fn test_rename_to_template_cleans_up_the_current_name() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("Raid Night Café.LOG");
+        let taken = temp_dir.path().join("raid_night_caf_.log");
+        fs::write(&path, FIRST_LINE).unwrap();
+        fs::write(&taken, "").unwrap();
+        let template = NameTemplate::current_name().with_cleanup(NameCleanup {
+            ascii_only: true,
+            replace_spaces: true,
+            lowercase: true,
+        });
+
+        let renamed = rename_to_template(&path, &template, 1, false).unwrap();
+
+        assert_eq!(renamed, temp_dir.path().join("raid_night_caf__2.log"));
+        assert!(taken.exists());
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_render_template_cleans_up_a_filled_in_template() {
+        let fields = NameFields {
+            time: parse_timestamp(FIRST_LINE).unwrap(),
+            index: 1,
+            ext: "log".to_string(),
+            stem: "x".to_string(),
+        };
+        let template = NameTemplate::parse("Raid {date}.{ext}")
+            .unwrap()
+            .with_cleanup(NameCleanup {
+                replace_spaces: true,
+                lowercase: true,
+                ..NameCleanup::default()
+            });
+
+        assert_eq!(render_template(&template, &fields), "raid_2024-01-01.log");
+    }
+
+    #[test]
+    fn test_rename_to_template_numbers_collisions_before_the_extension() {
+        let temp_dir = TempDir::new().unwrap();
+        let first = temp_dir.path().join("a.log");
+        let second = temp_dir.path().join("b.log");
+        fs::write(&first, FIRST_LINE).unwrap();
+        fs::write(&second, FIRST_LINE).unwrap();
+        let template = NameTemplate::parse("pull_{date}.{ext}").unwrap();
+
+        let renamed = rename_to_template(&first, &template, 1, false).unwrap();
+        assert_eq!(renamed, temp_dir.path().join("pull_2024-01-01.log"));
+        let renamed = rename_to_template(&second, &template, 2, false).unwrap();
+        assert_eq!(renamed, temp_dir.path().join("pull_2024-01-01_2.log"));
+
+        let plan = plan_renames(std::slice::from_ref(&renamed), &template);
+        assert_eq!(plan[0].as_ref().unwrap().to, renamed);
+    }
+
+    #[test]
+    fn test_plan_renames_numbers_files_for_index() {
+        let temp_dir = TempDir::new().unwrap();
+        let first = temp_dir.path().join("a.log");
+        let notes = temp_dir.path().join("notes.txt");
+        let second = temp_dir.path().join("b.log");
+        for path in [&first, &notes, &second] {
+            fs::write(path, FIRST_LINE).unwrap();
+        }
+        let template = NameTemplate::parse("{index}.{ext}").unwrap();
+
+        let plan = plan_renames(&[first, notes, second], &template);
+
+        let names: Vec<_> = plan
+            .iter()
+            .map(|proposal| proposal.as_ref().unwrap().to.clone())
+            .collect();
+        assert_eq!(
+            names,
+            [temp_dir.path().join("1.log"), temp_dir.path().join("2.log")]
+        );
+    }
+}
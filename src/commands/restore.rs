@@ -0,0 +1,152 @@
+use crate::commands::backup::move_file;
+use crate::output::{debug, sayln};
+use crate::paths;
+use crate::style;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Moves `bak_file` back into `log_dir`, the directory it was backed up
+/// from. A file already there is kept and the backup restored beside it
+/// with a `.restored` suffix. A backup on another drive is copied back and
+/// checked before it is removed.
+pub fn restore_log_file(bak_file: &Path, log_dir: &Path) -> io::Result<()> {
+    let file_name = paths::file_name(bak_file)?;
+    sayln!("Restoring {}...", file_name.display());
+
+    let result = restore_destination(bak_file, log_dir).and_then(|destination| {
+        move_file(bak_file, &destination)?;
+        Ok(destination)
+    });
+    match result {
+        Ok(destination) => {
+            sayln!(
+                "{} {}.",
+                style::success("Restored to"),
+                destination.display()
+            );
+            Ok(())
+        }
+        Err(err) => {
+            sayln!("{} {err}", style::failure("Failed:"));
+            Err(err)
+        }
+    }
+}
+
+fn restore_destination(bak_file: &Path, parent: &Path) -> io::Result<PathBuf> {
+    let file_name = paths::file_name(bak_file)?;
+    let destination = parent.join(file_name);
+    if !destination.exists() {
+        return Ok(destination);
+    }
+
+    let mut restored_name = file_name.to_owned();
+    restored_name.push(".restored");
+    let destination = parent.join(restored_name);
+    debug!(
+        "{} already exists, restoring as {}",
+        parent.join(file_name).display(),
+        destination.display()
+    );
+    if destination.exists() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} already exists", destination.display()),
+        ));
+    }
+    Ok(destination)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{self, File};
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    fn create_backup(dir: &Path, name: &str, content: &str) -> PathBuf {
+        let bak_dir = dir.join("bak");
+        fs::create_dir_all(&bak_dir).unwrap();
+        let bak_file = bak_dir.join(name);
+        let mut file = File::create(&bak_file).unwrap();
+        write!(file, "{content}").unwrap();
+        bak_file
+    }
+
+    #[test]
+    fn test_restore_log_file_moves_back_to_parent() {
+        let temp_dir = TempDir::new().unwrap();
+        let bak_file = create_backup(temp_dir.path(), "test.log", "test content");
+
+        restore_log_file(&bak_file, temp_dir.path()).unwrap();
+
+        let restored = temp_dir.path().join("test.log");
+        assert!(!bak_file.exists());
+        assert_eq!(fs::read_to_string(restored).unwrap(), "test content");
+    }
+
+    #[test]
+    fn test_restore_log_file_collision_appends_suffix() {
+        let temp_dir = TempDir::new().unwrap();
+        let bak_file = create_backup(temp_dir.path(), "test.log", "backup");
+        fs::write(temp_dir.path().join("test.log"), "current").unwrap();
+
+        restore_log_file(&bak_file, temp_dir.path()).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(temp_dir.path().join("test.log")).unwrap(),
+            "current"
+        );
+        assert_eq!(
+            fs::read_to_string(temp_dir.path().join("test.log.restored")).unwrap(),
+            "backup"
+        );
+    }
+
+    #[test]
+    fn test_restore_log_file_double_collision_fails() {
+        let temp_dir = TempDir::new().unwrap();
+        let bak_file = create_backup(temp_dir.path(), "test.log", "backup");
+        fs::write(temp_dir.path().join("test.log"), "current").unwrap();
+        fs::write(temp_dir.path().join("test.log.restored"), "earlier").unwrap();
+
+        let result = restore_log_file(&bak_file, temp_dir.path());
+
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::AlreadyExists);
+        assert!(bak_file.exists());
+    }
+
+    #[test]
+    fn test_restore_log_file_with_special_characters() {
+        let temp_dir = TempDir::new().unwrap();
+        let bak_file = create_backup(temp_dir.path(), "test file with spaces.log", "x");
+
+        restore_log_file(&bak_file, temp_dir.path()).unwrap();
+
+        assert!(temp_dir.path().join("test file with spaces.log").exists());
+    }
+
+    #[test]
+    fn test_restore_log_file_from_absolute_backup_dir() {
+        let temp_dir = TempDir::new().unwrap();
+        let archive = TempDir::new().unwrap();
+        let bak_file = archive.path().join("test.log");
+        fs::write(&bak_file, "backup").unwrap();
+
+        restore_log_file(&bak_file, temp_dir.path()).unwrap();
+
+        assert!(!bak_file.exists());
+        assert_eq!(
+            fs::read_to_string(temp_dir.path().join("test.log")).unwrap(),
+            "backup"
+        );
+    }
+
+    #[test]
+    fn test_restore_log_file_nonexistent() {
+        let temp_dir = TempDir::new().unwrap();
+        let bak_file = temp_dir.path().join("bak").join("missing.log");
+
+        assert!(restore_log_file(&bak_file, temp_dir.path()).is_err());
+    }
+}
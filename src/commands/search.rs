@@ -0,0 +1,321 @@
+use crate::encoding::Encoding;
+use crate::gzip::open_maybe_gz;
+use crate::output::debug;
+use crate::parser::parse_line;
+use crate::scan::collect_log_files;
+use std::io::{self, BufRead};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Match {
+    pub file: PathBuf,
+    pub line_number: usize,
+    pub line: String,
+}
+
+/// The lines of every log in `dir` that contain `needle`. With `opcodes`,
+/// only lines that parse as an event with one of those opcodes are
+/// searched; lines that do not parse are passed over.
+pub fn search_logs(
+    dir: &Path,
+    needle: &str,
+    ignore_case: bool,
+    opcodes: &[u8],
+    encoding: Encoding,
+) -> io::Result<Vec<Match>> {
+    let mut files: Vec<_> = collect_log_files(dir)?
+        .into_iter()
+        .map(|file| file.path)
+        .collect();
+    files.sort();
+
+    let needle = if ignore_case {
+        needle.to_lowercase()
+    } else {
+        needle.to_string()
+    };
+    let mut matches = Vec::new();
+    for file in files {
+        debug!("Searching {}", file.display());
+        search_file(&file, &needle, ignore_case, opcodes, encoding, &mut matches)?;
+    }
+    Ok(matches)
+}
+
+fn search_file(
+    path: &Path,
+    needle: &str,
+    ignore_case: bool,
+    opcodes: &[u8],
+    encoding: Encoding,
+    matches: &mut Vec<Match>,
+) -> io::Result<()> {
+    let mut reader = open_maybe_gz(path)?;
+    let mut buffer = Vec::new();
+    let mut line_number = 0;
+    // Lines are read as bytes so a stray invalid sequence in a log doesn't
+    // abort the whole search.
+    while reader.read_until(b'\n', &mut buffer)? > 0 {
+        line_number += 1;
+        let line = encoding.decode(&buffer);
+        let line = line.trim_end_matches(['\r', '\n']);
+        let wanted = opcodes.is_empty()
+            || parse_line(line).is_some_and(|event| opcodes.contains(&event.opcode));
+        let found = wanted
+            && if ignore_case {
+                line.to_lowercase().contains(needle)
+            } else {
+                line.contains(needle)
+            };
+        if found {
+            matches.push(Match {
+                file: path.to_path_buf(),
+                line_number,
+                line: line.to_string(),
+            });
+        }
+        buffer.clear();
+    }
+    Ok(())
+}
+
+/// Prints each match, then how many lines matched in each file and in all.
+pub fn print_matches(matches: &[Match]) {
+    for found in matches {
+        let file_name = found.file.file_name().unwrap_or_default();
+        println!(
+            "{}:{}: {}",
+            file_name.to_string_lossy(),
+            found.line_number,
+            found.line
+        );
+    }
+    let mut per_file: Vec<(&Path, usize)> = Vec::new();
+    for found in matches {
+        match per_file.last_mut() {
+            Some((file, count)) if *file == found.file => *count += 1,
+            _ => per_file.push((&found.file, 1)),
+        }
+    }
+    if !per_file.is_empty() {
+        println!();
+    }
+    for (file, count) in &per_file {
+        let file_name = file.file_name().unwrap_or_default();
+        println!("{}: {count} matches", file_name.to_string_lossy());
+    }
+    println!("{} matches", matches.len());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gzip;
+    use std::fs::{self, File};
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    fn create_file(dir: &Path, name: &str, lines: &[&str]) {
+        let mut file = File::create(dir.join(name)).unwrap();
+        for line in lines {
+            writeln!(file, "{line}").unwrap();
+        }
+    }
+
+    #[test]
+    fn test_search_logs_reads_gzip_files() {
+        let temp_dir = TempDir::new().unwrap();
+        create_file(temp_dir.path(), "a.log", &["21|Healer|Cure"]);
+        fs::write(
+            temp_dir.path().join("b.log.gz"),
+            gzip::compress_stored(b"00|Ready check\r\n21|Healer|Medica\r\n"),
+        )
+        .unwrap();
+
+        let matches = search_logs(temp_dir.path(), "healer", true, &[], Encoding::Utf8).unwrap();
+
+        assert_eq!(
+            matches,
+            vec![
+                Match {
+                    file: temp_dir.path().join("a.log"),
+                    line_number: 1,
+                    line: "21|Healer|Cure".to_string(),
+                },
+                Match {
+                    file: temp_dir.path().join("b.log.gz"),
+                    line_number: 2,
+                    line: "21|Healer|Medica".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_search_logs_reports_corrupt_gzip_file() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("a.log.gz"), "not compressed\n").unwrap();
+
+        let err = search_logs(temp_dir.path(), "Healer", false, &[], Encoding::Utf8).unwrap_err();
+
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_search_logs_reports_file_and_line_number() {
+        let temp_dir = TempDir::new().unwrap();
+        create_file(
+            temp_dir.path(),
+            "a.log",
+            &[
+                "21|Tank Name|Attack",
+                "21|Healer|Cure",
+                "21|Tank Name|Rampart",
+            ],
+        );
+        create_file(
+            temp_dir.path(),
+            "b.log",
+            &["00|Ready check", "21|Healer|Medica"],
+        );
+
+        let matches = search_logs(temp_dir.path(), "Healer", false, &[], Encoding::Utf8).unwrap();
+
+        assert_eq!(
+            matches,
+            vec![
+                Match {
+                    file: temp_dir.path().join("a.log"),
+                    line_number: 2,
+                    line: "21|Healer|Cure".to_string(),
+                },
+                Match {
+                    file: temp_dir.path().join("b.log"),
+                    line_number: 2,
+                    line: "21|Healer|Medica".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_search_logs_is_case_sensitive_by_default() {
+        let temp_dir = TempDir::new().unwrap();
+        create_file(temp_dir.path(), "a.log", &["Rampart", "rampart"]);
+
+        assert_eq!(
+            search_logs(temp_dir.path(), "Rampart", false, &[], Encoding::Utf8)
+                .unwrap()
+                .len(),
+            1
+        );
+        assert_eq!(
+            search_logs(temp_dir.path(), "RAMPART", true, &[], Encoding::Utf8)
+                .unwrap()
+                .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_search_logs_strips_line_endings() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("a.log"), "first\r\nsecond\r\n").unwrap();
+
+        let matches = search_logs(temp_dir.path(), "second", false, &[], Encoding::Utf8).unwrap();
+
+        assert_eq!(matches[0].line, "second");
+    }
+
+    #[test]
+    fn test_search_logs_tolerates_invalid_utf8() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("a.log"), b"\xff\xfe broken\nneedle\n").unwrap();
+
+        let matches = search_logs(temp_dir.path(), "needle", false, &[], Encoding::Utf8).unwrap();
+
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].line_number, 2);
+    }
+
+    #[test]
+    fn test_search_logs_decodes_shift_jis() {
+        let temp_dir = TempDir::new().unwrap();
+        // "21|タンク|攻撃" as the Japanese client writes it.
+        fs::write(
+            temp_dir.path().join("a.log"),
+            b"21|\x83\x5e\x83\x93\x83\x4e|\x8d\x55\x8c\x82\n",
+        )
+        .unwrap();
+
+        let matches = search_logs(temp_dir.path(), "攻撃", false, &[], Encoding::ShiftJis).unwrap();
+
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].line, "21|タンク|攻撃");
+    }
+
+    #[test]
+    fn test_search_logs_skips_directories() {
+        let temp_dir = TempDir::new().unwrap();
+        create_file(temp_dir.path(), "a.log", &["needle"]);
+        fs::create_dir(temp_dir.path().join("bak")).unwrap();
+        create_file(&temp_dir.path().join("bak"), "old.log", &["needle"]);
+
+        assert_eq!(
+            search_logs(temp_dir.path(), "needle", false, &[], Encoding::Utf8)
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_search_logs_nonexistent_directory() {
+        let temp_dir = TempDir::new().unwrap();
+
+        assert!(
+            search_logs(
+                &temp_dir.path().join("nonexistent"),
+                "x",
+                false,
+                &[],
+                Encoding::Utf8
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_print_matches_empty() {
+        print_matches(&[]);
+    }
+
+    #[test]
+    fn test_search_logs_filters_by_opcode() {
+        let temp_dir = TempDir::new().unwrap();
+        create_file(
+            temp_dir.path(),
+            "a.log",
+            &[
+                "00|2024-01-01T20:00:00.0000000+01:00|0839||Boss uses Flare|hash",
+                "21|2024-01-01T20:00:01.0000000+01:00|40001234|Boss|1F|Flare|10001234|Tank|hash",
+                "22|2024-01-01T20:00:02.0000000+01:00|40001234|Boss|1F|Flare|10005678|Healer|hash",
+                "Flare in a line that does not parse",
+            ],
+        );
+
+        let abilities =
+            search_logs(temp_dir.path(), "Flare", false, &[21, 22], Encoding::Utf8).unwrap();
+        let chat = search_logs(temp_dir.path(), "", false, &[0], Encoding::Utf8).unwrap();
+
+        let lines: Vec<_> = abilities.iter().map(|found| found.line_number).collect();
+        assert_eq!(lines, [2, 3]);
+        assert_eq!(chat.len(), 1);
+        assert_eq!(
+            search_logs(temp_dir.path(), "Flare", false, &[], Encoding::Utf8)
+                .unwrap()
+                .len(),
+            4
+        );
+    }
+}
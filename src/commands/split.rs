@@ -0,0 +1,164 @@
+use crate::gzip::open_maybe_gz;
+use crate::parser::{Event, Payload, parse_line};
+use std::fs::File;
+use std::io::{self, BufRead, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// The system message channel, where the game reports a duty ending.
+const SYSTEM_CHANNEL: &str = "0839";
+/// `33` director commands: the encounter was won, or the party wiped.
+const VICTORY: &str = "40000003";
+const WIPE: &str = "40000005";
+
+/// The path of part `number` of `file`: `<name>_part01.log` in `out_dir`.
+pub fn part_path(file: &Path, out_dir: &Path, number: usize) -> PathBuf {
+    let name = file.file_name().unwrap_or_default().to_string_lossy();
+    let stem = strip_suffix_ignore_case(&name, ".gz");
+    let stem = strip_suffix_ignore_case(stem, ".log");
+    out_dir.join(format!("{stem}_part{number:02}.log"))
+}
+
+fn strip_suffix_ignore_case<'a>(name: &'a str, suffix: &str) -> &'a str {
+    match name.len().checked_sub(suffix.len()) {
+        Some(at) if name.is_char_boundary(at) && name[at..].eq_ignore_ascii_case(suffix) => {
+            &name[..at]
+        }
+        _ => name,
+    }
+}
+
+/// Whether `event` closes an encounter: a wipe or a victory reported by the
+/// duty director, or the game's "... has ended." system message.
+fn ends_encounter(event: &Event) -> bool {
+    match &event.payload {
+        Payload::Chat {
+            channel, message, ..
+        } => channel == SYSTEM_CHANNEL && message.trim_end().ends_with("has ended."),
+        Payload::Other(fields) if event.opcode == 33 => {
+            matches!(fields.get(1).map(String::as_str), Some(VICTORY | WIPE))
+        }
+        _ => false,
+    }
+}
+
+/// Writes each encounter of `file` to its own `<name>_partNN.log` in
+/// `out_dir` and returns the parts written. An encounter runs up to and
+/// including the line that ends it; lines before the first boundary form
+/// part 1 and lines after the last one a final part. A boundary only ends
+/// a part in which abilities were used, so the "has ended." message that
+/// follows a clear does not become a part of its own. The file is read line
+/// by line, so even a whole evening's log is split without loading it.
+pub fn split_log(file: &Path, out_dir: &Path) -> io::Result<Vec<PathBuf>> {
+    println!("Splitting {} into {}...", file.display(), out_dir.display());
+    let mut reader = open_maybe_gz(file)?;
+    let mut parts = Vec::new();
+    let mut writer: Option<BufWriter<File>> = None;
+    let mut fought = false;
+    let mut buffer = Vec::new();
+    while reader.read_until(b'\n', &mut buffer)? > 0 {
+        let line = String::from_utf8_lossy(&buffer);
+        let line = line.trim_end_matches(['\r', '\n']);
+        let out = match writer.as_mut() {
+            Some(out) => out,
+            None => {
+                let path = part_path(file, out_dir, parts.len() + 1);
+                let out = writer.insert(BufWriter::new(File::create(&path)?));
+                parts.push(path);
+                out
+            }
+        };
+        writeln!(out, "{line}")?;
+        if let Some(event) = parse_line(line) {
+            fought |= matches!(event.payload, Payload::Ability { .. });
+            if fought && ends_encounter(&event) {
+                writer.take().map(|mut out| out.flush()).transpose()?;
+                fought = false;
+            }
+        }
+        buffer.clear();
+    }
+    writer.map(|mut out| out.flush()).transpose()?;
+
+    println!("Wrote {} parts.", parts.len());
+    Ok(parts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn line(opcode: &str, second: u32, rest: &str) -> String {
+        format!("{opcode}|2024-01-01T20:15:{second:02}.0000000+01:00|{rest}|hash")
+    }
+
+    #[test]
+    fn test_split_log_cuts_after_each_wipe_and_clear() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("Network_1.log");
+        let lines = [
+            line("21", 0, "10001234|Tank Name|1F|Attack|40001234|Boss"),
+            line("33", 1, "8003757F|40000005|00|00|00|00"),
+            line("00", 2, "0839||Ready check"),
+            line("21", 3, "10001234|Tank Name|1F|Attack|40001234|Boss"),
+            line("33", 4, "8003757F|40000003|00|00|00|00"),
+            line("00", 5, "0839||The Arena has ended."),
+            line("21", 6, "10001234|Tank Name|1F|Attack|40001234|Boss"),
+        ];
+        fs::write(&file, lines.join("\n") + "\n").unwrap();
+
+        let parts = split_log(&file, temp_dir.path()).unwrap();
+
+        let names: Vec<_> = parts
+            .iter()
+            .map(|part| part.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "Network_1_part01.log",
+                "Network_1_part02.log",
+                "Network_1_part03.log",
+            ]
+        );
+        let read = |index: usize| fs::read_to_string(&parts[index]).unwrap();
+        assert_eq!(read(0), format!("{}\n{}\n", lines[0], lines[1]));
+        assert_eq!(
+            read(1),
+            format!("{}\n{}\n{}\n", lines[2], lines[3], lines[4])
+        );
+        assert_eq!(read(2), format!("{}\n{}\n", lines[5], lines[6]));
+    }
+
+    #[test]
+    fn test_split_log_without_boundaries_writes_one_part() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("Network_1.log");
+        let out_dir = temp_dir.path().join("parts");
+        fs::create_dir(&out_dir).unwrap();
+        fs::write(&file, "not a log line\n").unwrap();
+
+        let parts = split_log(&file, &out_dir).unwrap();
+
+        assert_eq!(parts, [out_dir.join("Network_1_part01.log")]);
+        assert_eq!(fs::read_to_string(&parts[0]).unwrap(), "not a log line\n");
+    }
+
+    #[test]
+    fn test_part_path_drops_log_and_gz_extensions() {
+        let out = Path::new("out");
+        assert_eq!(
+            part_path(Path::new("logs/Network_1.log"), out, 3),
+            out.join("Network_1_part03.log")
+        );
+        assert_eq!(
+            part_path(Path::new("Network_1.LOG.gz"), out, 12),
+            out.join("Network_1_part12.log")
+        );
+        assert_eq!(
+            part_path(Path::new("notes"), out, 1),
+            out.join("notes_part01.log")
+        );
+    }
+}
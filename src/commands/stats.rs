@@ -0,0 +1,533 @@
+use crate::filters::{read_first_line, read_last_line};
+use crate::gzip::open_maybe_gz;
+use crate::json;
+use crate::parser::{Payload, parse_line, parse_timestamp};
+use crate::scan::{DirSummary, ScanEntry, collect_log_files, entries, with_path};
+use crate::table::{self, Column};
+use crate::util::{format_rfc3339, format_size, format_span, format_time};
+use clap::ValueEnum;
+use std::cmp::Reverse;
+use std::collections::HashSet;
+use std::io::{self, BufRead};
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum StatsSort {
+    /// File name, A to Z
+    Name,
+    /// File size, largest first
+    Size,
+    /// Time span, longest first
+    Span,
+}
+
+/// One row of the per-file table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileStats {
+    pub name: String,
+    pub size: u64,
+    pub modified: Option<SystemTime>,
+    /// Time between the first and last line, if both have a timestamp.
+    pub span: Option<Duration>,
+    /// Distinct combatant names, counted by `--deep`; `None` without it or
+    /// when the file could not be parsed.
+    pub combatants: Option<usize>,
+}
+
+#[derive(Debug, Default)]
+pub struct Summary {
+    pub file_count: usize,
+    pub total_bytes: u64,
+    pub largest: Option<(String, u64)>,
+    pub smallest: Option<(String, u64)>,
+    pub oldest: Option<(String, SystemTime)>,
+    pub newest: Option<(String, SystemTime)>,
+    pub dir_count: usize,
+    pub dir_bytes: u64,
+    pub dir_files: usize,
+    pub files: Vec<FileStats>,
+    /// Sum of the spans of every file whose span is known.
+    pub total_span: Duration,
+    pub unknown_spans: usize,
+    /// Distinct combatant names across all files; only counted by `--deep`.
+    pub combatants: Option<usize>,
+}
+
+impl Summary {
+    /// The headline figures as a single JSON object. Missing extremes are
+    /// `null`; times are RFC 3339 UTC timestamps.
+    pub fn to_json(&self) -> String {
+        let sized = |entry: &Option<(String, u64)>| match entry {
+            Some((name, size)) => format!("{{\"name\":{},\"size\":{size}}}", json::escape(name)),
+            None => "null".to_string(),
+        };
+        let dated = |entry: &Option<(String, SystemTime)>| match entry {
+            Some((name, time)) => format!(
+                "{{\"name\":{},\"modified\":{}}}",
+                json::escape(name),
+                json::escape(&format_rfc3339(*time))
+            ),
+            None => "null".to_string(),
+        };
+        let combatants = self
+            .combatants
+            .map(|count| format!(",\"combatants\":{count}"))
+            .unwrap_or_default();
+        format!(
+            "{{\"file_count\":{},\"total_bytes\":{},\"largest\":{},\"smallest\":{},\"oldest\":{},\"newest\":{}{combatants}}}",
+            self.file_count,
+            self.total_bytes,
+            sized(&self.largest),
+            sized(&self.smallest),
+            dated(&self.oldest),
+            dated(&self.newest)
+        )
+    }
+}
+
+/// How long a log covers: the time from its first to its last line. Only
+/// the start and end of the file are read.
+pub fn log_span(path: &Path) -> Option<Duration> {
+    let first = parse_timestamp(&read_first_line(path)?)?;
+    let last = parse_timestamp(&read_last_line(path)?)?;
+    let millis = last.unix_millis().checked_sub(first.unix_millis())?;
+    u64::try_from(millis).ok().map(Duration::from_millis)
+}
+
+/// The names of the players and NPCs added to the combatant list (`03`
+/// lines) anywhere in the log. Reads the whole file; `None` if it cannot be
+/// read or no line of it parses as a log event.
+pub fn combatant_names(path: &Path) -> Option<HashSet<String>> {
+    let mut reader = open_maybe_gz(path).ok()?;
+    let mut names = HashSet::new();
+    let mut parsed = false;
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        if reader.read_until(b'\n', &mut line).ok()? == 0 {
+            break;
+        }
+        let Some(event) = parse_line(&String::from_utf8_lossy(&line)) else {
+            continue;
+        };
+        parsed = true;
+        if let Payload::AddCombatant { name, .. } = event.payload {
+            names.insert(name);
+        }
+    }
+    parsed.then_some(names)
+}
+
+pub fn sort_files(files: &mut [FileStats], key: StatsSort) {
+    match key {
+        StatsSort::Name => files.sort_by(|a, b| a.name.cmp(&b.name)),
+        StatsSort::Size => files.sort_by_key(|file| Reverse(file.size)),
+        StatsSort::Span => files.sort_by_key(|file| Reverse(file.span)),
+    }
+}
+
+/// Summarizes the files in `dir`. With `include_dirs`, subdirectories are
+/// counted too, their contents adding to the total size. With `deep`, every
+/// file is read in full to count its combatants. Entries that cannot be
+/// read are reported and left out of the totals.
+pub fn summarize(dir: &Path, include_dirs: bool, deep: bool) -> Result<Summary, io::Error> {
+    let mut summary = Summary::default();
+    let mut all_combatants = HashSet::new();
+
+    if include_dirs {
+        for entry in entries(dir)? {
+            let sub_dir = match entry {
+                Ok(ScanEntry::Directory(path)) => {
+                    DirSummary::from_path(path.clone()).map_err(|err| with_path(&path, err))
+                }
+                Ok(_) => continue,
+                Err(err) => Err(err),
+            };
+            match sub_dir {
+                Ok(sub_dir) => {
+                    summary.dir_count += 1;
+                    summary.dir_bytes += sub_dir.size;
+                    summary.dir_files += sub_dir.files;
+                    summary.total_bytes += sub_dir.size;
+                }
+                Err(err) => eprintln!("Skipping {err}"),
+            }
+        }
+    }
+
+    for file in collect_log_files(dir)? {
+        let name = file.name();
+        let size = file.size;
+        let span = log_span(&file.path);
+        match span {
+            Some(span) => summary.total_span += span,
+            None => summary.unknown_spans += 1,
+        }
+        let combatants = deep
+            .then(|| combatant_names(&file.path))
+            .flatten()
+            .map(|names| {
+                let count = names.len();
+                all_combatants.extend(names);
+                count
+            });
+        summary.files.push(FileStats {
+            name: name.clone(),
+            size,
+            modified: file.modified,
+            span,
+            combatants,
+        });
+
+        summary.file_count += 1;
+        summary.total_bytes += size;
+        if summary.largest.as_ref().is_none_or(|(_, max)| size > *max) {
+            summary.largest = Some((name.clone(), size));
+        }
+        if summary.smallest.as_ref().is_none_or(|(_, min)| size < *min) {
+            summary.smallest = Some((name.clone(), size));
+        }
+        if let Some(modified) = file.modified {
+            if summary
+                .oldest
+                .as_ref()
+                .is_none_or(|(_, old)| modified < *old)
+            {
+                summary.oldest = Some((name.clone(), modified));
+            }
+            if summary
+                .newest
+                .as_ref()
+                .is_none_or(|(_, new)| modified > *new)
+            {
+                summary.newest = Some((name, modified));
+            }
+        }
+    }
+
+    if deep {
+        summary.combatants = Some(all_combatants.len());
+    }
+    sort_files(&mut summary.files, StatsSort::Name);
+    Ok(summary)
+}
+
+/// Prints the summary rows, then a table of the files, with a header row
+/// over it when `header` is set.
+pub fn print_summary(summary: &Summary, header: bool) {
+    const LABELS: [Column; 2] = [Column::left("Statistic"), Column::left("Value")];
+    let sized = |entry: &Option<(String, u64)>| match entry {
+        Some((name, size)) => format!("{name} ({size} bytes)"),
+        None => "-".to_string(),
+    };
+    let dated = |entry: &Option<(String, SystemTime)>| match entry {
+        Some((name, time)) => format!("{} ({name})", format_time(*time)),
+        None => "-".to_string(),
+    };
+
+    let mut rows = vec![("Files", summary.file_count.to_string())];
+    if summary.dir_count > 0 {
+        rows.push((
+            "Directories",
+            format!(
+                "{} ({} bytes in {} files)",
+                summary.dir_count, summary.dir_bytes, summary.dir_files
+            ),
+        ));
+    }
+    rows.extend([
+        ("Total size", format!("{} bytes", summary.total_bytes)),
+        ("Largest", sized(&summary.largest)),
+        ("Smallest", sized(&summary.smallest)),
+        ("Oldest", dated(&summary.oldest)),
+        ("Newest", dated(&summary.newest)),
+    ]);
+    let mut total_span = format_span(summary.total_span);
+    if summary.unknown_spans > 0 {
+        total_span.push_str(&format!(" ({} unknown)", summary.unknown_spans));
+    }
+    rows.push(("Total span", total_span));
+    if let Some(combatants) = summary.combatants {
+        rows.push(("Combatants", format!("{combatants} distinct")));
+    }
+    let rows: Vec<Vec<String>> = rows
+        .into_iter()
+        .map(|(label, value)| vec![label.to_string(), value])
+        .collect();
+    for line in table::render(&LABELS, &rows, false) {
+        println!("{line}");
+    }
+
+    if !summary.files.is_empty() {
+        println!();
+        render_files(&summary.files, summary.combatants.is_some(), header)
+            .iter()
+            .for_each(|row| println!("{row}"));
+    }
+}
+
+fn render_files(files: &[FileStats], deep: bool, header: bool) -> Vec<String> {
+    const COLUMNS: [Column; 3] = [
+        Column::left("Name"),
+        Column::right("Size"),
+        Column::left("Span"),
+    ];
+    let rows: Vec<Vec<String>> = files
+        .iter()
+        .map(|file| {
+            let mut span = file
+                .span
+                .map_or_else(|| "unknown span".to_string(), format_span);
+            if deep {
+                span.push_str(&match file.combatants {
+                    Some(1) => ", 1 combatant".to_string(),
+                    Some(count) => format!(", {count} combatants"),
+                    None => ", ? combatants".to_string(),
+                });
+            }
+            vec![file.name.clone(), format_size(file.size), span]
+        })
+        .collect();
+    table::render(&COLUMNS, &rows, header)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gzip;
+    use std::fs::{self, File};
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    fn create_file(dir: &Path, name: &str, size: usize) {
+        let mut file = File::create(dir.join(name)).unwrap();
+        file.write_all(&vec![b'x'; size]).unwrap();
+    }
+
+    #[test]
+    fn test_summarize_empty_directory() {
+        let temp_dir = TempDir::new().unwrap();
+
+        let summary = summarize(temp_dir.path(), false, false).unwrap();
+
+        assert_eq!(summary.file_count, 0);
+        assert_eq!(summary.total_bytes, 0);
+        assert!(summary.largest.is_none());
+        assert!(summary.newest.is_none());
+    }
+
+    #[test]
+    fn test_summarize_counts_and_sizes() {
+        let temp_dir = TempDir::new().unwrap();
+        create_file(temp_dir.path(), "small.log", 10);
+        create_file(temp_dir.path(), "medium.log", 100);
+        create_file(temp_dir.path(), "large.log", 1000);
+
+        let summary = summarize(temp_dir.path(), false, false).unwrap();
+
+        assert_eq!(summary.file_count, 3);
+        assert_eq!(summary.total_bytes, 1110);
+        assert_eq!(summary.largest, Some(("large.log".to_string(), 1000)));
+        assert_eq!(summary.smallest, Some(("small.log".to_string(), 10)));
+    }
+
+    #[test]
+    fn test_summarize_oldest_and_newest() {
+        let temp_dir = TempDir::new().unwrap();
+        create_file(temp_dir.path(), "old.log", 1);
+        create_file(temp_dir.path(), "new.log", 1);
+
+        let old = File::options()
+            .write(true)
+            .open(temp_dir.path().join("old.log"))
+            .unwrap();
+        old.set_modified(SystemTime::UNIX_EPOCH).unwrap();
+
+        let summary = summarize(temp_dir.path(), false, false).unwrap();
+
+        assert_eq!(summary.oldest.unwrap().0, "old.log");
+        assert_eq!(summary.newest.unwrap().0, "new.log");
+    }
+
+    #[test]
+    fn test_summarize_skips_directories() {
+        let temp_dir = TempDir::new().unwrap();
+        create_file(temp_dir.path(), "test.log", 5);
+        fs::create_dir(temp_dir.path().join("bak")).unwrap();
+
+        let summary = summarize(temp_dir.path(), false, false).unwrap();
+
+        assert_eq!(summary.file_count, 1);
+    }
+
+    #[test]
+    fn test_summarize_nonexistent_directory() {
+        let temp_dir = TempDir::new().unwrap();
+
+        assert!(summarize(&temp_dir.path().join("nonexistent"), false, false).is_err());
+    }
+
+    #[test]
+    fn test_to_json_contains_expected_keys() {
+        let temp_dir = TempDir::new().unwrap();
+        create_file(temp_dir.path(), "small.log", 10);
+        create_file(temp_dir.path(), "large.log", 1000);
+
+        let summary = summarize(temp_dir.path(), false, false).unwrap();
+        let value = json::parse(&summary.to_json()).unwrap();
+
+        for key in [
+            "file_count",
+            "total_bytes",
+            "largest",
+            "smallest",
+            "oldest",
+            "newest",
+        ] {
+            assert!(value.get(key).is_some(), "missing {key}");
+        }
+        assert_eq!(value.get("file_count"), Some(&json::Value::Number(2.0)));
+        let largest = value.get("largest").unwrap();
+        assert_eq!(
+            largest.get("name").and_then(json::Value::as_str),
+            Some("large.log")
+        );
+        assert!(value.get("newest").unwrap().get("modified").is_some());
+    }
+
+    #[test]
+    fn test_to_json_empty_uses_null() {
+        let value = json::parse(&Summary::default().to_json()).unwrap();
+
+        assert_eq!(value.get("largest"), Some(&json::Value::Null));
+        assert_eq!(value.get("total_bytes"), Some(&json::Value::Number(0.0)));
+    }
+
+    #[test]
+    fn test_print_summary_empty() {
+        print_summary(&Summary::default(), true);
+    }
+
+    const START: &str = "00|2024-01-01T20:00:00.0000000+01:00|0839||pull|hash";
+    const END: &str = "21|2024-01-01T20:12:30.5000000+01:00|10001234|Tank|1F|Attack|hash";
+
+    #[test]
+    fn test_log_span_reads_first_and_last_line() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("Network_1.log");
+        fs::write(&path, format!("{START}\nnoise\n{END}\n")).unwrap();
+
+        assert_eq!(log_span(&path), Some(Duration::from_millis(750_500)));
+    }
+
+    #[test]
+    fn test_log_span_reads_gzip_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("Network_1.log.gz");
+        let content = format!("{START}\nnoise\n{END}\n");
+        fs::write(&path, gzip::compress_stored(content.as_bytes())).unwrap();
+
+        assert_eq!(log_span(&path), Some(Duration::from_millis(750_500)));
+    }
+
+    #[test]
+    fn test_log_span_unknown_without_timestamps() {
+        let temp_dir = TempDir::new().unwrap();
+        let notes = temp_dir.path().join("notes.log");
+        fs::write(&notes, format!("{START}\njust notes\n")).unwrap();
+
+        assert_eq!(log_span(&notes), None);
+    }
+
+    #[test]
+    fn test_summarize_totals_spans_and_sorts() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("a.log"), format!("{START}\n{END}\n")).unwrap();
+        fs::write(temp_dir.path().join("b.log"), format!("{START}\n{START}\n")).unwrap();
+        create_file(temp_dir.path(), "c.log", 500);
+
+        let mut summary = summarize(temp_dir.path(), false, false).unwrap();
+
+        assert_eq!(summary.total_span, Duration::from_millis(750_500));
+        assert_eq!(summary.unknown_spans, 1);
+        let names = |files: &[FileStats]| files.iter().map(|f| f.name.clone()).collect::<Vec<_>>();
+        assert_eq!(names(&summary.files), ["a.log", "b.log", "c.log"]);
+        sort_files(&mut summary.files, StatsSort::Span);
+        assert_eq!(names(&summary.files), ["a.log", "b.log", "c.log"]);
+        sort_files(&mut summary.files, StatsSort::Size);
+        assert_eq!(names(&summary.files), ["c.log", "a.log", "b.log"]);
+        assert!(render_files(&summary.files, false, false)[0].ends_with("unknown span"));
+    }
+
+    #[test]
+    fn test_summarize_include_dirs() {
+        let temp_dir = TempDir::new().unwrap();
+        create_file(temp_dir.path(), "test.log", 5);
+        fs::create_dir(temp_dir.path().join("bak")).unwrap();
+        create_file(&temp_dir.path().join("bak"), "old.log", 20);
+        create_file(&temp_dir.path().join("bak"), "older.log", 30);
+
+        let summary = summarize(temp_dir.path(), true, false).unwrap();
+
+        assert_eq!(summary.file_count, 1);
+        assert_eq!(summary.dir_count, 1);
+        assert_eq!(summary.dir_bytes, 50);
+        assert_eq!(summary.dir_files, 2);
+        assert_eq!(summary.total_bytes, 55);
+        assert_eq!(summary.largest, Some(("test.log".to_string(), 5)));
+    }
+
+    const ADD_TANK: &str =
+        "03|2024-01-01T20:00:01.0000000+01:00|10001234|Tank Name|13|5A|0000|49|Gilgamesh|hash";
+    const ADD_BOSS: &str = "03|2024-01-01T20:00:02.0000000+01:00|40001234|Boss|0|5A|0000|0||hash";
+
+    #[test]
+    fn test_combatant_names() {
+        let temp_dir = TempDir::new().unwrap();
+        let log = temp_dir.path().join("Network_1.log");
+        fs::write(
+            &log,
+            format!("{START}\n{ADD_TANK}\n{ADD_BOSS}\n{ADD_TANK}\n"),
+        )
+        .unwrap();
+        let notes = temp_dir.path().join("notes.log");
+        fs::write(&notes, "just notes\n").unwrap();
+
+        let names = combatant_names(&log).unwrap();
+
+        assert_eq!(names.len(), 2);
+        assert!(names.contains("Tank Name") && names.contains("Boss"));
+        assert_eq!(combatant_names(&notes), None);
+        assert_eq!(combatant_names(&temp_dir.path().join("missing.log")), None);
+    }
+
+    #[test]
+    fn test_summarize_deep_counts_combatants_per_file_and_overall() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("a.log"),
+            format!("{ADD_TANK}\n{ADD_BOSS}\n"),
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("b.log"),
+            format!("{START}\n{ADD_TANK}\n"),
+        )
+        .unwrap();
+        create_file(temp_dir.path(), "c.log", 10);
+
+        let summary = summarize(temp_dir.path(), false, true).unwrap();
+
+        let counts: Vec<_> = summary.files.iter().map(|file| file.combatants).collect();
+        assert_eq!(counts, [Some(2), Some(1), None]);
+        assert_eq!(summary.combatants, Some(2));
+        assert!(render_files(&summary.files, true, false)[2].ends_with("? combatants"));
+        let value = json::parse(&summary.to_json()).unwrap();
+        assert_eq!(value.get("combatants"), Some(&json::Value::Number(2.0)));
+
+        let shallow = summarize(temp_dir.path(), false, false).unwrap();
+        assert_eq!(shallow.combatants, None);
+        assert!(shallow.files.iter().all(|file| file.combatants.is_none()));
+    }
+}
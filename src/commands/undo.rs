@@ -0,0 +1,303 @@
+//! Reverting the most recent backup run.
+//!
+//! Every `backup` appends the moves it made to a manifest in the log
+//! directory, one batch per run: a `# backup <time>` header followed by an
+//! `<original>\t<backup>` line for each moved file, both absolute.
+//!
+//! `undo` moves the files of the last batch back and drops that batch, so
+//! repeated undos walk back through earlier runs. Files that could not be
+//! moved back stay in the batch for the next attempt.
+
+use crate::commands::backup::{backup_path, move_file};
+use crate::output::{self, debug, sayln};
+use crate::style;
+use crate::summary::Summary;
+use crate::util::format_rfc3339;
+use std::fs::{File, OpenOptions, read_to_string, remove_file, write};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf, absolute};
+use std::time::SystemTime;
+
+/// The manifest's file name inside the log directory.
+pub const MANIFEST_NAME: &str = ".ff-log-cli-undo";
+const BATCH_PREFIX: &str = "# backup ";
+
+pub fn manifest_path(dir: &Path) -> PathBuf {
+    dir.join(MANIFEST_NAME)
+}
+
+/// Appends the moves of one backup run to the manifest. The file and the
+/// batch header are only written once the first move is recorded, so runs
+/// that move nothing leave no trace.
+pub struct UndoLog {
+    path: PathBuf,
+    file: Option<File>,
+}
+
+impl UndoLog {
+    pub fn new(dir: &Path) -> Self {
+        UndoLog {
+            path: manifest_path(dir),
+            file: None,
+        }
+    }
+
+    pub fn record(&mut self, from: &Path, to: &Path) -> io::Result<()> {
+        let file = match &mut self.file {
+            Some(file) => file,
+            None => {
+                let mut file = OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(&self.path)?;
+                writeln!(file, "{BATCH_PREFIX}{}", format_rfc3339(SystemTime::now()))?;
+                self.file.insert(file)
+            }
+        };
+        writeln!(
+            file,
+            "{}\t{}",
+            absolute(from)?.display(),
+            absolute(to)?.display()
+        )
+    }
+
+    /// Records `file` as moved into `backup_dir` by a backup.
+    pub fn record_backup(&mut self, file: &Path, backup_dir: &Path) -> io::Result<()> {
+        self.record(file, &backup_path(file, backup_dir)?)
+    }
+}
+
+/// Splits the manifest into the text before the last batch, the header
+/// line of that batch and its moves.
+fn split_last_batch(manifest: &str) -> (&str, &str, Vec<(PathBuf, PathBuf)>) {
+    let start = match manifest.rfind(BATCH_PREFIX) {
+        Some(start) => start,
+        None => return (manifest, "", Vec::new()),
+    };
+    let mut lines = manifest[start..].lines();
+    let header = lines.next().unwrap_or_default();
+    let moves = lines
+        .filter_map(|line| line.split_once('\t'))
+        .map(|(from, to)| (PathBuf::from(from), PathBuf::from(to)))
+        .collect();
+    (&manifest[..start], header, moves)
+}
+
+/// Drops the moves whose backups are among `pruned` from the manifest in
+/// `dir`, with any batch left empty, so that `--max-bak-size` removing old
+/// backups on purpose does not keep `undo` from reverting the rest.
+pub fn forget_backups(dir: &Path, pruned: &[PathBuf]) -> io::Result<()> {
+    let manifest_path = manifest_path(dir);
+    let manifest = match read_to_string(&manifest_path) {
+        Ok(manifest) if !pruned.is_empty() => manifest,
+        Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err),
+        _ => return Ok(()),
+    };
+    let pruned = pruned
+        .iter()
+        .map(absolute)
+        .collect::<io::Result<Vec<_>>>()?;
+
+    let mut kept = String::new();
+    let mut header = None;
+    for line in manifest.lines() {
+        if line.starts_with(BATCH_PREFIX) {
+            header = Some(line);
+        } else if !line
+            .split_once('\t')
+            .is_some_and(|(_, to)| pruned.iter().any(|path| path == Path::new(to)))
+        {
+            if let Some(header) = header.take() {
+                kept.push_str(header);
+                kept.push('\n');
+            }
+            kept.push_str(line);
+            kept.push('\n');
+        }
+    }
+    debug!(
+        "Dropping {} pruned backups from {}",
+        pruned.len(),
+        manifest_path.display()
+    );
+    if kept.is_empty() {
+        remove_file(&manifest_path)
+    } else {
+        write(&manifest_path, kept)
+    }
+}
+
+/// Moves every file of the last recorded backup back to where it came from.
+/// Nothing is moved if any original location is taken again or any backup
+/// has gone missing, so a batch is never half reverted. Should a move fail
+/// midway, the files not moved back are kept in the manifest.
+pub fn undo_last_backup(dir: &Path, summary: &mut Summary) -> io::Result<()> {
+    let manifest_path = manifest_path(dir);
+    let manifest = match read_to_string(&manifest_path) {
+        Ok(manifest) => manifest,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(err) => return Err(err),
+    };
+    let (earlier, header, moves) = split_last_batch(&manifest);
+    if moves.is_empty() {
+        sayln!("Nothing to undo in {}.", dir.display());
+        return Ok(());
+    }
+
+    let mut blocked = false;
+    for (original, backup) in &moves {
+        if original.exists() {
+            output::emit_err(format!(
+                "Cannot undo: {} already exists",
+                original.display()
+            ));
+            blocked = true;
+        } else if !backup.exists() {
+            output::emit_err(format!(
+                "Cannot undo: backup {} is missing",
+                backup.display()
+            ));
+            blocked = true;
+        }
+    }
+    if blocked {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            "the last backup can no longer be undone cleanly; nothing was moved",
+        ));
+    }
+
+    sayln!("Undoing the last backup of {} files...", moves.len());
+    let mut kept = String::new();
+    for (original, backup) in &moves {
+        let file_name = original.file_name().unwrap_or_default();
+        sayln!("Restoring {}...", file_name.display());
+        let result = move_file(backup, original);
+        match &result {
+            Ok(()) => sayln!("{}", style::success("Restored.")),
+            Err(err) => sayln!("{} {err}", style::failure("Failed:")),
+        }
+        if result.is_err() {
+            kept.push_str(&format!("{}\t{}\n", original.display(), backup.display()));
+        }
+        summary.record(result.is_ok());
+    }
+
+    if !kept.is_empty() {
+        debug!(
+            "Keeping the files not restored in {}",
+            manifest_path.display()
+        );
+        return write(&manifest_path, format!("{earlier}{header}\n{kept}"));
+    }
+    debug!("Dropping the last batch from {}", manifest_path.display());
+    if earlier.is_empty() {
+        remove_file(&manifest_path)
+    } else {
+        write(&manifest_path, earlier)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn back_up(temp_dir: &TempDir, name: &str, log: &mut UndoLog) -> (PathBuf, PathBuf) {
+        let original = temp_dir.path().join(name);
+        let backup = temp_dir.path().join("bak").join(name);
+        fs::create_dir_all(backup.parent().unwrap()).unwrap();
+        fs::write(&backup, name).unwrap();
+        log.record(&original, &backup).unwrap();
+        (original, backup)
+    }
+
+    #[test]
+    fn test_undo_reverts_only_the_last_batch() {
+        let temp_dir = TempDir::new().unwrap();
+        let (first, first_backup) = back_up(&temp_dir, "a.log", &mut UndoLog::new(temp_dir.path()));
+        let mut log = UndoLog::new(temp_dir.path());
+        let (second, _) = back_up(&temp_dir, "b.log", &mut log);
+        let (third, _) = back_up(&temp_dir, "c.log", &mut log);
+        let mut summary = Summary::new("undo", temp_dir.path().to_path_buf());
+
+        undo_last_backup(temp_dir.path(), &mut summary).unwrap();
+
+        assert_eq!(fs::read_to_string(&second).unwrap(), "b.log");
+        assert_eq!(fs::read_to_string(&third).unwrap(), "c.log");
+        assert!(!first.exists());
+        assert!(first_backup.exists());
+        assert_eq!(summary.succeeded, 2);
+
+        undo_last_backup(temp_dir.path(), &mut summary).unwrap();
+
+        assert!(first.exists());
+        assert!(!manifest_path(temp_dir.path()).exists());
+    }
+
+    #[test]
+    fn test_undo_refuses_when_original_exists() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut log = UndoLog::new(temp_dir.path());
+        let (first, first_backup) = back_up(&temp_dir, "a.log", &mut log);
+        let (second, _) = back_up(&temp_dir, "b.log", &mut log);
+        fs::write(&second, "new").unwrap();
+        let mut summary = Summary::new("undo", temp_dir.path().to_path_buf());
+
+        let result = undo_last_backup(temp_dir.path(), &mut summary);
+
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::AlreadyExists);
+        assert!(!first.exists());
+        assert!(first_backup.exists());
+        assert_eq!(fs::read_to_string(&second).unwrap(), "new");
+        assert!(manifest_path(temp_dir.path()).exists());
+    }
+
+    #[test]
+    fn test_undo_keeps_the_files_it_could_not_move_back() {
+        let temp_dir = TempDir::new().unwrap();
+        let (first, _) = back_up(&temp_dir, "a.log", &mut UndoLog::new(temp_dir.path()));
+        let mut log = UndoLog::new(temp_dir.path());
+        let (second, _) = back_up(&temp_dir, "b.log", &mut log);
+        // The folder the file came from has been removed since.
+        let original = temp_dir.path().join("gone").join("c.log");
+        let blocked = temp_dir.path().join("bak").join("c.log");
+        fs::write(&blocked, "c.log").unwrap();
+        log.record(&original, &blocked).unwrap();
+        let mut summary = Summary::new("undo", temp_dir.path().to_path_buf());
+
+        undo_last_backup(temp_dir.path(), &mut summary).unwrap();
+
+        assert_eq!(fs::read_to_string(&second).unwrap(), "b.log");
+        assert_eq!(summary.failed, 1);
+        let manifest = fs::read_to_string(manifest_path(temp_dir.path())).unwrap();
+        let (_, _, moves) = split_last_batch(&manifest);
+        assert_eq!(
+            moves,
+            [(absolute(&original).unwrap(), absolute(&blocked).unwrap())]
+        );
+        assert_eq!(manifest.matches(BATCH_PREFIX).count(), 2);
+        assert!(!first.exists());
+    }
+
+    #[test]
+    fn test_undo_without_manifest_does_nothing() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut summary = Summary::new("undo", temp_dir.path().to_path_buf());
+
+        undo_last_backup(temp_dir.path(), &mut summary).unwrap();
+
+        assert_eq!(summary.processed, 0);
+    }
+
+    #[test]
+    fn test_undo_log_without_moves_writes_nothing() {
+        let temp_dir = TempDir::new().unwrap();
+
+        drop(UndoLog::new(temp_dir.path()));
+
+        assert!(!manifest_path(temp_dir.path()).exists());
+    }
+}
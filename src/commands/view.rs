@@ -1,165 +1,1343 @@
-use std::fs;
-use std::io::{self, Write};
-use std::path::Path;
-
-pub fn view_log_files(log_dir: &Path) -> Result<(), io::Error> {
-    let mut log_files = Vec::new();
-
-    for entry in fs::read_dir(log_dir)? {
-        let entry = entry?;
-        let path = entry.path();
-        if path.is_file() {
-            log_files.push(path);
-        }
-    }
-
-    if log_files.is_empty() {
-        println!("No log files found in directory: {}", log_dir.display());
-        return Ok(());
-    }
-
-    println!("Available log files:");
-    for (index, file) in log_files.iter().enumerate() {
-        let file_name = file.file_name().unwrap_or_default();
-        println!("{}. {}", index + 1, file_name.to_string_lossy());
-    }
-
-    print!("Enter the number of the file you want to view: ");
-    io::stdout().flush()?;
-
-    let mut input = String::new();
-    io::stdin().read_line(&mut input)?;
-
-    let choice: usize = match input.trim().parse() {
-        Ok(num) => num,
-        Err(_) => {
-            println!("Invalid input. Please enter a number.");
-            return Ok(());
-        }
-    };
-
-    if choice == 0 || choice > log_files.len() {
-        println!(
-            "Invalid selection. Please choose a number between 1 and {}.",
-            log_files.len()
-        );
-        return Ok(());
-    }
-
-    let selected_file = &log_files[choice - 1];
-    println!(
-        "\nContents of {}:",
-        selected_file
-            .file_name()
-            .unwrap_or_default()
-            .to_string_lossy()
-    );
-    println!("{}", "=".repeat(50));
-
-    match fs::read_to_string(selected_file) {
-        Ok(content) => println!("{content}"),
-        Err(e) => println!("Error reading file: {e}"),
-    }
-
-    Ok(())
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::fs::{self, File};
-    use std::io::Write;
-    use tempfile::TempDir;
-
-    #[test]
-    fn test_view_log_files_empty_directory() {
-        let temp_dir = TempDir::new().unwrap();
-
-        let result = view_log_files(temp_dir.path());
-        assert!(result.is_ok());
-    }
-
-    #[test]
-    fn test_view_log_files_with_files() {
-        let temp_dir = TempDir::new().unwrap();
-
-        let file1 = temp_dir.path().join("test1.log");
-        let file2 = temp_dir.path().join("test2.log");
-
-        let mut f1 = File::create(&file1).unwrap();
-        writeln!(f1, "Log file 1 content").unwrap();
-
-        let mut f2 = File::create(&file2).unwrap();
-        writeln!(f2, "Log file 2 content").unwrap();
-
-        let result = view_log_files(temp_dir.path());
-        assert!(result.is_ok());
-    }
-
-    #[test]
-    fn test_view_log_files_mixed_files_and_directories() {
-        let temp_dir = TempDir::new().unwrap();
-
-        let file1 = temp_dir.path().join("test.log");
-        let mut f1 = File::create(&file1).unwrap();
-        writeln!(f1, "Log file content").unwrap();
-
-        let subdir = temp_dir.path().join("subdir");
-        fs::create_dir(&subdir).unwrap();
-
-        let result = view_log_files(temp_dir.path());
-        assert!(result.is_ok());
-    }
-
-    #[test]
-    fn test_view_log_files_nonexistent_directory() {
-        let temp_dir = TempDir::new().unwrap();
-        let nonexistent = temp_dir.path().join("nonexistent");
-
-        let result = view_log_files(&nonexistent);
-        assert!(result.is_err());
-    }
-
-    #[test]
-    fn test_view_log_files_with_special_characters() {
-        let temp_dir = TempDir::new().unwrap();
-
-        let file1 = temp_dir.path().join("test file with spaces.log");
-        let file2 = temp_dir.path().join("測試檔案.log");
-
-        let mut f1 = File::create(&file1).unwrap();
-        writeln!(f1, "Content with spaces").unwrap();
-
-        let mut f2 = File::create(&file2).unwrap();
-        writeln!(f2, "Unicode content").unwrap();
-
-        let result = view_log_files(temp_dir.path());
-        assert!(result.is_ok());
-    }
-
-    #[test]
-    fn test_view_log_files_unreadable_file() {
-        let temp_dir = TempDir::new().unwrap();
-
-        let file1 = temp_dir.path().join("readable.log");
-        let mut f1 = File::create(&file1).unwrap();
-        writeln!(f1, "Readable content").unwrap();
-
-        let result = view_log_files(temp_dir.path());
-        assert!(result.is_ok());
-    }
-
-    #[test]
-    fn test_view_log_files_large_directory() {
-        let temp_dir = TempDir::new().unwrap();
-
-        for i in 0..100 {
-            let file_path = temp_dir.path().join(format!("test_{i}.log"));
-            let mut file = File::create(&file_path).unwrap();
-            writeln!(file, "Log file {i} content").unwrap();
-        }
-
-        let result = view_log_files(temp_dir.path());
-        assert!(result.is_ok());
-    }
-}
+use crate::encoding::Encoding;
+use crate::filters::has_log_extension;
+use crate::gzip::{is_gz, open_maybe_gz};
+use crate::interrupt;
+use crate::output::{self, debug, sayln};
+use crate::paths::{explain_long_path, to_extended_path};
+use crate::prompt;
+use crate::rotation::{FileIdentity, file_identity, path_identity, rotation_message};
+use crate::scan::{LogFile, collect_log_files};
+use std::cmp::Reverse;
+use std::collections::{HashSet, VecDeque};
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, IsTerminal, Read, Seek, SeekFrom, Write};
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::process::{self, Child, Stdio};
+use std::thread;
+use std::time::Duration;
+
+const TAIL_CHUNK_SIZE: u64 = 8 * 1024;
+const DEFAULT_PAGE_LINES: usize = 24;
+/// How often a followed file is checked for new lines.
+const FOLLOW_INTERVAL: Duration = Duration::from_millis(500);
+
+/// How the contents of a file are paged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Paging {
+    /// Print everything at once
+    Off,
+    /// Pause after every screenful and wait for Enter
+    Prompt,
+    /// Pipe everything to the system pager
+    Pager,
+}
+
+/// A file picked on the command line instead of at the prompt.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Selection {
+    /// 1-based position in the listing
+    Number(usize),
+    /// Exact file name
+    Name(String),
+    /// The most recently modified file
+    Newest,
+    /// The least recently modified file
+    Oldest,
+}
+
+pub fn view_log_files(
+    log_dir: &Path,
+    selection: Option<&Selection>,
+    head: Option<usize>,
+    tail: Option<usize>,
+    paging: Paging,
+    follow: bool,
+    encoding: Encoding,
+) -> Result<(), io::Error> {
+    let paths = choose_files(log_dir, selection, "view")?;
+    let followed = paths.last().filter(|path| follow && !is_gz(path));
+    let shown = &paths[..paths.len() - usize::from(followed.is_some())];
+
+    let paged = paging == Paging::Pager && show_in_pager(shown, head, tail, encoding)?;
+    if !paged {
+        for path in shown {
+            show_file(path, head, tail, paging == Paging::Prompt, encoding);
+        }
+    }
+    if let Some(path) = followed {
+        let start = show_followed(path, tail, encoding)?;
+        follow_file(path, start, encoding)?;
+    } else if follow && !paths.is_empty() {
+        println!("\nA compressed log cannot be followed.");
+    }
+
+    Ok(())
+}
+
+/// Copies the picked files to `dest` instead of printing them: into it when
+/// it is a directory, else to that path, which then takes a single file.
+/// An existing file is only replaced after confirming, or with `overwrite`.
+pub fn save_log_files(
+    log_dir: &Path,
+    selection: Option<&Selection>,
+    dest: &Path,
+    overwrite: bool,
+) -> io::Result<()> {
+    let paths = choose_files(log_dir, selection, "save")?;
+    if paths.len() > 1 && !dest.is_dir() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "{} is not a directory; save several files into an existing directory",
+                dest.display()
+            ),
+        ));
+    }
+    for path in &paths {
+        save_file(path, dest, overwrite)?;
+    }
+    Ok(())
+}
+
+/// The files `selection` refers to, or the ones picked at the prompt, which
+/// asks which files to `action`. Empty when there is nothing to pick from.
+fn choose_files(
+    log_dir: &Path,
+    selection: Option<&Selection>,
+    action: &str,
+) -> io::Result<Vec<PathBuf>> {
+    let mut log_files = collect_log_files(log_dir)?;
+    log_files.sort_by(|a, b| a.path.cmp(&b.path));
+
+    if log_files.is_empty() {
+        output::emit(format_args!(
+            "No log files found in directory: {}",
+            log_dir.display()
+        ));
+        return Ok(Vec::new());
+    }
+
+    let choices = match selection {
+        Some(selection) => vec![find_selection(&log_files, selection).ok_or_else(|| {
+            let message = match selection {
+                Selection::Number(number) => format!(
+                    "no log file numbered {number}; choose between 1 and {}",
+                    log_files.len()
+                ),
+                Selection::Name(name) => format!("no log file named '{name}'"),
+                Selection::Newest | Selection::Oldest => {
+                    "no log file has a modification time".to_string()
+                }
+            };
+            io::Error::new(io::ErrorKind::NotFound, message)
+        })?],
+        None => {
+            output::emit("Available log files:");
+            for (index, file) in log_files.iter().enumerate() {
+                let file_name = file.path.file_name().unwrap_or_default();
+                output::emit(format_args!(
+                    "{}. {}",
+                    index + 1,
+                    file_name.to_string_lossy()
+                ));
+            }
+            if !io::stdin().is_terminal() {
+                println!("\nUse --select <N> or --file <NAME> to pick a file without the prompt.");
+                return Ok(Vec::new());
+            }
+
+            print!("Enter the numbers of the files you want to {action} (e.g. 1,3 or 2-4): ");
+            io::stdout().flush()?;
+
+            let mut input = String::new();
+            io::stdin().read_line(&mut input)?;
+
+            let Some((choices, out_of_range)) = parse_selection(&input, log_files.len()) else {
+                println!(
+                    "Invalid input. Please enter numbers between 1 and {} such as 1,3 or 2-4.",
+                    log_files.len()
+                );
+                return Ok(Vec::new());
+            };
+
+            for choice in out_of_range {
+                println!(
+                    "Skipping {choice}: choose a number between 1 and {}.",
+                    log_files.len()
+                );
+            }
+            choices
+        }
+    };
+
+    Ok(choices
+        .into_iter()
+        .map(|choice| log_files[choice - 1].path.clone())
+        .collect())
+}
+
+/// Copies `path` as it is, compressed or not, into the directory `dest` or
+/// to the file `dest`.
+fn save_file(path: &Path, dest: &Path, overwrite: bool) -> io::Result<()> {
+    let file_name = path.file_name().unwrap_or_default();
+    let target = if dest.is_dir() {
+        dest.join(file_name)
+    } else {
+        dest.to_path_buf()
+    };
+    if target.exists() {
+        if target.canonicalize()? == path.canonicalize()? {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("cannot save {} onto itself", path.display()),
+            ));
+        }
+        if !overwrite && !prompt::confirm(&format!("{} exists. Overwrite?", target.display()))? {
+            sayln!(
+                "Skipped {}: {} exists",
+                file_name.to_string_lossy(),
+                target.display()
+            );
+            return Ok(());
+        }
+    }
+    fs::copy(to_extended_path(path), to_extended_path(&target))
+        .map_err(|err| explain_long_path(err, &target))?;
+    sayln!(
+        "Saved {} to {}",
+        file_name.to_string_lossy(),
+        target.display()
+    );
+    Ok(())
+}
+
+/// Shows `path` like [`show_file`] and returns where following it starts:
+/// its length, taken before reading, so that nothing appended while it is
+/// shown is lost or shown twice.
+fn show_followed(path: &Path, tail: Option<usize>, encoding: Encoding) -> io::Result<Followed> {
+    print!("{}", header(path, None, tail));
+    let mut file =
+        File::open(to_extended_path(path)).map_err(|err| explain_long_path(err, path))?;
+    let end = file.metadata()?.len();
+    let mut stdout = io::stdout().lock();
+    match tail {
+        Some(lines) => {
+            let mut content = read_tail_to(&mut file, lines, end)?;
+            content.push(b'\n');
+            copy_decoded(&mut content.as_slice(), &mut stdout, encoding)?;
+        }
+        None => copy_decoded(
+            &mut BufReader::new((&mut file).take(end)),
+            &mut stdout,
+            encoding,
+        )?,
+    }
+    stdout.flush()?;
+    Ok(Followed {
+        offset: end,
+        identity: file_identity(&file),
+        pending: Vec::new(),
+    })
+}
+
+/// Prints whatever is appended to `path` until Ctrl-C, like `tail -f`. When
+/// ACT rotates to a new log in the same directory, that one is followed
+/// from its start instead.
+fn follow_file(path: &Path, mut followed: Followed, encoding: Encoding) -> io::Result<()> {
+    interrupt::install();
+    let mut path = path.to_path_buf();
+    let dir = path.parent().unwrap_or(Path::new(".")).to_path_buf();
+    let mut known = HashSet::new();
+    new_log(&dir, &mut known, None);
+    println!("\n(following {}, Ctrl-C to stop)", path.display());
+    let mut stdout = io::stdout();
+    while !interrupt::is_interrupted() {
+        thread::sleep(FOLLOW_INTERVAL);
+        followed = print_appended(&path, followed, encoding, &mut stdout)?;
+        if let Some(next) = new_log(&dir, &mut known, followed.identity) {
+            followed.finish(encoding, &mut stdout)?;
+            output::emit_err(rotation_message(&next));
+            followed = Followed::default();
+            path = next;
+        }
+    }
+    followed.finish(encoding, &mut stdout)?;
+    println!("\nStopped following.");
+    Ok(())
+}
+
+/// The newest log that appeared in `dir` since the names in `known` were
+/// seen, remembering every new name. A file with the `current` identity is
+/// the followed one under a new name, not a new log.
+fn new_log(
+    dir: &Path,
+    known: &mut HashSet<PathBuf>,
+    current: Option<FileIdentity>,
+) -> Option<PathBuf> {
+    let files = match collect_log_files(dir) {
+        Ok(files) => files,
+        Err(err) => {
+            debug!("Cannot check {} for a new log: {err}", dir.display());
+            return None;
+        }
+    };
+    files
+        .into_iter()
+        .filter(|file| has_log_extension(&file.path) && known.insert(file.path.clone()))
+        .filter(|file| current.is_none() || path_identity(&file.path) != current)
+        .max_by_key(|file| file.modified)
+        .map(|file| file.path)
+}
+
+/// How much of a followed file has been printed, and which file that was.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct Followed {
+    offset: u64,
+    identity: Option<FileIdentity>,
+    /// The start of a line still being written, printed once it is whole
+    /// so that a character split between two polls is decoded in one piece.
+    pending: Vec<u8>,
+}
+
+impl Followed {
+    /// Prints the unfinished line the followed file ended on, as the file
+    /// is being left.
+    fn finish(&mut self, encoding: Encoding, out: &mut impl Write) -> io::Result<()> {
+        if !self.pending.is_empty() {
+            writeln!(out, "{}", encoding.decode(&self.pending))?;
+            self.pending.clear();
+        }
+        Ok(())
+    }
+}
+
+/// Writes the lines added to `path` since `followed` to `out` and returns
+/// how far it has now been read. A file that shrank was truncated, and one
+/// with a new identity was rotated, so either is followed again from its
+/// start. While the name is missing, mid-rotation, nothing changes.
+fn print_appended(
+    path: &Path,
+    mut followed: Followed,
+    encoding: Encoding,
+    out: &mut impl Write,
+) -> io::Result<Followed> {
+    let mut file = match File::open(to_extended_path(path)) {
+        Ok(file) => file,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(followed),
+        Err(err) => return Err(explain_long_path(err, path)),
+    };
+    let (len, identity) = (file.metadata()?.len(), file_identity(&file));
+    let restart = if followed.identity.is_some() && identity != followed.identity {
+        Some(rotation_message(path))
+    } else if len < followed.offset {
+        Some(format!(
+            "{} was truncated, following from the start",
+            path.display()
+        ))
+    } else {
+        None
+    };
+    if let Some(message) = restart {
+        followed.finish(encoding, out)?;
+        output::emit_err(message);
+        followed.offset = 0;
+    }
+    if len > followed.offset {
+        file.seek(SeekFrom::Start(followed.offset))?;
+        file.take(len - followed.offset)
+            .read_to_end(&mut followed.pending)?;
+        if let Some(end) = followed.pending.iter().rposition(|&byte| byte == b'\n') {
+            let rest = followed.pending.split_off(end + 1);
+            out.write_all(encoding.decode(&followed.pending).as_bytes())?;
+            out.flush()?;
+            followed.pending = rest;
+        }
+    }
+    followed.offset = len;
+    followed.identity = identity;
+    Ok(followed)
+}
+
+/// Parses a prompt answer such as `1,3` or `2-4` into 1-based positions in
+/// the order given, along with the parts outside the `count` files listed,
+/// such as `12` or `8-10`, to report. Ranges include both ends and are cut
+/// to the files listed; `None` if any part is not a number or a range runs
+/// backwards.
+fn parse_selection(input: &str, count: usize) -> Option<(Vec<usize>, Vec<String>)> {
+    let mut choices = Vec::new();
+    let mut out_of_range = Vec::new();
+    for part in input.trim().split(',') {
+        let (start, end): (usize, usize) = match part.split_once('-') {
+            Some((start, end)) => (start.trim().parse().ok()?, end.trim().parse().ok()?),
+            None => {
+                let choice = part.trim().parse().ok()?;
+                (choice, choice)
+            }
+        };
+        if start > end {
+            return None;
+        }
+        if start == 0 {
+            out_of_range.push("0".to_string());
+        }
+        choices.extend(start.max(1)..=end.min(count));
+        if end > count {
+            let first = start.max(count + 1);
+            out_of_range.push(if first == end {
+                end.to_string()
+            } else {
+                format!("{first}-{end}")
+            });
+        }
+    }
+    Some((choices, out_of_range))
+}
+
+/// Prints the header for `path` followed by its contents.
+fn show_file(
+    path: &Path,
+    head: Option<usize>,
+    tail: Option<usize>,
+    page: bool,
+    encoding: Encoding,
+) {
+    print!("{}", header(path, head, tail));
+
+    if let Err(e) = print_file(path, head, tail, page, encoding) {
+        println!("\nError reading file: {e}");
+    }
+}
+
+/// The lines printed above the contents of `path`.
+fn header(path: &Path, head: Option<usize>, tail: Option<usize>) -> String {
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    let title = match (head, tail) {
+        (Some(lines), _) => format!("First {lines} lines of {file_name}:"),
+        (_, Some(lines)) => format!("Last {lines} lines of {file_name}:"),
+        (None, None) => format!("Contents of {file_name}:"),
+    };
+    format!("\n{title}\n{}\n", "=".repeat(50))
+}
+
+/// Writes the header and contents of each of `paths` to the system pager.
+/// Returns `false` without showing anything when stdout is not a terminal
+/// or no pager can be started, so the caller prints directly instead.
+fn show_in_pager(
+    paths: &[PathBuf],
+    head: Option<usize>,
+    tail: Option<usize>,
+    encoding: Encoding,
+) -> io::Result<bool> {
+    if !io::stdout().is_terminal() {
+        return Ok(false);
+    }
+    let Some(mut pager) = spawn_pager() else {
+        return Ok(false);
+    };
+    if let Some(mut stdin) = pager.stdin.take() {
+        for path in paths {
+            match write_file(path, head, tail, encoding, &mut stdin) {
+                // The pager was closed before reaching the end.
+                Err(err) if err.kind() == io::ErrorKind::BrokenPipe => break,
+                result => result?,
+            }
+        }
+    }
+    pager.wait()?;
+    Ok(true)
+}
+
+/// Starts `$PAGER`, else `less`, else `more`, reading from a pipe.
+fn spawn_pager() -> Option<Child> {
+    let configured = env::var("PAGER")
+        .ok()
+        .filter(|pager| !pager.trim().is_empty());
+    let candidates = configured
+        .into_iter()
+        .chain(["less".to_string(), "more".to_string()]);
+    for candidate in candidates {
+        // $PAGER may carry arguments, as in `less -R`.
+        let mut words = candidate.split_whitespace();
+        let Some(program) = words.next() else {
+            continue;
+        };
+        match process::Command::new(program)
+            .args(words)
+            .stdin(Stdio::piped())
+            .spawn()
+        {
+            Ok(pager) => return Some(pager),
+            Err(err) => debug!("Cannot start pager {program}: {err}"),
+        }
+    }
+    None
+}
+
+/// Writes the header for `path` followed by its contents to `out`. A file
+/// that cannot be read gets an error line instead of its contents.
+fn write_file(
+    path: &Path,
+    head: Option<usize>,
+    tail: Option<usize>,
+    encoding: Encoding,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    out.write_all(header(path, head, tail).as_bytes())?;
+    match open_selection(path, head, tail) {
+        Ok(mut reader) => copy_decoded(&mut reader, out, encoding)?,
+        Err(e) => writeln!(out, "\nError reading file: {e}")?,
+    }
+    Ok(())
+}
+
+/// The 1-based position of the file `selection` refers to, if any.
+fn find_selection(log_files: &[LogFile], selection: &Selection) -> Option<usize> {
+    let by_modified = || {
+        log_files
+            .iter()
+            .enumerate()
+            .filter_map(|(index, file)| Some((file.modified?, index + 1)))
+    };
+    match selection {
+        Selection::Number(number) => (1..=log_files.len()).contains(number).then_some(*number),
+        Selection::Name(name) => log_files
+            .iter()
+            .position(|file| {
+                file.path
+                    .file_name()
+                    .is_some_and(|file_name| file_name == name.as_str())
+            })
+            .map(|index| index + 1),
+        // Ties go to the file listed first.
+        Selection::Newest => by_modified()
+            .min_by_key(|&(modified, index)| (Reverse(modified), index))
+            .map(|(_, number)| number),
+        Selection::Oldest => by_modified().min().map(|(_, number)| number),
+    }
+}
+
+/// Copies the file to stdout a line at a time so memory stays bounded no
+/// matter how large the log is.
+fn print_file(
+    path: &Path,
+    head: Option<usize>,
+    tail: Option<usize>,
+    page: bool,
+    encoding: Encoding,
+) -> io::Result<()> {
+    let mut reader = open_selection(path, head, tail)?;
+
+    let mut stdout = io::stdout().lock();
+    if page {
+        page_to(
+            &mut reader,
+            &mut stdout,
+            &mut io::stdin().lock(),
+            page_lines(),
+            encoding,
+        )?;
+    } else {
+        copy_decoded(&mut reader, &mut stdout, encoding)?;
+    }
+    stdout.flush()
+}
+
+/// The part of the file `head` or `tail` asks for, or all of it.
+fn open_selection(
+    path: &Path,
+    head: Option<usize>,
+    tail: Option<usize>,
+) -> io::Result<Box<dyn BufRead>> {
+    Ok(match (head, tail) {
+        (Some(lines), _) => {
+            let content = read_head(&mut open_maybe_gz(path)?, lines)?;
+            Box::new(io::Cursor::new(content))
+        }
+        (_, Some(lines)) => {
+            let mut content = if is_gz(path) {
+                read_tail_streaming(&mut open_maybe_gz(path)?, lines)?
+            } else {
+                let mut file = File::open(to_extended_path(path))
+                    .map_err(|err| explain_long_path(err, path))?;
+                read_tail(&mut file, lines)?
+            };
+            content.push(b'\n');
+            Box::new(io::Cursor::new(content))
+        }
+        (None, None) => open_maybe_gz(path)?,
+    })
+}
+
+/// Writes each line of `reader` to `out` as UTF-8, decoded from `encoding`.
+fn copy_decoded(
+    reader: &mut impl BufRead,
+    out: &mut impl Write,
+    encoding: Encoding,
+) -> io::Result<()> {
+    let mut line = Vec::new();
+    while reader.read_until(b'\n', &mut line)? > 0 {
+        out.write_all(encoding.decode(&line).as_bytes())?;
+        line.clear();
+    }
+    Ok(())
+}
+
+fn page_lines() -> usize {
+    env::var("LINES")
+        .ok()
+        .and_then(|lines| lines.parse::<usize>().ok())
+        .filter(|&lines| lines > 1)
+        .map_or(DEFAULT_PAGE_LINES, |lines| lines - 1)
+}
+
+/// Writes `reader` to `out` a screenful at a time, waiting for Enter between
+/// pages. Entering 'q' stops early; once `input` is exhausted the remaining
+/// lines are written without pausing.
+fn page_to(
+    reader: &mut impl BufRead,
+    out: &mut impl Write,
+    input: &mut impl BufRead,
+    page_lines: usize,
+    encoding: Encoding,
+) -> io::Result<()> {
+    let mut line = Vec::new();
+    let mut shown = 0;
+    let mut paging = true;
+    loop {
+        line.clear();
+        if reader.read_until(b'\n', &mut line)? == 0 {
+            return Ok(());
+        }
+        if paging && shown == page_lines {
+            write!(out, "-- More -- (Enter to continue, q to quit) ")?;
+            out.flush()?;
+            let mut answer = String::new();
+            if input.read_line(&mut answer)? == 0 {
+                paging = false;
+            } else if answer.trim().eq_ignore_ascii_case("q") {
+                return Ok(());
+            }
+            shown = 0;
+        }
+        out.write_all(encoding.decode(&line).as_bytes())?;
+        shown += 1;
+    }
+}
+
+/// Returns the first `lines` lines of `reader`, stopping as soon as they
+/// have been read.
+pub fn read_head(reader: &mut impl BufRead, lines: usize) -> io::Result<Vec<u8>> {
+    let mut head = Vec::new();
+    for _ in 0..lines {
+        if reader.read_until(b'\n', &mut head)? == 0 {
+            break;
+        }
+    }
+    Ok(head)
+}
+
+/// Returns the last `lines` lines of `reader`, reading backwards from the end
+/// in fixed-size chunks so only the tail of a large log is loaded.
+fn read_tail<R: Read + Seek>(reader: &mut R, lines: usize) -> io::Result<Vec<u8>> {
+    let end = reader.seek(SeekFrom::End(0))?;
+    read_tail_to(reader, lines, end)
+}
+
+/// Like [`read_tail`], for the first `end` bytes of `reader` only.
+fn read_tail_to<R: Read + Seek>(reader: &mut R, lines: usize, end: u64) -> io::Result<Vec<u8>> {
+    if lines == 0 {
+        return Ok(Vec::new());
+    }
+
+    let mut start = end;
+    let mut tail = Vec::new();
+    let mut newlines = 0;
+    // One extra newline is needed because the file usually ends with one.
+    while start > 0 && newlines <= lines {
+        let size = TAIL_CHUNK_SIZE.min(start);
+        start -= size;
+        reader.seek(SeekFrom::Start(start))?;
+        let mut chunk = vec![0; size as usize];
+        reader.read_exact(&mut chunk)?;
+        newlines += chunk.iter().filter(|&&byte| byte == b'\n').count();
+        chunk.append(&mut tail);
+        tail = chunk;
+    }
+
+    let body = tail.strip_suffix(b"\n").unwrap_or(&tail);
+    let cut = body
+        .iter()
+        .enumerate()
+        .rev()
+        .filter(|(_, byte)| **byte == b'\n')
+        .nth(lines - 1)
+        .map_or(0, |(index, _)| index + 1);
+    Ok(body[cut..].to_vec())
+}
+
+/// Returns the last `lines` lines of a reader that cannot seek, such as a
+/// compressed log, keeping only those lines in memory while reading it all.
+fn read_tail_streaming(reader: &mut impl BufRead, lines: usize) -> io::Result<Vec<u8>> {
+    let mut tail = VecDeque::with_capacity(lines + 1);
+    let mut line = Vec::new();
+    while reader.read_until(b'\n', &mut line)? > 0 {
+        tail.push_back(mem::take(&mut line));
+        if tail.len() > lines {
+            tail.pop_front();
+        }
+    }
+    let tail: Vec<u8> = tail.into_iter().flatten().collect();
+    let body = tail.strip_suffix(b"\n").unwrap_or(&tail);
+    Ok(body.to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{self, File};
+    use std::io::Write;
+    use std::path::PathBuf;
+    use std::time::UNIX_EPOCH;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_selection_lists_and_ranges() {
+        let none = Vec::<String>::new();
+        assert_eq!(parse_selection("2\n", 9), Some((vec![2], none.clone())));
+        assert_eq!(parse_selection("1,3", 9), Some((vec![1, 3], none.clone())));
+        assert_eq!(
+            parse_selection("2-4", 9),
+            Some((vec![2, 3, 4], none.clone()))
+        );
+        assert_eq!(
+            parse_selection(" 5, 1 - 2 ,9", 9),
+            Some((vec![5, 1, 2, 9], none))
+        );
+    }
+
+    #[test]
+    fn test_parse_selection_cuts_ranges_to_the_files_listed() {
+        assert_eq!(
+            parse_selection("8-12", 9),
+            Some((vec![8, 9], vec!["10-12".to_string()]))
+        );
+        assert_eq!(
+            parse_selection("0-2,10,4", 9),
+            Some((vec![1, 2, 4], vec!["0".to_string(), "10".to_string()]))
+        );
+        assert_eq!(
+            parse_selection("9-10", 9),
+            Some((vec![9], vec!["10".to_string()]))
+        );
+        assert_eq!(
+            parse_selection("1-18446744073709551615", 2),
+            Some((vec![1, 2], vec!["3-18446744073709551615".to_string()]))
+        );
+    }
+
+    #[test]
+    fn test_parse_selection_rejects_malformed_input() {
+        assert_eq!(parse_selection("", 9), None);
+        assert_eq!(parse_selection("1,", 9), None);
+        assert_eq!(parse_selection("a", 9), None);
+        assert_eq!(parse_selection("4-2", 9), None);
+        assert_eq!(parse_selection("1-2-3", 9), None);
+    }
+
+    #[test]
+    fn test_view_log_files_empty_directory() {
+        let temp_dir = TempDir::new().unwrap();
+
+        let result = view_log_files(
+            temp_dir.path(),
+            None,
+            None,
+            None,
+            Paging::Off,
+            false,
+            Encoding::Utf8,
+        );
+        assert!(result.is_ok());
+        let newest = Selection::Newest;
+        let result = view_log_files(
+            temp_dir.path(),
+            Some(&newest),
+            None,
+            None,
+            Paging::Off,
+            false,
+            Encoding::Utf8,
+        );
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_view_log_files_with_files() {
+        let temp_dir = TempDir::new().unwrap();
+
+        let file1 = temp_dir.path().join("test1.log");
+        let file2 = temp_dir.path().join("test2.log");
+
+        let mut f1 = File::create(&file1).unwrap();
+        writeln!(f1, "Log file 1 content").unwrap();
+
+        let mut f2 = File::create(&file2).unwrap();
+        writeln!(f2, "Log file 2 content").unwrap();
+
+        let result = view_log_files(
+            temp_dir.path(),
+            None,
+            None,
+            None,
+            Paging::Off,
+            false,
+            Encoding::Utf8,
+        );
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_view_log_files_mixed_files_and_directories() {
+        let temp_dir = TempDir::new().unwrap();
+
+        let file1 = temp_dir.path().join("test.log");
+        let mut f1 = File::create(&file1).unwrap();
+        writeln!(f1, "Log file content").unwrap();
+
+        let subdir = temp_dir.path().join("subdir");
+        fs::create_dir(&subdir).unwrap();
+
+        let result = view_log_files(
+            temp_dir.path(),
+            None,
+            None,
+            None,
+            Paging::Off,
+            false,
+            Encoding::Utf8,
+        );
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_view_log_files_nonexistent_directory() {
+        let temp_dir = TempDir::new().unwrap();
+        let nonexistent = temp_dir.path().join("nonexistent");
+
+        let result = view_log_files(
+            &nonexistent,
+            None,
+            None,
+            None,
+            Paging::Off,
+            false,
+            Encoding::Utf8,
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_view_log_files_with_special_characters() {
+        let temp_dir = TempDir::new().unwrap();
+
+        let file1 = temp_dir.path().join("test file with spaces.log");
+        let file2 = temp_dir.path().join("測試檔案.log");
+
+        let mut f1 = File::create(&file1).unwrap();
+        writeln!(f1, "Content with spaces").unwrap();
+
+        let mut f2 = File::create(&file2).unwrap();
+        writeln!(f2, "Unicode content").unwrap();
+
+        let result = view_log_files(
+            temp_dir.path(),
+            None,
+            None,
+            None,
+            Paging::Off,
+            false,
+            Encoding::Utf8,
+        );
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_view_log_files_unreadable_file() {
+        let temp_dir = TempDir::new().unwrap();
+
+        let file1 = temp_dir.path().join("readable.log");
+        let mut f1 = File::create(&file1).unwrap();
+        writeln!(f1, "Readable content").unwrap();
+
+        let result = view_log_files(
+            temp_dir.path(),
+            None,
+            None,
+            None,
+            Paging::Off,
+            false,
+            Encoding::Utf8,
+        );
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_view_log_files_large_directory() {
+        let temp_dir = TempDir::new().unwrap();
+
+        for i in 0..100 {
+            let file_path = temp_dir.path().join(format!("test_{i}.log"));
+            let mut file = File::create(&file_path).unwrap();
+            writeln!(file, "Log file {i} content").unwrap();
+        }
+
+        let result = view_log_files(
+            temp_dir.path(),
+            None,
+            None,
+            None,
+            Paging::Off,
+            false,
+            Encoding::Utf8,
+        );
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_view_log_files_with_selection() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("a.log"), "first\n").unwrap();
+        fs::write(temp_dir.path().join("b.log"), "second\n").unwrap();
+
+        let by_number = Selection::Number(2);
+        let by_name = Selection::Name("a.log".to_string());
+        let missing = Selection::Number(3);
+
+        assert!(
+            view_log_files(
+                temp_dir.path(),
+                Some(&by_number),
+                None,
+                None,
+                Paging::Off,
+                false,
+                Encoding::Utf8
+            )
+            .is_ok()
+        );
+        assert!(
+            view_log_files(
+                temp_dir.path(),
+                Some(&by_name),
+                None,
+                None,
+                Paging::Off,
+                false,
+                Encoding::Utf8
+            )
+            .is_ok()
+        );
+        let err = view_log_files(
+            temp_dir.path(),
+            Some(&missing),
+            None,
+            None,
+            Paging::Off,
+            false,
+            Encoding::Utf8,
+        )
+        .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn test_write_file_writes_header_and_selected_lines() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("a.log");
+        fs::write(&path, "one\ntwo\nthree\n").unwrap();
+        let mut out = Vec::new();
+
+        write_file(&path, None, Some(2), Encoding::Utf8, &mut out).unwrap();
+        write_file(
+            &temp_dir.path().join("gone.log"),
+            None,
+            None,
+            Encoding::Utf8,
+            &mut out,
+        )
+        .unwrap();
+
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.starts_with("\nLast 2 lines of a.log:\n====="));
+        assert!(out.contains("=\ntwo\nthree\n\nContents of gone.log:"));
+        assert!(out.contains("=\n\nError reading file: "));
+    }
+
+    #[test]
+    fn test_write_file_decodes_shift_jis() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("a.log");
+        fs::write(
+            &path,
+            b"00|\x93\xfa\x96\x7b\n00|\x83\x60\x83\x83\x83\x62\x83\x67\n",
+        )
+        .unwrap();
+        let mut shift_jis = Vec::new();
+        let mut utf8 = Vec::new();
+
+        write_file(&path, None, Some(1), Encoding::ShiftJis, &mut shift_jis).unwrap();
+        write_file(&path, None, None, Encoding::Utf8, &mut utf8).unwrap();
+
+        assert!(
+            String::from_utf8(shift_jis)
+                .unwrap()
+                .ends_with("=\n00|チャット\n")
+        );
+        let utf8 = String::from_utf8(utf8).unwrap();
+        assert!(utf8.contains("\n00|\u{FFFD}\u{FFFD}\u{FFFD}"));
+        assert_eq!(utf8.lines().count(), 5);
+    }
+
+    #[test]
+    fn test_save_log_files_into_directory_and_to_file() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("a.log"), "first\n").unwrap();
+        let out_dir = TempDir::new().unwrap();
+        let by_name = Selection::Name("a.log".to_string());
+
+        save_log_files(temp_dir.path(), Some(&by_name), out_dir.path(), false).unwrap();
+        let renamed = out_dir.path().join("pull.log");
+        save_log_files(temp_dir.path(), Some(&by_name), &renamed, false).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(out_dir.path().join("a.log")).unwrap(),
+            "first\n"
+        );
+        assert_eq!(fs::read_to_string(renamed).unwrap(), "first\n");
+        assert!(temp_dir.path().join("a.log").exists());
+    }
+
+    #[test]
+    fn test_save_log_files_overwrites_with_yes() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("a.log"), "new\n").unwrap();
+        let out_dir = TempDir::new().unwrap();
+        fs::write(out_dir.path().join("a.log"), "old\n").unwrap();
+
+        save_log_files(
+            temp_dir.path(),
+            Some(&Selection::Newest),
+            out_dir.path(),
+            true,
+        )
+        .unwrap();
+
+        assert_eq!(
+            fs::read_to_string(out_dir.path().join("a.log")).unwrap(),
+            "new\n"
+        );
+    }
+
+    #[test]
+    fn test_save_log_files_refuses_to_save_onto_itself() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("a.log"), "first\n").unwrap();
+
+        let err = save_log_files(
+            temp_dir.path(),
+            Some(&Selection::Number(1)),
+            temp_dir.path(),
+            true,
+        )
+        .unwrap_err();
+
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(
+            fs::read_to_string(temp_dir.path().join("a.log")).unwrap(),
+            "first\n"
+        );
+    }
+
+    #[test]
+    fn test_find_selection() {
+        let files = [log_file("/logs/a.log", None), log_file("/logs/b.log", None)];
+
+        assert_eq!(find_selection(&files, &Selection::Number(1)), Some(1));
+        assert_eq!(find_selection(&files, &Selection::Number(2)), Some(2));
+        assert_eq!(find_selection(&files, &Selection::Number(0)), None);
+        assert_eq!(find_selection(&files, &Selection::Number(3)), None);
+        let name = Selection::Name("b.log".to_string());
+        assert_eq!(find_selection(&files, &name), Some(2));
+        let missing = Selection::Name("c.log".to_string());
+        assert_eq!(find_selection(&files, &missing), None);
+    }
+
+    fn log_file(path: &str, modified: Option<u64>) -> LogFile {
+        LogFile {
+            path: PathBuf::from(path),
+            size: 0,
+            modified: modified.map(|secs| UNIX_EPOCH + Duration::from_secs(secs)),
+        }
+    }
+
+    #[test]
+    fn test_find_selection_newest_and_oldest() {
+        let files = [
+            log_file("/logs/a.log", Some(200)),
+            log_file("/logs/b.log", Some(100)),
+            log_file("/logs/c.log", None),
+            log_file("/logs/d.log", Some(300)),
+            log_file("/logs/e.log", Some(300)),
+        ];
+
+        assert_eq!(find_selection(&files, &Selection::Newest), Some(4));
+        assert_eq!(find_selection(&files, &Selection::Oldest), Some(2));
+        assert_eq!(find_selection(&[], &Selection::Newest), None);
+    }
+
+    fn tail_of(content: &str, lines: usize) -> String {
+        String::from_utf8(read_tail(&mut io::Cursor::new(content.as_bytes()), lines).unwrap())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_print_appended_streams_new_lines_and_restarts_after_truncation() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("Network_1.log");
+        fs::write(&path, "first\n").unwrap();
+        let mut out = Vec::new();
+
+        let start = Followed {
+            offset: 6,
+            ..Followed::default()
+        };
+        let followed = print_appended(&path, start, Encoding::Utf8, &mut out).unwrap();
+        assert_eq!((followed.offset, out.as_slice()), (6, &b""[..]));
+
+        let mut file = fs::OpenOptions::new().append(true).open(&path).unwrap();
+        write!(file, "second\nthi").unwrap();
+        let followed = print_appended(&path, followed, Encoding::Utf8, &mut out).unwrap();
+        assert_eq!((followed.offset, out.as_slice()), (16, &b"second\n"[..]));
+
+        // The unfinished line is shown before the file starts over.
+        fs::write(&path, "new\n").unwrap();
+        out.clear();
+        let followed = print_appended(&path, followed, Encoding::Utf8, &mut out).unwrap();
+        assert_eq!((followed.offset, out.as_slice()), (4, &b"thi\nnew\n"[..]));
+    }
+
+    #[test]
+    fn test_print_appended_keeps_a_character_split_between_polls() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("Network_1.log");
+        let line = "21|ポリス\n".as_bytes();
+        let (before, after) = line.split_at(5);
+        fs::write(&path, before).unwrap();
+        let mut out = Vec::new();
+
+        let followed =
+            print_appended(&path, Followed::default(), Encoding::Utf8, &mut out).unwrap();
+        assert!(out.is_empty());
+
+        let mut file = fs::OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(after).unwrap();
+        let followed = print_appended(&path, followed, Encoding::Utf8, &mut out).unwrap();
+
+        assert_eq!(String::from_utf8(out).unwrap(), "21|ポリス\n");
+        assert!(followed.pending.is_empty());
+    }
+
+    #[test]
+    fn test_print_appended_follows_a_rotated_file_from_its_start() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("Network_1.log");
+        fs::write(&path, "old\n").unwrap();
+        let mut out = Vec::new();
+        let start = Followed {
+            offset: 4,
+            identity: path_identity(&path),
+            pending: Vec::new(),
+        };
+
+        // Rotated away: the name is missing for a moment.
+        fs::rename(&path, temp_dir.path().join("Network_1.log.1")).unwrap();
+        let followed = print_appended(&path, start.clone(), Encoding::Utf8, &mut out).unwrap();
+        assert_eq!((&followed, out.as_slice()), (&start, &b""[..]));
+
+        // A new file longer than the old one, so its size alone gives no hint.
+        fs::write(&path, "rotated line\n").unwrap();
+        let followed = print_appended(&path, followed, Encoding::Utf8, &mut out).unwrap();
+        assert_eq!(
+            (followed.offset, out.as_slice()),
+            (13, &b"rotated line\n"[..])
+        );
+    }
+
+    #[test]
+    fn test_new_log_finds_a_log_started_next_to_the_followed_one() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("Network_1.log");
+        fs::write(&path, "old\n").unwrap();
+        let identity = path_identity(&path);
+        let mut known = HashSet::new();
+        assert_eq!(new_log(temp_dir.path(), &mut known, identity), None);
+
+        // Neither another kind of file nor the followed log under a new name
+        // is a rotation.
+        fs::write(temp_dir.path().join("notes.txt"), "").unwrap();
+        let renamed = temp_dir.path().join("Network_1_old.log");
+        fs::rename(&path, &renamed).unwrap();
+        if identity.is_some() {
+            assert_eq!(new_log(temp_dir.path(), &mut known, identity), None);
+        }
+
+        let next = temp_dir.path().join("Network_2.log");
+        fs::write(&next, "new\n").unwrap();
+        assert_eq!(new_log(temp_dir.path(), &mut known, identity), Some(next));
+        assert_eq!(new_log(temp_dir.path(), &mut known, identity), None);
+    }
+
+    #[test]
+    fn test_read_tail_streaming_matches_read_tail() {
+        for content in ["one\ntwo\nthree\nfour\n", "one\ntwo\nthree", "", "only\n"] {
+            for lines in 0..5 {
+                let streamed =
+                    read_tail_streaming(&mut io::Cursor::new(content.as_bytes()), lines).unwrap();
+                assert_eq!(
+                    streamed,
+                    tail_of(content, lines).as_bytes(),
+                    "{content:?} {lines}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_read_tail_last_lines() {
+        assert_eq!(tail_of("one\ntwo\nthree\nfour\n", 2), "three\nfour");
+    }
+
+    #[test]
+    fn test_read_tail_without_trailing_newline() {
+        assert_eq!(tail_of("one\ntwo\nthree", 2), "two\nthree");
+    }
+
+    #[test]
+    fn test_read_tail_more_lines_than_file() {
+        assert_eq!(tail_of("one\ntwo\n", 50), "one\ntwo");
+        assert_eq!(tail_of("", 50), "");
+    }
+
+    #[test]
+    fn test_read_tail_zero_lines() {
+        assert_eq!(tail_of("one\ntwo\n", 0), "");
+    }
+
+    #[test]
+    fn test_read_tail_spans_chunks() {
+        let content: String = (0..5000).map(|i| format!("line {i}\n")).collect();
+        assert!(content.len() as u64 > TAIL_CHUNK_SIZE * 3);
+
+        let tail = tail_of(&content, 3);
+
+        assert_eq!(tail, "line 4997\nline 4998\nline 4999");
+        let all = tail_of(&content, 5000);
+        assert_eq!(all.lines().count(), 5000);
+        assert!(all.starts_with("line 0\n"));
+    }
+
+    #[test]
+    fn test_view_log_files_with_tail() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut file = File::create(temp_dir.path().join("test.log")).unwrap();
+        writeln!(file, "Log file content").unwrap();
+
+        let result = view_log_files(
+            temp_dir.path(),
+            None,
+            None,
+            Some(50),
+            Paging::Off,
+            false,
+            Encoding::Utf8,
+        );
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_read_head_first_lines() {
+        let mut reader = "one\ntwo\nthree\n".as_bytes();
+
+        assert_eq!(read_head(&mut reader, 2).unwrap(), b"one\ntwo\n");
+        assert_eq!(reader, b"three\n");
+    }
+
+    #[test]
+    fn test_read_head_short_file() {
+        assert_eq!(
+            read_head(&mut "one\ntwo".as_bytes(), 50).unwrap(),
+            b"one\ntwo"
+        );
+        assert!(read_head(&mut "one\n".as_bytes(), 0).unwrap().is_empty());
+    }
+
+    fn paged(content: &str, answers: &str, page_lines: usize) -> String {
+        let mut out = Vec::new();
+        page_to(
+            &mut content.as_bytes(),
+            &mut out,
+            &mut answers.as_bytes(),
+            page_lines,
+            Encoding::Utf8,
+        )
+        .unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_page_to_waits_between_pages() {
+        let output = paged("1\n2\n3\n4\n5\n", "\n\n", 2);
+
+        assert_eq!(output.matches("-- More --").count(), 2);
+        assert!(output.starts_with("1\n2\n-- More --"));
+        assert!(output.ends_with("5\n"));
+    }
+
+    #[test]
+    fn test_page_to_quits_on_q() {
+        let output = paged("1\n2\n3\n4\n5\n", "q\n", 2);
+
+        assert!(output.starts_with("1\n2\n-- More --"));
+        assert!(!output.contains('3'));
+    }
+
+    #[test]
+    fn test_page_to_without_input_writes_everything() {
+        let output = paged("1\n2\n3\n4\n5\n", "", 2);
+
+        assert_eq!(output.matches("-- More --").count(), 1);
+        assert!(output.ends_with("3\n4\n5\n"));
+    }
+
+    #[test]
+    fn test_page_to_short_file_never_prompts() {
+        assert_eq!(paged("1\n2", "", 5), "1\n2");
+    }
+
+    #[test]
+    fn test_print_file_missing_reports_error() {
+        let temp_dir = TempDir::new().unwrap();
+
+        let result = print_file(
+            &temp_dir.path().join("gone.log"),
+            None,
+            None,
+            false,
+            Encoding::Utf8,
+        );
+
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::NotFound);
+    }
+}
@@ -0,0 +1,399 @@
+use crate::commands::undo::UndoLog;
+use crate::commands::{backup, delete};
+use crate::interrupt;
+use crate::output::{self, debug};
+use crate::rotation::{FileIdentity, path_identity, rotation_message};
+use crate::scan::{LogFile, collect_log_files};
+use crate::summary::Summary;
+use clap::ValueEnum;
+use std::collections::HashMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+/// How long a file must stay the same size and age before it is treated as
+/// finished by ACT.
+const SETTLE_TIME: Duration = Duration::from_secs(5);
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum WatchAction {
+    /// Move each finished file into the backup directory
+    Backup,
+    /// Remove each finished file
+    Delete,
+}
+
+/// Polls `dir` and runs `action` on every log that appears (or changes)
+/// after the watch started, once it has been left alone for
+/// [`SETTLE_TIME`], or as soon as ACT rotates to a new log. Backups go to
+/// `backup_dir`, taken from `dir` when relative. Runs until Ctrl-C; every
+/// handled file is counted in `summary` so failures show in the exit code.
+pub fn watch_dir(
+    dir: &Path,
+    action: WatchAction,
+    backup_dir: &Path,
+    summary: &mut Summary,
+) -> io::Result<()> {
+    interrupt::install();
+    let mut tracker = Tracker::new(&collect_log_files(dir)?);
+    // The whole session is one batch, so `undo` reverts it in one go.
+    let mut undo_log = UndoLog::new(dir);
+    output::emit(format!("Watching {} (Ctrl-C to stop)...", dir.display()));
+
+    let mut scan_failing = false;
+    while !interrupt::is_interrupted() {
+        thread::sleep(POLL_INTERVAL);
+        let Some(current) = rescan(dir, &mut scan_failing) else {
+            continue;
+        };
+        let observed = tracker.observe(&current, Instant::now());
+        if let Some(path) = &observed.rotated_to {
+            output::emit(rotation_message(path));
+        }
+        for path in observed.ready {
+            debug!("{} unchanged for {SETTLE_TIME:?}", path.display());
+            let result = match action {
+                WatchAction::Backup => backup::backup_log_file(&path, backup_dir, false, false)
+                    .inspect(|()| {
+                        if let Err(err) = undo_log.record_backup(&path, backup_dir) {
+                            output::emit_err(format!(
+                                "Could not record {} for undo: {err}",
+                                path.display()
+                            ));
+                        }
+                    }),
+                WatchAction::Delete => delete::delete_log_file(&path, None, false),
+            };
+            if let Err(err) = &result {
+                output::emit_err(format!("Failed: {}: {err}", path.display()));
+            }
+            summary.record(result.is_ok());
+        }
+    }
+
+    output::emit("Stopped watching.");
+    output::emit(summary.report_line());
+    Ok(())
+}
+
+/// Lists the logs in `dir` for one poll. A failed scan, such as a network
+/// drive dropping out for a moment, is reported once and the watch goes on
+/// polling; `failing` remembers that it was reported until a scan works.
+fn rescan(dir: &Path, failing: &mut bool) -> Option<Vec<LogFile>> {
+    match collect_log_files(dir) {
+        Ok(files) => {
+            if std::mem::take(failing) {
+                output::emit(format!("Scanning {} again.", dir.display()));
+            }
+            Some(files)
+        }
+        Err(err) => {
+            if !std::mem::replace(failing, true) {
+                output::emit_err(format!(
+                    "Could not scan {}: {err}; still watching",
+                    dir.display()
+                ));
+            }
+            None
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Snapshot {
+    size: u64,
+    modified: Option<SystemTime>,
+}
+
+impl Snapshot {
+    fn of(file: &LogFile) -> Self {
+        Snapshot {
+            size: file.size,
+            modified: file.modified,
+        }
+    }
+}
+
+enum FileState {
+    /// Seen changing; ready once unchanged since the instant recorded.
+    Pending(Snapshot, Instant),
+    /// Present when the watch started, or already handed out.
+    Done(Snapshot),
+}
+
+/// Decides which files have settled between polls.
+struct Tracker {
+    files: HashMap<PathBuf, FileState>,
+    /// The log ACT is writing, the one that changed last, with its identity
+    /// so that it is still recognized after a rename.
+    active: Option<(PathBuf, Option<FileIdentity>)>,
+}
+
+/// What one poll found.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Observed {
+    /// Files to hand to the action, in name order.
+    ready: Vec<PathBuf>,
+    /// The new log ACT moved on to, when it rotated.
+    rotated_to: Option<PathBuf>,
+}
+
+impl Tracker {
+    /// Files that already exist are left alone unless they change later.
+    fn new(existing: &[LogFile]) -> Self {
+        let files = existing
+            .iter()
+            .map(|file| (file.path.clone(), FileState::Done(Snapshot::of(file))))
+            .collect();
+        Tracker {
+            files,
+            active: None,
+        }
+    }
+
+    /// Records the current directory contents and returns the files that
+    /// have been unchanged for [`SETTLE_TIME`], each only once. When ACT
+    /// starts writing another log, it has rotated: the previous one is
+    /// finished and handed out at once instead of after settling.
+    fn observe(&mut self, current: &[LogFile], now: Instant) -> Observed {
+        self.files
+            .retain(|path, _| current.iter().any(|file| &file.path == path));
+
+        let mut observed = Observed::default();
+        let mut written: Option<&LogFile> = None;
+        for file in current {
+            let snapshot = Snapshot::of(file);
+            let state = match self.files.remove(&file.path) {
+                Some(FileState::Done(seen)) if seen == snapshot => FileState::Done(seen),
+                Some(FileState::Pending(seen, since)) if seen == snapshot => {
+                    if now.duration_since(since) >= SETTLE_TIME {
+                        observed.ready.push(file.path.clone());
+                        FileState::Done(seen)
+                    } else {
+                        FileState::Pending(seen, since)
+                    }
+                }
+                None | Some(FileState::Done(_)) | Some(FileState::Pending(..)) => {
+                    if written.is_none_or(|latest| file.modified > latest.modified) {
+                        written = Some(file);
+                    }
+                    FileState::Pending(snapshot, now)
+                }
+            };
+            self.files.insert(file.path.clone(), state);
+        }
+        if let Some(file) = written {
+            self.note_written(file, current, &mut observed);
+        }
+        if let Some((active, _)) = &self.active
+            && observed.ready.contains(active)
+        {
+            self.active = None;
+        }
+        observed.ready.sort();
+        observed
+    }
+
+    /// Makes `file`, just seen changing, the active log. If another log
+    /// was active and `file` is not that one under a new name, ACT has
+    /// rotated and the old log is ready.
+    fn note_written(&mut self, file: &LogFile, current: &[LogFile], observed: &mut Observed) {
+        let identity = path_identity(&file.path);
+        if let Some((path, active_identity)) = self.active.take() {
+            let same_file = match (active_identity, identity) {
+                (Some(active_identity), Some(identity)) => active_identity == identity,
+                _ => path == file.path,
+            };
+            if !same_file {
+                // The old log may itself have been renamed away.
+                let old = match active_identity {
+                    Some(_) => current
+                        .iter()
+                        .map(|file| &file.path)
+                        .find(|candidate| path_identity(candidate) == active_identity),
+                    None => Some(&path).filter(|path| **path != file.path),
+                };
+                if let Some(old) = old
+                    && let Some(FileState::Pending(seen, _)) = self.files.remove(old)
+                {
+                    self.files.insert(old.clone(), FileState::Done(seen));
+                    observed.ready.push(old.clone());
+                }
+                observed.rotated_to = Some(file.path.clone());
+            }
+        }
+        self.active = Some((file.path.clone(), identity));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn log(name: &str, size: u64) -> LogFile {
+        LogFile {
+            path: PathBuf::from(name),
+            size,
+            modified: None,
+        }
+    }
+
+    #[test]
+    fn test_existing_files_are_ignored() {
+        let start = Instant::now();
+        let mut tracker = Tracker::new(&[log("old.log", 10)]);
+
+        let ready = tracker
+            .observe(&[log("old.log", 10)], start + SETTLE_TIME * 2)
+            .ready;
+
+        assert!(ready.is_empty());
+    }
+
+    #[test]
+    fn test_new_file_is_ready_after_settling() {
+        let start = Instant::now();
+        let mut tracker = Tracker::new(&[]);
+
+        assert!(
+            tracker
+                .observe(&[log("new.log", 10)], start)
+                .ready
+                .is_empty()
+        );
+        let early = start + SETTLE_TIME - Duration::from_secs(1);
+        assert!(
+            tracker
+                .observe(&[log("new.log", 10)], early)
+                .ready
+                .is_empty()
+        );
+        let ready = tracker
+            .observe(&[log("new.log", 10)], start + SETTLE_TIME)
+            .ready;
+
+        assert_eq!(ready, [PathBuf::from("new.log")]);
+        let later = start + SETTLE_TIME * 3;
+        assert!(
+            tracker
+                .observe(&[log("new.log", 10)], later)
+                .ready
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_growing_file_resets_the_timer() {
+        let start = Instant::now();
+        let mut tracker = Tracker::new(&[]);
+        let step = Duration::from_secs(3);
+
+        tracker.observe(&[log("raid.log", 10)], start);
+        assert!(
+            tracker
+                .observe(&[log("raid.log", 20)], start + step)
+                .ready
+                .is_empty()
+        );
+        assert!(
+            tracker
+                .observe(&[log("raid.log", 20)], start + step * 2)
+                .ready
+                .is_empty()
+        );
+        let ready = tracker
+            .observe(&[log("raid.log", 20)], start + step + SETTLE_TIME)
+            .ready;
+
+        assert_eq!(ready, [PathBuf::from("raid.log")]);
+    }
+
+    #[test]
+    fn test_existing_file_that_changes_is_picked_up() {
+        let start = Instant::now();
+        let mut tracker = Tracker::new(&[log("current.log", 10)]);
+
+        tracker.observe(&[log("current.log", 50)], start);
+        let ready = tracker
+            .observe(&[log("current.log", 50)], start + SETTLE_TIME)
+            .ready;
+
+        assert_eq!(ready, [PathBuf::from("current.log")]);
+    }
+
+    #[test]
+    fn test_removed_file_is_forgotten() {
+        let start = Instant::now();
+        let mut tracker = Tracker::new(&[]);
+
+        tracker.observe(&[log("gone.log", 10)], start);
+        tracker.observe(&[], start + Duration::from_secs(1));
+        let ready = tracker
+            .observe(&[log("gone.log", 10)], start + SETTLE_TIME)
+            .ready;
+
+        assert!(ready.is_empty());
+    }
+
+    #[test]
+    fn test_new_log_hands_out_the_rotated_one_at_once() {
+        let start = Instant::now();
+        let mut tracker = Tracker::new(&[]);
+        tracker.observe(&[log("Network_1.log", 10)], start);
+        tracker.observe(&[log("Network_1.log", 20)], start + POLL_INTERVAL);
+
+        let observed = tracker.observe(
+            &[log("Network_1.log", 20), log("Network_2.log", 1)],
+            start + POLL_INTERVAL * 2,
+        );
+
+        assert_eq!(observed.ready, [PathBuf::from("Network_1.log")]);
+        assert_eq!(observed.rotated_to, Some(PathBuf::from("Network_2.log")));
+        let later = start + SETTLE_TIME * 2;
+        let observed = tracker.observe(&[log("Network_1.log", 20), log("Network_2.log", 1)], later);
+        assert_eq!(observed.ready, [PathBuf::from("Network_2.log")]);
+        assert_eq!(observed.rotated_to, None);
+    }
+
+    #[test]
+    #[cfg(any(unix, windows))]
+    fn test_log_renamed_away_is_only_a_rotation_once_replaced() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("Network_1.log");
+        let moved = temp_dir.path().join("Network_1.old.log");
+        let scan = || collect_log_files(temp_dir.path()).unwrap();
+        let start = Instant::now();
+        let mut tracker = Tracker::new(&[]);
+        fs::write(&path, "first\n").unwrap();
+        tracker.observe(&scan(), start);
+
+        fs::rename(&path, &moved).unwrap();
+        let observed = tracker.observe(&scan(), start + POLL_INTERVAL);
+        assert_eq!(observed, Observed::default());
+
+        fs::write(&path, "second line\n").unwrap();
+        let observed = tracker.observe(&scan(), start + POLL_INTERVAL * 2);
+        assert_eq!(observed.ready, [moved]);
+        assert_eq!(observed.rotated_to, Some(path));
+    }
+
+    #[test]
+    fn test_rescan_keeps_watching_through_a_failed_scan() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path().join("logs");
+        let mut failing = false;
+
+        assert!(rescan(&dir, &mut failing).is_none());
+        assert!(failing);
+        assert!(rescan(&dir, &mut failing).is_none());
+
+        fs::create_dir(&dir).unwrap();
+        fs::write(dir.join("Network_1.log"), "a").unwrap();
+        assert_eq!(rescan(&dir, &mut failing).unwrap().len(), 1);
+        assert!(!failing);
+    }
+}
@@ -0,0 +1,64 @@
+//! Decoding log lines that are not UTF-8.
+//!
+//! ACT writes UTF-8, but older logs and some locales hold Latin-1 or, from
+//! the Japanese client, Shift_JIS bytes. `view`, `search` and `export` read
+//! lines as bytes and decode them here, so a stray byte never stops a read.
+
+use crate::shift_jis;
+use clap::ValueEnum;
+use std::borrow::Cow;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum Encoding {
+    /// UTF-8, with invalid bytes shown as U+FFFD
+    #[default]
+    Utf8,
+    /// ISO-8859-1, one byte per character
+    Latin1,
+    /// Shift_JIS as written by the Japanese client
+    #[value(name = "shift_jis", alias = "sjis")]
+    ShiftJis,
+}
+
+impl Encoding {
+    /// The text of `bytes`. Never fails: anything that is not valid in this
+    /// encoding becomes U+FFFD.
+    pub fn decode(self, bytes: &[u8]) -> Cow<'_, str> {
+        match self {
+            Encoding::Utf8 => String::from_utf8_lossy(bytes),
+            // ASCII reads the same in every encoding here.
+            _ if bytes.is_ascii() => String::from_utf8_lossy(bytes),
+            Encoding::Latin1 => Cow::Owned(bytes.iter().map(|&byte| char::from(byte)).collect()),
+            Encoding::ShiftJis => Cow::Owned(shift_jis::decode(bytes)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_utf8_replaces_invalid_bytes() {
+        assert_eq!(Encoding::Utf8.decode(b"ok \xff|"), "ok \u{FFFD}|");
+        assert!(matches!(Encoding::Utf8.decode(b"plain"), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn test_decode_latin1_maps_every_byte() {
+        assert_eq!(Encoding::Latin1.decode(b"Caf\xe9 \xff"), "Café ÿ");
+        assert_eq!(Encoding::Latin1.decode(b"plain"), "plain");
+    }
+
+    #[test]
+    fn test_decode_shift_jis_line() {
+        // "00|2024-01-01|日本語のチャット" as the Japanese client writes it.
+        let line =
+            b"00|2024-01-01|\x93\xfa\x96\x7b\x8c\xea\x82\xcc\x83\x60\x83\x83\x83\x62\x83\x67";
+
+        assert_eq!(
+            Encoding::ShiftJis.decode(line),
+            "00|2024-01-01|日本語のチャット"
+        );
+    }
+}
@@ -0,0 +1,78 @@
+use std::fmt;
+use std::io;
+
+/// Why an operation on a log file failed, for library callers.
+///
+/// The commands themselves work with [`io::Error`]; this sorts their
+/// failures into the cases a caller can act on and keeps the message the
+/// command would have printed.
+#[derive(Debug)]
+pub enum Error {
+    /// The file or directory does not exist.
+    NotFound(String),
+    /// The file or directory could not be read or written.
+    PermissionDenied(String),
+    /// The request does not make sense, e.g. the path is not a file or the
+    /// target of a move already exists.
+    InvalidInput(String),
+    /// The file's contents could not be read as a log.
+    InvalidData(String),
+    /// Any other I/O failure.
+    Io(io::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::NotFound(message)
+            | Error::PermissionDenied(message)
+            | Error::InvalidInput(message)
+            | Error::InvalidData(message) => f.write_str(message),
+            Error::Io(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        match err.kind() {
+            io::ErrorKind::NotFound => Error::NotFound(err.to_string()),
+            io::ErrorKind::PermissionDenied => Error::PermissionDenied(err.to_string()),
+            io::ErrorKind::InvalidInput | io::ErrorKind::AlreadyExists => {
+                Error::InvalidInput(err.to_string())
+            }
+            io::ErrorKind::InvalidData | io::ErrorKind::UnexpectedEof => {
+                Error::InvalidData(err.to_string())
+            }
+            _ => Error::Io(err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_from_io_error_keeps_the_message() {
+        let err = Error::from(io::Error::new(io::ErrorKind::NotFound, "a.log: gone"));
+        assert!(matches!(&err, Error::NotFound(message) if message == "a.log: gone"));
+        assert_eq!(err.to_string(), "a.log: gone");
+
+        let err = Error::from(io::Error::new(io::ErrorKind::AlreadyExists, "taken"));
+        assert!(matches!(err, Error::InvalidInput(_)));
+
+        let err = Error::from(io::Error::other("disk full"));
+        assert!(matches!(&err, Error::Io(inner) if inner.to_string() == "disk full"));
+        assert!(std::error::Error::source(&err).is_some());
+    }
+}
@@ -0,0 +1,155 @@
+//! Settings read from `config.toml`.
+//!
+//! The file lives in `~/.config/ff-log-cli/` (or `$XDG_CONFIG_HOME`) on Unix
+//! and in `%APPDATA%\ff-log-cli\` on Windows. Only top-level `key = "value"`
+//! string settings are understood; both basic (`"..."`) and literal (`'...'`)
+//! strings are accepted, the latter being handy for Windows paths.
+
+use std::env;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct FileConfig {
+    pub fflogs_dir: Option<String>,
+}
+
+impl FileConfig {
+    pub fn path() -> Option<PathBuf> {
+        #[cfg(windows)]
+        let dir = env::var_os("APPDATA").map(PathBuf::from);
+        #[cfg(not(windows))]
+        let dir = env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")));
+
+        dir.map(|dir| dir.join("ff-log-cli").join("config.toml"))
+    }
+
+    /// Reads the config file; a missing file yields the empty config.
+    pub fn load() -> Result<FileConfig, String> {
+        let Some(path) = FileConfig::path() else {
+            return Ok(FileConfig::default());
+        };
+        match fs::read_to_string(&path) {
+            Ok(content) => FileConfig::parse(&content)
+                .map_err(|err| format!("Invalid config file {}: {err}", path.display())),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(FileConfig::default()),
+            Err(err) => Err(format!(
+                "Could not read config file {}: {err}",
+                path.display()
+            )),
+        }
+    }
+
+    pub fn parse(content: &str) -> Result<FileConfig, String> {
+        let mut config = FileConfig::default();
+        for (index, line) in content.lines().enumerate() {
+            let line_number = index + 1;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                return Err(format!("line {line_number}: expected `key = \"value\"`"));
+            };
+            let value =
+                parse_string(value.trim()).map_err(|err| format!("line {line_number}: {err}"))?;
+            match key.trim() {
+                "fflogs_dir" => config.fflogs_dir = Some(value),
+                key => return Err(format!("line {line_number}: unknown setting '{key}'")),
+            }
+        }
+        Ok(config)
+    }
+}
+
+fn parse_string(value: &str) -> Result<String, String> {
+    let mut chars = value.chars();
+    let quote = match chars.next() {
+        Some(quote @ ('"' | '\'')) => quote,
+        _ => return Err("value must be a quoted string".to_string()),
+    };
+
+    let mut parsed = String::new();
+    loop {
+        match chars.next() {
+            None => return Err("unterminated string".to_string()),
+            Some(c) if c == quote => break,
+            Some('\\') if quote == '"' => match chars.next() {
+                Some('\\') => parsed.push('\\'),
+                Some('"') => parsed.push('"'),
+                Some('n') => parsed.push('\n'),
+                Some('t') => parsed.push('\t'),
+                Some(other) => return Err(format!("unsupported escape '\\{other}'")),
+                None => return Err("unterminated string".to_string()),
+            },
+            Some(c) => parsed.push(c),
+        }
+    }
+
+    let rest = chars.as_str().trim();
+    if !rest.is_empty() && !rest.starts_with('#') {
+        return Err(format!("unexpected text after the value: '{rest}'"));
+    }
+    Ok(parsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_basic_string() {
+        let config = FileConfig::parse("fflogs_dir = \"/home/user/ACT/FFXIVLogs\"\n").unwrap();
+
+        assert_eq!(
+            config.fflogs_dir.as_deref(),
+            Some("/home/user/ACT/FFXIVLogs")
+        );
+    }
+
+    #[test]
+    fn test_parse_windows_paths() {
+        let escaped = FileConfig::parse(r#"fflogs_dir = "C:\\ACT\\FFXIVLogs""#).unwrap();
+        let literal = FileConfig::parse(r"fflogs_dir = 'C:\ACT\FFXIVLogs'").unwrap();
+
+        assert_eq!(escaped.fflogs_dir.as_deref(), Some(r"C:\ACT\FFXIVLogs"));
+        assert_eq!(literal, escaped);
+    }
+
+    #[test]
+    fn test_parse_skips_comments_and_blank_lines() {
+        let content = "# ff-log-cli settings\n\nfflogs_dir = \"/logs\" # where ACT writes\n";
+
+        let config = FileConfig::parse(content).unwrap();
+
+        assert_eq!(config.fflogs_dir.as_deref(), Some("/logs"));
+    }
+
+    #[test]
+    fn test_parse_empty_file() {
+        assert_eq!(FileConfig::parse("").unwrap(), FileConfig::default());
+    }
+
+    #[test]
+    fn test_parse_malformed_reports_line() {
+        let unquoted = FileConfig::parse("\nfflogs_dir = /logs").unwrap_err();
+        let unterminated = FileConfig::parse("fflogs_dir = \"/logs").unwrap_err();
+        let no_equals = FileConfig::parse("fflogs_dir").unwrap_err();
+        let trailing = FileConfig::parse("fflogs_dir = \"/logs\" extra").unwrap_err();
+
+        assert_eq!(unquoted, "line 2: value must be a quoted string");
+        assert_eq!(unterminated, "line 1: unterminated string");
+        assert!(no_equals.starts_with("line 1: expected"));
+        assert!(trailing.contains("unexpected text"));
+    }
+
+    #[test]
+    fn test_parse_unknown_setting() {
+        let err = FileConfig::parse("log_dir = \"/logs\"").unwrap_err();
+
+        assert_eq!(err, "line 1: unknown setting 'log_dir'");
+    }
+}
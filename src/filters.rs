@@ -0,0 +1,858 @@
+use crate::gzip::{is_gz, open_maybe_gz};
+use crate::output::{self, debug};
+use crate::parser::parse_line;
+use crate::regex::Regex;
+use crate::scan::{LogFile, collect_log_files};
+use crate::style;
+use crate::util::Date;
+use std::fs::File;
+use std::io::{self, BufRead, Read, Seek, SeekFrom};
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+/// How much of a file is read when sniffing its first line.
+const SNIFF_LIMIT: u64 = 64 * 1024;
+
+pub fn matches_glob(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let name: Vec<char> = name.to_lowercase().chars().collect();
+
+    let (mut p, mut n) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                Some((star, matched)) => {
+                    p = star + 1;
+                    n = matched + 1;
+                    backtrack = Some((star, matched + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+pub fn file_name_matches(pattern: &str, path: &Path) -> bool {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    matches_glob(pattern, &name)
+}
+
+/// The first glob in `excludes` that matches the file name of `path`.
+pub fn excluded_by<'a>(excludes: &'a [String], path: &Path) -> Option<&'a str> {
+    excludes
+        .iter()
+        .map(String::as_str)
+        .find(|glob| file_name_matches(glob, path))
+}
+
+pub fn count_matching_files(dir: &Path, pattern: &str) -> io::Result<usize> {
+    let files = collect_log_files(dir)?;
+    Ok(files
+        .iter()
+        .filter(|file| file_name_matches(pattern, &file.path))
+        .count())
+}
+
+/// Whether `path` looks like an ACT network log: a `.log` file whose first
+/// line parses as an event.
+pub fn is_act_log(path: &Path) -> bool {
+    has_log_extension(path) && read_first_line(path).is_some_and(|line| parse_line(&line).is_some())
+}
+
+pub fn has_log_extension(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("log"))
+}
+
+/// The first line of the file without a byte order mark or line ending,
+/// reading at most [`SNIFF_LIMIT`] bytes (decompressed, for a `.gz` file).
+pub fn read_first_line(path: &Path) -> Option<String> {
+    let mut first_line = Vec::new();
+    open_maybe_gz(path)
+        .ok()?
+        .take(SNIFF_LIMIT)
+        .read_until(b'\n', &mut first_line)
+        .ok()?;
+    let first_line = String::from_utf8_lossy(&first_line);
+    let first_line = first_line
+        .trim_start_matches('\u{feff}')
+        .trim_end_matches(['\r', '\n']);
+    Some(first_line.to_string())
+}
+
+/// The last non-empty line of the file, reading at most [`SNIFF_LIMIT`]
+/// bytes from its end. A `.gz` file cannot be read from the end, so it is
+/// decompressed in full.
+pub fn read_last_line(path: &Path) -> Option<String> {
+    if is_gz(path) {
+        return read_last_line_streaming(open_maybe_gz(path).ok()?);
+    }
+    let mut file = File::open(path).ok()?;
+    let len = file.metadata().ok()?.len();
+    file.seek(SeekFrom::Start(len.saturating_sub(SNIFF_LIMIT)))
+        .ok()?;
+    let mut tail = Vec::new();
+    file.read_to_end(&mut tail).ok()?;
+    let tail = String::from_utf8_lossy(&tail);
+    let line = tail.trim_end_matches(['\r', '\n']).rsplit('\n').next()?;
+    Some(line.trim_end_matches('\r').to_string()).filter(|line| !line.is_empty())
+}
+
+fn read_last_line_streaming(mut reader: impl BufRead) -> Option<String> {
+    let mut last = None;
+    let mut buffer = Vec::new();
+    while reader.read_until(b'\n', &mut buffer).ok()? > 0 {
+        let line = String::from_utf8_lossy(&buffer);
+        let line = line.trim_end_matches(['\r', '\n']);
+        if !line.is_empty() {
+            last = Some(line.to_string());
+        }
+        buffer.clear();
+    }
+    last
+}
+
+/// Counts the files handed out under `--limit`.
+pub struct FileLimit {
+    max: Option<usize>,
+    taken: usize,
+}
+
+impl FileLimit {
+    pub fn new(max: Option<usize>) -> Self {
+        FileLimit { max, taken: 0 }
+    }
+
+    /// Whether one more file may be processed; each `true` uses up one.
+    pub fn allow(&mut self) -> bool {
+        if self.max.is_some_and(|max| self.taken >= max) {
+            return false;
+        }
+        self.taken += 1;
+        true
+    }
+}
+
+/// Why a file was left out of a run, tallied for the summary.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SkipReason {
+    /// Its name does not match `--pattern` or `--regex`, or matches `--exclude`.
+    Pattern,
+    /// Outside the age window or the date range, or its date is unknown.
+    Age,
+    /// Outside the size range.
+    Size,
+    /// Has content under `--empty-only`.
+    NotEmpty,
+    /// Outside the line count range.
+    Lines,
+    /// Not an ACT network log under `--strict`.
+    NotActLog,
+    /// Has no `.log` extension, for operations that only take logs.
+    NotLog,
+    /// A directory.
+    Directory,
+    /// Gone by the time it was processed.
+    Missing,
+    /// One of the most recent logs kept by `--keep-last`.
+    Kept,
+    /// Answered 'n' at a `--interactive` prompt.
+    Declined,
+}
+
+impl SkipReason {
+    /// The short label used in the breakdown after a run.
+    pub fn label(self) -> &'static str {
+        match self {
+            SkipReason::Pattern => "pattern",
+            SkipReason::Age => "age",
+            SkipReason::Size => "size",
+            SkipReason::NotEmpty => "not empty",
+            SkipReason::Lines => "lines",
+            SkipReason::NotActLog => "not an ACT log",
+            SkipReason::NotLog => "not .log",
+            SkipReason::Directory => "dir",
+            SkipReason::Missing => "missing",
+            SkipReason::Kept => "kept",
+            SkipReason::Declined => "declined",
+        }
+    }
+}
+
+/// Every criterion a file has to meet to be processed, as set by the
+/// selection flags of `list`, `delete` and `backup`. The default accepts
+/// every file.
+#[derive(Clone, Debug, Default)]
+pub struct FileFilter {
+    /// Glob the file name has to match.
+    pub pattern: Option<String>,
+    /// Regular expression the file name has to match.
+    pub regex: Option<Regex>,
+    /// Globs that exclude a file even if it matches `pattern`.
+    pub exclude: Vec<String>,
+    /// Minimum age in days.
+    pub older_than: Option<u64>,
+    /// Maximum age in days.
+    pub newer_than: Option<u64>,
+    /// Maximum age, for windows shorter than a day.
+    pub modified_within: Option<Duration>,
+    /// Earliest date, taken from an ACT file name or else the modification time.
+    pub since: Option<Date>,
+    /// Latest date, taken like `since`.
+    pub until: Option<Date>,
+    pub min_size: Option<u64>,
+    pub max_size: Option<u64>,
+    pub min_lines: Option<usize>,
+    pub max_lines: Option<usize>,
+    /// Only accept empty files.
+    pub empty_only: bool,
+    /// Only accept ACT network logs.
+    pub strict: bool,
+}
+
+impl FileFilter {
+    /// Checks `file` against every criterion and returns the first it
+    /// fails. The name, size and dates come from `file` itself; only the
+    /// line count and `strict` read the file, and only once the other
+    /// checks have passed. Nothing is printed here but the `-vv` trace;
+    /// [`FileFilter::skip_message`] gives the line to show for a skip.
+    pub fn check(&self, file: &LogFile) -> Result<(), SkipReason> {
+        let path = &file.path;
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        if let Some(pattern) = &self.pattern
+            && !matches_glob(pattern, &name)
+        {
+            debug!("Filtered out {}: does not match {pattern}", path.display());
+            return Err(SkipReason::Pattern);
+        }
+        if let Some(regex) = &self.regex
+            && !regex.is_match(&name)
+        {
+            debug!("Filtered out {}: does not match /{regex}/", path.display());
+            return Err(SkipReason::Pattern);
+        }
+        if let Some(glob) = excluded_by(&self.exclude, path) {
+            debug!("Filtered out {}: excluded by {glob}", path.display());
+            return Err(SkipReason::Pattern);
+        }
+        if self.older_than.is_some() || self.newer_than.is_some() || self.modified_within.is_some()
+        {
+            let Some(modified) = file.modified else {
+                return Err(SkipReason::Age);
+            };
+            let now = SystemTime::now();
+            if !age_in_window(modified, now, self.older_than, self.newer_than)
+                || self
+                    .modified_within
+                    .is_some_and(|window| !modified_within(modified, now, window))
+            {
+                debug!("Filtered out {}: outside the age window", path.display());
+                return Err(SkipReason::Age);
+            }
+        }
+        if self.since.is_some() || self.until.is_some() {
+            let Some(date) = parse_log_date(&name).or(file.modified.map(Date::from_system_time))
+            else {
+                return Err(SkipReason::Age);
+            };
+            if self.since.is_some_and(|since| date < since)
+                || self.until.is_some_and(|until| date > until)
+            {
+                debug!("Filtered out {}: outside the date range", path.display());
+                return Err(SkipReason::Age);
+            }
+        }
+        if !size_in_range(file.size, self.min_size, self.max_size) {
+            return Err(SkipReason::Size);
+        }
+        if self.empty_only && file.size != 0 {
+            return Err(SkipReason::NotEmpty);
+        }
+        if self.min_lines.is_some() || self.max_lines.is_some() {
+            let in_range = count_lines(path).is_ok_and(|lines| {
+                self.min_lines.is_none_or(|min| lines >= min)
+                    && self.max_lines.is_none_or(|max| lines <= max)
+            });
+            if !in_range {
+                return Err(SkipReason::Lines);
+            }
+        }
+        if self.strict && !is_act_log(path) {
+            return Err(SkipReason::NotActLog);
+        }
+        Ok(())
+    }
+
+    /// Whether an age or date criterion is set that `file` cannot be
+    /// judged by, for want of a modification time.
+    fn date_unknown(&self, file: &LogFile) -> bool {
+        if file.modified.is_some() {
+            return false;
+        }
+        let name = file.path.file_name().unwrap_or_default().to_string_lossy();
+        self.older_than.is_some()
+            || self.newer_than.is_some()
+            || self.modified_within.is_some()
+            || ((self.since.is_some() || self.until.is_some()) && parse_log_date(&name).is_none())
+    }
+
+    /// The line telling the user that `file` was skipped for `reason`, if
+    /// that skip is shown at all: files skipped by `strict` are always
+    /// named, as are those whose age or date cannot be told, and with `-v`
+    /// those outside the size or line count range or not empty. The caller
+    /// prints it, so JSON output stays clean.
+    pub fn skip_message(&self, file: &LogFile, reason: SkipReason) -> Option<String> {
+        let because = |why: &str| {
+            Some(format!(
+                "{} {}: {why}",
+                style::skipped("Skipping"),
+                file.path.display()
+            ))
+        };
+        match reason {
+            SkipReason::NotActLog => Some(format!(
+                "{} {}",
+                style::skipped("Skipping non-log file:"),
+                file.path.file_name().unwrap_or_default().to_string_lossy()
+            )),
+            SkipReason::Age if self.date_unknown(file) => because("modification time unavailable"),
+            SkipReason::Size if output::is_verbose() => because("outside the size range"),
+            SkipReason::NotEmpty if output::is_verbose() => because("not empty"),
+            SkipReason::Lines if output::is_verbose() => because("outside the line count range"),
+            _ => None,
+        }
+    }
+}
+
+/// The number of lines in `path`, counted while streaming it so memory
+/// stays bounded; a last line without a line ending counts too. A `.gz`
+/// file is counted decompressed.
+pub fn count_lines(path: &Path) -> io::Result<usize> {
+    let mut reader = open_maybe_gz(path)?;
+    let mut lines = 0;
+    let mut ends_with_newline = true;
+    loop {
+        let buffer = reader.fill_buf()?;
+        let Some(&last) = buffer.last() else {
+            break;
+        };
+        lines += buffer.iter().filter(|&&byte| byte == b'\n').count();
+        ends_with_newline = last == b'\n';
+        let len = buffer.len();
+        reader.consume(len);
+    }
+    Ok(lines + usize::from(!ends_with_newline))
+}
+
+/// The date in an ACT file name such as `Network_26802_20240101.log`: the
+/// first `_`-separated part of the stem that is a valid `YYYYMMDD` date.
+pub fn parse_log_date(name: &str) -> Option<Date> {
+    let stem = Path::new(name).file_stem()?.to_str()?;
+    stem.split('_').find_map(|part| {
+        if part.len() != 8 || !part.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        let year = part[..4].parse().ok()?;
+        let month = part[4..6].parse().ok()?;
+        let day = part[6..].parse().ok()?;
+        Date::new(year, month, day)
+    })
+}
+
+fn size_in_range(size: u64, min: Option<u64>, max: Option<u64>) -> bool {
+    min.is_none_or(|min| size >= min) && max.is_none_or(|max| size <= max)
+}
+
+fn age_in_window(
+    modified: SystemTime,
+    now: SystemTime,
+    older: Option<u64>,
+    newer: Option<u64>,
+) -> bool {
+    let age = now.duration_since(modified).unwrap_or(Duration::ZERO);
+    let days = |count: u64| Duration::from_secs(count * SECONDS_PER_DAY);
+
+    older.is_none_or(|older| age >= days(older)) && newer.is_none_or(|newer| age <= days(newer))
+}
+
+/// Whether `modified` is at most `window` before `now`. A modification
+/// time in the future counts as within any window.
+fn modified_within(modified: SystemTime, now: SystemTime, window: Duration) -> bool {
+    now.duration_since(modified).unwrap_or(Duration::ZERO) <= window
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{self, File};
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_matches_glob_literal() {
+        assert!(matches_glob("test.log", "test.log"));
+        assert!(!matches_glob("test.log", "test.txt"));
+    }
+
+    #[test]
+    fn test_matches_glob_star() {
+        assert!(matches_glob("Network_*.log", "Network_20240101.log"));
+        assert!(matches_glob("*", "anything"));
+        assert!(matches_glob("*.log", ".log"));
+        assert!(!matches_glob("Network_*.log", "Network_20240101.txt"));
+    }
+
+    #[test]
+    fn test_matches_glob_question_mark() {
+        assert!(matches_glob("test?.log", "test1.log"));
+        assert!(!matches_glob("test?.log", "test.log"));
+    }
+
+    #[test]
+    fn test_matches_glob_backtracking() {
+        assert!(matches_glob("*a*b", "xaxxab"));
+        assert!(matches_glob("a*b*c", "abbbbc"));
+        assert!(!matches_glob("a*b*c", "abbbb"));
+    }
+
+    fn selected(pattern: Option<&str>, excludes: &[&str], name: &str) -> bool {
+        let excludes: Vec<String> = excludes.iter().map(|glob| glob.to_string()).collect();
+        let path = Path::new(name);
+        pattern.is_none_or(|pattern| file_name_matches(pattern, path))
+            && excluded_by(&excludes, path).is_none()
+    }
+
+    #[test]
+    fn test_include_only() {
+        assert!(selected(Some("Network_*.log"), &[], "Network_1.log"));
+        assert!(!selected(Some("Network_*.log"), &[], "notes.log"));
+    }
+
+    #[test]
+    fn test_exclude_only() {
+        let excludes = ["*_current.log", "*.tmp"];
+        assert!(selected(None, &excludes, "Network_1.log"));
+        assert!(!selected(None, &excludes, "Network_current.log"));
+        assert!(!selected(None, &excludes, "Network_1.TMP"));
+        assert_eq!(
+            excluded_by(&["*.tmp".to_string()], Path::new("a.tmp")),
+            Some("*.tmp")
+        );
+    }
+
+    #[test]
+    fn test_exclude_wins_over_include() {
+        let excludes = ["*_current.log"];
+        assert!(selected(Some("Network_*.log"), &excludes, "Network_1.log"));
+        assert!(!selected(
+            Some("Network_*.log"),
+            &excludes,
+            "Network_current.log"
+        ));
+        assert!(!selected(
+            Some("Network_*.log"),
+            &excludes,
+            "other_current.log"
+        ));
+    }
+
+    #[test]
+    fn test_matches_glob_case_insensitive() {
+        assert!(matches_glob("network_*.LOG", "Network_20240101.log"));
+    }
+
+    #[test]
+    fn test_file_name_matches_ignores_parent_directories() {
+        assert!(file_name_matches(
+            "Network_*.log",
+            Path::new("/logs/Network_1.log")
+        ));
+        assert!(!file_name_matches(
+            "logs*",
+            Path::new("/logs/Network_1.log")
+        ));
+    }
+
+    fn days_ago(now: SystemTime, count: u64) -> SystemTime {
+        now - Duration::from_secs(count * SECONDS_PER_DAY)
+    }
+
+    #[test]
+    fn test_age_in_window_older_than() {
+        let now = SystemTime::now();
+
+        assert!(age_in_window(days_ago(now, 40), now, Some(30), None));
+        assert!(!age_in_window(days_ago(now, 10), now, Some(30), None));
+    }
+
+    #[test]
+    fn test_age_in_window_newer_than() {
+        let now = SystemTime::now();
+
+        assert!(age_in_window(days_ago(now, 2), now, None, Some(7)));
+        assert!(!age_in_window(days_ago(now, 8), now, None, Some(7)));
+    }
+
+    #[test]
+    fn test_age_in_window_combined_bounds() {
+        let now = SystemTime::now();
+
+        assert!(age_in_window(days_ago(now, 15), now, Some(10), Some(20)));
+        assert!(!age_in_window(days_ago(now, 5), now, Some(10), Some(20)));
+        assert!(!age_in_window(days_ago(now, 25), now, Some(10), Some(20)));
+    }
+
+    #[test]
+    fn test_modified_within_window() {
+        let now = SystemTime::now();
+        let hours = |count: u64| Duration::from_secs(count * 3600);
+
+        assert!(modified_within(now - hours(1), now, hours(2)));
+        assert!(modified_within(now - hours(2), now, hours(2)));
+        assert!(!modified_within(now - hours(3), now, hours(2)));
+        assert!(modified_within(now + hours(1), now, Duration::ZERO));
+    }
+
+    #[test]
+    fn test_age_in_window_future_mtime_counts_as_new() {
+        let now = SystemTime::now();
+        let future = now + Duration::from_secs(3600);
+
+        assert!(age_in_window(future, now, None, Some(1)));
+        assert!(!age_in_window(future, now, Some(1), None));
+    }
+
+    fn log_file(name: &str, size: u64, modified: Option<SystemTime>) -> LogFile {
+        LogFile {
+            path: PathBuf::from("/logs").join(name),
+            size,
+            modified,
+        }
+    }
+
+    #[test]
+    fn test_file_filter_default_accepts_everything() {
+        let filter = FileFilter::default();
+
+        assert!(filter.check(&log_file("notes.txt", 0, None)).is_ok());
+        assert!(
+            filter
+                .check(&log_file("Network_1.log", 1 << 30, Some(SystemTime::now())))
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_file_filter_age_needs_modification_time() {
+        let filter = FileFilter {
+            older_than: Some(1),
+            ..FileFilter::default()
+        };
+        let now = SystemTime::now();
+
+        assert!(filter.check(&log_file("a.log", 1, None)).is_err());
+        assert!(
+            filter
+                .check(&log_file("a.log", 1, Some(days_ago(now, 2))))
+                .is_ok()
+        );
+        assert!(filter.check(&log_file("a.log", 1, Some(now))).is_err());
+    }
+
+    #[test]
+    fn test_file_filter_modified_within() {
+        let filter = FileFilter {
+            modified_within: Some(Duration::from_secs(2 * 3600)),
+            ..FileFilter::default()
+        };
+        let now = SystemTime::now();
+
+        assert!(filter.check(&log_file("a.log", 1, Some(now))).is_ok());
+        assert_eq!(
+            filter.check(&log_file(
+                "a.log",
+                1,
+                Some(now - Duration::from_secs(3 * 3600))
+            )),
+            Err(SkipReason::Age)
+        );
+        assert!(filter.check(&log_file("a.log", 1, None)).is_err());
+    }
+
+    #[test]
+    fn test_file_filter_combines_criteria() {
+        let filter = FileFilter {
+            pattern: Some("Network_*.log".to_string()),
+            regex: Some(Regex::parse(r"_\d+\.log$").unwrap()),
+            exclude: vec!["*_2.log".to_string()],
+            newer_than: Some(7),
+            min_size: Some(10),
+            max_size: Some(100),
+            ..FileFilter::default()
+        };
+        let recent = Some(days_ago(SystemTime::now(), 1));
+
+        assert!(filter.check(&log_file("Network_1.log", 50, recent)).is_ok());
+        assert!(filter.check(&log_file("notes.log", 50, recent)).is_err());
+        assert!(
+            filter
+                .check(&log_file("Network_x.log", 50, recent))
+                .is_err()
+        );
+        assert!(
+            filter
+                .check(&log_file("Network_2.log", 50, recent))
+                .is_err()
+        );
+        assert!(
+            filter
+                .check(&log_file(
+                    "Network_1.log",
+                    50,
+                    Some(days_ago(SystemTime::now(), 8))
+                ))
+                .is_err()
+        );
+        assert!(filter.check(&log_file("Network_1.log", 5, recent)).is_err());
+        assert!(
+            filter
+                .check(&log_file("Network_1.log", 500, recent))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_file_filter_strict_skip_message_names_the_file() {
+        let filter = FileFilter {
+            strict: true,
+            ..FileFilter::default()
+        };
+        let file = log_file("notes.log", 5, Some(SystemTime::now()));
+
+        let message = filter.skip_message(&file, SkipReason::NotActLog).unwrap();
+
+        assert!(message.ends_with(" notes.log"));
+        assert_eq!(filter.skip_message(&file, SkipReason::Pattern), None);
+    }
+
+    #[test]
+    fn test_file_filter_skip_message_for_unknown_modification_time() {
+        let filter = FileFilter {
+            older_than: Some(1),
+            ..FileFilter::default()
+        };
+        let unknown = log_file("a.log", 1, None);
+        let recent = log_file("b.log", 1, Some(SystemTime::now()));
+
+        assert_eq!(filter.check(&unknown), Err(SkipReason::Age));
+        let message = filter.skip_message(&unknown, SkipReason::Age).unwrap();
+        assert!(message.ends_with("a.log: modification time unavailable"));
+        assert_eq!(filter.skip_message(&recent, SkipReason::Age), None);
+    }
+
+    #[test]
+    fn test_file_filter_check_names_the_failed_criterion() {
+        let filter = FileFilter {
+            pattern: Some("*.log".to_string()),
+            newer_than: Some(7),
+            min_size: Some(10),
+            ..FileFilter::default()
+        };
+        let recent = Some(days_ago(SystemTime::now(), 1));
+        let old = Some(days_ago(SystemTime::now(), 30));
+
+        assert_eq!(filter.check(&log_file("a.log", 50, recent)), Ok(()));
+        assert_eq!(
+            filter.check(&log_file("a.txt", 50, recent)),
+            Err(SkipReason::Pattern)
+        );
+        assert_eq!(
+            filter.check(&log_file("a.log", 50, old)),
+            Err(SkipReason::Age)
+        );
+        assert_eq!(
+            filter.check(&log_file("a.log", 5, recent)),
+            Err(SkipReason::Size)
+        );
+    }
+
+    #[test]
+    fn test_file_filter_empty_only() {
+        let filter = FileFilter {
+            empty_only: true,
+            ..FileFilter::default()
+        };
+
+        assert!(filter.check(&log_file("a.log", 0, None)).is_ok());
+        assert!(filter.check(&log_file("a.log", 1, None)).is_err());
+    }
+
+    #[test]
+    fn test_size_in_range_bounds_are_inclusive() {
+        assert!(size_in_range(10, None, None));
+        assert!(size_in_range(10, Some(10), Some(10)));
+        assert!(!size_in_range(9, Some(10), None));
+        assert!(!size_in_range(11, None, Some(10)));
+    }
+
+    #[test]
+    fn test_parse_log_date_from_act_names() {
+        let date = Date::new(2024, 1, 1);
+
+        assert_eq!(parse_log_date("Network_26802_20240101.log"), date);
+        assert_eq!(parse_log_date("Network_20240101_foo.log"), date);
+        assert_eq!(parse_log_date("Network_26802_20241301.log"), None);
+        assert_eq!(parse_log_date("notes.txt"), None);
+    }
+
+    #[test]
+    fn test_file_filter_date_prefers_name_over_mtime() {
+        let filter = |since, until| FileFilter {
+            since,
+            until,
+            ..FileFilter::default()
+        };
+        let named = log_file("Network_26802_20240110.log", 0, Some(SystemTime::now()));
+        let unnamed = log_file("notes.log", 0, Some(SystemTime::now()));
+        let since = Date::new(2024, 1, 8);
+        let until = Date::new(2024, 1, 14);
+
+        assert!(filter(since, until).check(&named).is_ok());
+        assert!(filter(until, None).check(&named).is_err());
+        // Modified just now, so far outside the raid week.
+        assert!(filter(since, until).check(&unnamed).is_err());
+        assert!(filter(since, None).check(&unnamed).is_ok());
+        assert!(
+            filter(since, None)
+                .check(&log_file("notes.log", 0, None))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_read_first_and_last_line() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("test.log");
+        fs::write(&file_path, "\u{feff}first\r\nmiddle\r\nlast\r\n\r\n").unwrap();
+        let empty = temp_dir.path().join("empty.log");
+        File::create(&empty).unwrap();
+
+        assert_eq!(read_first_line(&file_path).as_deref(), Some("first"));
+        assert_eq!(read_last_line(&file_path).as_deref(), Some("last"));
+        assert_eq!(read_last_line(&empty), None);
+        assert_eq!(read_last_line(&temp_dir.path().join("gone.log")), None);
+    }
+
+    #[test]
+    fn test_count_lines() {
+        let temp_dir = TempDir::new().unwrap();
+        let cases = [
+            ("empty.log", "", 0),
+            ("one.log", "only\n", 1),
+            ("unterminated.log", "first\r\nsecond", 2),
+            ("blank.log", "\n\n\n", 3),
+        ];
+        for (name, content, lines) in cases {
+            let path = temp_dir.path().join(name);
+            fs::write(&path, content).unwrap();
+            assert_eq!(count_lines(&path).unwrap(), lines, "{name}");
+        }
+        assert!(count_lines(&temp_dir.path().join("gone.log")).is_err());
+    }
+
+    #[test]
+    fn test_file_filter_selects_files_by_line_count() {
+        let temp_dir = TempDir::new().unwrap();
+        for lines in [5, 50, 500] {
+            fs::write(
+                temp_dir.path().join(format!("{lines}.log")),
+                format!("{ACT_LINE}\n").repeat(lines),
+            )
+            .unwrap();
+        }
+        let filter = FileFilter {
+            min_lines: Some(10),
+            max_lines: Some(100),
+            ..FileFilter::default()
+        };
+
+        let mut selected: Vec<_> = collect_log_files(temp_dir.path())
+            .unwrap()
+            .into_iter()
+            .filter(|file| filter.check(file).is_ok())
+            .map(|file| file.name())
+            .collect();
+        selected.sort();
+
+        assert_eq!(selected, ["50.log"]);
+        let gone = LogFile {
+            path: temp_dir.path().join("gone.log"),
+            size: 0,
+            modified: None,
+        };
+        assert!(filter.check(&gone).is_err());
+    }
+
+    #[test]
+    fn test_file_limit_without_max_allows_everything() {
+        let mut limit = FileLimit::new(None);
+
+        assert!((0..1000).all(|_| limit.allow()));
+    }
+
+    #[test]
+    fn test_count_matching_files() {
+        let temp_dir = TempDir::new().unwrap();
+        File::create(temp_dir.path().join("Network_1.log")).unwrap();
+        File::create(temp_dir.path().join("Network_2.log")).unwrap();
+        File::create(temp_dir.path().join("notes.txt")).unwrap();
+        fs::create_dir(temp_dir.path().join("Network_dir.log")).unwrap();
+
+        assert_eq!(
+            count_matching_files(temp_dir.path(), "Network_*.log").unwrap(),
+            2
+        );
+        assert_eq!(count_matching_files(temp_dir.path(), "*.dat").unwrap(), 0);
+    }
+
+    const ACT_LINE: &str = "00|2024-01-01T20:15:42.0000000+01:00|0839||Ready check initiated.|hash";
+
+    #[test]
+    fn test_is_act_log_accepts_network_log() {
+        let temp_dir = TempDir::new().unwrap();
+        let log = temp_dir.path().join("Network_1.log");
+        let with_bom = temp_dir.path().join("Network_2.LOG");
+        fs::write(&log, format!("{ACT_LINE}\n{ACT_LINE}\n")).unwrap();
+        fs::write(&with_bom, format!("\u{feff}{ACT_LINE}\r\n")).unwrap();
+
+        assert!(is_act_log(&log));
+        assert!(is_act_log(&with_bom));
+    }
+
+    #[test]
+    fn test_is_act_log_rejects_other_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let notes = temp_dir.path().join("notes.txt");
+        let plain = temp_dir.path().join("plain.log");
+        let empty = temp_dir.path().join("empty.log");
+        fs::write(&notes, format!("{ACT_LINE}\n")).unwrap();
+        fs::write(&plain, "remember to buy potions\n").unwrap();
+        File::create(&empty).unwrap();
+
+        assert!(!is_act_log(&notes));
+        assert!(!is_act_log(&plain));
+        assert!(!is_act_log(&empty));
+        assert!(!is_act_log(&temp_dir.path().join("missing.log")));
+    }
+}
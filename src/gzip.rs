@@ -0,0 +1,544 @@
+use crate::hash::crc32;
+use crate::paths::{explain_long_path, to_extended_path};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::Path;
+
+/// How far back a DEFLATE match may reach.
+const WINDOW_SIZE: usize = 32 * 1024;
+/// How much output is decoded ahead of the reader at a time.
+const CHUNK_SIZE: usize = 16 * 1024;
+const MAX_BITS: usize = 15;
+
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+const DISTANCE_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+/// The order in which a dynamic block lists its code length code lengths.
+const CODE_LENGTH_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("invalid gzip data: {message}"),
+    )
+}
+
+/// Whether `path` is named like a gzip file (`.gz`).
+pub fn is_gz(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("gz"))
+}
+
+/// Opens `path` for reading line by line, decompressing it on the fly when
+/// it is a `.gz` file.
+pub fn open_maybe_gz(path: &Path) -> io::Result<Box<dyn BufRead>> {
+    let file = File::open(to_extended_path(path)).map_err(|err| explain_long_path(err, path))?;
+    if is_gz(path) {
+        Ok(Box::new(BufReader::new(GzDecoder::new(BufReader::new(
+            file,
+        )))))
+    } else {
+        Ok(Box::new(BufReader::new(file)))
+    }
+}
+
+/// Reads a byte stream least significant bit first, as DEFLATE packs it.
+struct BitReader<R> {
+    inner: R,
+    bits: u32,
+    count: u32,
+}
+
+impl<R: BufRead> BitReader<R> {
+    fn new(inner: R) -> Self {
+        BitReader {
+            inner,
+            bits: 0,
+            count: 0,
+        }
+    }
+
+    fn next_byte(&mut self) -> io::Result<Option<u8>> {
+        let byte = self.inner.fill_buf()?.first().copied();
+        if byte.is_some() {
+            self.inner.consume(1);
+        }
+        Ok(byte)
+    }
+
+    fn bits(&mut self, n: u32) -> io::Result<u32> {
+        while self.count < n {
+            let byte = self.next_byte()?.ok_or_else(|| {
+                io::Error::new(io::ErrorKind::UnexpectedEof, "gzip data ends early")
+            })?;
+            self.bits |= u32::from(byte) << self.count;
+            self.count += 8;
+        }
+        let value = self.bits & ((1u64 << n) - 1) as u32;
+        self.bits = self.bits.checked_shr(n).unwrap_or(0);
+        self.count -= n;
+        Ok(value)
+    }
+
+    /// Skips to the next byte boundary.
+    fn align(&mut self) {
+        let partial = self.count % 8;
+        self.bits >>= partial;
+        self.count -= partial;
+    }
+
+    fn byte(&mut self) -> io::Result<u8> {
+        Ok(self.bits(8)? as u8)
+    }
+
+    fn u16_le(&mut self) -> io::Result<u16> {
+        Ok(self.bits(16)? as u16)
+    }
+
+    fn u32_le(&mut self) -> io::Result<u32> {
+        Ok(self.bits(16)? | (self.bits(16)? << 16))
+    }
+
+    /// Whether the input is exhausted; only meaningful on a byte boundary.
+    fn at_end(&mut self) -> io::Result<bool> {
+        Ok(self.count == 0 && self.inner.fill_buf()?.is_empty())
+    }
+}
+
+/// A canonical Huffman code, decoded a bit at a time: `counts[len]` codes
+/// have each length and `symbols` lists them in code order.
+struct Huffman {
+    counts: [u16; MAX_BITS + 1],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> io::Result<Self> {
+        let mut counts = [0u16; MAX_BITS + 1];
+        lengths
+            .iter()
+            .for_each(|&length| counts[length as usize] += 1);
+
+        let mut left: i32 = 1;
+        for &count in &counts[1..] {
+            left = (left << 1) - i32::from(count);
+            if left < 0 {
+                return Err(invalid("oversubscribed Huffman code"));
+            }
+        }
+
+        let mut offsets = [0u16; MAX_BITS + 1];
+        for len in 1..MAX_BITS {
+            offsets[len + 1] = offsets[len] + counts[len];
+        }
+        let mut symbols = vec![0; lengths.len()];
+        for (symbol, &length) in lengths.iter().enumerate() {
+            if length != 0 {
+                symbols[offsets[length as usize] as usize] = symbol as u16;
+                offsets[length as usize] += 1;
+            }
+        }
+        Ok(Huffman { counts, symbols })
+    }
+
+    fn decode<R: BufRead>(&self, input: &mut BitReader<R>) -> io::Result<u16> {
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+        for &count in &self.counts[1..] {
+            code |= input.bits(1)? as i32;
+            let count = i32::from(count);
+            if code - count < first {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err(invalid("bad Huffman code"))
+    }
+}
+
+fn fixed_tables() -> io::Result<(Huffman, Huffman)> {
+    let mut lengths = [0u8; 288];
+    lengths[..144].fill(8);
+    lengths[144..256].fill(9);
+    lengths[256..280].fill(7);
+    lengths[280..].fill(8);
+    Ok((Huffman::new(&lengths)?, Huffman::new(&[5; 30])?))
+}
+
+fn dynamic_tables<R: BufRead>(input: &mut BitReader<R>) -> io::Result<(Huffman, Huffman)> {
+    let literals = input.bits(5)? as usize + 257;
+    let distances = input.bits(5)? as usize + 1;
+    let code_lengths = input.bits(4)? as usize + 4;
+    if literals > 286 || distances > 30 {
+        return Err(invalid("too many length or distance codes"));
+    }
+
+    let mut lengths = [0u8; 19];
+    for &index in &CODE_LENGTH_ORDER[..code_lengths] {
+        lengths[index] = input.bits(3)? as u8;
+    }
+    let code_length_code = Huffman::new(&lengths)?;
+
+    let mut lengths = vec![0u8; literals + distances];
+    let mut index = 0;
+    while index < lengths.len() {
+        let symbol = code_length_code.decode(input)?;
+        let (length, repeat) = match symbol {
+            0..=15 => (symbol as u8, 1),
+            16 if index > 0 => (lengths[index - 1], 3 + input.bits(2)?),
+            16 => return Err(invalid("repeated length with no previous length")),
+            17 => (0, 3 + input.bits(3)?),
+            _ => (0, 11 + input.bits(7)?),
+        };
+        let end = index + repeat as usize;
+        if end > lengths.len() {
+            return Err(invalid("too many code lengths"));
+        }
+        lengths[index..end].fill(length);
+        index = end;
+    }
+    if lengths[256] == 0 {
+        return Err(invalid("missing end-of-block code"));
+    }
+    Ok((
+        Huffman::new(&lengths[..literals])?,
+        Huffman::new(&lengths[literals..])?,
+    ))
+}
+
+enum State {
+    Header,
+    BlockStart,
+    Stored(usize),
+    Codes(Box<(Huffman, Huffman)>),
+    Trailer,
+    Done,
+}
+
+/// Decompresses a gzip stream (one or more concatenated members) as it is
+/// read, keeping only the last 32 KiB of output that DEFLATE may refer back
+/// to. Each member's CRC-32 and length are checked at its end.
+pub struct GzDecoder<R> {
+    input: BitReader<R>,
+    state: State,
+    final_block: bool,
+    members: usize,
+    window: Vec<u8>,
+    /// How much of `window` has been handed out.
+    read: usize,
+    crc: u32,
+    size: u32,
+}
+
+impl<R: BufRead> GzDecoder<R> {
+    pub fn new(inner: R) -> Self {
+        GzDecoder {
+            input: BitReader::new(inner),
+            state: State::Header,
+            final_block: false,
+            members: 0,
+            window: Vec::new(),
+            read: 0,
+            crc: 0,
+            size: 0,
+        }
+    }
+
+    /// Decodes some more output; `false` once the stream has ended.
+    fn step(&mut self) -> io::Result<bool> {
+        self.trim_window();
+        match &self.state {
+            State::Header => {
+                if self.members > 0 && self.input.at_end()? {
+                    self.state = State::Done;
+                    return Ok(false);
+                }
+                self.read_header()?;
+                self.members += 1;
+                self.state = State::BlockStart;
+            }
+            State::BlockStart => {
+                self.final_block = self.input.bits(1)? == 1;
+                self.state = match self.input.bits(2)? {
+                    0 => {
+                        self.input.align();
+                        let len = self.input.u16_le()?;
+                        if self.input.u16_le()? != !len {
+                            return Err(invalid("stored block length mismatch"));
+                        }
+                        State::Stored(usize::from(len))
+                    }
+                    1 => State::Codes(Box::new(fixed_tables()?)),
+                    2 => State::Codes(Box::new(dynamic_tables(&mut self.input)?)),
+                    _ => return Err(invalid("unknown block type")),
+                };
+            }
+            &State::Stored(remaining) => {
+                let take = remaining.min(CHUNK_SIZE);
+                for _ in 0..take {
+                    let byte = self.input.byte()?;
+                    self.window.push(byte);
+                }
+                if take == remaining {
+                    self.end_block();
+                } else {
+                    self.state = State::Stored(remaining - take);
+                }
+            }
+            State::Codes(_) => self.inflate_codes()?,
+            State::Trailer => {
+                self.input.align();
+                if self.input.u32_le()? != self.crc {
+                    return Err(invalid("CRC-32 mismatch"));
+                }
+                if self.input.u32_le()? != self.size {
+                    return Err(invalid("length mismatch"));
+                }
+                self.crc = 0;
+                self.size = 0;
+                self.state = State::Header;
+            }
+            State::Done => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    fn read_header(&mut self) -> io::Result<()> {
+        const FHCRC: u8 = 0x02;
+        const FEXTRA: u8 = 0x04;
+        const FNAME: u8 = 0x08;
+        const FCOMMENT: u8 = 0x10;
+
+        let input = &mut self.input;
+        if input.byte()? != 0x1f || input.byte()? != 0x8b {
+            return Err(invalid("not a gzip file"));
+        }
+        if input.byte()? != 8 {
+            return Err(invalid("unknown compression method"));
+        }
+        let flags = input.byte()?;
+        // Modification time, extra flags and operating system.
+        for _ in 0..6 {
+            input.byte()?;
+        }
+        if flags & FEXTRA != 0 {
+            for _ in 0..input.u16_le()? {
+                input.byte()?;
+            }
+        }
+        for flag in [FNAME, FCOMMENT] {
+            if flags & flag != 0 {
+                while input.byte()? != 0 {}
+            }
+        }
+        if flags & FHCRC != 0 {
+            input.u16_le()?;
+        }
+        Ok(())
+    }
+
+    fn inflate_codes(&mut self) -> io::Result<()> {
+        let State::Codes(tables) = &self.state else {
+            unreachable!("inflate_codes outside a Huffman block");
+        };
+        let (literals, distances) = &**tables;
+        while self.window.len() - self.read < CHUNK_SIZE {
+            let symbol = literals.decode(&mut self.input)?;
+            if symbol < 256 {
+                self.window.push(symbol as u8);
+                continue;
+            }
+            if symbol == 256 {
+                self.end_block();
+                return Ok(());
+            }
+            let symbol = usize::from(symbol - 257);
+            if symbol >= LENGTH_BASE.len() {
+                return Err(invalid("bad length code"));
+            }
+            let length = usize::from(LENGTH_BASE[symbol])
+                + self.input.bits(u32::from(LENGTH_EXTRA[symbol]))? as usize;
+            let symbol = usize::from(distances.decode(&mut self.input)?);
+            if symbol >= DISTANCE_BASE.len() {
+                return Err(invalid("bad distance code"));
+            }
+            let distance = usize::from(DISTANCE_BASE[symbol])
+                + self.input.bits(u32::from(DISTANCE_EXTRA[symbol]))? as usize;
+            if distance > self.window.len() {
+                return Err(invalid("distance too far back"));
+            }
+            // Byte by byte, as a match may overlap the bytes it produces.
+            let start = self.window.len() - distance;
+            for offset in 0..length {
+                let byte = self.window[start + offset];
+                self.window.push(byte);
+            }
+        }
+        Ok(())
+    }
+
+    fn end_block(&mut self) {
+        self.state = if self.final_block {
+            State::Trailer
+        } else {
+            State::BlockStart
+        };
+    }
+
+    /// Drops output that has been read and is too far back to be matched.
+    fn trim_window(&mut self) {
+        let excess = self.read.saturating_sub(WINDOW_SIZE);
+        if excess > WINDOW_SIZE {
+            self.window.drain(..excess);
+            self.read -= excess;
+        }
+    }
+}
+
+impl<R: BufRead> Read for GzDecoder<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.read == self.window.len() {
+            if !self.step()? {
+                return Ok(0);
+            }
+        }
+        let pending = &self.window[self.read..];
+        let n = pending.len().min(buf.len());
+        buf[..n].copy_from_slice(&pending[..n]);
+        self.crc = crc32(self.crc, &pending[..n]);
+        self.size = self.size.wrapping_add(n as u32);
+        self.read += n;
+        Ok(n)
+    }
+}
+
+/// Wraps `data` in a gzip member made of stored (uncompressed) blocks.
+#[cfg(test)]
+pub fn compress_stored(data: &[u8]) -> Vec<u8> {
+    let mut gz = vec![0x1f, 0x8b, 8, 0, 0, 0, 0, 0, 0, 0xff];
+    let mut chunks = data.chunks(usize::from(u16::MAX)).peekable();
+    if chunks.peek().is_none() {
+        gz.extend_from_slice(&[1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(chunk) = chunks.next() {
+        let len = chunk.len() as u16;
+        gz.push(u8::from(chunks.peek().is_none()));
+        gz.extend_from_slice(&len.to_le_bytes());
+        gz.extend_from_slice(&(!len).to_le_bytes());
+        gz.extend_from_slice(chunk);
+    }
+    gz.extend_from_slice(&crc32(0, data).to_le_bytes());
+    gz.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    gz
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn decompress(gz: &[u8]) -> io::Result<Vec<u8>> {
+        let mut out = Vec::new();
+        GzDecoder::new(gz).read_to_end(&mut out)?;
+        Ok(out)
+    }
+
+    /// `gzip -9` of "hello hello hello\n": a fixed Huffman block with a match.
+    const FIXED: [u8; 29] = [
+        0x1f, 0x8b, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0x03, 0xcb, 0x48, 0xcd, 0xc9, 0xc9,
+        0x57, 0xc8, 0x40, 0x90, 0x5c, 0x00, 0x3b, 0x7c, 0x8a, 0xdf, 0x12, 0x00, 0x00, 0x00,
+    ];
+
+    /// `gzip -9` of [`dynamic_text`]: a dynamic Huffman block.
+    const DYNAMIC: [u8; 118] = [
+        0x1f, 0x8b, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0x03, 0x95, 0xd1, 0xbb, 0x0d, 0x80,
+        0x30, 0x10, 0x04, 0xd1, 0x9c, 0x2a, 0x68, 0x00, 0xe9, 0xf6, 0xb0, 0xf9, 0x38, 0x83, 0x02,
+        0x88, 0xdc, 0x80, 0x85, 0x88, 0x2c, 0x20, 0xb0, 0xc3, 0x2b, 0x1e, 0x0a, 0x80, 0x60, 0xa5,
+        0x09, 0x5f, 0x36, 0x0a, 0x53, 0x51, 0xd7, 0x09, 0xde, 0xa2, 0x4a, 0x80, 0x0f, 0x22, 0x16,
+        0xd3, 0x95, 0xdb, 0x2d, 0x9d, 0x87, 0x2d, 0xb5, 0xa6, 0x3d, 0xdb, 0x7a, 0x97, 0xd2, 0xe8,
+        0x17, 0x06, 0x83, 0x95, 0xc1, 0x3d, 0x83, 0x1d, 0x83, 0x3d, 0x83, 0x07, 0x06, 0x8f, 0x0c,
+        0x9e, 0x18, 0x3c, 0x13, 0x18, 0xcc, 0x41, 0x30, 0x07, 0xc1, 0x1c, 0x04, 0x73, 0x10, 0xcc,
+        0x41, 0xfc, 0x1d, 0x7c, 0x00, 0x5a, 0xe5, 0xd5, 0xa6, 0xd0, 0x02, 0x00, 0x00,
+    ];
+
+    fn dynamic_text() -> String {
+        (0..16)
+            .map(|second| format!("21|2024-01-01T20:15:{second:02}|Tank Name|Attack|Boss\n"))
+            .collect()
+    }
+
+    #[test]
+    fn test_decodes_fixed_huffman_block() {
+        assert_eq!(decompress(&FIXED).unwrap(), b"hello hello hello\n");
+    }
+
+    #[test]
+    fn test_decodes_dynamic_huffman_block() {
+        assert_eq!(decompress(&DYNAMIC).unwrap(), dynamic_text().as_bytes());
+    }
+
+    #[test]
+    fn test_stored_round_trip_and_concatenated_members() {
+        let data: Vec<u8> = (0..200_000u32).map(|n| (n % 251) as u8).collect();
+        let mut gz = compress_stored(&data);
+        gz.extend(compress_stored(b"more\n"));
+
+        let mut expected = data.clone();
+        expected.extend_from_slice(b"more\n");
+        assert_eq!(decompress(&gz).unwrap(), expected);
+        assert_eq!(decompress(&compress_stored(b"")).unwrap(), b"");
+    }
+
+    #[test]
+    fn test_rejects_corrupt_data() {
+        let mut gz = compress_stored(b"hello\n");
+        let crc = gz.len() - 8;
+        gz[crc] ^= 0xff;
+
+        let err = decompress(&gz).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().contains("CRC-32"));
+        assert!(decompress(b"plain text\n").is_err());
+        assert_eq!(
+            decompress(&FIXED[..20]).unwrap_err().kind(),
+            io::ErrorKind::UnexpectedEof
+        );
+    }
+
+    #[test]
+    fn test_open_maybe_gz_by_extension() {
+        let temp_dir = TempDir::new().unwrap();
+        let plain = temp_dir.path().join("a.log");
+        let packed = temp_dir.path().join("b.log.GZ");
+        fs::write(&plain, "plain\n").unwrap();
+        fs::write(&packed, compress_stored(b"packed\n")).unwrap();
+
+        let read = |path: &Path| {
+            let mut content = String::new();
+            open_maybe_gz(path)
+                .unwrap()
+                .read_to_string(&mut content)
+                .unwrap();
+            content
+        };
+        assert!(is_gz(&packed) && !is_gz(&plain));
+        assert_eq!(read(&plain), "plain\n");
+        assert_eq!(read(&packed), "packed\n");
+    }
+}
@@ -0,0 +1,121 @@
+use crate::json::{self, Value};
+use std::fs::read_to_string;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+/// Reads the files listed in the JSON array at `source`, or on stdin for
+/// `-`. Errors name the source they came from.
+pub fn read_json_paths(source: &str, base_dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let named = |err: io::Error| match source {
+        "-" => io::Error::new(err.kind(), format!("stdin: {err}")),
+        _ => io::Error::new(err.kind(), format!("{source}: {err}")),
+    };
+    let text = if source == "-" {
+        let mut text = String::new();
+        io::stdin().read_to_string(&mut text).map_err(named)?;
+        text
+    } else {
+        read_to_string(source).map_err(named)?
+    };
+    parse_json_paths(&text, base_dir).map_err(named)
+}
+
+fn parse_json_paths(text: &str, base_dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+
+    let Value::Array(items) = json::parse(text).map_err(invalid)? else {
+        return Err(invalid("expected a JSON array of files".to_string()));
+    };
+
+    items
+        .iter()
+        .enumerate()
+        .map(|(index, item)| {
+            item.as_str()
+                .or_else(|| item.get("path").and_then(Value::as_str))
+                .or_else(|| item.get("name").and_then(Value::as_str))
+                .map(|entry| base_dir.join(entry))
+                .ok_or_else(|| invalid(format!("entry {index} has no \"path\" or \"name\"")))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{self, File};
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_json_paths_from_names() {
+        let base = Path::new("/logs");
+        let paths = parse_json_paths(
+            r#"[{"name": "a.log", "size": 1}, {"name": "b.log", "size": 2}]"#,
+            base,
+        )
+        .unwrap();
+
+        assert_eq!(paths, vec![base.join("a.log"), base.join("b.log")]);
+    }
+
+    #[test]
+    fn test_parse_json_paths_prefers_path_over_name() {
+        let base = Path::new("/logs");
+        let paths = parse_json_paths(r#"[{"name": "a.log", "path": "sub/a.log"}]"#, base).unwrap();
+
+        assert_eq!(paths, vec![base.join("sub/a.log")]);
+    }
+
+    #[test]
+    fn test_parse_json_paths_from_plain_strings() {
+        let base = Path::new("/logs");
+        let paths = parse_json_paths(r#"["a.log", "b.log"]"#, base).unwrap();
+
+        assert_eq!(paths, vec![base.join("a.log"), base.join("b.log")]);
+    }
+
+    #[test]
+    fn test_parse_json_paths_rejects_non_array() {
+        let result = parse_json_paths(r#"{"name": "a.log"}"#, Path::new("/logs"));
+
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_parse_json_paths_rejects_entry_without_name() {
+        let result = parse_json_paths(r#"[{"size": 1}]"#, Path::new("/logs"));
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_read_json_paths_from_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let json_path = temp_dir.path().join("selection.json");
+
+        let mut file = File::create(&json_path).unwrap();
+        write!(file, r#"[{{"name": "test.log"}}]"#).unwrap();
+
+        let paths = read_json_paths(&json_path.to_string_lossy(), temp_dir.path()).unwrap();
+        assert_eq!(paths, vec![temp_dir.path().join("test.log")]);
+    }
+
+    #[test]
+    fn test_read_json_paths_errors_name_the_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let missing = temp_dir.path().join("missing.json");
+        let invalid = temp_dir.path().join("invalid.json");
+        fs::write(&invalid, "not json").unwrap();
+
+        for (path, kind) in [
+            (missing, io::ErrorKind::NotFound),
+            (invalid, io::ErrorKind::InvalidData),
+        ] {
+            let source = path.to_string_lossy();
+            let err = read_json_paths(&source, temp_dir.path()).unwrap_err();
+            assert_eq!(err.kind(), kind);
+            assert!(err.to_string().starts_with(&format!("{source}: ")));
+        }
+    }
+}
@@ -0,0 +1,31 @@
+//! Ctrl-C handling.
+//!
+//! After [`install`], the first Ctrl-C only sets a flag that long-running
+//! loops poll through [`is_interrupted`] so they can stop between files. A
+//! second Ctrl-C exits immediately.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+extern "C" fn on_interrupt(_signal: libc::c_int) {
+    if INTERRUPTED.swap(true, Ordering::SeqCst) {
+        // SAFETY: `_exit` is async-signal-safe.
+        unsafe { libc::_exit(130) };
+    }
+    // Some platforms reset the handler once it has run.
+    install();
+}
+
+pub fn install() {
+    let handler = on_interrupt as extern "C" fn(libc::c_int) as libc::sighandler_t;
+    // SAFETY: the handler only touches an atomic, re-registers itself and
+    // may call `_exit`, all of which are async-signal-safe.
+    unsafe {
+        libc::signal(libc::SIGINT, handler);
+    }
+}
+
+pub fn is_interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
@@ -0,0 +1,99 @@
+//! Running a per-file operation on several threads.
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+
+/// Calls `work` for every path on up to `jobs` worker threads and hands each
+/// result to `done` on the calling thread, in completion order. With a
+/// single job the paths are processed in order without spawning threads.
+pub fn for_each_file<T, W, D>(paths: Vec<PathBuf>, jobs: usize, work: W, mut done: D)
+where
+    T: Send,
+    W: Fn(&Path) -> T + Sync,
+    D: FnMut(&Path, T),
+{
+    if jobs <= 1 {
+        for path in &paths {
+            done(path, work(path));
+        }
+        return;
+    }
+
+    let next = AtomicUsize::new(0);
+    let (sender, receiver) = mpsc::channel();
+    thread::scope(|scope| {
+        for _ in 0..jobs.min(paths.len()) {
+            let sender = sender.clone();
+            let (paths, next, work) = (&paths, &next, &work);
+            scope.spawn(move || {
+                while let Some(path) = paths.get(next.fetch_add(1, Ordering::Relaxed)) {
+                    if sender.send((path, work(path))).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(sender);
+        for (path, result) in receiver {
+            done(path, result);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    fn paths(count: usize) -> Vec<PathBuf> {
+        (0..count)
+            .map(|i| PathBuf::from(format!("{i}.log")))
+            .collect()
+    }
+
+    #[test]
+    fn test_single_job_keeps_order() {
+        let mut seen = Vec::new();
+
+        for_each_file(
+            paths(5),
+            1,
+            |path| path.to_path_buf(),
+            |_, path| seen.push(path),
+        );
+
+        assert_eq!(seen, paths(5));
+    }
+
+    #[test]
+    fn test_many_jobs_process_every_file_once() {
+        let mut seen = HashSet::new();
+        let mut calls = 0;
+
+        for_each_file(
+            paths(200),
+            8,
+            |path| path.to_path_buf(),
+            |path, result| {
+                assert_eq!(path, result);
+                calls += 1;
+                seen.insert(result);
+            },
+        );
+
+        assert_eq!(calls, 200);
+        assert_eq!(seen, paths(200).into_iter().collect());
+    }
+
+    #[test]
+    fn test_more_jobs_than_files() {
+        let mut calls = 0;
+
+        for_each_file(paths(2), 16, |_| (), |_, ()| calls += 1);
+        for_each_file(Vec::new(), 4, |_| (), |_, ()| calls += 1);
+
+        assert_eq!(calls, 2);
+    }
+}
@@ -0,0 +1,55 @@
+//! Managing the Final Fantasy XIV combat logs written by Advanced Combat
+//! Tracker (ACT).
+//!
+//! The `ff-log-cli` binary is a thin wrapper around [`cli::run`]. Other
+//! programs can call the same commands directly: either one file at a time,
+//! with [`backup_log_file`], [`delete_log_file`] and friends, or for a whole
+//! directory with [`Operation::run`]. Both report failures as an [`Error`].
+
+mod api;
+pub mod cli;
+mod commands;
+mod encoding;
+mod error;
+mod file_config;
+mod filters;
+mod gzip;
+mod hash;
+mod input;
+mod interrupt;
+mod jobs;
+mod json;
+mod operation;
+mod output;
+mod parser;
+mod paths;
+mod progress;
+mod prompt;
+mod regex;
+mod retry;
+mod rotation;
+mod scan;
+mod shift_jis;
+mod style;
+mod summary;
+mod table;
+mod trash;
+#[cfg(all(feature = "tui", unix))]
+mod tui;
+mod util;
+mod zip;
+
+pub use api::{
+    archive_logs, backup_log_file, collect_log_files, delete_log_file, export_to_csv,
+    list_log_file, merge_logs, normalize_name, restore_log_file, safe_delete_log_file, scrub,
+    search_logs, split_log,
+};
+pub use cli::{Command, Config};
+pub use commands::backup::DEFAULT_BACKUP_DIR;
+pub use commands::search::Match;
+pub use error::Error;
+pub use operation::{FileOutcome, Operation, OperationConfig};
+pub use output::set_quiet;
+pub use scan::LogFile;
+pub use summary::Summary;
+pub use trash::{SystemTrash, Trash};
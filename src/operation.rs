@@ -0,0 +1,196 @@
+use crate::commands::backup::{DEFAULT_BACKUP_DIR, backup_log_file};
+use crate::commands::delete::delete_log_file;
+use crate::commands::rename::normalize_name;
+use crate::commands::restore::restore_log_file;
+use crate::commands::undo::UndoLog;
+use crate::error::Error;
+use crate::output;
+use crate::scan::collect_log_files;
+use crate::trash::{SystemTrash, Trash};
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// A command that handles each log file of a directory on its own, as the
+/// `backup`, `delete`, `restore` and `rename` subcommands do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    /// Move each file into [`OperationConfig::backup_dir`].
+    Backup,
+    /// Delete each file, or move it to the trash.
+    Delete,
+    /// Move each file in the directory's [`OperationConfig::backup_dir`]
+    /// back next to it.
+    Restore,
+    /// Rename each `.log` file to `YYYY-MM-DD_HH-MM.log`.
+    Rename,
+}
+
+/// How an [`Operation`] treats the files it is given. The default backs up
+/// to `bak/`, deletes permanently and changes files for real.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OperationConfig {
+    /// Where `Backup` moves files and `Restore` takes them from; a relative
+    /// path is taken from each file's own directory.
+    pub backup_dir: PathBuf,
+    /// Only report what would be done. `Restore` has no dry run and fails.
+    pub dry_run: bool,
+    /// Check each backup by SHA-256 before the original is removed.
+    pub verify: bool,
+    /// Move deleted files to the system trash instead.
+    pub trash: bool,
+}
+
+impl Default for OperationConfig {
+    fn default() -> Self {
+        OperationConfig {
+            backup_dir: PathBuf::from(DEFAULT_BACKUP_DIR),
+            dry_run: false,
+            verify: false,
+            trash: false,
+        }
+    }
+}
+
+/// What happened to one file during [`Operation::run`].
+#[derive(Debug)]
+pub struct FileOutcome {
+    pub path: PathBuf,
+    pub result: Result<(), Error>,
+}
+
+impl Operation {
+    /// The subcommand this operation corresponds to.
+    pub fn name(self) -> &'static str {
+        match self {
+            Operation::Backup => "backup",
+            Operation::Delete => "delete",
+            Operation::Restore => "restore",
+            Operation::Rename => "rename",
+        }
+    }
+
+    /// Applies the operation to every file directly inside `dir`, in name
+    /// order, and returns one outcome per file. A file that fails does not
+    /// stop the others; only a directory that cannot be read is an error.
+    pub fn run(self, dir: &Path, config: &OperationConfig) -> Result<Vec<FileOutcome>, Error> {
+        if self == Operation::Restore && config.dry_run {
+            return Err(Error::InvalidInput("restore has no dry run".to_string()));
+        }
+        let log_dir = dir;
+        let dir = match self {
+            Operation::Restore => dir.join(&config.backup_dir),
+            _ => dir.to_path_buf(),
+        };
+        let mut files = collect_log_files(&dir)?;
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        // Backups are recorded like the `backup` subcommand's, so `undo`
+        // reverts them too.
+        let mut undo_log =
+            (self == Operation::Backup && !config.dry_run).then(|| UndoLog::new(log_dir));
+        Ok(files
+            .into_iter()
+            .map(|file| {
+                let result = self.run_file(&file.path, log_dir, config);
+                if let (Some(undo_log), Ok(())) = (undo_log.as_mut(), &result)
+                    && let Err(err) = undo_log.record_backup(&file.path, &config.backup_dir)
+                {
+                    output::emit_err(format!(
+                        "Could not record {} for undo: {err}",
+                        file.path.display()
+                    ));
+                }
+                let result = result.map_err(Error::from);
+                FileOutcome {
+                    path: file.path,
+                    result,
+                }
+            })
+            .collect())
+    }
+
+    fn run_file(self, path: &Path, log_dir: &Path, config: &OperationConfig) -> io::Result<()> {
+        match self {
+            Operation::Backup => {
+                backup_log_file(path, &config.backup_dir, config.dry_run, config.verify)
+            }
+            Operation::Delete => {
+                let trash = config.trash.then_some(&SystemTrash as &dyn Trash);
+                delete_log_file(path, trash, config.dry_run)
+            }
+            Operation::Restore => restore_log_file(path, log_dir),
+            Operation::Rename => normalize_name(path, config.dry_run).map(|_| ()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_operation_run_backs_up_and_restores_each_file() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("b.log"), "b").unwrap();
+        fs::write(temp_dir.path().join("a.log"), "a").unwrap();
+        let config = OperationConfig::default();
+
+        let outcomes = Operation::Backup.run(temp_dir.path(), &config).unwrap();
+
+        let paths: Vec<_> = outcomes.iter().map(|outcome| &outcome.path).collect();
+        assert_eq!(
+            paths,
+            [
+                &temp_dir.path().join("a.log"),
+                &temp_dir.path().join("b.log")
+            ]
+        );
+        assert!(outcomes.iter().all(|outcome| outcome.result.is_ok()));
+        assert!(temp_dir.path().join("bak").join("a.log").exists());
+        assert!(!temp_dir.path().join("a.log").exists());
+        let manifest = fs::read_to_string(temp_dir.path().join(".ff-log-cli-undo")).unwrap();
+        assert_eq!(manifest.lines().count(), 3);
+
+        let outcomes = Operation::Restore.run(temp_dir.path(), &config).unwrap();
+
+        assert_eq!(outcomes.len(), 2);
+        assert_eq!(
+            fs::read_to_string(temp_dir.path().join("a.log")).unwrap(),
+            "a"
+        );
+    }
+
+    #[test]
+    fn test_operation_run_dry_run_changes_nothing() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("a.log");
+        fs::write(&file, "a").unwrap();
+        let config = OperationConfig {
+            dry_run: true,
+            ..OperationConfig::default()
+        };
+
+        for operation in [Operation::Backup, Operation::Delete] {
+            let outcomes = operation.run(temp_dir.path(), &config).unwrap();
+            assert!(outcomes[0].result.is_ok());
+        }
+
+        assert!(file.exists());
+        assert!(!temp_dir.path().join("bak").exists());
+        assert!(matches!(
+            Operation::Restore.run(temp_dir.path(), &config),
+            Err(Error::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn test_operation_run_reports_missing_directory() {
+        let temp_dir = TempDir::new().unwrap();
+        let missing = temp_dir.path().join("missing");
+
+        let result = Operation::Delete.run(&missing, &OperationConfig::default());
+
+        assert!(matches!(result, Err(Error::NotFound(_))));
+    }
+}
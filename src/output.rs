@@ -0,0 +1,118 @@
+//! Status output.
+//!
+//! Operations report each file they touch through [`say!`] and [`sayln!`]
+//! instead of `print!`/`println!`, so `--quiet` can silence those lines
+//! while the final summary is still shown.
+//!
+//! `-v` raises the verbosity to [`VERBOSE`], which adds the lines about
+//! paths that were passed over; `-vv` reaches [`DEBUG`], where [`debug!`]
+//! traces each decision (files scanned, filtered out, where a file goes)
+//! on stderr.
+//!
+//! Status lines go through [`emit`], which prefixes each line with the
+//! current time under `--timestamps`. Structured output such as JSON is
+//! printed directly so it stays parseable.
+
+use crate::util::format_rfc3339;
+use std::fmt::Display;
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::time::SystemTime;
+
+pub const VERBOSE: u8 = 1;
+pub const DEBUG: u8 = 2;
+
+static QUIET: AtomicBool = AtomicBool::new(false);
+static VERBOSITY: AtomicU8 = AtomicU8::new(0);
+static TIMESTAMPS: AtomicBool = AtomicBool::new(false);
+/// Set while a [`say!`] has started a line that is not finished yet.
+static MID_LINE: AtomicBool = AtomicBool::new(false);
+
+pub fn set_quiet(quiet: bool) {
+    QUIET.store(quiet, Ordering::Relaxed);
+}
+
+pub fn is_quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
+
+pub fn set_verbosity(level: u8) {
+    VERBOSITY.store(level, Ordering::Relaxed);
+}
+
+pub fn verbosity() -> u8 {
+    VERBOSITY.load(Ordering::Relaxed)
+}
+
+pub fn is_verbose() -> bool {
+    verbosity() >= VERBOSE
+}
+
+pub fn set_timestamps(timestamps: bool) {
+    TIMESTAMPS.store(timestamps, Ordering::Relaxed);
+}
+
+/// Prints a status line on stdout, completing a line begun by [`say!`].
+pub fn emit(message: impl Display) {
+    let prefix = prefix(MID_LINE.swap(false, Ordering::Relaxed));
+    println!("{prefix}{message}");
+}
+
+/// Prints the start of a status line that a later [`emit`] completes.
+pub fn emit_partial(message: impl Display) {
+    let prefix = prefix(MID_LINE.swap(true, Ordering::Relaxed));
+    print!("{prefix}{message}");
+}
+
+/// Prints a status line on stderr.
+pub fn emit_err(message: impl Display) {
+    eprintln!("{}{message}", prefix(false));
+}
+
+fn prefix(continues_line: bool) -> String {
+    let stamped = TIMESTAMPS.load(Ordering::Relaxed) && !continues_line;
+    stamp(stamped.then(SystemTime::now))
+}
+
+fn stamp(time: Option<SystemTime>) -> String {
+    time.map_or_else(String::new, |time| format!("[{}] ", format_rfc3339(time)))
+}
+
+macro_rules! say {
+    ($($arg:tt)*) => {
+        if !$crate::output::is_quiet() {
+            $crate::output::emit_partial(format_args!($($arg)*));
+        }
+    };
+}
+
+macro_rules! sayln {
+    ($($arg:tt)*) => {
+        if !$crate::output::is_quiet() {
+            $crate::output::emit(format_args!($($arg)*));
+        }
+    };
+}
+
+macro_rules! debug {
+    ($($arg:tt)*) => {
+        if $crate::output::verbosity() >= $crate::output::DEBUG {
+            eprintln!("debug: {}", format_args!($($arg)*));
+        }
+    };
+}
+
+pub(crate) use {debug, say, sayln};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn test_stamp() {
+        let time = UNIX_EPOCH + Duration::from_secs(1_704_067_200 + 3661);
+
+        assert_eq!(stamp(Some(time)), "[2024-01-01T01:01:01Z] ");
+        assert_eq!(stamp(None), "");
+    }
+}
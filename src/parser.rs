@@ -0,0 +1,378 @@
+//! Parsing of ACT network log lines.
+//!
+//! Every line in an ACT `Network_*.log` file is pipe-delimited: a two-digit
+//! decimal opcode, an ISO-8601 timestamp with a UTC offset, the
+//! opcode-specific fields, and a trailing checksum, e.g.
+//!
+//! ```text
+//! 21|2024-01-01T20:15:42.1230000+01:00|10001234|Tank Name|1F|Attack|40001234|Boss|...|a1b2c3d4
+//! ```
+
+use crate::util::{civil_from_days, days_from_civil};
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct DateTime {
+    pub year: i64,
+    pub month: u32,
+    pub day: u32,
+    pub hour: u32,
+    pub minute: u32,
+    pub second: u32,
+    pub nanosecond: u32,
+    pub offset_minutes: i32,
+}
+
+impl DateTime {
+    pub fn parse(value: &str) -> Option<DateTime> {
+        let (date, rest) = value.split_once('T')?;
+        let mut date_parts = date.splitn(3, '-');
+        let year = date_parts.next()?.parse().ok()?;
+        let month = date_parts.next()?.parse().ok()?;
+        let day = date_parts.next()?.parse().ok()?;
+
+        let offset_at = rest.rfind(['+', '-']).filter(|&at| at > 0);
+        let (time, offset_minutes) = match offset_at {
+            Some(at) => (&rest[..at], parse_offset(&rest[at..])?),
+            None => (rest.strip_suffix('Z').unwrap_or(rest), 0),
+        };
+
+        let (clock, fraction) = time.split_once('.').unwrap_or((time, ""));
+        let mut clock_parts = clock.splitn(3, ':');
+        let hour = clock_parts.next()?.parse().ok()?;
+        let minute = clock_parts.next()?.parse().ok()?;
+        let second = clock_parts.next()?.parse().ok()?;
+        let nanosecond = parse_fraction(fraction)?;
+
+        let valid = (1..=12).contains(&month)
+            && (1..=31).contains(&day)
+            && hour < 24
+            && minute < 60
+            && second < 61;
+        valid.then_some(DateTime {
+            year,
+            month,
+            day,
+            hour,
+            minute,
+            second,
+            nanosecond,
+            offset_minutes,
+        })
+    }
+
+    /// Milliseconds since the Unix epoch in UTC, for ordering events that
+    /// were logged with different offsets.
+    pub fn unix_millis(&self) -> i64 {
+        let days = days_from_civil(self.year, self.month, self.day);
+        let seconds = days * 86_400
+            + i64::from(self.hour) * 3600
+            + i64::from(self.minute) * 60
+            + i64::from(self.second)
+            - i64::from(self.offset_minutes) * 60;
+        seconds * 1000 + i64::from(self.nanosecond / 1_000_000)
+    }
+
+    pub fn from_unix_millis(millis: i64) -> DateTime {
+        let seconds = millis.div_euclid(1000);
+        let (year, month, day) = civil_from_days(seconds.div_euclid(86_400));
+        let of_day = seconds.rem_euclid(86_400) as u32;
+        DateTime {
+            year,
+            month,
+            day,
+            hour: of_day / 3600,
+            minute: of_day % 3600 / 60,
+            second: of_day % 60,
+            nanosecond: millis.rem_euclid(1000) as u32 * 1_000_000,
+            offset_minutes: 0,
+        }
+    }
+}
+
+impl fmt::Display for DateTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.offset_minutes < 0 { '-' } else { '+' };
+        let offset = self.offset_minutes.unsigned_abs();
+        write!(
+            f,
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:07}{sign}{:02}:{:02}",
+            self.year,
+            self.month,
+            self.day,
+            self.hour,
+            self.minute,
+            self.second,
+            self.nanosecond / 100,
+            offset / 60,
+            offset % 60
+        )
+    }
+}
+
+fn parse_offset(value: &str) -> Option<i32> {
+    let sign = if value.starts_with('-') { -1 } else { 1 };
+    let (hours, minutes) = value[1..].split_once(':').unwrap_or((&value[1..], "0"));
+    let hours: i32 = hours.parse().ok()?;
+    let minutes: i32 = minutes.parse().ok()?;
+    Some(sign * (hours * 60 + minutes))
+}
+
+fn parse_fraction(value: &str) -> Option<u32> {
+    if value.is_empty() {
+        return Some(0);
+    }
+    if value.len() > 9 || !value.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let digits: u32 = value.parse().ok()?;
+    Some(digits * 10u32.pow(9 - value.len() as u32))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Payload {
+    /// `00`: a chat or system log message
+    Chat {
+        channel: String,
+        sender: String,
+        message: String,
+    },
+    /// `03`: a player or NPC entering the combatant list
+    AddCombatant {
+        id: String,
+        name: String,
+        job: String,
+        level: String,
+        owner_id: String,
+        world: String,
+    },
+    /// `21`/`22`: an ability used on a single target or one of many
+    Ability {
+        source_id: String,
+        source_name: String,
+        ability_id: String,
+        ability_name: String,
+        target_id: String,
+        target_name: String,
+    },
+    /// Any other opcode, with its fields (checksum excluded) kept as-is
+    Other(Vec<String>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Event {
+    pub opcode: u8,
+    pub timestamp: DateTime,
+    pub payload: Payload,
+}
+
+impl Event {
+    pub fn is_player(id: &str) -> bool {
+        id.starts_with("10") && id.len() == 8
+    }
+}
+
+fn is_opcode(field: &str) -> bool {
+    !field.is_empty() && field.len() <= 3 && field.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// Reads only the leading opcode and timestamp of a line, for callers that
+/// order lines without caring about the event itself.
+pub fn parse_timestamp(line: &str) -> Option<DateTime> {
+    let mut fields = line.splitn(3, '|');
+    if !is_opcode(fields.next()?) {
+        return None;
+    }
+    DateTime::parse(fields.next()?.trim_end_matches(['\r', '\n']))
+}
+
+pub fn parse_line(line: &str) -> Option<Event> {
+    let mut fields: Vec<&str> = line.trim_end_matches(['\r', '\n']).split('|').collect();
+    if fields.len() < 3 {
+        return None;
+    }
+    let opcode_field = fields[0];
+    if !is_opcode(opcode_field) {
+        return None;
+    }
+    let opcode = opcode_field.parse().ok()?;
+    let timestamp = DateTime::parse(fields[1])?;
+    // The last field is ACT's per-line checksum.
+    fields.pop();
+    let data = &fields[2..];
+
+    let field = |index: usize| data.get(index).map(|value| value.to_string());
+    let payload = match opcode {
+        0 => Payload::Chat {
+            channel: field(0)?,
+            sender: field(1)?,
+            message: data.get(2..).map(|rest| rest.join("|"))?,
+        },
+        3 => Payload::AddCombatant {
+            id: field(0)?,
+            name: field(1)?,
+            job: field(2)?,
+            level: field(3)?,
+            owner_id: field(4)?,
+            world: field(6).unwrap_or_default(),
+        },
+        21 | 22 => Payload::Ability {
+            source_id: field(0)?,
+            source_name: field(1)?,
+            ability_id: field(2)?,
+            ability_name: field(3)?,
+            target_id: field(4)?,
+            target_name: field(5)?,
+        },
+        _ => Payload::Other(data.iter().map(|value| value.to_string()).collect()),
+    };
+
+    Some(Event {
+        opcode,
+        timestamp,
+        payload,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHAT: &str =
+        "00|2024-01-01T20:15:42.1230000+01:00|0839||Ready check initiated.|0123456789abcdef";
+    const ADD_COMBATANT: &str = "03|2024-01-01T20:15:40.0000000+01:00|10001234|Tank Name|13|5A|0000|49|Gilgamesh|0|0|120000|120000|10000|10000|||100.00|100.00|0.00|-3.14|fedcba9876543210";
+    const ABILITY: &str = "21|2024-01-01T20:15:43.5000000+01:00|10001234|Tank Name|1F|Attack|40001234|Boss|710003|1A2B0000|0|0|0|0|0|0|0|0|0|0|0|0|0|0|1000|1000|0|0|||100.00|100.00|0.00|0.00|120000|120000|10000|10000|||100.00|100.00|0.00|0.00|00001234|0|1|abcdef0123456789";
+
+    #[test]
+    fn test_parse_chat_line() {
+        let event = parse_line(CHAT).unwrap();
+
+        assert_eq!(event.opcode, 0);
+        assert_eq!(
+            event.payload,
+            Payload::Chat {
+                channel: "0839".to_string(),
+                sender: String::new(),
+                message: "Ready check initiated.".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_add_combatant_line() {
+        let event = parse_line(ADD_COMBATANT).unwrap();
+
+        assert_eq!(event.opcode, 3);
+        let Payload::AddCombatant {
+            id,
+            name,
+            job,
+            world,
+            ..
+        } = event.payload
+        else {
+            panic!("expected add combatant payload");
+        };
+        assert_eq!(id, "10001234");
+        assert_eq!(name, "Tank Name");
+        assert_eq!(job, "13");
+        assert_eq!(world, "Gilgamesh");
+        assert!(Event::is_player(&id));
+    }
+
+    #[test]
+    fn test_parse_ability_line() {
+        let event = parse_line(ABILITY).unwrap();
+
+        assert_eq!(event.opcode, 21);
+        assert_eq!(
+            event.payload,
+            Payload::Ability {
+                source_id: "10001234".to_string(),
+                source_name: "Tank Name".to_string(),
+                ability_id: "1F".to_string(),
+                ability_name: "Attack".to_string(),
+                target_id: "40001234".to_string(),
+                target_name: "Boss".to_string(),
+            }
+        );
+        assert!(!Event::is_player("40001234"));
+    }
+
+    #[test]
+    fn test_parse_unknown_opcode_keeps_fields() {
+        let event = parse_line("26|2024-01-01T20:15:44.0000000+01:00|A|B|C|hash").unwrap();
+
+        assert_eq!(event.opcode, 26);
+        assert_eq!(
+            event.payload,
+            Payload::Other(vec!["A".to_string(), "B".to_string(), "C".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_parse_timestamp() {
+        let event = parse_line(CHAT).unwrap();
+
+        assert_eq!(
+            event.timestamp,
+            DateTime {
+                year: 2024,
+                month: 1,
+                day: 1,
+                hour: 20,
+                minute: 15,
+                second: 42,
+                nanosecond: 123_000_000,
+                offset_minutes: 60,
+            }
+        );
+        assert_eq!(
+            event.timestamp.to_string(),
+            "2024-01-01T20:15:42.1230000+01:00"
+        );
+    }
+
+    #[test]
+    fn test_unix_millis_respects_offset() {
+        let plus_one = DateTime::parse("2024-01-01T21:00:00.0000000+01:00").unwrap();
+        let utc = DateTime::parse("2024-01-01T20:00:00.0000000Z").unwrap();
+        let minus_five = DateTime::parse("2024-01-01T15:00:00.0000000-05:00").unwrap();
+
+        assert_eq!(plus_one.unix_millis(), utc.unix_millis());
+        assert_eq!(minus_five.unix_millis(), utc.unix_millis());
+        assert_eq!(utc.unix_millis(), 1_704_139_200_000);
+    }
+
+    #[test]
+    fn test_from_unix_millis_roundtrip() {
+        let utc = DateTime::parse("2024-02-29T23:59:58.2500000Z").unwrap();
+
+        assert_eq!(DateTime::from_unix_millis(utc.unix_millis()), utc);
+    }
+
+    #[test]
+    fn test_parse_line_rejects_non_log_lines() {
+        assert!(parse_line("").is_none());
+        assert!(parse_line("just some notes").is_none());
+        assert!(parse_line("xx|2024-01-01T20:15:42.0000000+01:00|a|hash").is_none());
+        assert!(parse_line("21|not a timestamp|a|hash").is_none());
+        assert!(parse_line("21|2024-01-01T20:15:42.0000000+01:00|too|few|hash").is_none());
+    }
+
+    #[test]
+    fn test_parse_line_trims_line_endings() {
+        let line = format!("{CHAT}\r\n");
+
+        assert!(parse_line(&line).is_some());
+    }
+
+    #[test]
+    fn test_parse_timestamp_reads_leading_fields_only() {
+        let timestamp = parse_timestamp("00|2024-01-01T20:15:42.0000000+01:00|short").unwrap();
+
+        assert_eq!(timestamp.to_string(), "2024-01-01T20:15:42.0000000+01:00");
+        assert_eq!(parse_timestamp("not a log line"), None);
+        assert_eq!(parse_timestamp("abc|2024-01-01T20:15:42+01:00|x"), None);
+    }
+}
@@ -0,0 +1,395 @@
+//! Locating the ACT log directory.
+
+use std::env;
+use std::ffi::{OsStr, OsString};
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+/// Environment variable naming the log directory used for `default`.
+pub const FFLOGS_DIR_VAR: &str = "FFLOGS_DIR";
+
+/// Environment variable that makes `default` an error, like `--no-default`.
+pub const NO_DEFAULT_VAR: &str = "FF_LOG_CLI_NO_DEFAULT";
+
+/// Whether the value of [`NO_DEFAULT_VAR`] forbids `default`; any non-empty
+/// value does.
+pub fn no_default_from_env(value: Option<OsString>) -> bool {
+    value.is_some_and(|value| !value.is_empty())
+}
+
+/// The directory named by the value of [`FFLOGS_DIR_VAR`]; unset and empty
+/// both mean no directory. The value is passed in rather than read here so
+/// tests never touch the process environment.
+pub fn fflogs_dir_from_env(value: Option<OsString>) -> Option<PathBuf> {
+    value.filter(|value| !value.is_empty()).map(PathBuf::from)
+}
+
+/// The user's home directory, which a leading `~` stands for: `HOME`, or
+/// `USERPROFILE` on Windows, where `HOME` is usually unset.
+pub fn home_dir() -> Option<PathBuf> {
+    let home = env::var_os("HOME");
+    #[cfg(windows)]
+    let home = home.or_else(|| env::var_os("USERPROFILE"));
+    home.filter(|home| !home.is_empty()).map(PathBuf::from)
+}
+
+/// `path` made absolute: a leading `~` is replaced by `home`, a relative
+/// path is taken from `cwd`, and `.` and `..` are resolved without touching
+/// the file system. Like [`fflogs_dir_from_env`], the home and working
+/// directories are passed in so tests never depend on the environment. An
+/// empty path, as left by an unset shell variable, stays empty rather than
+/// becoming the working directory.
+pub fn expand_path(path: &Path, home: Option<&Path>, cwd: &Path) -> PathBuf {
+    if path.as_os_str().is_empty() {
+        return PathBuf::new();
+    }
+    let path = match (path.strip_prefix("~"), home) {
+        (Ok(rest), Some(home)) => home.join(rest),
+        _ => path.to_path_buf(),
+    };
+    let mut expanded = PathBuf::new();
+    for component in cwd.join(path).components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir if expanded.file_name().is_some() => {
+                expanded.pop();
+            }
+            // `..` cannot climb above the root.
+            Component::ParentDir if expanded.has_root() => {}
+            component => expanded.push(component),
+        }
+    }
+    expanded
+}
+
+/// The FFXIV log folder ACT creates under `base`.
+pub fn act_log_dir(base: &Path) -> PathBuf {
+    base.join("Advanced Combat Tracker").join("FFXIVLogs")
+}
+
+/// The places ACT is usually found on this platform, most likely first.
+pub fn default_dir_candidates() -> Vec<PathBuf> {
+    #[cfg(windows)]
+    let candidates = env::var_os("APPDATA")
+        .map(|app_data| vec![act_log_dir(Path::new(&app_data))])
+        .unwrap_or_default();
+
+    #[cfg(target_os = "macos")]
+    let candidates = env::var_os("HOME")
+        .map(|home| candidates_for_macos_home(Path::new(&home)))
+        .unwrap_or_default();
+
+    #[cfg(all(unix, not(target_os = "macos")))]
+    let candidates = env::var_os("HOME")
+        .map(|home| vec![act_log_dir(Path::new(&home))])
+        .unwrap_or_default();
+
+    candidates
+}
+
+/// ACT runs under Wine or CrossOver on macOS, which keep their application
+/// data in `~/Library/Application Support`.
+#[cfg(any(target_os = "macos", test))]
+fn candidates_for_macos_home(home: &Path) -> Vec<PathBuf> {
+    vec![
+        act_log_dir(&home.join("Library").join("Application Support")),
+        act_log_dir(home),
+    ]
+}
+
+/// The first candidate that exists, or the most likely one when none do so
+/// the error message names a sensible path. Wine and Proton sometimes
+/// create the log folder in another case, e.g. `ffxivlogs`, so when no
+/// candidate exists as spelled, their parents are searched for one whose
+/// name differs only in case.
+pub fn default_fflogs_dir() -> Option<PathBuf> {
+    let candidates = default_dir_candidates();
+    candidates
+        .iter()
+        .find(|candidate| candidate.is_dir())
+        .cloned()
+        .or_else(|| {
+            candidates.iter().find_map(|candidate| {
+                let name = candidate.file_name()?.to_str()?;
+                find_dir_case_insensitive(candidate.parent()?, name)
+            })
+        })
+        .or(candidates.first().cloned())
+}
+
+/// A directory in `parent` whose name is `name` ignoring ASCII case. Should
+/// several match, the first by name is taken so the choice is stable.
+pub fn find_dir_case_insensitive(parent: &Path, name: &str) -> Option<PathBuf> {
+    let mut matches: Vec<PathBuf> = parent
+        .read_dir()
+        .ok()?
+        .flatten()
+        .filter(|entry| {
+            entry
+                .file_name()
+                .to_str()
+                .is_some_and(|entry_name| entry_name.eq_ignore_ascii_case(name))
+        })
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .collect();
+    matches.sort();
+    matches.into_iter().next()
+}
+
+/// `path` in the form file operations should use. On Windows it is made
+/// absolute and given the `\\?\` extended-length prefix, which lifts the
+/// 260 character MAX_PATH limit that deep ACT folders can run into.
+#[cfg(windows)]
+pub fn to_extended_path(path: &Path) -> PathBuf {
+    match std::path::absolute(path) {
+        Ok(absolute) => match absolute.to_str() {
+            Some(absolute) => PathBuf::from(with_extended_prefix(absolute)),
+            None => absolute,
+        },
+        Err(_) => path.to_path_buf(),
+    }
+}
+
+/// `path` in the form file operations should use; unchanged outside
+/// Windows, where there is no MAX_PATH limit to work around.
+#[cfg(not(windows))]
+pub fn to_extended_path(path: &Path) -> PathBuf {
+    path.to_path_buf()
+}
+
+/// Prefixes an absolute Windows path with `\\?\`, or `\\?\UNC\` for a
+/// network share. Paths that already carry the prefix are returned as is.
+#[cfg(any(windows, test))]
+fn with_extended_prefix(absolute: &str) -> String {
+    if absolute.starts_with(r"\\?\") {
+        absolute.to_string()
+    } else if let Some(share) = absolute.strip_prefix(r"\\") {
+        format!(r"\\?\UNC\{share}")
+    } else {
+        format!(r"\\?\{absolute}")
+    }
+}
+
+/// Replaces an OS error about an over-long name with one that says so and
+/// names the length of `path`; other errors pass through untouched.
+pub fn explain_long_path(err: io::Error, path: &Path) -> io::Error {
+    if err.kind() != io::ErrorKind::InvalidFilename {
+        return err;
+    }
+    let length = path.as_os_str().len();
+    io::Error::new(
+        err.kind(),
+        format!(
+            "{err}: the path is {length} characters long and may exceed the system's \
+             path length limit; on Windows enable long paths or move the ACT folder \
+             closer to the drive root"
+        ),
+    )
+}
+
+/// The file name of `path`, or an `InvalidInput` error for a path such as
+/// `/` or `..` that does not name a file.
+pub fn file_name(path: &Path) -> io::Result<&OsStr> {
+    path.file_name().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} is not a file", path.display()),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_act_log_dir_joins_components() {
+        let dir = act_log_dir(Path::new("base"));
+
+        let components: Vec<_> = dir.components().map(|c| c.as_os_str()).collect();
+        assert_eq!(components, ["base", "Advanced Combat Tracker", "FFXIVLogs"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_act_log_dir_uses_forward_slashes_on_unix() {
+        let dir = act_log_dir(Path::new("/home/user"));
+
+        assert_eq!(
+            dir,
+            PathBuf::from("/home/user/Advanced Combat Tracker/FFXIVLogs")
+        );
+        assert!(!dir.to_string_lossy().contains('\\'));
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_act_log_dir_uses_backslashes_on_windows() {
+        let dir = act_log_dir(Path::new(r"C:\Users\user\AppData\Roaming"));
+
+        assert_eq!(
+            dir.to_string_lossy(),
+            r"C:\Users\user\AppData\Roaming\Advanced Combat Tracker\FFXIVLogs"
+        );
+    }
+
+    #[test]
+    fn test_candidates_for_macos_home_prefers_application_support() {
+        let candidates = candidates_for_macos_home(Path::new("home"));
+
+        assert_eq!(
+            candidates,
+            [
+                Path::new("home")
+                    .join("Library")
+                    .join("Application Support")
+                    .join("Advanced Combat Tracker")
+                    .join("FFXIVLogs"),
+                Path::new("home")
+                    .join("Advanced Combat Tracker")
+                    .join("FFXIVLogs"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find_dir_case_insensitive() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::create_dir(temp_dir.path().join("ffxivlogs")).unwrap();
+        fs::write(temp_dir.path().join("Notes"), "").unwrap();
+
+        assert_eq!(
+            find_dir_case_insensitive(temp_dir.path(), "FFXIVLogs"),
+            Some(temp_dir.path().join("ffxivlogs"))
+        );
+        assert_eq!(find_dir_case_insensitive(temp_dir.path(), "notes"), None);
+        assert_eq!(
+            find_dir_case_insensitive(&temp_dir.path().join("missing"), "FFXIVLogs"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_fflogs_dir_from_env_set() {
+        let value = Some(OsString::from("/logs/ffxiv"));
+
+        assert_eq!(
+            fflogs_dir_from_env(value),
+            Some(PathBuf::from("/logs/ffxiv"))
+        );
+    }
+
+    #[test]
+    fn test_fflogs_dir_from_env_unset_or_empty() {
+        assert_eq!(fflogs_dir_from_env(None), None);
+        assert_eq!(fflogs_dir_from_env(Some(OsString::new())), None);
+    }
+
+    #[test]
+    fn test_expand_path_replaces_leading_tilde() {
+        let home = Path::new("/home/user");
+        let cwd = Path::new("/work");
+
+        assert_eq!(expand_path(Path::new("~"), Some(home), cwd), home);
+        assert_eq!(
+            expand_path(Path::new("~/sub"), Some(home), cwd),
+            Path::new("/home/user/sub")
+        );
+        assert_eq!(
+            expand_path(Path::new("~user/sub"), Some(home), cwd),
+            Path::new("/work/~user/sub")
+        );
+        assert_eq!(
+            expand_path(Path::new("~/sub"), None, cwd),
+            Path::new("/work/~/sub")
+        );
+    }
+
+    #[test]
+    fn test_expand_path_resolves_relative_paths() {
+        let cwd = Path::new("/work/logs");
+
+        assert_eq!(expand_path(Path::new("."), None, cwd), cwd);
+        assert_eq!(expand_path(Path::new(".."), None, cwd), Path::new("/work"));
+        assert_eq!(
+            expand_path(Path::new("./raid/../bak"), None, cwd),
+            Path::new("/work/logs/bak")
+        );
+        assert_eq!(
+            expand_path(Path::new("../../../.."), None, cwd),
+            Path::new("/")
+        );
+        assert_eq!(
+            expand_path(Path::new("/abs/./logs"), None, cwd),
+            Path::new("/abs/logs")
+        );
+    }
+
+    #[test]
+    fn test_expand_path_keeps_empty_path() {
+        assert_eq!(
+            expand_path(Path::new(""), None, Path::new("/work")),
+            PathBuf::new()
+        );
+    }
+
+    #[test]
+    fn test_no_default_from_env() {
+        assert!(no_default_from_env(Some(OsString::from("1"))));
+        assert!(!no_default_from_env(Some(OsString::new())));
+        assert!(!no_default_from_env(None));
+    }
+
+    #[test]
+    fn test_expand_path_keeps_leading_parents_without_working_directory() {
+        assert_eq!(
+            expand_path(Path::new("../logs/."), None, Path::new("")),
+            Path::new("../logs")
+        );
+    }
+
+    #[test]
+    fn test_with_extended_prefix() {
+        assert_eq!(
+            with_extended_prefix(r"C:\Users\me\ACT"),
+            r"\\?\C:\Users\me\ACT"
+        );
+        assert_eq!(
+            with_extended_prefix(r"\\server\share\ACT"),
+            r"\\?\UNC\server\share\ACT"
+        );
+        assert_eq!(with_extended_prefix(r"\\?\C:\ACT"), r"\\?\C:\ACT");
+    }
+
+    #[test]
+    fn test_explain_long_path_mentions_length() {
+        let path = Path::new("deep").join("x".repeat(300));
+        let err = io::Error::from(io::ErrorKind::InvalidFilename);
+
+        let explained = explain_long_path(err, &path);
+
+        assert_eq!(explained.kind(), io::ErrorKind::InvalidFilename);
+        assert!(explained.to_string().contains("305 characters long"));
+        assert!(explained.to_string().contains("path length limit"));
+    }
+
+    #[test]
+    fn test_file_name_rejects_paths_without_one() {
+        assert_eq!(file_name(Path::new("logs/a.log")).unwrap(), "a.log");
+        let err = file_name(Path::new("/")).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(err.to_string(), "/ is not a file");
+    }
+
+    #[test]
+    fn test_explain_long_path_passes_other_errors_through() {
+        let err = io::Error::new(io::ErrorKind::NotFound, "gone");
+
+        let explained = explain_long_path(err, Path::new("a.log"));
+
+        assert_eq!(explained.to_string(), "gone");
+    }
+}
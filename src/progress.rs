@@ -0,0 +1,344 @@
+use crate::json;
+use clap::ValueEnum;
+use std::collections::VecDeque;
+use std::io::{self, IsTerminal, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+const JSON_THROTTLE: Duration = Duration::from_millis(250);
+/// How many of the most recent files the transfer rate is averaged over,
+/// so one tiny or huge file does not swing the estimate.
+const RATE_WINDOW: usize = 8;
+/// Carriage return plus "erase to end of line".
+const CLEAR_LINE: &str = "\r\x1b[K";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum ProgressFormat {
+    /// One `[done/total] file` line per processed file
+    Human,
+    /// Throttled JSON objects, one per line
+    Json,
+}
+
+pub struct Progress {
+    format: ProgressFormat,
+    total: usize,
+    done: usize,
+    /// The size of every file in the run, counted before it starts.
+    bytes_total: u64,
+    bytes_done: u64,
+    last_emit: Option<Instant>,
+    /// When the previous file finished, or the run started.
+    last_advance: Instant,
+    /// Bytes and time taken by the last [`RATE_WINDOW`] files.
+    samples: VecDeque<(u64, Duration)>,
+    /// Redraw a single status line instead of printing one per file; only
+    /// used for human output on a terminal.
+    in_place: bool,
+    /// Whether an in-place line is currently on screen.
+    drawn: bool,
+}
+
+impl Progress {
+    pub fn new(format: ProgressFormat, total: usize, bytes_total: u64) -> Self {
+        Progress {
+            format,
+            total,
+            done: 0,
+            bytes_total,
+            bytes_done: 0,
+            last_emit: None,
+            last_advance: Instant::now(),
+            samples: VecDeque::with_capacity(RATE_WINDOW),
+            in_place: format == ProgressFormat::Human && io::stderr().is_terminal(),
+            drawn: false,
+        }
+    }
+
+    pub fn advance(&mut self, current: &Path, bytes: u64) {
+        let now = Instant::now();
+        self.done += 1;
+        self.bytes_done += bytes;
+        self.record_sample(bytes, now);
+
+        if !self.should_emit(now) {
+            return;
+        }
+        let line = self.render(current);
+        // Anything the file's operation printed must reach the terminal
+        // before the status line is drawn below it.
+        let _ = io::stdout().flush();
+        let _ = self.write_update(&mut io::stderr().lock(), &line);
+    }
+
+    /// Erases the in-place status line so per-file output starts on a clean
+    /// line. Call before processing each file.
+    pub fn clear(&mut self) {
+        let _ = self.write_clear(&mut io::stderr().lock());
+    }
+
+    /// Leaves the last status line on screen and moves past it.
+    pub fn finish(&mut self) {
+        if self.drawn {
+            let _ = writeln!(io::stderr().lock());
+            self.drawn = false;
+        }
+    }
+
+    fn write_update(&mut self, out: &mut impl Write, line: &str) -> io::Result<()> {
+        if self.in_place {
+            write!(out, "{CLEAR_LINE}{line}")?;
+            self.drawn = true;
+            out.flush()
+        } else {
+            writeln!(out, "{line}")
+        }
+    }
+
+    fn write_clear(&mut self, out: &mut impl Write) -> io::Result<()> {
+        if self.drawn {
+            write!(out, "{CLEAR_LINE}")?;
+            self.drawn = false;
+            out.flush()?;
+        }
+        Ok(())
+    }
+
+    fn record_sample(&mut self, bytes: u64, now: Instant) {
+        if self.samples.len() == RATE_WINDOW {
+            self.samples.pop_front();
+        }
+        self.samples
+            .push_back((bytes, now.duration_since(self.last_advance)));
+        self.last_advance = now;
+    }
+
+    /// Bytes per second over the last few files.
+    fn rate(&self) -> Option<f64> {
+        let bytes: u64 = self.samples.iter().map(|&(bytes, _)| bytes).sum();
+        let elapsed: Duration = self.samples.iter().map(|&(_, elapsed)| elapsed).sum();
+        (bytes > 0 && !elapsed.is_zero()).then(|| bytes as f64 / elapsed.as_secs_f64())
+    }
+
+    /// The time left at the current rate, once there is one to go by.
+    fn eta(&self) -> Option<Duration> {
+        if self.done >= self.total {
+            return None;
+        }
+        let remaining = self.bytes_total.saturating_sub(self.bytes_done);
+        let rate = self.rate()?;
+        Some(Duration::from_secs_f64(remaining as f64 / rate))
+    }
+
+    fn should_emit(&mut self, now: Instant) -> bool {
+        let due = match (self.format, self.last_emit) {
+            (ProgressFormat::Human, _) => true,
+            (ProgressFormat::Json, None) => true,
+            (ProgressFormat::Json, Some(last)) => {
+                self.done == self.total || now.duration_since(last) >= JSON_THROTTLE
+            }
+        };
+        if due {
+            self.last_emit = Some(now);
+        }
+        due
+    }
+
+    fn render(&self, current: &Path) -> String {
+        let name = current
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned();
+        match (self.format, self.eta()) {
+            (ProgressFormat::Human, Some(eta)) => format!(
+                "[{}/{}] {name} ({} remaining)",
+                self.done,
+                self.total,
+                format_eta(eta)
+            ),
+            (ProgressFormat::Human, None) => format!("[{}/{}] {name}", self.done, self.total),
+            (ProgressFormat::Json, eta) => format!(
+                "{{\"done\":{},\"total\":{},\"bytes_done\":{},\"bytes_total\":{},\"eta_secs\":{},\"current\":{}}}",
+                self.done,
+                self.total,
+                self.bytes_done,
+                self.bytes_total,
+                eta.map_or("null".to_string(), |eta| eta.as_secs().to_string()),
+                json::escape(&name)
+            ),
+        }
+    }
+}
+
+/// A rough remaining time such as `~45s`, `~2m` or `~1h 5m`.
+fn format_eta(eta: Duration) -> String {
+    let secs = eta.as_secs();
+    match secs {
+        0..60 => format!("~{secs}s"),
+        60..3600 => format!("~{}m", secs.div_ceil(60)),
+        _ => format!("~{}h {}m", secs / 3600, secs % 3600 / 60),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_human() {
+        let mut progress = Progress::new(ProgressFormat::Human, 340, 0);
+        progress.done = 12;
+
+        assert_eq!(
+            progress.render(Path::new("/logs/test.log")),
+            "[12/340] test.log"
+        );
+    }
+
+    #[test]
+    fn test_render_json() {
+        let mut progress = Progress::new(ProgressFormat::Json, 340, 4096);
+        progress.done = 12;
+        progress.bytes_done = 2048;
+
+        assert_eq!(
+            progress.render(Path::new("/logs/test \"1\".log")),
+            "{\"done\":12,\"total\":340,\"bytes_done\":2048,\"bytes_total\":4096,\"eta_secs\":null,\"current\":\"test \\\"1\\\".log\"}"
+        );
+    }
+
+    #[test]
+    fn test_json_updates_are_throttled() {
+        let mut progress = Progress::new(ProgressFormat::Json, 10, 0);
+        let start = Instant::now();
+
+        progress.done = 1;
+        assert!(progress.should_emit(start));
+        progress.done = 2;
+        assert!(!progress.should_emit(start + Duration::from_millis(10)));
+        progress.done = 3;
+        assert!(progress.should_emit(start + JSON_THROTTLE));
+    }
+
+    #[test]
+    fn test_json_final_update_always_emitted() {
+        let mut progress = Progress::new(ProgressFormat::Json, 2, 0);
+        let start = Instant::now();
+
+        progress.done = 1;
+        assert!(progress.should_emit(start));
+        progress.done = 2;
+        assert!(progress.should_emit(start + Duration::from_millis(1)));
+    }
+
+    #[test]
+    fn test_human_updates_every_file() {
+        let mut progress = Progress::new(ProgressFormat::Human, 3, 0);
+        let start = Instant::now();
+
+        for done in 1..=3 {
+            progress.done = done;
+            assert!(progress.should_emit(start));
+        }
+    }
+
+    #[test]
+    fn test_in_place_updates_overwrite_the_line() {
+        let mut progress = Progress::new(ProgressFormat::Human, 2, 0);
+        progress.in_place = true;
+        let mut out = Vec::new();
+
+        progress.write_update(&mut out, "[1/2] a.log").unwrap();
+        progress.write_update(&mut out, "[2/2] b.log").unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "\r\x1b[K[1/2] a.log\r\x1b[K[2/2] b.log"
+        );
+        assert!(progress.drawn);
+    }
+
+    #[test]
+    fn test_clear_only_erases_a_drawn_line() {
+        let mut progress = Progress::new(ProgressFormat::Human, 2, 0);
+        progress.in_place = true;
+        let mut out = Vec::new();
+
+        progress.write_clear(&mut out).unwrap();
+        assert!(out.is_empty());
+
+        progress.write_update(&mut out, "[1/2] a.log").unwrap();
+        out.clear();
+        progress.write_clear(&mut out).unwrap();
+        progress.write_clear(&mut out).unwrap();
+
+        assert_eq!(String::from_utf8(out).unwrap(), "\r\x1b[K");
+        assert!(!progress.drawn);
+    }
+
+    #[test]
+    fn test_updates_are_lines_when_not_in_place() {
+        let mut progress = Progress::new(ProgressFormat::Human, 2, 0);
+        progress.in_place = false;
+        let mut out = Vec::new();
+
+        progress.write_update(&mut out, "[1/2] a.log").unwrap();
+        progress.write_clear(&mut out).unwrap();
+
+        assert_eq!(String::from_utf8(out).unwrap(), "[1/2] a.log\n");
+    }
+
+    #[test]
+    fn test_advance_accumulates_bytes() {
+        let mut progress = Progress::new(ProgressFormat::Json, 2, 0);
+
+        progress.advance(Path::new("a.log"), 100);
+        progress.advance(Path::new("b.log"), 50);
+
+        assert_eq!(progress.done, 2);
+        assert_eq!(progress.bytes_done, 150);
+    }
+
+    #[test]
+    fn test_eta_averages_the_recent_rate() {
+        let mut progress = Progress::new(ProgressFormat::Human, 4, 4_000);
+        let start = progress.last_advance;
+        assert_eq!(progress.eta(), None);
+
+        // 100 bytes in a second, then 900 in a second: 500 bytes/s.
+        progress.done = 2;
+        progress.bytes_done = 1_000;
+        progress.record_sample(100, start + Duration::from_secs(1));
+        progress.record_sample(900, start + Duration::from_secs(2));
+
+        assert_eq!(progress.eta(), Some(Duration::from_secs(6)));
+        assert_eq!(
+            progress.render(Path::new("b.log")),
+            "[2/4] b.log (~6s remaining)"
+        );
+
+        progress.done = 4;
+        assert_eq!(progress.eta(), None);
+    }
+
+    #[test]
+    fn test_rate_forgets_old_files() {
+        let mut progress = Progress::new(ProgressFormat::Human, 100, 0);
+        let start = progress.last_advance;
+
+        progress.record_sample(1_000_000, start + Duration::from_secs(1));
+        for i in 0..RATE_WINDOW as u64 {
+            progress.record_sample(10, start + Duration::from_secs(2 + i));
+        }
+
+        assert_eq!(progress.rate(), Some(10.0));
+    }
+
+    #[test]
+    fn test_format_eta() {
+        assert_eq!(format_eta(Duration::from_secs(45)), "~45s");
+        assert_eq!(format_eta(Duration::from_secs(61)), "~2m");
+        assert_eq!(format_eta(Duration::from_secs(3900)), "~1h 5m");
+    }
+}
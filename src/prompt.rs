@@ -0,0 +1,116 @@
+use std::io::{self, BufRead, Write};
+
+pub fn confirm(question: &str) -> io::Result<bool> {
+    confirm_from(&mut io::stdin().lock(), question)
+}
+
+fn confirm_from(input: &mut impl BufRead, question: &str) -> io::Result<bool> {
+    print!("{question} [y/N] ");
+    io::stdout().flush()?;
+
+    let mut answer = String::new();
+    input.read_line(&mut answer)?;
+    Ok(is_yes(&answer))
+}
+
+fn is_yes(answer: &str) -> bool {
+    matches!(answer.trim().to_lowercase().as_str(), "y" | "yes")
+}
+
+/// An answer to a per-file question.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Answer {
+    Yes,
+    No,
+    /// Yes to this file and every one after it.
+    All,
+    /// No to this file and stop.
+    Quit,
+}
+
+/// Asks `question` with `[y/n/a/q]` until the answer is one of those. The
+/// end of input counts as quitting, so a closed stdin never says yes.
+pub fn ask(question: &str) -> io::Result<Answer> {
+    ask_from(&mut io::stdin().lock(), question)
+}
+
+fn ask_from(input: &mut impl BufRead, question: &str) -> io::Result<Answer> {
+    loop {
+        print!("{question} [y/n/a/q] ");
+        io::stdout().flush()?;
+
+        let mut answer = String::new();
+        if input.read_line(&mut answer)? == 0 {
+            println!();
+            return Ok(Answer::Quit);
+        }
+        match parse_answer(&answer) {
+            Some(answer) => return Ok(answer),
+            None => println!("Please answer y (yes), n (no), a (all) or q (quit)."),
+        }
+    }
+}
+
+fn parse_answer(answer: &str) -> Option<Answer> {
+    match answer.trim().to_lowercase().as_str() {
+        "y" | "yes" => Some(Answer::Yes),
+        "n" | "no" => Some(Answer::No),
+        "a" | "all" => Some(Answer::All),
+        "q" | "quit" => Some(Answer::Quit),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_yes_accepts_y_and_yes() {
+        assert!(is_yes("y\n"));
+        assert!(is_yes("YES\r\n"));
+        assert!(is_yes("  Yes  "));
+    }
+
+    #[test]
+    fn test_is_yes_rejects_everything_else() {
+        assert!(!is_yes("\n"));
+        assert!(!is_yes("n"));
+        assert!(!is_yes("yeah"));
+        assert!(!is_yes(""));
+    }
+
+    #[test]
+    fn test_confirm_from_reads_answer() {
+        let mut input = "y\n".as_bytes();
+        assert!(confirm_from(&mut input, "Delete 3 files?").unwrap());
+
+        let mut input = "no\n".as_bytes();
+        assert!(!confirm_from(&mut input, "Delete 3 files?").unwrap());
+    }
+
+    #[test]
+    fn test_confirm_from_end_of_input_is_no() {
+        let mut input = "".as_bytes();
+        assert!(!confirm_from(&mut input, "Delete 3 files?").unwrap());
+    }
+
+    #[test]
+    fn test_parse_answer() {
+        assert_eq!(parse_answer("y\n"), Some(Answer::Yes));
+        assert_eq!(parse_answer("No"), Some(Answer::No));
+        assert_eq!(parse_answer(" a "), Some(Answer::All));
+        assert_eq!(parse_answer("QUIT\r\n"), Some(Answer::Quit));
+        assert_eq!(parse_answer("\n"), None);
+        assert_eq!(parse_answer("maybe"), None);
+    }
+
+    #[test]
+    fn test_ask_from_repeats_until_answered() {
+        let mut input = "\nwhat\nn\n".as_bytes();
+        assert_eq!(ask_from(&mut input, "Delete a.log?").unwrap(), Answer::No);
+
+        let mut input = "".as_bytes();
+        assert_eq!(ask_from(&mut input, "Delete a.log?").unwrap(), Answer::Quit);
+    }
+}
@@ -0,0 +1,425 @@
+//! A small backtracking regular expression matcher for `--regex`.
+//!
+//! Supports the everyday syntax: literals, `.`, classes such as `[a-z_]` and
+//! `[^0-9]`, the escapes `\d \w \s` (and their negations `\D \W \S`),
+//! groups `( )` and `(?: )`, alternation `|`, the anchors `^` and `$`, and
+//! the repetitions `* + ? {n} {n,} {n,m}` with lazy `?` variants. A leading
+//! `(?i)` makes the whole pattern case-insensitive. A pattern matches
+//! anywhere in the text unless it is anchored.
+
+use std::fmt;
+
+#[derive(Clone)]
+pub struct Regex {
+    source: String,
+    node: Node,
+    ignore_case: bool,
+}
+
+#[derive(Clone, Debug)]
+enum Node {
+    Char(char),
+    Any,
+    Class {
+        ranges: Vec<(char, char)>,
+        negated: bool,
+    },
+    Start,
+    End,
+    Concat(Vec<Node>),
+    Alternation(Vec<Node>),
+    Repeat(Repeat),
+}
+
+#[derive(Clone, Debug)]
+struct Repeat {
+    node: Box<Node>,
+    min: usize,
+    max: Option<usize>,
+    /// Try as many iterations as possible first, rather than as few.
+    greedy: bool,
+}
+
+const DIGIT: &[(char, char)] = &[('0', '9')];
+const WORD: &[(char, char)] = &[('0', '9'), ('A', 'Z'), ('_', '_'), ('a', 'z')];
+const SPACE: &[(char, char)] = &[('\t', '\r'), (' ', ' ')];
+/// Upper bound for `{n,m}` counts, which keeps typos like `{99999999}` from
+/// turning into endless backtracking.
+const MAX_REPEAT: usize = 1000;
+
+impl Regex {
+    /// Compiles `pattern`, so a typo is reported before any file is touched.
+    pub fn parse(pattern: &str) -> Result<Regex, String> {
+        let (ignore_case, body) = match pattern.strip_prefix("(?i)") {
+            Some(body) => (true, body),
+            None => (false, pattern),
+        };
+        let mut parser = Parser {
+            chars: body.chars().collect(),
+            at: 0,
+        };
+        let node = parser
+            .alternation()
+            .and_then(|node| match parser.peek() {
+                None => Ok(node),
+                Some(_) => Err("unmatched ')'".to_string()),
+            })
+            .map_err(|err| format!("invalid regex '{pattern}': {err}"))?;
+        Ok(Regex {
+            source: pattern.to_string(),
+            node,
+            ignore_case,
+        })
+    }
+
+    /// Whether the pattern matches anywhere in `text`.
+    pub fn is_match(&self, text: &str) -> bool {
+        let text: Vec<char> = text.chars().collect();
+        let matcher = Matcher {
+            text: &text,
+            ignore_case: self.ignore_case,
+        };
+        (0..=text.len()).any(|start| matcher.run(&self.node, start, &mut |_| true))
+    }
+}
+
+impl fmt::Display for Regex {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+impl fmt::Debug for Regex {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Regex({:?})", self.source)
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    at: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.at).copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek();
+        self.at += 1;
+        c
+    }
+
+    fn eat(&mut self, expected: char) -> bool {
+        let found = self.peek() == Some(expected);
+        if found {
+            self.at += 1;
+        }
+        found
+    }
+
+    fn alternation(&mut self) -> Result<Node, String> {
+        let mut branches = vec![self.concat()?];
+        while self.eat('|') {
+            branches.push(self.concat()?);
+        }
+        Ok(match branches.len() {
+            1 => branches.remove(0),
+            _ => Node::Alternation(branches),
+        })
+    }
+
+    fn concat(&mut self) -> Result<Node, String> {
+        let mut items = Vec::new();
+        while let Some(c) = self.peek() {
+            if c == '|' || c == ')' {
+                break;
+            }
+            let atom = self.atom()?;
+            items.push(self.repeat(atom)?);
+        }
+        Ok(Node::Concat(items))
+    }
+
+    fn atom(&mut self) -> Result<Node, String> {
+        match self.next() {
+            Some('(') => {
+                if self.eat('?') && !self.eat(':') {
+                    return Err("only (?:...) groups and a leading (?i) are supported".to_string());
+                }
+                let node = self.alternation()?;
+                if !self.eat(')') {
+                    return Err("unclosed group".to_string());
+                }
+                Ok(node)
+            }
+            Some('[') => self.class(),
+            Some('.') => Ok(Node::Any),
+            Some('^') => Ok(Node::Start),
+            Some('$') => Ok(Node::End),
+            Some('\\') => self.escape(),
+            Some(c @ ('*' | '+' | '?' | '{')) => Err(format!("nothing to repeat before '{c}'")),
+            Some(c) => Ok(Node::Char(c)),
+            None => Err("unexpected end of pattern".to_string()),
+        }
+    }
+
+    fn escape(&mut self) -> Result<Node, String> {
+        let class = |ranges: &[(char, char)], negated| Node::Class {
+            ranges: ranges.to_vec(),
+            negated,
+        };
+        match self.next() {
+            Some('d') => Ok(class(DIGIT, false)),
+            Some('D') => Ok(class(DIGIT, true)),
+            Some('w') => Ok(class(WORD, false)),
+            Some('W') => Ok(class(WORD, true)),
+            Some('s') => Ok(class(SPACE, false)),
+            Some('S') => Ok(class(SPACE, true)),
+            Some('t') => Ok(Node::Char('\t')),
+            Some(c) if !c.is_alphanumeric() => Ok(Node::Char(c)),
+            Some(c) => Err(format!("unsupported escape '\\{c}'")),
+            None => Err("trailing backslash".to_string()),
+        }
+    }
+
+    fn class(&mut self) -> Result<Node, String> {
+        let negated = self.eat('^');
+        let mut ranges = Vec::new();
+        let mut first = true;
+        loop {
+            let start = match self.next() {
+                None => return Err("unclosed character class".to_string()),
+                Some(']') if !first => break,
+                Some('\\') => match self.escape()? {
+                    Node::Char(c) => c,
+                    Node::Class {
+                        ranges: escaped,
+                        negated: false,
+                    } => {
+                        ranges.extend(escaped);
+                        first = false;
+                        continue;
+                    }
+                    _ => return Err("negated escapes are not supported inside [...]".to_string()),
+                },
+                Some(c) => c,
+            };
+            first = false;
+            if self.peek() == Some('-') && self.chars.get(self.at + 1).is_some_and(|&c| c != ']') {
+                self.at += 1;
+                let end = match self.next() {
+                    Some('\\') => match self.escape()? {
+                        Node::Char(c) => c,
+                        _ => return Err("invalid range end in character class".to_string()),
+                    },
+                    Some(c) => c,
+                    None => return Err("unclosed character class".to_string()),
+                };
+                if end < start {
+                    return Err(format!("invalid range '{start}-{end}'"));
+                }
+                ranges.push((start, end));
+            } else {
+                ranges.push((start, start));
+            }
+        }
+        Ok(Node::Class { ranges, negated })
+    }
+
+    fn repeat(&mut self, mut node: Node) -> Result<Node, String> {
+        loop {
+            let (min, max) = if self.eat('*') {
+                (0, None)
+            } else if self.eat('+') {
+                (1, None)
+            } else if self.eat('?') {
+                (0, Some(1))
+            } else if self.eat('{') {
+                self.counts()?
+            } else {
+                return Ok(node);
+            };
+            if matches!(node, Node::Start | Node::End) {
+                return Err("nothing to repeat".to_string());
+            }
+            let greedy = !self.eat('?');
+            node = Node::Repeat(Repeat {
+                node: Box::new(node),
+                min,
+                max,
+                greedy,
+            });
+        }
+    }
+
+    /// Parses the inside of `{n}`, `{n,}` or `{n,m}` after the opening brace.
+    fn counts(&mut self) -> Result<(usize, Option<usize>), String> {
+        let number = |parser: &mut Parser| {
+            let start = parser.at;
+            while parser.peek().is_some_and(|c| c.is_ascii_digit()) {
+                parser.at += 1;
+            }
+            let digits: String = parser.chars[start..parser.at].iter().collect();
+            (!digits.is_empty()).then(|| digits.parse::<usize>().unwrap_or(usize::MAX))
+        };
+        let min = number(self).ok_or("expected a count after '{'")?;
+        let max = if self.eat(',') {
+            number(self)
+        } else {
+            Some(min)
+        };
+        if !self.eat('}') {
+            return Err("unclosed counted repetition".to_string());
+        }
+        if max.is_some_and(|max| max < min) || min.max(max.unwrap_or(0)) > MAX_REPEAT {
+            return Err(format!("invalid repetition count (at most {MAX_REPEAT})"));
+        }
+        Ok((min, max))
+    }
+}
+
+struct Matcher<'a> {
+    text: &'a [char],
+    ignore_case: bool,
+}
+
+impl Matcher<'_> {
+    fn same(&self, a: char, b: char) -> bool {
+        a == b || (self.ignore_case && a.to_lowercase().eq(b.to_lowercase()))
+    }
+
+    fn in_class(&self, c: char, ranges: &[(char, char)]) -> bool {
+        let within = |c: char| {
+            ranges
+                .iter()
+                .any(|&(start, end)| (start..=end).contains(&c))
+        };
+        within(c)
+            || (self.ignore_case && (c.to_lowercase().any(within) || c.to_uppercase().any(within)))
+    }
+
+    /// Matches `node` at `at` and calls `next` with every position where the
+    /// match could end, stopping at the first one `next` accepts.
+    fn run(&self, node: &Node, at: usize, next: &mut dyn FnMut(usize) -> bool) -> bool {
+        match node {
+            Node::Char(expected) => {
+                self.text.get(at).is_some_and(|&c| self.same(c, *expected)) && next(at + 1)
+            }
+            Node::Any => at < self.text.len() && next(at + 1),
+            Node::Class { ranges, negated } => {
+                self.text
+                    .get(at)
+                    .is_some_and(|&c| self.in_class(c, ranges) != *negated)
+                    && next(at + 1)
+            }
+            Node::Start => at == 0 && next(at),
+            Node::End => at == self.text.len() && next(at),
+            Node::Concat(items) => self.concat(items, at, next),
+            Node::Alternation(branches) => branches.iter().any(|branch| self.run(branch, at, next)),
+            Node::Repeat(repeat) => self.repeat(repeat, 0, at, next),
+        }
+    }
+
+    fn concat(&self, items: &[Node], at: usize, next: &mut dyn FnMut(usize) -> bool) -> bool {
+        match items.split_first() {
+            None => next(at),
+            Some((first, rest)) => self.run(first, at, &mut |end| self.concat(rest, end, next)),
+        }
+    }
+
+    /// Matches further iterations of `repeat` after `count` of them ended
+    /// at `at`.
+    fn repeat(
+        &self,
+        repeat: &Repeat,
+        count: usize,
+        at: usize,
+        next: &mut dyn FnMut(usize) -> bool,
+    ) -> bool {
+        let can_stop = count >= repeat.min;
+        let can_go_on = repeat.max.is_none_or(|max| count < max);
+        let more = |next: &mut dyn FnMut(usize) -> bool| {
+            can_go_on
+                && self.run(&repeat.node, at, &mut |end| {
+                    // An empty iteration cannot make progress; stop looping.
+                    (end > at || count < repeat.min) && self.repeat(repeat, count + 1, end, next)
+                })
+        };
+        if repeat.greedy {
+            if more(next) {
+                return true;
+            }
+            can_stop && next(at)
+        } else {
+            if can_stop && next(at) {
+                return true;
+            }
+            more(next)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(pattern: &str, text: &str) -> bool {
+        Regex::parse(pattern).unwrap().is_match(text)
+    }
+
+    #[test]
+    fn test_matches_anywhere_unless_anchored() {
+        assert!(matches("2024", "Network_20240101.log"));
+        assert!(!matches("^2024", "Network_20240101.log"));
+        assert!(matches(r"\.log$", "Network_20240101.log"));
+        assert!(!matches(r"\.log$", "Network_20240101.log.bak"));
+    }
+
+    #[test]
+    fn test_classes_and_repetition() {
+        assert!(matches(r"^Network_\d{8}\.log$", "Network_20240101.log"));
+        assert!(!matches(r"^Network_\d{8}\.log$", "Network_2024011.log"));
+        assert!(matches("^[A-Z][a-z]+_[0-9]+", "Network_26802"));
+        assert!(matches("^[^_]+$", "notes.log"));
+        assert!(!matches("^[^_]+$", "Network_1.log"));
+        assert!(matches("^a.*b.*c$", "axxbyyc"));
+        assert!(matches("^colou?r$", "color"));
+        assert!(matches(r"^\w+\s\w+$", "hello world"));
+    }
+
+    #[test]
+    fn test_groups_and_alternation() {
+        assert!(matches("^(Network|Combat)_", "Combat_1.log"));
+        assert!(!matches("^(Network|Combat)_", "Other_1.log"));
+        assert!(matches("^(?:ab)+$", "ababab"));
+        assert!(!matches("^(?:ab)+$", "ababa"));
+        assert!(matches("^(a|)+$", "aaa"));
+    }
+
+    #[test]
+    fn test_lazy_and_nested_repetition() {
+        assert!(matches("^a+?b$", "aaab"));
+        assert!(matches("^(a*)*$", "aaaa"));
+        assert!(!matches("^(a*)*b$", "aaaa"));
+    }
+
+    #[test]
+    fn test_ignore_case_flag() {
+        assert!(matches("(?i)^network_", "Network_1.log"));
+        assert!(matches("(?i)^[a-z]+_", "NETWORK_1.log"));
+        assert!(!matches("^network_", "Network_1.log"));
+    }
+
+    #[test]
+    fn test_parse_errors_name_the_pattern() {
+        for pattern in ["(ab", "ab)", "[a-", "*a", r"a\", "a{2,1}", "[z-a]", r"\q"] {
+            let err = Regex::parse(pattern).unwrap_err();
+            assert!(
+                err.starts_with(&format!("invalid regex '{pattern}'")),
+                "{err}"
+            );
+        }
+    }
+}
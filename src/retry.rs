@@ -0,0 +1,167 @@
+//! Retrying file operations that fail only for a moment.
+//!
+//! While ACT has a log open, Windows refuses to move or delete it with a
+//! sharing violation, and a virus scanner briefly opening a new file makes
+//! the same calls fail with "access denied". Both clear up on their own, so
+//! [`with_retries`] runs such an operation again after a short pause,
+//! doubling the pause each time, before reporting the failure. `--retries`
+//! sets how often through [`set_retries`].
+
+use crate::output::debug;
+use std::io;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::thread;
+use std::time::Duration;
+
+/// Retries after the first attempt, so three attempts in all.
+pub const DEFAULT_RETRIES: u32 = 2;
+/// The pause before the first retry; each later one waits twice as long.
+const FIRST_DELAY: Duration = Duration::from_millis(100);
+
+static RETRIES: AtomicU32 = AtomicU32::new(DEFAULT_RETRIES);
+
+pub fn set_retries(retries: u32) {
+    RETRIES.store(retries, Ordering::Relaxed);
+}
+
+/// Whether `err` is one that goes away once another program lets go of
+/// the file: a sharing or lock violation or a temporary "access denied" on
+/// Windows, "resource busy" elsewhere.
+pub fn is_transient(err: &io::Error) -> bool {
+    #[cfg(windows)]
+    const TRANSIENT: [i32; 3] = [
+        5,  // ERROR_ACCESS_DENIED
+        32, // ERROR_SHARING_VIOLATION
+        33, // ERROR_LOCK_VIOLATION
+    ];
+    #[cfg(not(windows))]
+    const TRANSIENT: [i32; 2] = [libc::EBUSY, libc::EAGAIN];
+
+    err.kind() == io::ErrorKind::Interrupted
+        || err
+            .raw_os_error()
+            .is_some_and(|code| TRANSIENT.contains(&code))
+}
+
+/// Runs `operation`, retrying it as often as `--retries` allows while it
+/// fails with a [transient](is_transient) error.
+pub fn with_retries<T>(operation: impl FnMut() -> io::Result<T>) -> io::Result<T> {
+    retry(
+        RETRIES.load(Ordering::Relaxed),
+        FIRST_DELAY,
+        thread::sleep,
+        operation,
+    )
+}
+
+fn retry<T>(
+    retries: u32,
+    first_delay: Duration,
+    mut sleep: impl FnMut(Duration),
+    mut operation: impl FnMut() -> io::Result<T>,
+) -> io::Result<T> {
+    let mut delay = first_delay;
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        match operation() {
+            Err(err) if is_transient(&err) && attempt <= retries => {
+                debug!("Attempt {attempt} failed: {err}; retrying in {delay:?}");
+                sleep(delay);
+                delay *= 2;
+            }
+            Err(err) if is_transient(&err) && attempt > 1 => {
+                return Err(io::Error::new(
+                    err.kind(),
+                    format!(
+                        "{err} (gave up after {attempt} attempts; is ACT or another program still using the file?)"
+                    ),
+                ));
+            }
+            result => return result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transient_error() -> io::Error {
+        #[cfg(windows)]
+        return io::Error::from_raw_os_error(32);
+        #[cfg(not(windows))]
+        return io::Error::from_raw_os_error(libc::EBUSY);
+    }
+
+    #[test]
+    fn test_retry_succeeds_after_two_transient_failures() {
+        let mut calls = 0;
+        let mut delays = Vec::new();
+
+        let result = retry(
+            2,
+            Duration::from_millis(100),
+            |delay| delays.push(delay),
+            || {
+                calls += 1;
+                if calls < 3 {
+                    Err(transient_error())
+                } else {
+                    Ok(calls)
+                }
+            },
+        );
+
+        assert_eq!(result.unwrap(), 3);
+        assert_eq!(
+            delays,
+            [Duration::from_millis(100), Duration::from_millis(200)]
+        );
+    }
+
+    #[test]
+    fn test_retry_gives_up_after_the_last_attempt() {
+        let mut calls = 0;
+
+        let result: io::Result<()> = retry(
+            2,
+            Duration::ZERO,
+            |_| {},
+            || {
+                calls += 1;
+                Err(transient_error())
+            },
+        );
+
+        assert_eq!(calls, 3);
+        let err = result.unwrap_err();
+        assert_eq!(err.kind(), transient_error().kind());
+        assert!(err.to_string().contains("gave up after 3 attempts"));
+    }
+
+    #[test]
+    fn test_retry_does_not_repeat_other_errors() {
+        let mut calls = 0;
+
+        let result: io::Result<()> = retry(
+            2,
+            Duration::ZERO,
+            |_| panic!("should not wait"),
+            || {
+                calls += 1;
+                Err(io::Error::from(io::ErrorKind::NotFound))
+            },
+        );
+
+        assert_eq!(calls, 1);
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn test_is_transient() {
+        assert!(is_transient(&transient_error()));
+        assert!(is_transient(&io::Error::from(io::ErrorKind::Interrupted)));
+        assert!(!is_transient(&io::Error::from(io::ErrorKind::NotFound)));
+    }
+}
@@ -0,0 +1,113 @@
+//! Noticing when ACT rotates to a new log.
+//!
+//! ACT either starts a new `Network_*.log` next to the current one or, with
+//! some setups, renames the current file away and starts over under its
+//! name. `view --follow` and `watch` tell files apart by their identity
+//! rather than their name, so a file that takes over a name is recognized
+//! as new, and a file that only moved is not mistaken for one.
+
+use crate::paths::to_extended_path;
+use std::fs::File;
+use std::path::Path;
+
+/// What tells one file from another that later took its name: the device
+/// and inode on Unix, the volume serial number and file index on Windows.
+/// Both survive a rename and change when the file is recreated.
+pub type FileIdentity = (u64, u64);
+
+/// The identity of the open `file`, when the platform has one.
+#[cfg(unix)]
+pub fn file_identity(file: &File) -> Option<FileIdentity> {
+    use std::os::unix::fs::MetadataExt;
+    let metadata = file.metadata().ok()?;
+    Some((metadata.dev(), metadata.ino()))
+}
+
+/// The identity of the open `file`, when the platform has one. The creation
+/// time is no use here: NTFS tunneling hands it on to a file created under
+/// a name that was just renamed away.
+#[cfg(windows)]
+pub fn file_identity(file: &File) -> Option<FileIdentity> {
+    use std::ffi::c_void;
+    use std::mem::MaybeUninit;
+    use std::os::windows::io::AsRawHandle;
+
+    /// `BY_HANDLE_FILE_INFORMATION` from `fileapi.h`.
+    #[repr(C)]
+    struct ByHandleFileInformation {
+        file_attributes: u32,
+        creation_time: [u32; 2],
+        last_access_time: [u32; 2],
+        last_write_time: [u32; 2],
+        volume_serial_number: u32,
+        file_size_high: u32,
+        file_size_low: u32,
+        number_of_links: u32,
+        file_index_high: u32,
+        file_index_low: u32,
+    }
+
+    #[link(name = "kernel32")]
+    unsafe extern "system" {
+        fn GetFileInformationByHandle(
+            file: *mut c_void,
+            information: *mut ByHandleFileInformation,
+        ) -> i32;
+    }
+
+    let mut information = MaybeUninit::<ByHandleFileInformation>::uninit();
+    // SAFETY: the handle belongs to `file`, which outlives the call, and the
+    // structure is only read after the call reports it filled in.
+    let information = unsafe {
+        if GetFileInformationByHandle(file.as_raw_handle(), information.as_mut_ptr()) == 0 {
+            return None;
+        }
+        information.assume_init()
+    };
+    let index =
+        (u64::from(information.file_index_high) << 32) | u64::from(information.file_index_low);
+    Some((u64::from(information.volume_serial_number), index))
+}
+
+/// Without a file identity, only a shrinking file or a new name shows a
+/// rotation.
+#[cfg(not(any(unix, windows)))]
+pub fn file_identity(_file: &File) -> Option<FileIdentity> {
+    None
+}
+
+/// The identity of the file at `path`, if it can be opened.
+pub fn path_identity(path: &Path) -> Option<FileIdentity> {
+    File::open(to_extended_path(path))
+        .ok()
+        .and_then(|file| file_identity(&file))
+}
+
+/// The message shown when monitoring moves on to `path`.
+pub fn rotation_message(path: &Path) -> String {
+    format!("Rotation detected, following {}", path.display())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    #[cfg(any(unix, windows))]
+    fn test_identity_follows_the_file_not_the_name() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("Network_1.log");
+        let moved = temp_dir.path().join("Network_1.old.log");
+        fs::write(&path, "first\n").unwrap();
+        let original = path_identity(&path).unwrap();
+
+        fs::rename(&path, &moved).unwrap();
+        fs::write(&path, "second\n").unwrap();
+
+        assert_eq!(path_identity(&moved), Some(original));
+        assert_ne!(path_identity(&path), Some(original));
+        assert_eq!(path_identity(&temp_dir.path().join("missing.log")), None);
+    }
+}
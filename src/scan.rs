@@ -0,0 +1,579 @@
+//! Directory scanning.
+//!
+//! [`entries`] walks a directory lazily, one `read_dir` entry at a time, so
+//! operations that handle each file independently never hold the whole
+//! directory in memory. Streaming operations:
+//!
+//! - `list`
+//! - `backup`
+//! - `delete --yes` and `delete --dry-run`
+//!
+//! Buffering is kept only where the full set is required up front:
+//!
+//! - `delete` without `--yes` counts the files for its confirmation prompt
+//!   and the plan line shown with it
+//! - `--progress-format` collects the entries first to know the total
+//! - `backup --keep-last` sorts the files by modification time
+//! - `backup --dedupe` hashes every file before any is moved
+//! - `--jobs` hands the whole file list to the worker threads
+//! - `--paths-from-json` reads its whole input array
+//! - `view` collects the files to present a numbered selection
+//! - `watch` compares each poll of the directory with the previous one
+//!
+//! The undo manifest that `backup` keeps in the log directory is never
+//! reported, so it is not listed, moved or deleted along with the logs.
+//!
+//! [`collect_log_files`] is the buffered counterpart for those cases: it
+//! returns the regular files of a directory with their size and
+//! modification time already read.
+
+use crate::commands::undo;
+use crate::filters::SkipReason;
+use std::collections::HashSet;
+use std::ffi::OsString;
+use std::fs::{ReadDir, metadata, read_dir};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ScanEntry {
+    File(PathBuf),
+    Directory(PathBuf),
+    Missing(PathBuf),
+    /// A file a filter left out, kept in the stream so it can be counted.
+    Skipped(PathBuf, SkipReason),
+}
+
+impl ScanEntry {
+    pub fn from_path(path: PathBuf) -> Self {
+        if path.is_dir() {
+            ScanEntry::Directory(path)
+        } else if path.exists() {
+            ScanEntry::File(path)
+        } else {
+            ScanEntry::Missing(path)
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogFile {
+    pub path: PathBuf,
+    pub size: u64,
+    pub modified: Option<SystemTime>,
+}
+
+impl LogFile {
+    pub fn from_path(path: PathBuf) -> io::Result<Self> {
+        let meta = metadata(&path)?;
+        Ok(LogFile {
+            path,
+            size: meta.len(),
+            modified: meta.modified().ok(),
+        })
+    }
+
+    /// The file name, or the whole path for one such as `..` that does
+    /// not end in a name.
+    pub fn name(&self) -> String {
+        let file_name = self.path.file_name().unwrap_or(self.path.as_os_str());
+        file_name.to_string_lossy().into_owned()
+    }
+}
+
+/// A directory reported by `--include-dirs`, with the combined size and
+/// count of the files anywhere beneath it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirSummary {
+    pub path: PathBuf,
+    pub size: u64,
+    pub files: usize,
+}
+
+impl DirSummary {
+    pub fn from_path(path: PathBuf) -> io::Result<Self> {
+        let (mut size, mut files) = (0, 0);
+        for entry in entries(&path)?.recursive(&path) {
+            if let ScanEntry::File(file) = entry? {
+                size += metadata(&file)?.len();
+                files += 1;
+            }
+        }
+        Ok(DirSummary { path, size, files })
+    }
+
+    pub fn name(&self) -> String {
+        let dir_name = self.path.file_name().unwrap_or_default();
+        dir_name.to_string_lossy().into_owned()
+    }
+}
+
+pub struct Entries {
+    stack: Vec<ReadDir>,
+    recursive: bool,
+    max_depth: Option<usize>,
+    visited: HashSet<PathBuf>,
+    excluded: Option<OsString>,
+}
+
+impl Entries {
+    /// Descends into subdirectories depth-first instead of reporting them.
+    /// Each directory is visited once by canonical path, so symlink loops
+    /// terminate.
+    pub fn recursive(mut self, root: &Path) -> Self {
+        self.recursive = true;
+        if let Ok(canonical) = root.canonicalize() {
+            self.visited.insert(canonical);
+        }
+        self
+    }
+
+    /// Stops a recursive walk `depth` levels below the root; directories
+    /// deeper than that are reported as entries instead of being walked.
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = Some(depth);
+        self
+    }
+
+    /// Never descends into subdirectories with this name, e.g. the `bak`
+    /// folders that a recursive backup creates while walking.
+    pub fn excluding(mut self, name: &str) -> Self {
+        self.excluded = Some(OsString::from(name));
+        self
+    }
+
+    fn descend(&mut self, dir: &Path) -> io::Result<()> {
+        if self.excluded.as_deref() == dir.file_name() {
+            return Ok(());
+        }
+        if self.visited.insert(dir.canonicalize()?) {
+            self.stack.push(read_dir(dir)?);
+        }
+        Ok(())
+    }
+}
+
+impl Iterator for Entries {
+    type Item = io::Result<ScanEntry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let entry = match self.stack.last_mut()?.next() {
+                Some(entry) => entry,
+                None => {
+                    self.stack.pop();
+                    continue;
+                }
+            };
+            let entry = match entry {
+                Ok(entry) if entry.file_name() == undo::MANIFEST_NAME => continue,
+                Ok(entry) => ScanEntry::from_path(entry.path()),
+                Err(err) => return Some(Err(err)),
+            };
+            match entry {
+                // The directory sits `stack.len() - 1` levels below the root,
+                // so its contents would be one level further down.
+                ScanEntry::Directory(dir)
+                    if self.recursive
+                        && self.max_depth.is_none_or(|max| self.stack.len() <= max) =>
+                {
+                    if let Err(err) = self.descend(&dir) {
+                        return Some(Err(with_path(&dir, err)));
+                    }
+                }
+                entry => return Some(Ok(entry)),
+            }
+        }
+    }
+}
+
+pub fn entries(dir: &Path) -> io::Result<Entries> {
+    Ok(Entries {
+        stack: vec![read_dir(dir)?],
+        recursive: false,
+        max_depth: None,
+        visited: HashSet::new(),
+        excluded: None,
+    })
+}
+
+/// Prefixes `err` with the path it concerns, keeping its kind, so a scan
+/// error can be reported as `Skipping <path>: <error>`.
+pub fn with_path(path: &Path, err: io::Error) -> io::Error {
+    io::Error::new(err.kind(), format!("{}: {err}", path.display()))
+}
+
+/// The entries a run works through: one or more scans, after any stage
+/// that filters or reorders them.
+pub type EntryStream = Box<dyn Iterator<Item = io::Result<ScanEntry>>>;
+
+/// Splits `entries` into the files `wanted` accepts, in scan order, and
+/// every other entry, for a stage that needs all the files at once.
+pub fn split_files(
+    entries: EntryStream,
+    wanted: impl Fn(&Path) -> bool,
+) -> (Vec<PathBuf>, Vec<io::Result<ScanEntry>>) {
+    let mut files = Vec::new();
+    let mut others = Vec::new();
+    for entry in entries {
+        match entry {
+            Ok(ScanEntry::File(path)) if wanted(&path) => files.push(path),
+            other => others.push(other),
+        }
+    }
+    (files, others)
+}
+
+/// The stream a stage hands on after [`split_files`]: `others` first, then
+/// `files` in the order given.
+pub fn rejoin(others: Vec<io::Result<ScanEntry>>, files: Vec<PathBuf>) -> EntryStream {
+    let files = files.into_iter().map(|path| Ok(ScanEntry::File(path)));
+    Box::new(others.into_iter().chain(files))
+}
+
+/// The directory among `dirs` that holds `path`, the innermost one when
+/// they are nested.
+pub fn holding_dir<'a>(dirs: &'a [PathBuf], path: &Path) -> Option<&'a Path> {
+    dirs.iter()
+        .filter(|dir| path.starts_with(dir))
+        .max_by_key(|dir| dir.components().count())
+        .map(PathBuf::as_path)
+}
+
+/// The line introducing one directory's files in a run over several,
+/// separated from the previous directory by a blank line.
+pub fn dir_header(dir: &Path, first: bool) -> String {
+    let gap = if first { "" } else { "\n" };
+    format!("{gap}{}:", dir.display())
+}
+
+/// Returns the regular files directly inside `dir`; subdirectories are
+/// skipped. Only an unreadable `dir` is an error: entries that cannot be
+/// read, for example because ACT holds a lock or the file vanished after
+/// being listed, are reported on stderr and left out.
+pub fn collect_log_files(dir: &Path) -> io::Result<Vec<LogFile>> {
+    Ok(log_files(entries(dir)?))
+}
+
+fn log_files(entries: impl Iterator<Item = io::Result<ScanEntry>>) -> Vec<LogFile> {
+    entries
+        .filter_map(|entry| match entry {
+            Ok(ScanEntry::File(path)) => LogFile::from_path(path.clone())
+                .inspect_err(|err| eprintln!("Skipping {}: {err}", path.display()))
+                .ok(),
+            Ok(_) => None,
+            Err(err) => {
+                eprintln!("Skipping {err}");
+                None
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{self, File};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_entries_classifies_files_and_directories() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("test.log");
+        let subdir = temp_dir.path().join("subdir");
+        File::create(&file_path).unwrap();
+        fs::create_dir(&subdir).unwrap();
+
+        let mut found: Vec<ScanEntry> = entries(temp_dir.path())
+            .unwrap()
+            .collect::<io::Result<_>>()
+            .unwrap();
+        found.sort_by_key(|entry| matches!(entry, ScanEntry::Directory(_)));
+
+        assert_eq!(
+            found,
+            vec![ScanEntry::File(file_path), ScanEntry::Directory(subdir)]
+        );
+    }
+
+    #[test]
+    fn test_entries_skips_undo_manifest() {
+        let temp_dir = TempDir::new().unwrap();
+        File::create(temp_dir.path().join(undo::MANIFEST_NAME)).unwrap();
+
+        assert_eq!(entries(temp_dir.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_entries_nonexistent_directory() {
+        let temp_dir = TempDir::new().unwrap();
+
+        assert!(entries(&temp_dir.path().join("nonexistent")).is_err());
+    }
+
+    #[test]
+    fn test_from_path_missing() {
+        let temp_dir = TempDir::new().unwrap();
+        let missing = temp_dir.path().join("gone.log");
+
+        assert_eq!(
+            ScanEntry::from_path(missing.clone()),
+            ScanEntry::Missing(missing)
+        );
+    }
+
+    fn collect_files(scan: Entries) -> Vec<PathBuf> {
+        let mut files: Vec<PathBuf> = scan
+            .map(|entry| match entry.unwrap() {
+                ScanEntry::File(path) => path,
+                other => panic!("unexpected entry {other:?}"),
+            })
+            .collect();
+        files.sort();
+        files
+    }
+
+    #[test]
+    fn test_entries_recursive_descends_into_subdirectories() {
+        let temp_dir = TempDir::new().unwrap();
+        let nested = temp_dir.path().join("zone").join("raid");
+        fs::create_dir_all(&nested).unwrap();
+        File::create(temp_dir.path().join("top.log")).unwrap();
+        File::create(nested.join("deep.log")).unwrap();
+
+        let scan = entries(temp_dir.path()).unwrap().recursive(temp_dir.path());
+
+        assert_eq!(
+            collect_files(scan),
+            vec![temp_dir.path().join("top.log"), nested.join("deep.log")]
+        );
+    }
+
+    #[test]
+    fn test_entries_recursive_skips_excluded_directories() {
+        let temp_dir = TempDir::new().unwrap();
+        let bak = temp_dir.path().join("zone").join("bak");
+        fs::create_dir_all(&bak).unwrap();
+        File::create(temp_dir.path().join("zone").join("live.log")).unwrap();
+        File::create(bak.join("old.log")).unwrap();
+
+        let scan = entries(temp_dir.path())
+            .unwrap()
+            .recursive(temp_dir.path())
+            .excluding("bak");
+
+        assert_eq!(
+            collect_files(scan),
+            vec![temp_dir.path().join("zone").join("live.log")]
+        );
+    }
+
+    #[test]
+    fn test_entries_recursive_stops_at_max_depth() {
+        let temp_dir = TempDir::new().unwrap();
+        let first = temp_dir.path().join("a");
+        let second = first.join("b");
+        let third = second.join("c");
+        fs::create_dir_all(&third).unwrap();
+        File::create(temp_dir.path().join("top.log")).unwrap();
+        File::create(first.join("one.log")).unwrap();
+        File::create(second.join("two.log")).unwrap();
+        File::create(third.join("three.log")).unwrap();
+
+        let mut found: Vec<ScanEntry> = entries(temp_dir.path())
+            .unwrap()
+            .recursive(temp_dir.path())
+            .max_depth(1)
+            .map(Result::unwrap)
+            .collect();
+        found.sort_by(|a, b| format!("{a:?}").cmp(&format!("{b:?}")));
+
+        assert_eq!(
+            found,
+            vec![
+                ScanEntry::Directory(second),
+                ScanEntry::File(first.join("one.log")),
+                ScanEntry::File(temp_dir.path().join("top.log")),
+            ]
+        );
+
+        let shallow = entries(temp_dir.path())
+            .unwrap()
+            .recursive(temp_dir.path())
+            .max_depth(0);
+        assert_eq!(
+            shallow
+                .filter(|entry| matches!(entry, Ok(ScanEntry::File(_))))
+                .count(),
+            1
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_entries_recursive_survives_symlink_loop() {
+        let temp_dir = TempDir::new().unwrap();
+        let sub = temp_dir.path().join("sub");
+        fs::create_dir(&sub).unwrap();
+        File::create(sub.join("test.log")).unwrap();
+        std::os::unix::fs::symlink(temp_dir.path(), sub.join("loop")).unwrap();
+
+        let scan = entries(temp_dir.path()).unwrap().recursive(temp_dir.path());
+
+        assert_eq!(collect_files(scan), vec![sub.join("test.log")]);
+    }
+
+    #[test]
+    fn test_entries_streams_large_directory() {
+        let temp_dir = TempDir::new().unwrap();
+        for i in 0..5000 {
+            File::create(temp_dir.path().join(format!("Network_{i:05}.log"))).unwrap();
+        }
+
+        let scan = entries(temp_dir.path()).unwrap();
+        // A pre-collected buffer would report an exact size; a lazy walk
+        // over read_dir cannot know how many entries remain.
+        assert_eq!(scan.size_hint(), (0, None));
+
+        let mut files = 0;
+        for entry in scan {
+            assert!(matches!(entry.unwrap(), ScanEntry::File(_)));
+            files += 1;
+        }
+        assert_eq!(files, 5000);
+    }
+
+    #[test]
+    fn test_collect_log_files_reads_metadata_and_skips_directories() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("test.log"), "12345").unwrap();
+        fs::create_dir(temp_dir.path().join("bak")).unwrap();
+        fs::write(temp_dir.path().join("bak").join("old.log"), "x").unwrap();
+
+        let files = collect_log_files(temp_dir.path()).unwrap();
+
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].path, temp_dir.path().join("test.log"));
+        assert_eq!(files[0].name(), "test.log");
+        let parent = LogFile {
+            path: PathBuf::from(".."),
+            ..files[0].clone()
+        };
+        assert_eq!(parent.name(), "..");
+        assert_eq!(files[0].size, 5);
+        assert!(files[0].modified.is_some());
+    }
+
+    #[test]
+    fn test_log_files_skips_file_removed_after_scan() {
+        let temp_dir = TempDir::new().unwrap();
+        let kept = temp_dir.path().join("kept.log");
+        let removed = temp_dir.path().join("removed.log");
+        fs::write(&kept, "x").unwrap();
+        let scanned = vec![
+            Ok(ScanEntry::File(removed.clone())),
+            Err(with_path(
+                &temp_dir.path().join("locked"),
+                io::Error::from(io::ErrorKind::PermissionDenied),
+            )),
+            Ok(ScanEntry::File(kept.clone())),
+        ];
+
+        let files = log_files(scanned.into_iter());
+
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].path, kept);
+    }
+
+    #[test]
+    fn test_with_path_keeps_kind() {
+        let err = with_path(
+            Path::new("/logs/sub"),
+            io::Error::from(io::ErrorKind::PermissionDenied),
+        );
+
+        assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+        assert!(err.to_string().starts_with("/logs/sub: "));
+    }
+
+    #[test]
+    fn test_collect_log_files_nonexistent_directory() {
+        let temp_dir = TempDir::new().unwrap();
+
+        assert!(collect_log_files(&temp_dir.path().join("nonexistent")).is_err());
+    }
+
+    #[test]
+    fn test_log_file_from_missing_path_fails() {
+        let temp_dir = TempDir::new().unwrap();
+
+        assert!(LogFile::from_path(temp_dir.path().join("gone.log")).is_err());
+    }
+
+    #[test]
+    fn test_dir_summary_counts_nested_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path().join("bak");
+        fs::create_dir_all(dir.join("old")).unwrap();
+        fs::write(dir.join("a.log"), "12345").unwrap();
+        fs::write(dir.join("old").join("b.log"), "123").unwrap();
+
+        let summary = DirSummary::from_path(dir.clone()).unwrap();
+
+        assert_eq!(
+            summary,
+            DirSummary {
+                path: dir,
+                size: 8,
+                files: 2
+            }
+        );
+        assert_eq!(summary.name(), "bak");
+    }
+
+    #[test]
+    fn test_holding_dir_prefers_the_innermost_directory() {
+        let dirs = [PathBuf::from("/logs"), PathBuf::from("/logs/archive")];
+
+        assert_eq!(
+            holding_dir(&dirs, Path::new("/logs/a.log")),
+            Some(Path::new("/logs"))
+        );
+        assert_eq!(
+            holding_dir(&dirs, Path::new("/logs/archive/b.log")),
+            Some(Path::new("/logs/archive"))
+        );
+        assert_eq!(holding_dir(&dirs, Path::new("/other/c.log")), None);
+        assert_eq!(dir_header(Path::new("/logs"), true), "/logs:");
+        assert_eq!(dir_header(Path::new("/logs"), false), "\n/logs:");
+    }
+
+    #[test]
+    fn test_split_files_and_rejoin_keep_the_order() {
+        let entries: EntryStream = Box::new(
+            [
+                Ok(ScanEntry::File(PathBuf::from("b.log"))),
+                Ok(ScanEntry::Directory(PathBuf::from("sub"))),
+                Ok(ScanEntry::File(PathBuf::from("notes.txt"))),
+                Ok(ScanEntry::File(PathBuf::from("a.log"))),
+            ]
+            .into_iter(),
+        );
+
+        let (files, others) = split_files(entries, |path| path.extension() == Some("log".as_ref()));
+
+        assert_eq!(files, [PathBuf::from("b.log"), PathBuf::from("a.log")]);
+        assert_eq!(others.len(), 2);
+        let rejoined: Vec<ScanEntry> = rejoin(others, files).map(Result::unwrap).collect();
+        assert_eq!(
+            rejoined,
+            [
+                ScanEntry::Directory(PathBuf::from("sub")),
+                ScanEntry::File(PathBuf::from("notes.txt")),
+                ScanEntry::File(PathBuf::from("b.log")),
+                ScanEntry::File(PathBuf::from("a.log")),
+            ]
+        );
+    }
+}
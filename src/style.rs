@@ -0,0 +1,90 @@
+//! Colored status words.
+//!
+//! The outcome of each file ("Moved.", "Failed:", "Skipping ...") is
+//! colored green, red or yellow so long runs are easy to scan. `--color`
+//! decides when: `auto` colors only when stdout is a terminal and
+//! `NO_COLOR` is unset, so piped output stays plain.
+
+use clap::ValueEnum;
+use std::env;
+use std::fmt::Display;
+use std::io::{self, IsTerminal};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum ColorChoice {
+    /// Color when writing to a terminal and NO_COLOR is not set
+    Auto,
+    /// Always color, even when piped
+    Always,
+    /// Never color
+    Never,
+}
+
+const GREEN: &str = "32";
+const RED: &str = "31";
+const YELLOW: &str = "33";
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+pub fn set_color(choice: ColorChoice) {
+    let no_color = env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+    let enabled = resolve(choice, no_color, io::stdout().is_terminal());
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+fn resolve(choice: ColorChoice, no_color: bool, is_terminal: bool) -> bool {
+    match choice {
+        ColorChoice::Auto => is_terminal && !no_color,
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+    }
+}
+
+pub fn success(text: impl Display) -> String {
+    paint(GREEN, text)
+}
+
+pub fn failure(text: impl Display) -> String {
+    paint(RED, text)
+}
+
+pub fn skipped(text: impl Display) -> String {
+    paint(YELLOW, text)
+}
+
+fn paint(color: &str, text: impl Display) -> String {
+    paint_if(ENABLED.load(Ordering::Relaxed), color, text)
+}
+
+fn paint_if(enabled: bool, color: &str, text: impl Display) -> String {
+    if enabled {
+        format!("\x1b[{color}m{text}\x1b[0m")
+    } else {
+        text.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_auto_needs_terminal_and_no_no_color() {
+        assert!(resolve(ColorChoice::Auto, false, true));
+        assert!(!resolve(ColorChoice::Auto, false, false));
+        assert!(!resolve(ColorChoice::Auto, true, true));
+    }
+
+    #[test]
+    fn test_resolve_explicit_choices_win() {
+        assert!(resolve(ColorChoice::Always, true, false));
+        assert!(!resolve(ColorChoice::Never, false, true));
+    }
+
+    #[test]
+    fn test_paint_if() {
+        assert_eq!(paint_if(true, GREEN, "Moved."), "\x1b[32mMoved.\x1b[0m");
+        assert_eq!(paint_if(false, RED, "Failed:"), "Failed:");
+    }
+}
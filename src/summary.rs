@@ -0,0 +1,300 @@
+use crate::filters::SkipReason;
+use crate::json;
+use crate::util::format_size;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::time::Duration;
+
+pub struct Summary {
+    pub operation: &'static str,
+    pub directory: PathBuf,
+    pub processed: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub skipped: usize,
+    /// How many of the `skipped` files were left out for each reason.
+    pub skip_reasons: BTreeMap<SkipReason, usize>,
+    pub freed_bytes: u64,
+    pub freed_files: usize,
+    /// Removed files whose size could not be read beforehand.
+    pub freed_unknown: usize,
+    /// Set when `--limit` stopped the run before every file was processed.
+    pub limited: Option<usize>,
+    /// Set when Ctrl-C stopped the run after the file in flight.
+    pub interrupted: bool,
+}
+
+impl Summary {
+    pub fn new(operation: &'static str, directory: PathBuf) -> Self {
+        Summary {
+            operation,
+            directory,
+            processed: 0,
+            succeeded: 0,
+            failed: 0,
+            skipped: 0,
+            skip_reasons: BTreeMap::new(),
+            freed_bytes: 0,
+            freed_files: 0,
+            freed_unknown: 0,
+            limited: None,
+            interrupted: false,
+        }
+    }
+
+    pub fn record(&mut self, succeeded: bool) {
+        self.processed += 1;
+        if succeeded {
+            self.succeeded += 1;
+        } else {
+            self.failed += 1;
+        }
+    }
+
+    pub fn record_skip(&mut self, reason: SkipReason) {
+        self.skipped += 1;
+        *self.skip_reasons.entry(reason).or_default() += 1;
+    }
+
+    /// The breakdown of skipped files by reason, e.g.
+    /// `Skipped 12 (8 pattern, 3 age, 1 dir)`, or `None` if nothing was
+    /// skipped.
+    pub fn skip_line(&self) -> Option<String> {
+        if self.skipped == 0 {
+            return None;
+        }
+        let reasons: Vec<String> = self
+            .skip_reasons
+            .iter()
+            .map(|(reason, count)| format!("{count} {}", reason.label()))
+            .collect();
+        Some(if reasons.is_empty() {
+            format!("Skipped {}", self.skipped)
+        } else {
+            format!("Skipped {} ({})", self.skipped, reasons.join(", "))
+        })
+    }
+
+    /// Counts a removed file towards the reclaimed space; `bytes` is `None`
+    /// when its size could not be read.
+    pub fn record_freed(&mut self, bytes: Option<u64>) {
+        self.freed_files += 1;
+        match bytes {
+            Some(bytes) => self.freed_bytes += bytes,
+            None => self.freed_unknown += 1,
+        }
+    }
+
+    pub fn freed_line(&self) -> String {
+        let noun = if self.freed_files == 1 {
+            "file"
+        } else {
+            "files"
+        };
+        let mut line = format!(
+            "Freed {} across {} {noun}",
+            format_size(self.freed_bytes),
+            self.freed_files
+        );
+        if self.freed_unknown > 0 {
+            line.push_str(&format!(" ({} of unknown size)", self.freed_unknown));
+        }
+        line
+    }
+
+    /// 130 after Ctrl-C, as shells report for SIGINT; otherwise 1 if any
+    /// file failed.
+    pub fn exit_code(&self) -> u8 {
+        if self.interrupted {
+            130
+        } else if self.failed > 0 {
+            1
+        } else {
+            0
+        }
+    }
+
+    pub fn report_line(&self) -> String {
+        let noun = if self.processed == 1 { "file" } else { "files" };
+        let mut line = format!(
+            "Processed {} {noun}: {} ok, {} failed",
+            self.processed, self.succeeded, self.failed
+        );
+        if self.skipped > 0 {
+            line.push_str(&format!(", {} skipped", self.skipped));
+        }
+        if let Some(limit) = self.limited {
+            line.push_str(&format!(" (limited to {limit})"));
+        }
+        if self.interrupted {
+            line.push_str(" (interrupted)");
+        }
+        line
+    }
+
+    /// The run as one JSON object for `--summary-json`, with the code the
+    /// process exits with, which a run stopped early decides itself.
+    /// `limited` is the `--limit` that stopped the run, or `null`.
+    pub fn to_json(&self, elapsed: Duration, exit_code: u8) -> String {
+        format!(
+            "{{\"operation\":{},\"directory\":{},\"processed\":{},\"succeeded\":{},\"failed\":{},\"skipped\":{},\"limited\":{},\"freed_bytes\":{},\"elapsed_ms\":{},\"exit_code\":{}}}",
+            json::escape(self.operation),
+            json::escape(&self.directory.to_string_lossy()),
+            self.processed,
+            self.succeeded,
+            self.failed,
+            self.skipped,
+            self.limited
+                .map_or_else(|| "null".to_string(), |limit| limit.to_string()),
+            self.freed_bytes,
+            elapsed.as_millis(),
+            exit_code
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json::{Value, parse};
+
+    #[test]
+    fn test_record_tallies_outcomes() {
+        let mut summary = Summary::new("delete", PathBuf::from("/logs"));
+        summary.record(true);
+        summary.record(true);
+        summary.record(false);
+
+        assert_eq!(summary.processed, 3);
+        assert_eq!(summary.succeeded, 2);
+        assert_eq!(summary.failed, 1);
+    }
+
+    #[test]
+    fn test_report_line() {
+        let mut summary = Summary::new("delete", PathBuf::from("/logs"));
+        summary.record(true);
+        summary.record(false);
+
+        assert_eq!(summary.report_line(), "Processed 2 files: 1 ok, 1 failed");
+
+        summary.skipped = 3;
+        assert_eq!(
+            summary.report_line(),
+            "Processed 2 files: 1 ok, 1 failed, 3 skipped"
+        );
+
+        summary.limited = Some(2);
+        assert_eq!(
+            summary.report_line(),
+            "Processed 2 files: 1 ok, 1 failed, 3 skipped (limited to 2)"
+        );
+    }
+
+    #[test]
+    fn test_skip_line_breaks_skips_down_by_reason() {
+        let mut summary = Summary::new("backup", PathBuf::from("/logs"));
+        assert_eq!(summary.skip_line(), None);
+
+        for reason in [
+            SkipReason::Directory,
+            SkipReason::Pattern,
+            SkipReason::Age,
+            SkipReason::Pattern,
+        ] {
+            summary.record_skip(reason);
+        }
+
+        assert_eq!(summary.skipped, 4);
+        assert_eq!(
+            summary.skip_line().as_deref(),
+            Some("Skipped 4 (2 pattern, 1 age, 1 dir)")
+        );
+    }
+
+    #[test]
+    fn test_report_line_single_file() {
+        let mut summary = Summary::new("list", PathBuf::from("/logs"));
+        summary.record(true);
+
+        assert_eq!(summary.report_line(), "Processed 1 file: 1 ok, 0 failed");
+    }
+
+    #[test]
+    fn test_freed_line() {
+        let mut summary = Summary::new("delete", PathBuf::from("/logs"));
+        summary.record_freed(Some(1024));
+        summary.record_freed(Some(512));
+
+        assert_eq!(summary.freed_bytes, 1536);
+        assert_eq!(summary.freed_line(), "Freed 1.5 KB across 2 files");
+    }
+
+    #[test]
+    fn test_freed_line_notes_unknown_sizes() {
+        let mut summary = Summary::new("delete", PathBuf::from("/logs"));
+        summary.record_freed(Some(100));
+        summary.record_freed(None);
+
+        assert_eq!(
+            summary.freed_line(),
+            "Freed 100 B across 2 files (1 of unknown size)"
+        );
+    }
+
+    #[test]
+    fn test_exit_code_reflects_failures() {
+        let mut summary = Summary::new("backup", PathBuf::from("/logs"));
+        summary.record(true);
+        assert_eq!(summary.exit_code(), 0);
+
+        summary.record(false);
+        assert_eq!(summary.exit_code(), 1);
+    }
+
+    #[test]
+    fn test_interrupted_run() {
+        let mut summary = Summary::new("backup", PathBuf::from("/logs"));
+        summary.record(true);
+        summary.interrupted = true;
+
+        assert_eq!(
+            summary.report_line(),
+            "Processed 1 file: 1 ok, 0 failed (interrupted)"
+        );
+        assert_eq!(summary.exit_code(), 130);
+    }
+
+    #[test]
+    fn test_to_json_contains_all_fields() {
+        let mut summary = Summary::new("delete", PathBuf::from("/logs"));
+        summary.record(true);
+        summary.record(false);
+        summary.skipped = 4;
+        summary.freed_bytes = 2048;
+
+        let value = parse(&summary.to_json(Duration::from_millis(1500), 1)).unwrap();
+
+        assert_eq!(
+            value.get("operation").and_then(Value::as_str),
+            Some("delete")
+        );
+        assert_eq!(
+            value.get("directory").and_then(Value::as_str),
+            Some("/logs")
+        );
+        assert_eq!(value.get("processed"), Some(&Value::Number(2.0)));
+        assert_eq!(value.get("succeeded"), Some(&Value::Number(1.0)));
+        assert_eq!(value.get("failed"), Some(&Value::Number(1.0)));
+        assert_eq!(value.get("skipped"), Some(&Value::Number(4.0)));
+        assert_eq!(value.get("limited"), Some(&Value::Null));
+        assert_eq!(value.get("freed_bytes"), Some(&Value::Number(2048.0)));
+        assert_eq!(value.get("elapsed_ms"), Some(&Value::Number(1500.0)));
+        assert_eq!(value.get("exit_code"), Some(&Value::Number(1.0)));
+
+        summary.limited = Some(3);
+        let value = parse(&summary.to_json(Duration::ZERO, 2)).unwrap();
+        assert_eq!(value.get("exit_code"), Some(&Value::Number(2.0)));
+        assert_eq!(value.get("limited"), Some(&Value::Number(3.0)));
+    }
+}
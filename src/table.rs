@@ -0,0 +1,126 @@
+//! Aligned columns for list-like output.
+//!
+//! `list`, `stats` and `backup --dedupe` lay their rows out with [`render`]
+//! so every table pads its columns the same way.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Align {
+    Left,
+    Right,
+}
+
+/// One column of a table: the title shown in its header row, and which
+/// side its cells are padded on.
+#[derive(Clone, Copy, Debug)]
+pub struct Column {
+    pub header: &'static str,
+    pub align: Align,
+}
+
+impl Column {
+    pub const fn left(header: &'static str) -> Self {
+        Column {
+            header,
+            align: Align::Left,
+        }
+    }
+
+    pub const fn right(header: &'static str) -> Self {
+        Column {
+            header,
+            align: Align::Right,
+        }
+    }
+}
+
+/// The lines of a table: a header row when `header` is set, then one line
+/// per row. Columns are two spaces apart and as wide as their widest cell
+/// in characters, and no line ends in spaces. Rows shorter than `columns`
+/// leave the missing cells empty.
+pub fn render(columns: &[Column], rows: &[Vec<String>], header: bool) -> Vec<String> {
+    let titles: Vec<String> = columns
+        .iter()
+        .map(|column| column.header.to_string())
+        .collect();
+    let lines: Vec<&[String]> = header
+        .then_some(titles.as_slice())
+        .into_iter()
+        .chain(rows.iter().map(Vec::as_slice))
+        .collect();
+    let widths: Vec<usize> = (0..columns.len())
+        .map(|i| {
+            lines
+                .iter()
+                .filter_map(|cells| cells.get(i))
+                .map(|cell| cell.chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    lines
+        .into_iter()
+        .map(|cells| {
+            let padded: Vec<String> = columns
+                .iter()
+                .zip(&widths)
+                .enumerate()
+                .map(|(i, (column, &width))| {
+                    let cell = cells.get(i).map_or("", String::as_str);
+                    match column.align {
+                        Align::Left => format!("{cell:<width$}"),
+                        Align::Right => format!("{cell:>width$}"),
+                    }
+                })
+                .collect();
+            padded.join("  ").trim_end().to_string()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COLUMNS: [Column; 3] = [
+        Column::left("Name"),
+        Column::right("Size"),
+        Column::left("Modified"),
+    ];
+
+    fn rows() -> Vec<Vec<String>> {
+        vec![
+            vec!["a.log".into(), "1.5 KB".into(), "2024-01-01".into()],
+            vec!["Network_1.log".into(), "12 B".into(), "-".into()],
+        ]
+    }
+
+    #[test]
+    fn test_render_pads_columns_to_the_widest_cell() {
+        assert_eq!(
+            render(&COLUMNS, &rows(), false),
+            [
+                "a.log          1.5 KB  2024-01-01",
+                "Network_1.log    12 B  -",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render_header_counts_towards_the_width() {
+        let rows = vec![vec!["a".into(), "1".into(), "x".into()]];
+
+        assert_eq!(
+            render(&COLUMNS, &rows, true),
+            ["Name  Size  Modified", "a        1  x"]
+        );
+    }
+
+    #[test]
+    fn test_render_fills_missing_cells() {
+        let rows = vec![vec!["a.log".into()]];
+
+        assert_eq!(render(&COLUMNS, &rows, false), ["a.log"]);
+        assert!(render(&COLUMNS, &[], false).is_empty());
+    }
+}
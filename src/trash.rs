@@ -0,0 +1,309 @@
+//! Moving files to the platform trash instead of removing them.
+//!
+//! On Linux and other Unix desktops files go to the freedesktop.org trash
+//! (`$XDG_DATA_HOME/Trash`, usually `~/.local/share/Trash`) together with a
+//! `.trashinfo` record so file managers can restore them. On macOS they are
+//! moved to `~/.Trash`, and on Windows to the Recycle Bin. Other platforms
+//! report an `Unsupported` error.
+
+use std::io;
+use std::path::Path;
+
+pub trait Trash {
+    fn trash(&self, path: &Path) -> io::Result<()>;
+}
+
+pub struct SystemTrash;
+
+impl Trash for SystemTrash {
+    #[cfg(all(unix, not(target_os = "macos")))]
+    fn trash(&self, path: &Path) -> io::Result<()> {
+        let root = match std::env::var_os("XDG_DATA_HOME") {
+            Some(data_home) => std::path::PathBuf::from(data_home),
+            None => home_dir()?.join(".local").join("share"),
+        };
+        freedesktop::trash_into(&root.join("Trash"), path)
+    }
+
+    #[cfg(target_os = "macos")]
+    fn trash(&self, path: &Path) -> io::Result<()> {
+        let trash_dir = home_dir()?.join(".Trash");
+        let file_name = crate::paths::file_name(path)?;
+        let destination = unique_path(&trash_dir, &file_name.to_string_lossy(), |candidate| {
+            trash_dir.join(candidate).exists()
+        });
+        std::fs::rename(path, destination).map_err(not_trashable)
+    }
+
+    #[cfg(windows)]
+    fn trash(&self, path: &Path) -> io::Result<()> {
+        recycle_bin::trash(path)
+    }
+
+    #[cfg(not(any(unix, windows)))]
+    fn trash(&self, _path: &Path) -> io::Result<()> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "the trash is not supported on this platform; use --safe-delete instead",
+        ))
+    }
+}
+
+#[cfg(unix)]
+fn home_dir() -> io::Result<std::path::PathBuf> {
+    std::env::var_os("HOME")
+        .map(std::path::PathBuf::from)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "HOME is not set"))
+}
+
+/// A trash on another filesystem cannot take the file by a rename, and
+/// copying it there would defeat the point of a quick delete, so the error
+/// points to `--safe-delete` instead.
+#[cfg(unix)]
+fn not_trashable(err: io::Error) -> io::Error {
+    let hint = match err.kind() {
+        io::ErrorKind::CrossesDevices => {
+            "; the trash is on another filesystem, use --safe-delete <DIR> to keep a copy instead"
+        }
+        _ => "",
+    };
+    io::Error::new(err.kind(), format!("could not move to trash: {err}{hint}"))
+}
+
+/// Picks `name`, then `name.2`, `name.3`, ... until `taken` says it is free.
+#[cfg(unix)]
+fn unique_path(dir: &Path, name: &str, taken: impl Fn(&str) -> bool) -> std::path::PathBuf {
+    let mut candidate = name.to_string();
+    let mut suffix = 1;
+    while taken(&candidate) {
+        suffix += 1;
+        candidate = format!("{name}.{suffix}");
+    }
+    dir.join(candidate)
+}
+
+/// The Recycle Bin, through the same shell call Explorer uses to delete.
+#[cfg(windows)]
+mod recycle_bin {
+    use std::ffi::c_void;
+    use std::io;
+    use std::os::windows::ffi::OsStrExt;
+    use std::path::{self, Path};
+    use std::ptr;
+
+    const FO_DELETE: u32 = 3;
+    const FOF_SILENT: u16 = 0x0004;
+    const FOF_NOCONFIRMATION: u16 = 0x0010;
+    const FOF_ALLOWUNDO: u16 = 0x0040;
+    const FOF_NOERRORUI: u16 = 0x0400;
+
+    /// `SHFILEOPSTRUCTW`, byte-packed on 32-bit Windows as in `shellapi.h`.
+    #[cfg_attr(target_pointer_width = "32", repr(C, packed(1)))]
+    #[cfg_attr(not(target_pointer_width = "32"), repr(C))]
+    struct ShFileOpStructW {
+        hwnd: *mut c_void,
+        func: u32,
+        from: *const u16,
+        to: *const u16,
+        flags: u16,
+        any_operations_aborted: i32,
+        name_mappings: *mut c_void,
+        progress_title: *const u16,
+    }
+
+    #[link(name = "shell32")]
+    unsafe extern "system" {
+        fn SHFileOperationW(file_op: *mut ShFileOpStructW) -> i32;
+    }
+
+    pub fn trash(path: &Path) -> io::Result<()> {
+        // Only an absolute path can be undone from the Recycle Bin, and the
+        // shell takes a list of paths ended by an empty one.
+        let absolute = path::absolute(path)?;
+        let from: Vec<u16> = absolute.as_os_str().encode_wide().chain([0, 0]).collect();
+        let mut file_op = ShFileOpStructW {
+            hwnd: ptr::null_mut(),
+            func: FO_DELETE,
+            from: from.as_ptr(),
+            to: ptr::null(),
+            flags: FOF_ALLOWUNDO | FOF_NOCONFIRMATION | FOF_SILENT | FOF_NOERRORUI,
+            any_operations_aborted: 0,
+            name_mappings: ptr::null_mut(),
+            progress_title: ptr::null(),
+        };
+        // SAFETY: every field is initialized, and `from` is a list ended by
+        // two NULs that outlives the call.
+        let code = unsafe { SHFileOperationW(&mut file_op) };
+        if code != 0 {
+            return Err(io::Error::other(format!(
+                "could not move to the Recycle Bin (shell error {code:#x})"
+            )));
+        }
+        if file_op.any_operations_aborted != 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::Interrupted,
+                "moving to the Recycle Bin was cancelled",
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+mod freedesktop {
+    use super::{not_trashable, unique_path};
+    use crate::paths;
+    use crate::util::format_rfc3339;
+    use std::fs::{File, create_dir_all, remove_file, rename};
+    use std::io::{self, Write};
+    use std::os::unix::ffi::OsStrExt;
+    use std::path::Path;
+    use std::time::SystemTime;
+
+    pub fn trash_into(trash_dir: &Path, path: &Path) -> io::Result<()> {
+        // Not canonicalized: a symlink is trashed itself, not its target.
+        let absolute = std::path::absolute(path)?;
+        let files_dir = trash_dir.join("files");
+        let info_dir = trash_dir.join("info");
+        create_dir_all(&files_dir)?;
+        create_dir_all(&info_dir)?;
+
+        let file_name = paths::file_name(&absolute)?.to_string_lossy();
+        let destination = unique_path(&files_dir, &file_name, |candidate| {
+            files_dir.join(candidate).exists()
+                || info_dir.join(format!("{candidate}.trashinfo")).exists()
+        });
+        let trashed_name = paths::file_name(&destination)?;
+        let mut info_name = trashed_name.to_owned();
+        info_name.push(".trashinfo");
+        let info_path = info_dir.join(info_name);
+
+        let deleted_at = format_rfc3339(SystemTime::now());
+        let mut info = File::create_new(&info_path)?;
+        write!(
+            info,
+            "[Trash Info]\nPath={}\nDeletionDate={}\n",
+            percent_encode(absolute.as_os_str().as_bytes()),
+            deleted_at.trim_end_matches('Z')
+        )?;
+
+        rename(&absolute, &destination).map_err(|err| {
+            let _ = remove_file(&info_path);
+            not_trashable(err)
+        })
+    }
+
+    pub fn percent_encode(bytes: &[u8]) -> String {
+        let mut encoded = String::with_capacity(bytes.len());
+        for &byte in bytes {
+            if byte.is_ascii_alphanumeric() || b"/-_.~".contains(&byte) {
+                encoded.push(byte as char);
+            } else {
+                encoded.push_str(&format!("%{byte:02X}"));
+            }
+        }
+        encoded
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use std::fs;
+        use tempfile::TempDir;
+
+        #[test]
+        fn test_trash_into_moves_file_and_writes_info() {
+            let temp_dir = TempDir::new().unwrap();
+            let trash_dir = temp_dir.path().join("Trash");
+            let file_path = temp_dir.path().join("test file.log");
+            fs::write(&file_path, "content").unwrap();
+
+            trash_into(&trash_dir, &file_path).unwrap();
+
+            assert!(!file_path.exists());
+            assert_eq!(
+                fs::read_to_string(trash_dir.join("files").join("test file.log")).unwrap(),
+                "content"
+            );
+            let info =
+                fs::read_to_string(trash_dir.join("info").join("test file.log.trashinfo")).unwrap();
+            assert!(info.starts_with("[Trash Info]\nPath=/"));
+            assert!(info.contains("test%20file.log\n"));
+            assert!(info.contains("DeletionDate="));
+        }
+
+        #[test]
+        fn test_trash_into_does_not_overwrite_earlier_files() {
+            let temp_dir = TempDir::new().unwrap();
+            let trash_dir = temp_dir.path().join("Trash");
+            let file_path = temp_dir.path().join("test.log");
+
+            fs::write(&file_path, "first").unwrap();
+            trash_into(&trash_dir, &file_path).unwrap();
+            fs::write(&file_path, "second").unwrap();
+            trash_into(&trash_dir, &file_path).unwrap();
+
+            let files = trash_dir.join("files");
+            assert_eq!(fs::read_to_string(files.join("test.log")).unwrap(), "first");
+            assert_eq!(
+                fs::read_to_string(files.join("test.log.2")).unwrap(),
+                "second"
+            );
+            assert!(trash_dir.join("info").join("test.log.2.trashinfo").exists());
+        }
+
+        #[test]
+        fn test_trash_into_missing_file_fails() {
+            let temp_dir = TempDir::new().unwrap();
+
+            let result = trash_into(
+                &temp_dir.path().join("Trash"),
+                &temp_dir.path().join("gone.log"),
+            );
+
+            assert_eq!(result.unwrap_err().kind(), io::ErrorKind::NotFound);
+        }
+
+        #[test]
+        fn test_percent_encode() {
+            assert_eq!(
+                percent_encode("/logs/Network 1 (a).log".as_bytes()),
+                "/logs/Network%201%20%28a%29.log"
+            );
+            assert_eq!(percent_encode("/測".as_bytes()), "/%E6%B8%AC");
+        }
+
+        #[test]
+        fn test_trash_into_moves_a_symlink_not_its_target() {
+            let temp_dir = TempDir::new().unwrap();
+            let target = temp_dir.path().join("target.log");
+            let link = temp_dir.path().join("link.log");
+            fs::write(&target, "content").unwrap();
+            std::os::unix::fs::symlink(&target, &link).unwrap();
+
+            trash_into(&temp_dir.path().join("Trash"), &link).unwrap();
+
+            assert!(target.exists());
+            assert!(fs::symlink_metadata(&link).is_err());
+            let info = fs::read_to_string(
+                temp_dir
+                    .path()
+                    .join("Trash")
+                    .join("info")
+                    .join("link.log.trashinfo"),
+            )
+            .unwrap();
+            assert!(info.contains("link.log\n"));
+        }
+
+        #[test]
+        fn test_trash_on_another_filesystem_suggests_safe_delete() {
+            let err = not_trashable(io::Error::from(io::ErrorKind::CrossesDevices));
+
+            assert_eq!(err.kind(), io::ErrorKind::CrossesDevices);
+            assert!(err.to_string().contains("--safe-delete"));
+            let err = not_trashable(io::Error::from(io::ErrorKind::PermissionDenied));
+            assert!(!err.to_string().contains("--safe-delete"));
+        }
+    }
+}
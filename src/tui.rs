@@ -0,0 +1,499 @@
+//! The full-screen log browser behind `view --tui`.
+//!
+//! The logs are listed with their size and date on the left and the start
+//! of the highlighted one is previewed on the right; the highlighted log
+//! can be backed up or deleted in place. The screen is drawn with plain
+//! ANSI escape sequences on a terminal switched to raw mode through
+//! `termios`, so the browser needs nothing beyond `libc`. It is only built
+//! with the `tui` feature, and only on Unix.
+
+use crate::commands::backup::{DEFAULT_BACKUP_DIR, backup_log_file};
+use crate::commands::delete::delete_log_file;
+use crate::commands::view::read_head;
+use crate::gzip::open_maybe_gz;
+use crate::output;
+use crate::scan::{LogFile, collect_log_files};
+use crate::util::{format_size, format_time};
+use std::io::{self, IsTerminal, Write};
+use std::mem;
+use std::path::{Path, PathBuf};
+
+/// How many lines of the highlighted log are read for the preview.
+const PREVIEW_LINES: usize = 200;
+const HELP: &str = "Up/Down move  PgUp/PgDn page  b back up  d delete  r reload  q quit";
+const REVERSE: &str = "\x1b[7m";
+const RESET: &str = "\x1b[0m";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Key {
+    Up,
+    Down,
+    PageUp,
+    PageDown,
+    Home,
+    End,
+    Escape,
+    Char(char),
+}
+
+/// Decodes the bytes of one read from the terminal. Escape sequences for
+/// keys the browser does not use are dropped.
+fn parse_keys(mut bytes: &[u8]) -> Vec<Key> {
+    const SEQUENCES: [(&[u8], Key); 12] = [
+        (b"\x1b[A", Key::Up),
+        (b"\x1bOA", Key::Up),
+        (b"\x1b[B", Key::Down),
+        (b"\x1bOB", Key::Down),
+        (b"\x1b[5~", Key::PageUp),
+        (b"\x1b[6~", Key::PageDown),
+        (b"\x1b[H", Key::Home),
+        (b"\x1b[1~", Key::Home),
+        (b"\x1bOH", Key::Home),
+        (b"\x1b[F", Key::End),
+        (b"\x1b[4~", Key::End),
+        (b"\x1bOF", Key::End),
+    ];
+    let mut keys = Vec::new();
+    'bytes: while let Some(&first) = bytes.first() {
+        if first == 0x1b {
+            for (sequence, key) in SEQUENCES {
+                if let Some(rest) = bytes.strip_prefix(sequence) {
+                    keys.push(key);
+                    bytes = rest;
+                    continue 'bytes;
+                }
+            }
+            if let [_, b'[' | b'O', rest @ ..] = bytes {
+                // An unknown sequence: skip up to its final byte.
+                let end = rest
+                    .iter()
+                    .position(|byte| (0x40..=0x7e).contains(byte))
+                    .map_or(rest.len(), |at| at + 1);
+                bytes = &rest[end..];
+            } else {
+                keys.push(Key::Escape);
+                bytes = &bytes[1..];
+            }
+            continue;
+        }
+        keys.push(Key::Char(char::from(first)));
+        bytes = &bytes[1..];
+    }
+    keys
+}
+
+/// `text` cut or padded with spaces to exactly `width` characters.
+fn fit(text: &str, width: usize) -> String {
+    let mut fitted: String = text.chars().take(width).collect();
+    let len = fitted.chars().count();
+    fitted.extend(std::iter::repeat_n(' ', width - len));
+    fitted
+}
+
+/// A log line made safe to draw: tabs become spaces and other control
+/// characters, which could move the cursor, are dropped.
+fn printable(line: &str) -> String {
+    line.chars()
+        .filter_map(|c| match c {
+            '\t' => Some(' '),
+            c if c.is_control() => None,
+            c => Some(c),
+        })
+        .collect()
+}
+
+/// The state of the browser, kept apart from the terminal so it can be
+/// driven and drawn in tests.
+struct Browser {
+    dir: PathBuf,
+    files: Vec<LogFile>,
+    selected: usize,
+    /// The first file shown in the list.
+    top: usize,
+    /// The preview lines and the file they were read from.
+    preview: Option<(PathBuf, Vec<String>)>,
+    /// Set after `d` until the deletion is confirmed or cancelled.
+    confirm_delete: bool,
+    /// Replaces the key help in the bottom line until the next key.
+    message: Option<String>,
+}
+
+impl Browser {
+    fn new(dir: &Path) -> io::Result<Self> {
+        let mut browser = Browser {
+            dir: dir.to_path_buf(),
+            files: Vec::new(),
+            selected: 0,
+            top: 0,
+            preview: None,
+            confirm_delete: false,
+            message: None,
+        };
+        browser.reload()?;
+        Ok(browser)
+    }
+
+    fn reload(&mut self) -> io::Result<()> {
+        let mut files = collect_log_files(&self.dir)?;
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        self.files = files;
+        self.selected = self.selected.min(self.files.len().saturating_sub(1));
+        self.preview = None;
+        Ok(())
+    }
+
+    /// Applies `key`; `rows` is how many files fit on the screen, the step
+    /// of PgUp and PgDn. Returns false once the browser should close.
+    fn handle(&mut self, key: Key, rows: usize) -> bool {
+        self.message = None;
+        if mem::take(&mut self.confirm_delete) {
+            match key {
+                Key::Char('y' | 'Y') => self.delete_selected(),
+                _ => self.message = Some("Delete cancelled.".to_string()),
+            }
+            return true;
+        }
+        let last = self.files.len().saturating_sub(1);
+        match key {
+            Key::Up | Key::Char('k') => self.selected = self.selected.saturating_sub(1),
+            Key::Down | Key::Char('j') => self.selected = (self.selected + 1).min(last),
+            Key::PageUp => self.selected = self.selected.saturating_sub(rows.max(1)),
+            Key::PageDown => self.selected = (self.selected + rows.max(1)).min(last),
+            Key::Home | Key::Char('g') => self.selected = 0,
+            Key::End | Key::Char('G') => self.selected = last,
+            Key::Char('b') => self.backup_selected(),
+            Key::Char('d') => {
+                if let Some(file) = self.files.get(self.selected) {
+                    self.message = Some(format!("Delete {}? Press y to confirm.", file.name()));
+                    self.confirm_delete = true;
+                }
+            }
+            Key::Char('r') => {
+                if let Err(err) = self.reload() {
+                    self.message = Some(format!("Could not reload: {err}"));
+                }
+            }
+            // Raw mode turns Ctrl-C into a plain byte.
+            Key::Char('q' | '\x03') | Key::Escape => return false,
+            Key::Char(_) => {}
+        }
+        true
+    }
+
+    fn backup_selected(&mut self) {
+        let Some(file) = self.files.get(self.selected) else {
+            return;
+        };
+        let name = file.name();
+        match backup_log_file(&file.path, Path::new(DEFAULT_BACKUP_DIR), false, false) {
+            Ok(()) => {
+                self.message = Some(format!("Backed up {name} to {DEFAULT_BACKUP_DIR}/."));
+                self.remove_selected();
+            }
+            Err(err) => self.message = Some(format!("Could not back up {name}: {err}")),
+        }
+    }
+
+    fn delete_selected(&mut self) {
+        let Some(file) = self.files.get(self.selected) else {
+            return;
+        };
+        let name = file.name();
+        match delete_log_file(&file.path, None, false) {
+            Ok(()) => {
+                self.message = Some(format!("Deleted {name}."));
+                self.remove_selected();
+            }
+            Err(err) => self.message = Some(format!("Could not delete {name}: {err}")),
+        }
+    }
+
+    fn remove_selected(&mut self) {
+        self.files.remove(self.selected);
+        self.selected = self.selected.min(self.files.len().saturating_sub(1));
+    }
+
+    fn preview_lines(&mut self) -> &[String] {
+        let Some(file) = self.files.get(self.selected) else {
+            return &[];
+        };
+        if self
+            .preview
+            .as_ref()
+            .is_none_or(|(path, _)| *path != file.path)
+        {
+            let lines = match open_maybe_gz(&file.path)
+                .and_then(|mut reader| read_head(&mut reader, PREVIEW_LINES))
+            {
+                Ok(head) => String::from_utf8_lossy(&head)
+                    .lines()
+                    .map(printable)
+                    .collect(),
+                Err(err) => vec![format!("Could not read {}: {err}", file.name())],
+            };
+            self.preview = Some((file.path.clone(), lines));
+        }
+        self.preview.as_ref().map_or(&[], |(_, lines)| lines)
+    }
+
+    /// Draws the screen as `height` lines of exactly `width` characters,
+    /// not counting the escape sequences that highlight the selection.
+    fn render(&mut self, width: usize, height: usize) -> Vec<String> {
+        let rows = height.saturating_sub(2);
+        if self.selected < self.top {
+            self.top = self.selected;
+        } else if rows > 0 && self.selected >= self.top + rows {
+            self.top = self.selected + 1 - rows;
+        }
+
+        let list_width = (width * 2 / 5).clamp(20.min(width), 60.min(width));
+        let preview_width = width.saturating_sub(list_width + 3);
+        let title = format!("{} ({} files)", self.dir.display(), self.files.len());
+        let mut screen = vec![fit(&title, width)];
+
+        let list: Vec<String> = if self.files.is_empty() {
+            vec![format!(
+                "No log files found in directory: {}",
+                self.dir.display()
+            )]
+        } else {
+            let name_width = list_width.saturating_sub(29);
+            self.files
+                .iter()
+                .enumerate()
+                .skip(self.top)
+                .take(rows)
+                .map(|(index, file)| {
+                    let date = file.modified.map(format_time).unwrap_or_default();
+                    let date: String = date.chars().take(16).collect();
+                    let row = format!(
+                        "{}  {:>9}  {date:16}",
+                        fit(&file.name(), name_width),
+                        format_size(file.size)
+                    );
+                    let row = fit(&row, list_width);
+                    if index == self.selected {
+                        format!("{REVERSE}{row}{RESET}")
+                    } else {
+                        row
+                    }
+                })
+                .collect()
+        };
+        let preview = self.preview_lines().to_vec();
+        for row in 0..rows {
+            let left = list
+                .get(row)
+                .cloned()
+                .unwrap_or_else(|| fit("", list_width));
+            if preview_width == 0 {
+                screen.push(left);
+                continue;
+            }
+            let right = preview.get(row).map_or("", String::as_str);
+            screen.push(format!("{left} | {}", fit(right, preview_width)));
+        }
+
+        let footer = self.message.as_deref().unwrap_or(HELP);
+        screen.push(fit(footer, width));
+        screen.truncate(height);
+        screen
+    }
+}
+
+/// Puts the terminal in raw mode on the alternate screen, and restores it
+/// when dropped, even if the browser panics.
+struct Terminal {
+    original: libc::termios,
+}
+
+impl Terminal {
+    fn enter() -> io::Result<Self> {
+        // SAFETY: `termios` is plain data, filled in by `tcgetattr` before
+        // it is read.
+        let mut original: libc::termios = unsafe { mem::zeroed() };
+        // SAFETY: the pointer is to a live, writable `termios`.
+        if unsafe { libc::tcgetattr(libc::STDIN_FILENO, &mut original) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let mut raw = original;
+        // SAFETY: `raw` is a valid `termios` read back from the terminal.
+        unsafe { libc::cfmakeraw(&mut raw) };
+        // SAFETY: as above; `tcsetattr` only reads `raw`.
+        if unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &raw) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let terminal = Terminal { original };
+        print!("\x1b[?1049h\x1b[?25l");
+        io::stdout().flush()?;
+        Ok(terminal)
+    }
+
+    /// The terminal's width and height, or 80x24 if it cannot be asked.
+    fn size(&self) -> (usize, usize) {
+        // SAFETY: `winsize` is plain data that `ioctl` fills in.
+        let mut size: libc::winsize = unsafe { mem::zeroed() };
+        // SAFETY: TIOCGWINSZ writes one `winsize` through the pointer.
+        let result = unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) };
+        if result != 0 || size.ws_col == 0 || size.ws_row == 0 {
+            return (80, 24);
+        }
+        (usize::from(size.ws_col), usize::from(size.ws_row))
+    }
+
+    /// Blocks until at least one key is pressed.
+    fn read_keys(&self) -> io::Result<Vec<Key>> {
+        let mut buffer = [0u8; 64];
+        // SAFETY: `read` writes at most `buffer.len()` bytes into `buffer`.
+        let read =
+            unsafe { libc::read(libc::STDIN_FILENO, buffer.as_mut_ptr().cast(), buffer.len()) };
+        if read < 0 {
+            let err = io::Error::last_os_error();
+            return match err.kind() {
+                io::ErrorKind::Interrupted => Ok(Vec::new()),
+                _ => Err(err),
+            };
+        }
+        Ok(parse_keys(&buffer[..read as usize]))
+    }
+
+    fn draw(&self, screen: &[String]) -> io::Result<()> {
+        let mut frame = String::new();
+        for (row, line) in screen.iter().enumerate() {
+            frame.push_str(&format!("\x1b[{};1H{line}\x1b[K", row + 1));
+        }
+        let mut stdout = io::stdout().lock();
+        stdout.write_all(frame.as_bytes())?;
+        stdout.flush()
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        print!("\x1b[?25h\x1b[?1049l");
+        let _ = io::stdout().flush();
+        // SAFETY: `original` is the `termios` read in `enter`.
+        unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &self.original) };
+    }
+}
+
+/// Opens the browser on the logs in `dir` until `q` is pressed.
+pub fn browse(dir: &Path) -> io::Result<()> {
+    if !io::stdin().is_terminal() || !io::stdout().is_terminal() {
+        return Err(io::Error::other("--tui needs a terminal"));
+    }
+    let mut browser = Browser::new(dir)?;
+    // The commands' own status lines would scribble over the screen.
+    let quiet = output::is_quiet();
+    output::set_quiet(true);
+
+    let result = (|| {
+        let terminal = Terminal::enter()?;
+        loop {
+            let (width, height) = terminal.size();
+            terminal.draw(&browser.render(width, height))?;
+            let rows = height.saturating_sub(2);
+            for key in terminal.read_keys()? {
+                if !browser.handle(key, rows) {
+                    return Ok(());
+                }
+            }
+        }
+    })();
+    output::set_quiet(quiet);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn browser_with(names: &[&str]) -> (TempDir, Browser) {
+        let temp_dir = TempDir::new().unwrap();
+        for name in names {
+            fs::write(temp_dir.path().join(name), format!("{name}\nsecond\n")).unwrap();
+        }
+        let browser = Browser::new(temp_dir.path()).unwrap();
+        (temp_dir, browser)
+    }
+
+    fn strip_highlight(line: &str) -> String {
+        line.replace(REVERSE, "").replace(RESET, "")
+    }
+
+    #[test]
+    fn test_parse_keys() {
+        assert_eq!(
+            parse_keys(b"\x1b[A\x1b[Bq\x1b[5~\x1bOF"),
+            [Key::Up, Key::Down, Key::Char('q'), Key::PageUp, Key::End]
+        );
+        assert_eq!(parse_keys(b"\x1b"), [Key::Escape]);
+        // F5 is not used and is skipped whole.
+        assert_eq!(parse_keys(b"\x1b[15~j"), [Key::Char('j')]);
+    }
+
+    #[test]
+    fn test_fit_pads_and_cuts() {
+        assert_eq!(fit("abc", 5), "abc  ");
+        assert_eq!(fit("abcdef", 3), "abc");
+        assert_eq!(printable("a\tb\x1b[2Jc"), "a b[2Jc");
+    }
+
+    #[test]
+    fn test_browser_moves_and_scrolls() {
+        let (_temp_dir, mut browser) = browser_with(&["a.log", "b.log", "c.log", "d.log"]);
+
+        assert!(browser.handle(Key::Down, 2));
+        assert!(browser.handle(Key::PageDown, 2));
+        assert_eq!(browser.selected, 3);
+        assert!(browser.handle(Key::Down, 2));
+        assert_eq!(browser.selected, 3);
+
+        let screen = browser.render(100, 4);
+
+        assert_eq!(screen.len(), 4);
+        assert!(screen[1].starts_with("c.log"));
+        assert!(screen[2].starts_with(REVERSE));
+        assert!(screen[1].contains("| d.log"));
+        assert!(screen[2].contains("| second"));
+        assert!(screen[3].starts_with("Up/Down move"));
+        for line in &screen {
+            assert_eq!(strip_highlight(line).chars().count(), 100);
+        }
+        assert!(!browser.handle(Key::Char('q'), 2));
+    }
+
+    #[test]
+    fn test_browser_backs_up_the_highlighted_file() {
+        let (temp_dir, mut browser) = browser_with(&["a.log", "b.log"]);
+
+        browser.handle(Key::Down, 10);
+        browser.handle(Key::Char('b'), 10);
+
+        assert!(temp_dir.path().join("bak").join("b.log").exists());
+        assert_eq!(browser.files.len(), 1);
+        assert_eq!(browser.selected, 0);
+        assert_eq!(browser.message.as_deref(), Some("Backed up b.log to bak/."));
+    }
+
+    #[test]
+    fn test_browser_deletes_only_after_confirmation() {
+        let (temp_dir, mut browser) = browser_with(&["a.log"]);
+        let file = temp_dir.path().join("a.log");
+
+        browser.handle(Key::Char('d'), 10);
+        browser.handle(Key::Char('n'), 10);
+        assert!(file.exists());
+        assert_eq!(browser.message.as_deref(), Some("Delete cancelled."));
+
+        browser.handle(Key::Char('d'), 10);
+        browser.handle(Key::Char('y'), 10);
+        assert!(!file.exists());
+        assert!(browser.files.is_empty());
+
+        let screen = browser.render(80, 5);
+        assert!(screen[1].starts_with("No log files found in directory"));
+    }
+}
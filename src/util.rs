@@ -0,0 +1,342 @@
+use std::fmt;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+pub fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let month = i64::from(month);
+    let mp = if month > 2 { month - 3 } else { month + 9 };
+    let day_of_year = (153 * mp + 2) / 5 + i64::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+pub fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{value:.1} {}", UNITS[unit])
+}
+
+/// Formats a duration as `1h 02m 03s`, `12m 05s` or `45s`.
+pub fn format_span(span: Duration) -> String {
+    let seconds = span.as_secs();
+    let (hours, minutes, seconds) = (seconds / 3600, seconds % 3600 / 60, seconds % 60);
+    if hours > 0 {
+        format!("{hours}h {minutes:02}m {seconds:02}s")
+    } else if minutes > 0 {
+        format!("{minutes}m {seconds:02}s")
+    } else {
+        format!("{seconds}s")
+    }
+}
+
+/// Parses a size such as `512`, `10K`, `10M` or `2GB` into bytes. Units
+/// are binary (1K = 1024 bytes) to match [`format_size`].
+pub fn parse_size(value: &str) -> Result<u64, String> {
+    let invalid =
+        || format!("invalid size '{value}': expected a number with an optional K, M or G suffix");
+    let upper = value.trim().to_ascii_uppercase();
+    let number = upper.strip_suffix('B').unwrap_or(&upper);
+    let (digits, multiplier) = match number.as_bytes().last() {
+        Some(b'K') => (&number[..number.len() - 1], 1 << 10),
+        Some(b'M') => (&number[..number.len() - 1], 1 << 20),
+        Some(b'G') => (&number[..number.len() - 1], 1 << 30),
+        _ => (number, 1),
+    };
+    let count: u64 = digits.trim().parse().map_err(|_| invalid())?;
+    count.checked_mul(multiplier).ok_or_else(invalid)
+}
+
+/// Parses a duration such as `90m`, `2h`, `3d` or `1h30m` into a
+/// [`Duration`]. Each part is a whole number followed by `s`, `m`, `h`, `d`
+/// or `w`; a bare number is rejected, since its unit would be a guess.
+pub fn parse_duration(value: &str) -> Result<Duration, String> {
+    let invalid = || {
+        format!(
+            "invalid duration '{value}': expected a number with s, m, h, d or w, e.g. 2h or 1h30m"
+        )
+    };
+    let lower = value.trim().to_ascii_lowercase();
+    if lower.is_empty() {
+        return Err(invalid());
+    }
+    let mut seconds: u64 = 0;
+    let mut rest = lower.as_str();
+    while !rest.is_empty() {
+        let digits = rest.len() - rest.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+        let (count, after) = rest.split_at(digits);
+        let count: u64 = count.parse().map_err(|_| invalid())?;
+        let mut units = after.chars();
+        let unit = match units.next() {
+            Some('s') => 1,
+            Some('m') => 60,
+            Some('h') => 60 * 60,
+            Some('d') => 24 * 60 * 60,
+            Some('w') => 7 * 24 * 60 * 60,
+            _ => return Err(invalid()),
+        };
+        seconds = count
+            .checked_mul(unit)
+            .and_then(|part| seconds.checked_add(part))
+            .ok_or_else(invalid)?;
+        rest = units.as_str();
+    }
+    Ok(Duration::from_secs(seconds))
+}
+
+fn unix_seconds(time: SystemTime) -> i64 {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(elapsed) => elapsed.as_secs() as i64,
+        Err(err) => -(err.duration().as_secs() as i64),
+    }
+}
+
+/// A calendar date, as given to `--since`/`--until` or embedded in an ACT
+/// file name. Dates taken from a file's modification time are in UTC.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Date {
+    pub year: i64,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl Date {
+    /// Builds a date, or `None` if it does not exist (e.g. February 30th).
+    pub fn new(year: i64, month: u32, day: u32) -> Option<Date> {
+        let valid = (1..=12).contains(&month)
+            && (1..=31).contains(&day)
+            && civil_from_days(days_from_civil(year, month, day)) == (year, month, day);
+        valid.then_some(Date { year, month, day })
+    }
+
+    /// Parses a `YYYY-MM-DD` date.
+    pub fn parse(value: &str) -> Result<Date, String> {
+        let invalid = || format!("invalid date '{value}': expected YYYY-MM-DD");
+        let mut parts = value.trim().splitn(3, '-');
+        let mut next = |len: usize| {
+            parts
+                .next()
+                .filter(|part| part.len() == len && part.bytes().all(|b| b.is_ascii_digit()))
+                .and_then(|part| part.parse::<u32>().ok())
+                .ok_or_else(invalid)
+        };
+        let (year, month, day) = (next(4)?, next(2)?, next(2)?);
+        Date::new(i64::from(year), month, day).ok_or_else(invalid)
+    }
+
+    pub fn from_system_time(time: SystemTime) -> Date {
+        let (year, month, day) = civil_from_days(unix_seconds(time).div_euclid(86_400));
+        Date { year, month, day }
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+pub fn format_time(time: SystemTime) -> String {
+    let seconds = unix_seconds(time);
+    let (year, month, day) = civil_from_days(seconds.div_euclid(86_400));
+    let of_day = seconds.rem_euclid(86_400);
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02} UTC",
+        of_day / 3600,
+        of_day % 3600 / 60,
+        of_day % 60
+    )
+}
+
+/// Formats a time as an RFC 3339 UTC timestamp, e.g. `2024-01-01T01:01:01Z`.
+pub fn format_rfc3339(time: SystemTime) -> String {
+    let seconds = unix_seconds(time);
+    let (year, month, day) = civil_from_days(seconds.div_euclid(86_400));
+    let of_day = seconds.rem_euclid(86_400);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        of_day / 3600,
+        of_day % 3600 / 60,
+        of_day % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_civil_from_days_epoch() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+    }
+
+    #[test]
+    fn test_civil_from_days_leap_day() {
+        assert_eq!(civil_from_days(19_782), (2024, 2, 29));
+    }
+
+    #[test]
+    fn test_civil_from_days_before_epoch() {
+        assert_eq!(civil_from_days(-1), (1969, 12, 31));
+    }
+
+    #[test]
+    fn test_days_from_civil_roundtrip() {
+        for days in [-1, 0, 19_782, 2_932_896] {
+            let (year, month, day) = civil_from_days(days);
+            assert_eq!(days_from_civil(year, month, day), days);
+        }
+    }
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(0), "0 B");
+        assert_eq!(format_size(1023), "1023 B");
+        assert_eq!(format_size(1024), "1.0 KB");
+        assert_eq!(format_size(1536), "1.5 KB");
+        assert_eq!(format_size(250 * 1024 * 1024), "250.0 MB");
+        assert_eq!(format_size(3 * 1024 * 1024 * 1024), "3.0 GB");
+        assert_eq!(format_size(u64::MAX), "16777216.0 TB");
+    }
+
+    #[test]
+    fn test_format_span() {
+        assert_eq!(format_span(Duration::ZERO), "0s");
+        assert_eq!(format_span(Duration::from_millis(45_900)), "45s");
+        assert_eq!(format_span(Duration::from_secs(725)), "12m 05s");
+        assert_eq!(format_span(Duration::from_secs(3723)), "1h 02m 03s");
+    }
+
+    #[test]
+    fn test_parse_size_units() {
+        assert_eq!(parse_size("512"), Ok(512));
+        assert_eq!(parse_size("0"), Ok(0));
+        assert_eq!(parse_size("10K"), Ok(10 * 1024));
+        assert_eq!(parse_size("10m"), Ok(10 * 1024 * 1024));
+        assert_eq!(parse_size("2G"), Ok(2 * 1024 * 1024 * 1024));
+        assert_eq!(parse_size("2GB"), Ok(2 * 1024 * 1024 * 1024));
+        assert_eq!(parse_size("100B"), Ok(100));
+    }
+
+    #[test]
+    fn test_parse_duration_units() {
+        assert_eq!(parse_duration("45s"), Ok(Duration::from_secs(45)));
+        assert_eq!(parse_duration("90m"), Ok(Duration::from_secs(90 * 60)));
+        assert_eq!(parse_duration("2h"), Ok(Duration::from_secs(2 * 3600)));
+        assert_eq!(parse_duration("3D"), Ok(Duration::from_secs(3 * 86_400)));
+        assert_eq!(parse_duration("1w"), Ok(Duration::from_secs(7 * 86_400)));
+        assert_eq!(parse_duration("0m"), Ok(Duration::ZERO));
+    }
+
+    #[test]
+    fn test_parse_duration_combined_units() {
+        assert_eq!(parse_duration("1h30m"), Ok(Duration::from_secs(5400)));
+        assert_eq!(
+            parse_duration("1d2h3m4s"),
+            Ok(Duration::from_secs(86_400 + 2 * 3600 + 3 * 60 + 4))
+        );
+        assert_eq!(parse_duration(" 30m30m "), Ok(Duration::from_secs(3600)));
+    }
+
+    #[test]
+    fn test_parse_duration_rejects_invalid_input() {
+        for value in [
+            "",
+            "h",
+            "90",
+            "2x",
+            "1.5h",
+            "-2h",
+            "1h 30m",
+            "2hh",
+            "99999999999999999999w",
+        ] {
+            assert!(parse_duration(value).is_err(), "{value} should be rejected");
+        }
+        assert!(parse_duration("soon").unwrap_err().contains("'soon'"));
+    }
+
+    #[test]
+    fn test_parse_size_rejects_invalid_input() {
+        for value in [
+            "",
+            "M",
+            "ten",
+            "-5K",
+            "1.5M",
+            "10T",
+            "99999999999999999999G",
+        ] {
+            assert!(parse_size(value).is_err(), "{value} should be rejected");
+        }
+        assert!(parse_size("abc").unwrap_err().contains("'abc'"));
+    }
+
+    #[test]
+    fn test_date_parse() {
+        assert_eq!(
+            Date::parse("2024-02-29"),
+            Ok(Date::new(2024, 2, 29).unwrap())
+        );
+        assert_eq!(Date::parse("2024-01-05").unwrap().to_string(), "2024-01-05");
+        for value in [
+            "2023-02-29",
+            "2024-13-01",
+            "2024-1-5",
+            "20240105",
+            "yesterday",
+            "",
+        ] {
+            assert!(Date::parse(value).is_err(), "{value} should be rejected");
+        }
+        assert!(
+            Date::parse("2024-00-10")
+                .unwrap_err()
+                .contains("YYYY-MM-DD")
+        );
+    }
+
+    #[test]
+    fn test_date_from_system_time() {
+        let time = UNIX_EPOCH + Duration::from_secs(1_704_067_200 + 86_399);
+
+        assert_eq!(Date::from_system_time(time), Date::new(2024, 1, 1).unwrap());
+    }
+
+    #[test]
+    fn test_format_time() {
+        let time = UNIX_EPOCH + Duration::from_secs(1_704_067_200 + 3661);
+
+        assert_eq!(format_time(time), "2024-01-01 01:01:01 UTC");
+    }
+
+    #[test]
+    fn test_format_rfc3339() {
+        let time = UNIX_EPOCH + Duration::from_secs(1_704_067_200 + 3661);
+
+        assert_eq!(format_rfc3339(time), "2024-01-01T01:01:01Z");
+    }
+}
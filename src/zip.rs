@@ -0,0 +1,267 @@
+use crate::hash::crc32;
+use crate::util::civil_from_days;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const LOCAL_HEADER: u32 = 0x0403_4b50;
+const CENTRAL_HEADER: u32 = 0x0201_4b50;
+const END_OF_CENTRAL_DIRECTORY: u32 = 0x0605_4b50;
+/// Names are stored as UTF-8.
+const FLAG_UTF8: u16 = 0x0800;
+/// The "extended timestamp" extra field, holding the Unix modification time.
+const EXTENDED_TIMESTAMP: u16 = 0x5455;
+const VERSION: u16 = 20;
+
+struct Entry {
+    name: String,
+    modified: u32,
+    dos_time: (u16, u16),
+    crc: u32,
+    size: u32,
+    offset: u32,
+}
+
+/// Writes a zip archive one entry at a time. Contents are stored without
+/// compression and streamed straight through; each entry's checksum and
+/// size are patched into its header afterwards, so the output must be
+/// seekable. Archives are limited to 4 GB and 65535 entries (no Zip64).
+pub struct ZipWriter<W: Write + Seek> {
+    out: W,
+    offset: u64,
+    entries: Vec<Entry>,
+}
+
+fn too_large(what: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("{what} is too large for a zip archive"),
+    )
+}
+
+/// MS-DOS time and date fields for `time`, in UTC. Zip cannot store dates
+/// before 1980, so earlier times are clamped.
+fn dos_time(time: SystemTime) -> (u16, u16) {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs());
+    let (year, month, day) = civil_from_days((secs / 86_400) as i64);
+    if year < 1980 {
+        return (0, 1 << 5 | 1);
+    }
+    let secs_of_day = secs % 86_400;
+    let (hour, minute, second) = (secs_of_day / 3600, secs_of_day / 60 % 60, secs_of_day % 60);
+    let time = (hour << 11 | minute << 5 | (second / 2)) as u16;
+    let date = ((year.min(2107) - 1980) << 9 | i64::from(month) << 5 | i64::from(day)) as u16;
+    (time, date)
+}
+
+impl<W: Write + Seek> ZipWriter<W> {
+    pub fn new(out: W) -> Self {
+        ZipWriter {
+            out,
+            offset: 0,
+            entries: Vec::new(),
+        }
+    }
+
+    /// Adds an entry called `name` with the contents of `data`.
+    pub fn add(
+        &mut self,
+        name: &str,
+        modified: SystemTime,
+        data: &mut impl Read,
+    ) -> io::Result<()> {
+        if self.entries.len() == usize::from(u16::MAX) {
+            return Err(too_large("the number of files"));
+        }
+        let offset = u32::try_from(self.offset).map_err(|_| too_large("the archive"))?;
+        let mut entry = Entry {
+            name: name.to_string(),
+            modified: modified
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |since| since.as_secs().min(u64::from(u32::MAX)) as u32),
+            dos_time: dos_time(modified),
+            crc: 0,
+            size: 0,
+            offset,
+        };
+        let header = local_header(&entry);
+        self.out.write_all(&header)?;
+
+        let mut buffer = vec![0; 64 * 1024];
+        let mut size = 0u64;
+        loop {
+            let read = data.read(&mut buffer)?;
+            if read == 0 {
+                break;
+            }
+            size += read as u64;
+            if size > u64::from(u32::MAX) {
+                return Err(too_large(name));
+            }
+            entry.crc = crc32(entry.crc, &buffer[..read]);
+            self.out.write_all(&buffer[..read])?;
+        }
+        entry.size = size as u32;
+
+        // The checksum and both sizes sit 14 bytes into the local header.
+        let end = self.offset + header.len() as u64 + size;
+        self.out.seek(SeekFrom::Start(self.offset + 14))?;
+        self.out.write_all(&entry.crc.to_le_bytes())?;
+        self.out.write_all(&entry.size.to_le_bytes())?;
+        self.out.write_all(&entry.size.to_le_bytes())?;
+        self.out.seek(SeekFrom::Start(end))?;
+        self.offset = end;
+        self.entries.push(entry);
+        Ok(())
+    }
+
+    /// Writes the central directory and returns the output.
+    pub fn finish(mut self) -> io::Result<W> {
+        let start = u32::try_from(self.offset).map_err(|_| too_large("the archive"))?;
+        let mut directory = Vec::new();
+        for entry in &self.entries {
+            directory.extend_from_slice(&CENTRAL_HEADER.to_le_bytes());
+            directory.extend_from_slice(&VERSION.to_le_bytes());
+            directory.extend_from_slice(&common_fields(entry));
+            directory.extend_from_slice(&[0; 6]); // comment length, disk, internal attributes
+            directory.extend_from_slice(&0u32.to_le_bytes()); // external attributes
+            directory.extend_from_slice(&entry.offset.to_le_bytes());
+            directory.extend_from_slice(entry.name.as_bytes());
+            directory.extend_from_slice(&extra_field(entry));
+        }
+        let count = (self.entries.len() as u16).to_le_bytes();
+        let size = u32::try_from(directory.len()).map_err(|_| too_large("the archive"))?;
+        directory.extend_from_slice(&END_OF_CENTRAL_DIRECTORY.to_le_bytes());
+        directory.extend_from_slice(&[0; 4]); // disk numbers
+        directory.extend_from_slice(&count);
+        directory.extend_from_slice(&count);
+        directory.extend_from_slice(&size.to_le_bytes());
+        directory.extend_from_slice(&start.to_le_bytes());
+        directory.extend_from_slice(&0u16.to_le_bytes()); // comment length
+        self.out.write_all(&directory)?;
+        self.out.flush()?;
+        Ok(self.out)
+    }
+}
+
+/// The fields shared by the local and the central header, from "version
+/// needed" up to the extra field length.
+fn common_fields(entry: &Entry) -> Vec<u8> {
+    let mut fields = Vec::new();
+    fields.extend_from_slice(&VERSION.to_le_bytes());
+    fields.extend_from_slice(&FLAG_UTF8.to_le_bytes());
+    fields.extend_from_slice(&0u16.to_le_bytes()); // stored
+    fields.extend_from_slice(&entry.dos_time.0.to_le_bytes());
+    fields.extend_from_slice(&entry.dos_time.1.to_le_bytes());
+    fields.extend_from_slice(&entry.crc.to_le_bytes());
+    fields.extend_from_slice(&entry.size.to_le_bytes());
+    fields.extend_from_slice(&entry.size.to_le_bytes());
+    fields.extend_from_slice(&(entry.name.len() as u16).to_le_bytes());
+    fields.extend_from_slice(&(extra_field(entry).len() as u16).to_le_bytes());
+    fields
+}
+
+fn local_header(entry: &Entry) -> Vec<u8> {
+    let mut header = LOCAL_HEADER.to_le_bytes().to_vec();
+    header.extend_from_slice(&common_fields(entry));
+    header.extend_from_slice(entry.name.as_bytes());
+    header.extend_from_slice(&extra_field(entry));
+    header
+}
+
+fn extra_field(entry: &Entry) -> Vec<u8> {
+    let mut field = EXTENDED_TIMESTAMP.to_le_bytes().to_vec();
+    field.extend_from_slice(&5u16.to_le_bytes());
+    field.push(1); // modification time present
+    field.extend_from_slice(&entry.modified.to_le_bytes());
+    field
+}
+
+/// An entry read back by [`read_entries`].
+#[cfg(test)]
+#[derive(Debug, PartialEq, Eq)]
+pub struct ReadEntry {
+    pub name: String,
+    pub modified: u32,
+    pub data: Vec<u8>,
+}
+
+/// Reads the entries of an archive written by [`ZipWriter`] through its
+/// central directory, checking each entry's CRC-32.
+#[cfg(test)]
+pub fn read_entries(zip: &[u8]) -> Vec<ReadEntry> {
+    let u16_at = |at: usize| u16::from_le_bytes([zip[at], zip[at + 1]]) as usize;
+    let u32_at = |at: usize| u32::from_le_bytes(zip[at..at + 4].try_into().unwrap());
+
+    let end = zip.len() - 22;
+    assert_eq!(u32_at(end), END_OF_CENTRAL_DIRECTORY);
+    let count = u16_at(end + 10);
+    let mut at = u32_at(end + 16) as usize;
+    let mut entries = Vec::new();
+    for _ in 0..count {
+        assert_eq!(u32_at(at), CENTRAL_HEADER);
+        let (crc, size) = (u32_at(at + 16), u32_at(at + 24) as usize);
+        let (name_len, extra_len) = (u16_at(at + 28), u16_at(at + 30));
+        let local = u32_at(at + 42) as usize;
+        let name = String::from_utf8(zip[at + 46..at + 46 + name_len].to_vec()).unwrap();
+        let modified = u32_at(at + 46 + name_len + 5);
+
+        assert_eq!(u32_at(local), LOCAL_HEADER);
+        assert_eq!(u32_at(local + 14), crc);
+        let data_start = local + 30 + u16_at(local + 26) + u16_at(local + 28);
+        let data = zip[data_start..data_start + size].to_vec();
+        assert_eq!(crc32(0, &data), crc);
+
+        entries.push(ReadEntry {
+            name,
+            modified,
+            data,
+        });
+        at += 46 + name_len + extra_len;
+    }
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+    use std::time::Duration;
+
+    #[test]
+    fn test_zip_writer_round_trip() {
+        let modified = UNIX_EPOCH + Duration::from_secs(1_704_139_200);
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        zip.add("a.log", modified, &mut &b"first\n"[..]).unwrap();
+        zip.add("empty.log", UNIX_EPOCH, &mut &b""[..]).unwrap();
+        let bytes = zip.finish().unwrap().into_inner();
+
+        assert_eq!(
+            read_entries(&bytes),
+            vec![
+                ReadEntry {
+                    name: "a.log".to_string(),
+                    modified: 1_704_139_200,
+                    data: b"first\n".to_vec(),
+                },
+                ReadEntry {
+                    name: "empty.log".to_string(),
+                    modified: 0,
+                    data: Vec::new(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dos_time() {
+        // 2024-01-01T20:15:41Z; DOS time has two-second resolution.
+        let time = UNIX_EPOCH + Duration::from_secs(1_704_140_141);
+        assert_eq!(
+            dos_time(time),
+            (20 << 11 | 15 << 5 | 20, 44 << 9 | 1 << 5 | 1)
+        );
+        assert_eq!(dos_time(UNIX_EPOCH), (0, 1 << 5 | 1));
+    }
+}